@@ -1,5 +1,6 @@
 use std::ffi::OsStr;
 use std::fs;
+use std::io;
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -108,6 +109,36 @@ const fn jit_bin(config: &'static str, source: &'static str, args: &'static str)
         ]);
         runner.run_out_command("gen_block_iterate", &[]);
     }),
+    TestCase::custom("aot.debuginfo", &|runner| {
+        runner.run_rustc(["example/debuginfo.rs"]);
+        let Some(output) = runner.run_gdb(
+            "debuginfo",
+            &[
+                "break break_here",
+                "run",
+                "up",
+                "info args",
+                "info locals",
+                "echo == second stop ==\\n",
+                "continue",
+                "up",
+                "info locals",
+            ],
+        ) else {
+            return;
+        };
+        let (first_stop, second_stop) = output.split_once("== second stop ==").unwrap();
+        for expected in ["arg = 42", "outer = 43", "inner = 86"] {
+            assert!(first_stop.contains(expected), "missing `{expected}` in:\n{first_stop}");
+        }
+        for expected in
+            ["outer = 43", "small = debuginfo::Small::B", "aligned = debuginfo::Aligned::B"]
+        {
+            assert!(second_stop.contains(expected), "missing `{expected}` in:\n{second_stop}");
+        }
+        // `inner` is out of scope after its block.
+        assert!(!second_stop.contains("inner ="), "`inner` is in scope in:\n{second_stop}");
+    }),
 ];
 
 pub(crate) static RAND_REPO: GitRepo = GitRepo::github(
@@ -487,6 +518,32 @@ fn run_rustc<I, S>(&self, args: I)
         spawn_and_wait(self.rustc_command(args));
     }
 
+    /// Run an example under gdb with the given commands and return the output of gdb, or `None`
+    /// if gdb isn't installed or can't debug the target.
+    #[must_use]
+    fn run_gdb(&self, name: &str, commands: &[&str]) -> Option<String> {
+        if !self.is_native {
+            eprintln!("[AOT] {name}: skipping gdb, not a native target");
+            return None;
+        }
+        let mut cmd = Command::new("gdb");
+        cmd.args(["-nx", "-batch"]);
+        for command in commands {
+            cmd.args(["-ex", command]);
+        }
+        cmd.arg(BUILD_EXAMPLE_OUT_DIR.to_path(&self.dirs).join(name));
+        let output = match cmd.output() {
+            Ok(output) => output,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                eprintln!("[AOT] {name}: skipping gdb, it is not installed");
+                return None;
+            }
+            Err(err) => panic!("failed to run gdb: {err}"),
+        };
+        assert!(output.status.success(), "gdb failed: {}", String::from_utf8_lossy(&output.stderr));
+        Some(String::from_utf8(output.stdout).unwrap())
+    }
+
     fn run_out_command(&self, name: &str, args: &[&str]) {
         let mut full_cmd = vec![];
 
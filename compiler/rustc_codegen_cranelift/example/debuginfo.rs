@@ -0,0 +1,53 @@
+// Inspected with gdb by the aot.debuginfo test of the build system. The locals are borrowed at the
+// end of `scopes`, so that they are stored in stack slots and are known at every breakpoint.
+
+use std::hint::black_box;
+
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+#[repr(u8)]
+enum Small {
+    A,
+    B,
+}
+
+// The enum is larger than its `u8` tag.
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+#[repr(align(32))]
+enum Aligned {
+    A,
+    B,
+}
+
+// The tag of `High` doesn't fit in 64 bits, so the variants of `Wide` can't be described.
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+#[repr(u128)]
+enum Wide {
+    Low(u8) = 1,
+    High(u8) = 1 << 64,
+}
+
+#[no_mangle]
+#[inline(never)]
+extern "C" fn break_here() {}
+
+#[inline(never)]
+fn scopes(arg: u32) {
+    let outer = arg + 1;
+    {
+        let inner = outer * 2;
+        break_here();
+        black_box(&inner);
+    }
+    let small = Small::B;
+    let aligned = Aligned::B;
+    let wide = Wide::High(7);
+    break_here();
+    black_box((&arg, &outer, &small, &aligned, &wide));
+}
+
+fn main() {
+    scopes(42);
+}
@@ -428,8 +428,12 @@ pub(crate) fn set_debug_loc(&mut self, source_info: mir::SourceInfo) {
                 debug_context.add_source_file(&file)
             };
 
-            let source_loc =
-                self.func_debug_cx.as_mut().unwrap().add_dbg_loc(file_id, line, column);
+            let source_loc = self.func_debug_cx.as_mut().unwrap().add_dbg_loc(
+                file_id,
+                line,
+                column,
+                source_info.scope,
+            );
             self.bcx.set_srcloc(source_loc);
         }
     }
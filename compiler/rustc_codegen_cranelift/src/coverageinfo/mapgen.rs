@@ -0,0 +1,315 @@
+//! Writes the profile data and coverage mappings of a codegen unit in the formats of LLVM 17,
+//! which are read by LLVM's profiler runtime, `llvm-profdata` and `llvm-cov`.
+//!
+//! The LLVM backend lets the `InstrProfiling` pass and `CoverageMappingWriter` of LLVM write
+//! these. The layouts written here mirror theirs: version 8 of the raw profile data records and
+//! version 6 of the coverage mapping format.
+
+use cranelift_module::DataId;
+use cranelift_object::ObjectProduct;
+use gimli::write::{EndianVec, Writer};
+use gimli::RunTimeEndian;
+use object::write::Relocation;
+use object::{elf, RelocationEncoding, RelocationKind, SectionFlags, SectionKind};
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_index::IndexVec;
+use rustc_middle::mir::coverage::{CovTerm, Expression, ExpressionId, MappingKind, Op};
+use rustc_session::RemapFileNameExt;
+use rustc_span::Symbol;
+
+use super::{md5, FunctionCoverage};
+use crate::prelude::*;
+
+/// The coverage mapping format version 6, which is encoded as 5.
+const COVERAGE_MAPPING_VERSION: u32 = 5;
+
+/// The region kind of a branch region, as encoded in the header of a region with a zero counter.
+const BRANCH_REGION: u64 = 4;
+
+/// The coverage data of a codegen unit, encoded on the main thread since it needs the interned
+/// file names, and written to the object file of the codegen unit by [`CoverageMap::emit`].
+pub(crate) struct CoverageMap {
+    endian: RunTimeEndian,
+    pointer_size: u8,
+    covmap: Vec<u8>,
+    covfun: Vec<u8>,
+    names: Vec<u8>,
+    profile_data: Vec<ProfileData>,
+}
+
+/// The fields of the `__llvm_profile_data` record of a function with counters.
+struct ProfileData {
+    name_hash: u64,
+    source_hash: u64,
+    counters: DataId,
+    num_counters: u32,
+}
+
+pub(super) fn generate_coverage_map(
+    tcx: TyCtxt<'_>,
+    functions: &FxIndexMap<String, FunctionCoverage>,
+) -> CoverageMap {
+    let endian = match tcx.data_layout.endian {
+        rustc_target::abi::Endian::Little => RunTimeEndian::Little,
+        rustc_target::abi::Endian::Big => RunTimeEndian::Big,
+    };
+
+    // The global file ID 0 is the working directory, against which `llvm-cov` resolves relative
+    // paths. The file names of the mappings follow it in sorted order.
+    let mut file_table: FxIndexSet<Symbol> = functions
+        .values()
+        .flat_map(|function| &function.mappings)
+        .map(|mapping| mapping.code_region.file_name)
+        .collect();
+    file_table.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+    let working_dir = tcx.sess.opts.working_dir.for_codegen(tcx.sess).to_string_lossy();
+    let filenames = encode_filenames(
+        std::iter::once(&*working_dir).chain(file_table.iter().map(Symbol::as_str)),
+    );
+
+    // The number of records and the size of the mappings in the header are unused since version 4
+    // of the format, which moved the function records to `__llvm_covfun`.
+    let mut covmap = EndianVec::new(endian);
+    covmap.write_u32(0).unwrap();
+    covmap.write_u32(filenames.len().try_into().unwrap()).unwrap();
+    covmap.write_u32(0).unwrap();
+    covmap.write_u32(COVERAGE_MAPPING_VERSION).unwrap();
+    covmap.write(&filenames).unwrap();
+    pad_to_8(&mut covmap);
+
+    let filenames_hash = md5::hash(&filenames);
+    let mut covfun = EndianVec::new(endian);
+    let mut names = vec![];
+    let mut profile_data = vec![];
+    for (symbol_name, function) in functions {
+        if function.mappings.is_empty() {
+            assert!(!function.is_used, "no mappings for used function {symbol_name}");
+            continue;
+        }
+        let mapping = encode_mapping(&file_table, function);
+        let name_hash = md5::hash(symbol_name.as_bytes());
+        let source_hash = if function.is_used { function.source_hash } else { 0 };
+
+        covfun.write_u64(name_hash).unwrap();
+        covfun.write_u32(mapping.len().try_into().unwrap()).unwrap();
+        covfun.write_u64(source_hash).unwrap();
+        covfun.write_u64(filenames_hash).unwrap();
+        covfun.write(&mapping).unwrap();
+        pad_to_8(&mut covfun);
+
+        // `llvm-cov` looks up the names of all functions with mappings, including the unused
+        // ones, in `__llvm_prf_names`.
+        if !names.is_empty() {
+            names.push(b'\x01');
+        }
+        names.extend_from_slice(symbol_name.as_bytes());
+
+        if let Some(counters) = function.counters {
+            profile_data.push(ProfileData {
+                name_hash,
+                source_hash,
+                counters,
+                num_counters: function.num_counters.try_into().unwrap(),
+            });
+        }
+    }
+
+    // The names are prefixed by their uncompressed and compressed lengths, with a compressed
+    // length of zero meaning that they are not compressed.
+    let mut names_section = vec![];
+    write_uleb128(&mut names_section, names.len() as u64);
+    write_uleb128(&mut names_section, 0);
+    names_section.extend_from_slice(&names);
+
+    CoverageMap {
+        endian,
+        pointer_size: tcx.data_layout.pointer_size.bytes().try_into().unwrap(),
+        covmap: covmap.into_vec(),
+        covfun: covfun.into_vec(),
+        names: names_section,
+        profile_data,
+    }
+}
+
+impl CoverageMap {
+    pub(crate) fn emit(self, product: &mut ObjectProduct) {
+        // The coverage mappings are only read by `llvm-cov`, so like LLVM we don't allocate them,
+        // which also keeps the linker from garbage collecting them.
+        add_section(product, "__llvm_covmap", SectionKind::Other, self.covmap, 0);
+        add_section(product, "__llvm_covfun", SectionKind::Other, self.covfun, 0);
+
+        // The profiler runtime finds the profile data through the `__start_` and `__stop_`
+        // symbols of these sections, so they have to be retained like LLVM's `llvm.used` globals.
+        let names_flags = elf::SHF_ALLOC | elf::SHF_GNU_RETAIN;
+        add_section(
+            product,
+            "__llvm_prf_names",
+            SectionKind::ReadOnlyData,
+            self.names,
+            names_flags,
+        );
+
+        if self.profile_data.is_empty() {
+            return;
+        }
+        let mut data = EndianVec::new(self.endian);
+        let mut relocations = vec![];
+        for profile_data in &self.profile_data {
+            let record_start = data.len() as u64;
+            data.write_u64(profile_data.name_hash).unwrap();
+            data.write_u64(profile_data.source_hash).unwrap();
+            // The counters are addressed relative to the start of the record.
+            let counter_ptr_offset = data.len() as u64;
+            relocations.push(Relocation {
+                offset: counter_ptr_offset,
+                symbol: product.data_symbol(profile_data.counters),
+                kind: RelocationKind::Relative,
+                encoding: RelocationEncoding::Generic,
+                size: self.pointer_size * 8,
+                addend: i64::try_from(counter_ptr_offset - record_start).unwrap(),
+            });
+            data.write_udata(0, self.pointer_size).unwrap();
+            // The function pointer and the value profiling data, which are only used for value
+            // profiling.
+            data.write_udata(0, self.pointer_size).unwrap();
+            data.write_udata(0, self.pointer_size).unwrap();
+            data.write_u32(profile_data.num_counters).unwrap();
+            // The number of value sites of each value kind.
+            data.write_u16(0).unwrap();
+            data.write_u16(0).unwrap();
+            pad_to_8(&mut data);
+        }
+        let data_flags = elf::SHF_ALLOC | elf::SHF_WRITE | elf::SHF_GNU_RETAIN;
+        let section_id =
+            add_section(product, "__llvm_prf_data", SectionKind::Data, data.into_vec(), data_flags);
+        for relocation in relocations {
+            product.object.add_relocation(section_id, relocation).unwrap();
+        }
+    }
+}
+
+fn add_section(
+    product: &mut ObjectProduct,
+    name: &str,
+    kind: SectionKind,
+    data: Vec<u8>,
+    flags: u32,
+) -> object::write::SectionId {
+    let section_id = product.object.add_section(vec![], name.as_bytes().to_vec(), kind);
+    let section = product.object.section_mut(section_id);
+    section.set_data(data, 8);
+    section.flags = SectionFlags::Elf { sh_flags: u64::from(flags) };
+    section_id
+}
+
+/// Encodes the filenames table of `__llvm_covmap` like `CoverageFilenamesSectionWriter` in LLVM.
+fn encode_filenames<'a>(filenames: impl Iterator<Item = &'a str>) -> Vec<u8> {
+    let mut num_filenames = 0;
+    let mut encoded = vec![];
+    for filename in filenames {
+        write_uleb128(&mut encoded, filename.len() as u64);
+        encoded.extend_from_slice(filename.as_bytes());
+        num_filenames += 1;
+    }
+
+    let mut buffer = vec![];
+    write_uleb128(&mut buffer, num_filenames);
+    write_uleb128(&mut buffer, encoded.len() as u64);
+    // A compressed length of zero means that the filenames are not compressed.
+    write_uleb128(&mut buffer, 0);
+    buffer.extend_from_slice(&encoded);
+    buffer
+}
+
+/// Encodes the mappings of a function like `CoverageMappingWriter` in LLVM, without its
+/// minimization of the expressions.
+fn encode_mapping(file_table: &FxIndexSet<Symbol>, function: &FunctionCoverage) -> Vec<u8> {
+    // Unused functions have no counters, so all of their regions are mapped to zero.
+    let no_expressions = IndexVec::new();
+    let expressions = if function.is_used { &function.expressions } else { &no_expressions };
+    let term = |term| if function.is_used { term } else { CovTerm::Zero };
+
+    // The local file IDs of the function are assigned in the order in which its mappings use
+    // the files, and map to the global file IDs of `__llvm_covmap`.
+    let mut virtual_files = FxIndexSet::default();
+    let mut regions: Vec<_> = function
+        .mappings
+        .iter()
+        .map(|mapping| {
+            let global_file_id =
+                file_table.get_index_of(&mapping.code_region.file_name).unwrap() + 1;
+            (virtual_files.insert_full(global_file_id).0, mapping)
+        })
+        .collect();
+    regions.sort_by_key(|&(file_id, mapping)| {
+        let region_kind = match mapping.kind {
+            MappingKind::Code(_) => 0,
+            MappingKind::Branch { .. } => BRANCH_REGION,
+            MappingKind::MCDCBranch { .. } | MappingKind::MCDCDecision(_) => {
+                bug!("MC/DC coverage is not supported")
+            }
+        };
+        (file_id, mapping.code_region.start_line, mapping.code_region.start_col, region_kind)
+    });
+
+    let mut buffer = vec![];
+    write_uleb128(&mut buffer, virtual_files.len() as u64);
+    for &global_file_id in &virtual_files {
+        write_uleb128(&mut buffer, global_file_id as u64);
+    }
+    write_uleb128(&mut buffer, expressions.len() as u64);
+    for expression in expressions {
+        write_uleb128(&mut buffer, encode_term(expressions, expression.lhs));
+        write_uleb128(&mut buffer, encode_term(expressions, expression.rhs));
+    }
+
+    for file_id in 0..virtual_files.len() {
+        let file_regions: Vec<_> =
+            regions.iter().filter(|&&(region_file_id, _)| region_file_id == file_id).collect();
+        write_uleb128(&mut buffer, file_regions.len() as u64);
+        let mut previous_line = 0;
+        for &&(_, mapping) in &file_regions {
+            match mapping.kind {
+                MappingKind::Code(counter) => {
+                    write_uleb128(&mut buffer, encode_term(expressions, term(counter)));
+                }
+                MappingKind::Branch { true_term, false_term } => {
+                    write_uleb128(&mut buffer, BRANCH_REGION << 3);
+                    write_uleb128(&mut buffer, encode_term(expressions, term(true_term)));
+                    write_uleb128(&mut buffer, encode_term(expressions, term(false_term)));
+                }
+                MappingKind::MCDCBranch { .. } | MappingKind::MCDCDecision(_) => unreachable!(),
+            }
+            let region = &mapping.code_region;
+            write_uleb128(&mut buffer, u64::from(region.start_line - previous_line));
+            write_uleb128(&mut buffer, u64::from(region.start_col));
+            write_uleb128(&mut buffer, u64::from(region.end_line - region.start_line));
+            write_uleb128(&mut buffer, u64::from(region.end_col));
+            previous_line = region.start_line;
+        }
+    }
+    buffer
+}
+
+/// Encodes a counter, whose two lowest bits are a tag for its kind, followed by its ID.
+fn encode_term(expressions: &IndexVec<ExpressionId, Expression>, term: CovTerm) -> u64 {
+    let (tag, id) = match term {
+        CovTerm::Zero => (0, 0),
+        CovTerm::Counter(id) => (1, id.as_u32()),
+        CovTerm::Expression(id) => match expressions[id].op {
+            Op::Subtract => (2, id.as_u32()),
+            Op::Add => (3, id.as_u32()),
+        },
+    };
+    tag | u64::from(id) << 2
+}
+
+fn write_uleb128(buffer: &mut Vec<u8>, value: u64) {
+    gimli::leb128::write::unsigned(buffer, value).unwrap();
+}
+
+fn pad_to_8(writer: &mut EndianVec<RunTimeEndian>) {
+    while writer.len() % 8 != 0 {
+        writer.write_u8(0).unwrap();
+    }
+}
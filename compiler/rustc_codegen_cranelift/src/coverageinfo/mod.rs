@@ -0,0 +1,162 @@
+//! Coverage instrumentation for `-Cinstrument-coverage`.
+//!
+//! Like in the LLVM backend, every instrumented function gets an array of counters, and the
+//! `CounterIncrement` coverage statements are lowered into increments of these counters. Cranelift
+//! has no equivalent of the LLVM passes that turn the counters into the profile data written by
+//! LLVM's profiler runtime and that write the coverage mappings read by `llvm-cov`, so the
+//! [`mapgen`] module writes these in the same format as LLVM.
+
+use cranelift_module::DataId;
+use rustc_index::IndexVec;
+use rustc_middle::mir::coverage::{CoverageKind, Expression, ExpressionId, Mapping};
+use rustc_middle::mir::mono::CodegenUnit;
+
+use crate::prelude::*;
+use crate::{BackendConfig, CodegenMode};
+
+mod mapgen;
+mod md5;
+
+pub(crate) use mapgen::CoverageMap;
+
+/// The coverage data of the functions codegened in a codegen unit, and of the functions inlined
+/// into them.
+pub(crate) struct CoverageContext {
+    /// The functions by symbol name.
+    functions: FxIndexMap<String, FunctionCoverage>,
+}
+
+struct FunctionCoverage {
+    /// The counters of the function, which are defined when the first counter increment of the
+    /// function is codegened.
+    counters: Option<DataId>,
+    num_counters: usize,
+    source_hash: u64,
+    expressions: IndexVec<ExpressionId, Expression>,
+    mappings: Vec<Mapping>,
+    /// Whether the function was codegened. The mappings of unused functions are emitted too, so
+    /// that `llvm-cov` reports them as never executed.
+    is_used: bool,
+}
+
+impl FunctionCoverage {
+    fn new(function_coverage_info: &mir::coverage::FunctionCoverageInfo, is_used: bool) -> Self {
+        FunctionCoverage {
+            counters: None,
+            num_counters: function_coverage_info.num_counters,
+            source_hash: function_coverage_info.function_source_hash,
+            expressions: function_coverage_info.expressions.clone(),
+            mappings: function_coverage_info.mappings.clone(),
+            is_used,
+        }
+    }
+}
+
+impl CoverageContext {
+    pub(crate) fn new(tcx: TyCtxt<'_>, backend_config: &BackendConfig) -> Self {
+        if !matches!(backend_config.codegen_mode, CodegenMode::Aot) {
+            tcx.dcx().fatal("-Cinstrument-coverage is not supported in jit mode");
+        }
+        if tcx.sess.instrument_coverage_mcdc() {
+            tcx.dcx().fatal("-Cinstrument-coverage=mcdc is not yet supported");
+        }
+        let binary_format = crate::target_triple(tcx.sess).binary_format;
+        if binary_format != target_lexicon::BinaryFormat::Elf {
+            tcx.dcx().fatal(format!(
+                "-Cinstrument-coverage is not yet supported for the {binary_format:?} binary format"
+            ));
+        }
+
+        CoverageContext { functions: FxIndexMap::default() }
+    }
+
+    /// Adds the unused functions to the coverage data if `cgu` is the codegen unit designated
+    /// for them, and encodes the coverage data of the codegen unit.
+    pub(crate) fn finalize(
+        mut self,
+        tcx: TyCtxt<'_>,
+        cgu: &CodegenUnit<'_>,
+    ) -> Option<CoverageMap> {
+        if cgu.is_code_coverage_dead_code_cgu() {
+            for (instance, function_coverage_info) in
+                rustc_codegen_ssa::coverageinfo::unused_functions(tcx)
+            {
+                let symbol_name = tcx.symbol_name(instance).name.to_owned();
+                self.functions
+                    .entry(symbol_name)
+                    .or_insert_with(|| FunctionCoverage::new(function_coverage_info, false));
+            }
+        }
+
+        if self.functions.is_empty() {
+            return None;
+        }
+        Some(mapgen::generate_coverage_map(tcx, &self.functions))
+    }
+}
+
+pub(crate) fn codegen_coverage<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    coverage: &Coverage,
+    scope: SourceScope,
+) {
+    match coverage.kind {
+        // Marker statements have no effect during codegen.
+        CoverageKind::SpanMarker | CoverageKind::BlockMarker { .. } => return,
+        // `CoverageContext::new` rejects MC/DC coverage, so these can only be inlined from an
+        // upstream crate, whose MC/DC bitmaps aren't updated.
+        CoverageKind::CondBitmapUpdate { .. } | CoverageKind::TestVectorBitmapUpdate { .. } => {
+            return;
+        }
+        CoverageKind::CounterIncrement { .. } | CoverageKind::ExpressionUsed { .. } => {}
+    }
+
+    // Determine the instance that coverage data was originally generated for.
+    let instance = if let Some(inlined) = scope.inlined_instance(&fx.mir.source_scopes) {
+        fx.monomorphize(inlined)
+    } else {
+        fx.instance
+    };
+    let Some(function_coverage_info) =
+        fx.tcx.instance_mir(instance.def).function_coverage_info.as_deref()
+    else {
+        return;
+    };
+
+    // Coverage statements can be inlined from an instrumented upstream crate into a crate that
+    // isn't instrumented.
+    let Some(coverage_context) = fx.cx.coverage_context.as_mut() else { return };
+    let symbol_name = fx.tcx.symbol_name(instance).name;
+    let function = coverage_context
+        .functions
+        .entry(symbol_name.to_owned())
+        .or_insert_with(|| FunctionCoverage::new(function_coverage_info, true));
+
+    if let CoverageKind::CounterIncrement { id } = coverage.kind {
+        let num_counters = function.num_counters;
+        let counters = *function
+            .counters
+            .get_or_insert_with(|| define_counters(&mut *fx.module, symbol_name, num_counters));
+        let counters = fx.module.declare_data_in_func(counters, &mut fx.bcx.func);
+        let counters = fx.bcx.ins().global_value(fx.pointer_type, counters);
+        // Like LLVM, increment the counter non-atomically.
+        let offset = i32::try_from(id.as_usize() * 8).unwrap();
+        let count = fx.bcx.ins().load(types::I64, MemFlags::trusted(), counters, offset);
+        let count = fx.bcx.ins().iadd_imm(count, 1);
+        fx.bcx.ins().store(MemFlags::trusted(), count, counters, offset);
+    }
+}
+
+/// Defines the zero-initialized counters of a function in the section from which LLVM's profiler
+/// runtime reads them.
+fn define_counters(module: &mut dyn Module, symbol_name: &str, num_counters: usize) -> DataId {
+    let data_id = module
+        .declare_data(&format!("__profc_{symbol_name}"), Linkage::Local, true, false)
+        .unwrap();
+    let mut data = DataDescription::new();
+    data.define(vec![0; num_counters * 8].into_boxed_slice());
+    data.set_align(8);
+    data.set_segment_section("", "__llvm_prf_cnts");
+    module.define_data(data_id, &data).unwrap();
+    data_id
+}
@@ -3,19 +3,25 @@
 mod emit;
 mod line_info;
 mod object;
+mod types;
 mod unwind;
+mod variables;
 
 use cranelift_codegen::ir::Endianness;
 use cranelift_codegen::isa::TargetIsa;
 use gimli::write::{
-    Address, AttributeValue, DwarfUnit, FileId, LineProgram, LineString, Range, RangeList,
-    UnitEntryId,
+    Address, AttributeValue, DwarfUnit, Expression, FileId, LineProgram, LineString, Range,
+    RangeList, UnitEntryId,
 };
 use gimli::{Encoding, Format, LineEncoding, RunTimeEndian};
 use indexmap::IndexSet;
+use rustc_index::IndexVec;
 
 pub(crate) use self::emit::{DebugReloc, DebugRelocName};
+pub(crate) use self::types::TypeDebugContext;
 pub(crate) use self::unwind::UnwindContext;
+use self::variables::VariableLocation;
+pub(crate) use self::variables::{define_variables, var_label, PlaceLocation};
 use crate::prelude::*;
 
 pub(crate) fn producer() -> String {
@@ -38,7 +44,13 @@ pub(crate) struct DebugContext {
 pub(crate) struct FunctionDebugContext {
     entry_id: UnitEntryId,
     function_source_loc: (FileId, u64, u64),
-    source_loc_set: IndexSet<(FileId, u64, u64)>,
+    source_loc_set: IndexSet<((FileId, u64, u64), SourceScope)>,
+    /// The parent of each source scope of the function.
+    scope_parents: IndexVec<SourceScope, Option<SourceScope>>,
+    /// The lexical block of each source scope that contains variables, or the function itself
+    /// for the outermost scope.
+    scope_entries: IndexVec<SourceScope, Option<UnitEntryId>>,
+    variables: Vec<(UnitEntryId, VariableLocation)>,
 }
 
 impl DebugContext {
@@ -122,9 +134,9 @@ pub(crate) fn define_function(
         &mut self,
         tcx: TyCtxt<'_>,
         name: &str,
-        function_span: Span,
+        mir: &Body<'_>,
     ) -> FunctionDebugContext {
-        let (file, line, column) = DebugContext::get_span_loc(tcx, function_span, function_span);
+        let (file, line, column) = DebugContext::get_span_loc(tcx, mir.span, mir.span);
 
         let file_id = self.add_source_file(&file);
 
@@ -142,10 +154,22 @@ pub(crate) fn define_function(
         entry.set(gimli::DW_AT_decl_line, AttributeValue::Udata(line));
         entry.set(gimli::DW_AT_decl_column, AttributeValue::Udata(column));
 
+        // The locations of the variables of the function on the stack are relative to the CFA.
+        let mut frame_base = Expression::new();
+        frame_base.op(gimli::DW_OP_call_frame_cfa);
+        entry.set(gimli::DW_AT_frame_base, AttributeValue::Exprloc(frame_base));
+
         FunctionDebugContext {
             entry_id,
             function_source_loc: (file_id, line, column),
             source_loc_set: IndexSet::new(),
+            scope_parents: mir.source_scopes.iter().map(|scope| scope.parent_scope).collect(),
+            scope_entries: mir
+                .source_scopes
+                .indices()
+                .map(|scope| (scope == OUTERMOST_SOURCE_SCOPE).then_some(entry_id))
+                .collect(),
+            variables: Vec::new(),
         }
     }
 }
@@ -154,12 +178,15 @@ impl FunctionDebugContext {
     pub(crate) fn finalize(
         mut self,
         debug_context: &mut DebugContext,
+        isa: &dyn TargetIsa,
         func_id: FuncId,
         context: &Context,
     ) {
         let symbol = func_id.as_u32() as usize;
 
         let end = self.create_debug_lines(debug_context, symbol, context);
+        self.finalize_scopes(debug_context, symbol, context);
+        self.finalize_variables(debug_context, isa, symbol, context);
 
         debug_context.unit_range_list.0.push(Range::StartLength {
             begin: Address::Symbol { symbol, addend: 0 },
@@ -0,0 +1,348 @@
+//! Types of the variables.
+
+use gimli::write::{AttributeValue, UnitEntryId};
+use rustc_codegen_ssa::debuginfo::type_names::compute_debuginfo_type_name;
+use rustc_codegen_ssa::debuginfo::wants_c_like_enum_debuginfo;
+use rustc_middle::ty::layout::{IntegerExt, PrimitiveExt};
+use rustc_middle::ty::util::IntTypeExt;
+use rustc_target::abi::{Integer, TagEncoding, Variants};
+
+use crate::prelude::*;
+
+/// The type DIEs of a codegen unit. They are shared by all functions of the codegen unit.
+#[derive(Default)]
+pub(crate) struct TypeDebugContext<'tcx> {
+    type_map: FxHashMap<Ty<'tcx>, UnitEntryId>,
+}
+
+/// The kind of metadata a pointer to a type carries.
+enum PointeeKind<'tcx> {
+    Thin,
+    Slice(Ty<'tcx>),
+    Dyn,
+}
+
+impl DebugContext {
+    pub(crate) fn debug_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        ty: Ty<'tcx>,
+    ) -> UnitEntryId {
+        if let Some(&type_id) = type_dbg.type_map.get(&ty) {
+            return type_id;
+        }
+
+        let layout = RevealAllLayoutCx(tcx).layout_of(ty);
+        let pointee = match ty.kind() {
+            ty::RawPtr(ty::TypeAndMut { ty: pointee_ty, .. }) | ty::Ref(_, pointee_ty, _) => {
+                Some(*pointee_ty)
+            }
+            ty::Adt(adt_def, args)
+                if adt_def.is_box()
+                    && RevealAllLayoutCx(tcx).layout_of(args.type_at(1)).is_zst() =>
+            {
+                Some(ty.boxed_ty())
+            }
+            _ => None,
+        };
+        let pointee_kind = pointee.map(|pointee_ty| {
+            match tcx.struct_tail_erasing_lifetimes(pointee_ty, ParamEnv::reveal_all()).kind() {
+                ty::Slice(elem_ty) => PointeeKind::Slice(*elem_ty),
+                ty::Str => PointeeKind::Slice(tcx.types.u8),
+                ty::Dynamic(..) => PointeeKind::Dyn,
+                _ => PointeeKind::Thin,
+            }
+        });
+
+        let tag = match (ty.kind(), &pointee_kind) {
+            (ty::Never | ty::Bool | ty::Char | ty::Int(_) | ty::Uint(_) | ty::Float(_), _) => {
+                gimli::DW_TAG_base_type
+            }
+            (ty::Tuple(elems), _) if elems.is_empty() => gimli::DW_TAG_base_type,
+            (_, Some(PointeeKind::Thin)) | (ty::FnPtr(_), _) => gimli::DW_TAG_pointer_type,
+            (ty::Array(..), _) => gimli::DW_TAG_array_type,
+            (ty::Adt(adt_def, _), _) if adt_def.is_union() => gimli::DW_TAG_union_type,
+            (ty::Adt(..), _) if wants_c_like_enum_debuginfo(layout) => {
+                gimli::DW_TAG_enumeration_type
+            }
+            _ => gimli::DW_TAG_structure_type,
+        };
+
+        // The entry is added to the type map before the types it refers to, so that recursive
+        // types refer to it instead of being defined again.
+        let name = compute_debuginfo_type_name(tcx, ty, true);
+        let type_id = self.add_type_entry(self.dwarf.unit.root(), tag, &name, layout.size);
+        type_dbg.type_map.insert(ty, type_id);
+
+        match (ty.kind(), pointee_kind) {
+            (ty::Never | ty::Tuple(_), _) if tag == gimli::DW_TAG_base_type => {
+                self.set_encoding(type_id, gimli::DW_ATE_unsigned);
+            }
+            (ty::Bool, _) => self.set_encoding(type_id, gimli::DW_ATE_boolean),
+            (ty::Char, _) => self.set_encoding(type_id, gimli::DW_ATE_UTF),
+            (ty::Int(_), _) => self.set_encoding(type_id, gimli::DW_ATE_signed),
+            (ty::Uint(_), _) => self.set_encoding(type_id, gimli::DW_ATE_unsigned),
+            (ty::Float(_), _) => self.set_encoding(type_id, gimli::DW_ATE_float),
+            (_, Some(PointeeKind::Thin)) => {
+                let pointee_id = self.debug_type(tcx, type_dbg, pointee.unwrap());
+                self.set_type(type_id, pointee_id);
+            }
+            (_, Some(pointee_kind)) => {
+                self.fat_pointer_members(tcx, type_dbg, type_id, pointee_kind)
+            }
+            (ty::FnPtr(_), _) => {
+                // FIXME describe the signature of the function
+                let subroutine_id =
+                    self.dwarf.unit.add(self.dwarf.unit.root(), gimli::DW_TAG_subroutine_type);
+                self.set_type(type_id, subroutine_id);
+            }
+            (&ty::Array(elem_ty, len), _) => {
+                let elem_id = self.debug_type(tcx, type_dbg, elem_ty);
+                self.set_type(type_id, elem_id);
+                let len = len.eval_target_usize(tcx, ParamEnv::reveal_all());
+                let subrange_id = self.dwarf.unit.add(type_id, gimli::DW_TAG_subrange_type);
+                let subrange = self.dwarf.unit.get_mut(subrange_id);
+                subrange.set(gimli::DW_AT_count, AttributeValue::Udata(len));
+            }
+            (ty::Adt(adt_def, _), _) if tag == gimli::DW_TAG_enumeration_type => {
+                // The enum is larger than its tag if it is over-aligned, e.g. with
+                // `#[repr(align(32))]`.
+                let tag_ty = match layout.variants {
+                    Variants::Single { .. } => adt_def.repr().discr_type().to_ty(tcx),
+                    Variants::Multiple { tag, .. } => tag.primitive().to_ty(tcx),
+                };
+                let signed = tag_ty.is_signed();
+                let tag_size = RevealAllLayoutCx(tcx).layout_of(tag_ty).size;
+                let tag_id = self.debug_type(tcx, type_dbg, tag_ty);
+                self.set_type(type_id, tag_id);
+                for (variant_index, discr) in adt_def.discriminants(tcx) {
+                    // Enumerators that can't be encoded are omitted, so that the debugger shows
+                    // their value as an integer instead of a wrong enumerator.
+                    let Some(value) = discr_value(tag_size, signed, discr.val) else {
+                        continue;
+                    };
+                    let name = adt_def.variant(variant_index).name.as_str();
+                    let enumerator_id = self.dwarf.unit.add(type_id, gimli::DW_TAG_enumerator);
+                    let name_id = self.dwarf.strings.add(name);
+                    let enumerator = self.dwarf.unit.get_mut(enumerator_id);
+                    enumerator.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+                    enumerator.set(gimli::DW_AT_const_value, value);
+                }
+            }
+            (ty::Adt(adt_def, _), _) if adt_def.is_enum() => {
+                self.enum_members(tcx, type_dbg, type_id, layout);
+            }
+            (ty::Adt(..) | ty::Tuple(_) | ty::Closure(..), _) => {
+                if let Variants::Single { .. } = layout.variants {
+                    self.field_members(tcx, type_dbg, type_id, layout);
+                }
+            }
+            // FIXME describe the remaining types, e.g. coroutines
+            _ => {}
+        }
+
+        type_id
+    }
+
+    fn add_type_entry(
+        &mut self,
+        parent_id: UnitEntryId,
+        tag: gimli::DwTag,
+        name: &str,
+        size: Size,
+    ) -> UnitEntryId {
+        let type_id = self.dwarf.unit.add(parent_id, tag);
+        let name_id = self.dwarf.strings.add(name);
+        let type_entry = self.dwarf.unit.get_mut(type_id);
+        type_entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+        type_entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(size.bytes()));
+        type_id
+    }
+
+    fn set_encoding(&mut self, type_id: UnitEntryId, encoding: gimli::DwAte) {
+        let type_entry = self.dwarf.unit.get_mut(type_id);
+        type_entry.set(gimli::DW_AT_encoding, AttributeValue::Encoding(encoding));
+    }
+
+    fn set_type(&mut self, entry_id: UnitEntryId, type_id: UnitEntryId) {
+        let entry = self.dwarf.unit.get_mut(entry_id);
+        entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(type_id));
+    }
+
+    fn add_member(
+        &mut self,
+        parent_id: UnitEntryId,
+        name: &str,
+        type_id: UnitEntryId,
+        offset: Size,
+    ) -> UnitEntryId {
+        let member_id = self.dwarf.unit.add(parent_id, gimli::DW_TAG_member);
+        let name_id = self.dwarf.strings.add(name);
+        let member = self.dwarf.unit.get_mut(member_id);
+        member.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+        member.set(gimli::DW_AT_type, AttributeValue::UnitRef(type_id));
+        member.set(gimli::DW_AT_data_member_location, AttributeValue::Udata(offset.bytes()));
+        member_id
+    }
+
+    /// Fat pointers are described as a struct of the data pointer and the metadata, like the LLVM
+    /// backend does.
+    fn fat_pointer_members<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        type_id: UnitEntryId,
+        pointee_kind: PointeeKind<'tcx>,
+    ) {
+        let (data_name, data_ty, meta_name, meta_ty) = match pointee_kind {
+            PointeeKind::Thin => unreachable!(),
+            PointeeKind::Slice(elem_ty) => {
+                ("data_ptr", Ty::new_imm_ptr(tcx, elem_ty), "length", tcx.types.usize)
+            }
+            PointeeKind::Dyn => {
+                let unit_ptr = Ty::new_imm_ptr(tcx, tcx.types.unit);
+                ("pointer", unit_ptr, "vtable", unit_ptr)
+            }
+        };
+        let data_id = self.debug_type(tcx, type_dbg, data_ty);
+        let meta_id = self.debug_type(tcx, type_dbg, meta_ty);
+        self.add_member(type_id, data_name, data_id, Size::ZERO);
+        self.add_member(type_id, meta_name, meta_id, tcx.data_layout.pointer_size);
+    }
+
+    /// Add a member for each field of a struct, union, tuple, closure or enum variant.
+    fn field_members<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        type_id: UnitEntryId,
+        layout: TyAndLayout<'tcx>,
+    ) {
+        let cx = RevealAllLayoutCx(tcx);
+        let capture_names = match *layout.ty.kind() {
+            ty::Closure(def_id, _) => Some(tcx.closure_saved_names_of_captured_variables(def_id)),
+            _ => None,
+        };
+        for i in 0..layout.fields.count() {
+            let field_name = match (layout.ty.kind(), layout.variants) {
+                (ty::Adt(adt_def, _), Variants::Single { index }) => {
+                    adt_def.variant(index).fields[FieldIdx::new(i)].name.to_string()
+                }
+                _ => match capture_names {
+                    Some(capture_names) => capture_names[FieldIdx::new(i)].to_string(),
+                    None => format!("__{i}"),
+                },
+            };
+            let field_layout = layout.field(&cx, i);
+            let field_type_id = self.debug_type(tcx, type_dbg, field_layout.ty);
+            self.add_member(type_id, &field_name, field_type_id, layout.fields.offset(i));
+        }
+    }
+
+    /// Enums are described with a variant part, like the LLVM backend does for DWARF:
+    ///
+    /// ```txt
+    /// DW_TAG_structure_type           (the enum)
+    ///   DW_TAG_variant_part
+    ///     DW_AT_discr                 (reference to the tag member)
+    ///     DW_TAG_member               (the tag)
+    ///     DW_TAG_variant
+    ///       DW_AT_discr_value         (the value of the tag for this variant)
+    ///       DW_TAG_member             (the variant, of the type below)
+    ///   DW_TAG_structure_type         (the fields of the variant)
+    /// ```
+    ///
+    /// The variant whose tag isn't stored in a niche has no `DW_AT_discr_value`, which makes it
+    /// the default variant. If the value of a tag can't be encoded, only the structure of the enum
+    /// itself is described.
+    fn enum_members<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        type_id: UnitEntryId,
+        layout: TyAndLayout<'tcx>,
+    ) {
+        let ty::Adt(adt_def, _) = *layout.ty.kind() else { unreachable!() };
+        let cx = RevealAllLayoutCx(tcx);
+
+        let variants: Vec<(VariantIdx, Option<AttributeValue>)> = match &layout.variants {
+            Variants::Single { index } => {
+                if adt_def.variants().is_empty() {
+                    return;
+                }
+                vec![(*index, None)]
+            }
+            Variants::Multiple { tag, tag_encoding, variants, .. } => {
+                let tag_size = tag.size(&tcx);
+                let discr_values = variants.indices().map(|variant_index| {
+                    let value = match *tag_encoding {
+                        TagEncoding::Direct => {
+                            layout.ty.discriminant_for_variant(tcx, variant_index).unwrap().val
+                        }
+                        TagEncoding::Niche {
+                            untagged_variant,
+                            ref niche_variants,
+                            niche_start,
+                        } => {
+                            if variant_index == untagged_variant {
+                                return Some((variant_index, None));
+                            }
+                            let niche_value =
+                                variant_index.as_u32() - niche_variants.start().as_u32();
+                            u128::from(niche_value).wrapping_add(niche_start)
+                        }
+                    };
+                    Some((variant_index, Some(discr_value(tag_size, false, value)?)))
+                });
+                // Without the value of every tag the debugger would pick the wrong variant, so
+                // the variants are omitted altogether if a tag doesn't fit in 64 bits.
+                match discr_values.collect() {
+                    Some(variants) => variants,
+                    None => return,
+                }
+            }
+        };
+
+        let variant_part_id = self.dwarf.unit.add(type_id, gimli::DW_TAG_variant_part);
+        if let Variants::Multiple { tag, tag_field, .. } = layout.variants {
+            let tag_ty = Integer::from_size(tag.size(&tcx)).unwrap().to_ty(tcx, false);
+            let tag_type_id = self.debug_type(tcx, type_dbg, tag_ty);
+            let tag_offset = layout.fields.offset(tag_field);
+            let tag_member_id = self.add_member(variant_part_id, "", tag_type_id, tag_offset);
+            let tag_member = self.dwarf.unit.get_mut(tag_member_id);
+            tag_member.set(gimli::DW_AT_artificial, AttributeValue::Flag(true));
+            let variant_part = self.dwarf.unit.get_mut(variant_part_id);
+            variant_part.set(gimli::DW_AT_discr, AttributeValue::UnitRef(tag_member_id));
+        }
+
+        for (variant_index, discr_value) in variants {
+            let variant_name = adt_def.variant(variant_index).name.as_str();
+            let variant_layout = layout.for_variant(&cx, variant_index);
+            let variant_type_id = self.add_type_entry(
+                type_id,
+                gimli::DW_TAG_structure_type,
+                variant_name,
+                layout.size,
+            );
+            self.field_members(tcx, type_dbg, variant_type_id, variant_layout);
+            let variant_id = self.dwarf.unit.add(variant_part_id, gimli::DW_TAG_variant);
+            if let Some(discr_value) = discr_value {
+                let variant = self.dwarf.unit.get_mut(variant_id);
+                variant.set(gimli::DW_AT_discr_value, discr_value);
+            }
+            self.add_member(variant_id, variant_name, variant_type_id, Size::ZERO);
+        }
+    }
+}
+
+/// The value of a tag as a DWARF constant, or `None` if it doesn't fit in 64 bits, which is the
+/// most gimli can encode. This is the case for some values of `#[repr(u128)]` enums.
+fn discr_value(size: Size, signed: bool, value: u128) -> Option<AttributeValue> {
+    if signed {
+        let value = size.sign_extend(value) as i128;
+        i64::try_from(value).ok().map(AttributeValue::Sdata)
+    } else {
+        u64::try_from(size.truncate(value)).ok().map(AttributeValue::Udata)
+    }
+}
@@ -0,0 +1,335 @@
+//! Local variables and function parameters.
+
+use cranelift_codegen::entity::EntityRef;
+use cranelift_codegen::ir::{LabelValueLoc, ValueLabel};
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::{MachSrcLoc, ValueLabelsRanges, ValueLocRange};
+use gimli::write::{
+    Address, AttributeValue, Expression, Location, LocationList, Range, RangeList, UnitEntryId,
+};
+use gimli::Register;
+use rustc_index::IndexVec;
+
+use super::types::TypeDebugContext;
+use super::FunctionDebugContext;
+use crate::prelude::*;
+
+/// Where the value of a place lives during the execution of the function.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum PlaceLocation {
+    Var(Variable),
+    VarPair(Variable, Variable),
+    /// At an offset from an address that isn't a stack slot, e.g. for arguments passed by
+    /// reference.
+    Addr(Value, i64),
+    Stack(StackSlot, i64),
+}
+
+/// The location of a variable, resolved once the function is compiled.
+pub(super) enum VariableLocation {
+    /// The variable is stored in the labeled value.
+    Value(ValueLabel),
+    /// The variable is a scalar pair stored in two labeled values, with the given sizes and
+    /// padding between them.
+    ValuePair((ValueLabel, u64), u64, (ValueLabel, u64)),
+    /// The variable is in memory, at an offset from the labeled address.
+    Indirect(ValueLabel, i64),
+    Stack(StackSlot, i64),
+}
+
+/// The value label of an SSA variable.
+pub(crate) fn var_label(var: Variable) -> ValueLabel {
+    ValueLabel::new(var.index())
+}
+
+/// SSA variables use their index as value label, so the labels of addresses are allocated from the
+/// other end of the label space.
+fn addr_label(local: Local) -> ValueLabel {
+    ValueLabel::from_u32(u32::MAX - 1 - local.as_u32())
+}
+
+/// Define the variables and parameters of the function, once its body has been translated and
+/// the places of its locals are known.
+pub(crate) fn define_variables<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    type_dbg: &mut TypeDebugContext<'tcx>,
+) {
+    let (Some(debug_context), Some(func_debug_cx)) =
+        (fx.cx.debug_context.as_mut(), fx.func_debug_cx.as_mut())
+    else {
+        return;
+    };
+
+    for var_debug_info in &fx.mir.var_debug_info {
+        // FIXME describe constants, projections and composite variables
+        let VarDebugInfoContents::Place(place) = var_debug_info.value else { continue };
+        if !place.projection.is_empty() || var_debug_info.composite.is_some() {
+            continue;
+        }
+
+        let local = place.local;
+        let place = fx.local_map[local];
+        let location = place.debug_location().map(|location| match location {
+            PlaceLocation::Var(var) => VariableLocation::Value(var_label(var)),
+            PlaceLocation::VarPair(var1, var2) => {
+                let Abi::ScalarPair(a, b) = place.layout().abi else { unreachable!() };
+                let a_size = a.size(&fx.tcx);
+                let b_offset = a_size.align_to(b.align(&fx.tcx).abi);
+                VariableLocation::ValuePair(
+                    (var_label(var1), a_size.bytes()),
+                    (b_offset - a_size).bytes(),
+                    (var_label(var2), b.size(&fx.tcx).bytes()),
+                )
+            }
+            PlaceLocation::Addr(addr, offset) => {
+                let label = addr_label(local);
+                fx.bcx.set_val_label(addr, label);
+                VariableLocation::Indirect(label, offset)
+            }
+            PlaceLocation::Stack(stack_slot, offset) => VariableLocation::Stack(stack_slot, offset),
+        });
+
+        let type_id = debug_context.debug_type(fx.tcx, type_dbg, place.layout().ty);
+        let (file, line, _column) =
+            DebugContext::get_span_loc(fx.tcx, fx.mir.span, var_debug_info.source_info.span);
+        let file_id = debug_context.add_source_file(&file);
+
+        let tag = if var_debug_info.argument_index.is_some() {
+            gimli::DW_TAG_formal_parameter
+        } else {
+            gimli::DW_TAG_variable
+        };
+        let scope_id = func_debug_cx.scope_entry(debug_context, var_debug_info.source_info.scope);
+        let var_id = debug_context.dwarf.unit.add(scope_id, tag);
+        let name_id = debug_context.dwarf.strings.add(var_debug_info.name.as_str());
+        let var_entry = debug_context.dwarf.unit.get_mut(var_id);
+        var_entry.set(gimli::DW_AT_name, AttributeValue::StringRef(name_id));
+        var_entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(type_id));
+        var_entry.set(gimli::DW_AT_decl_file, AttributeValue::FileIndex(Some(file_id)));
+        var_entry.set(gimli::DW_AT_decl_line, AttributeValue::Udata(line));
+
+        if let Some(location) = location {
+            func_debug_cx.variables.push((var_id, location));
+        }
+    }
+}
+
+impl FunctionDebugContext {
+    /// The entry of the lexical block of a source scope. It is created on first use, together with
+    /// the blocks of the parent scopes.
+    ///
+    /// Scopes inlined from other functions are described as lexical blocks too.
+    fn scope_entry(&mut self, debug_context: &mut DebugContext, scope: SourceScope) -> UnitEntryId {
+        if let Some(entry_id) = self.scope_entries[scope] {
+            return entry_id;
+        }
+        let parent_id = self.scope_entry(debug_context, self.scope_parents[scope].unwrap());
+        let entry_id = debug_context.dwarf.unit.add(parent_id, gimli::DW_TAG_lexical_block);
+        self.scope_entries[scope] = Some(entry_id);
+        entry_id
+    }
+
+    /// Set the code ranges of the lexical blocks, now that it is known which instructions belong
+    /// to which source scope. The instructions of a scope also belong to all of its parents.
+    pub(super) fn finalize_scopes(
+        &self,
+        debug_context: &mut DebugContext,
+        symbol: usize,
+        context: &Context,
+    ) {
+        let has_lexical_block = |scope: SourceScope| {
+            scope != OUTERMOST_SOURCE_SCOPE && self.scope_entries[scope].is_some()
+        };
+        if !self.scope_entries.indices().any(has_lexical_block) {
+            return;
+        }
+
+        let mut scope_ranges: IndexVec<SourceScope, Vec<(u32, u32)>> =
+            self.scope_entries.iter().map(|_| vec![]).collect();
+        let mcr = context.compiled_code().unwrap();
+        for &MachSrcLoc { start, end, loc } in mcr.buffer.get_srclocs_sorted() {
+            if loc.is_default() {
+                continue;
+            }
+            let (_, mut scope) = self.source_loc_set[loc.bits() as usize];
+            loop {
+                if has_lexical_block(scope) {
+                    // The source locations are sorted, so adjacent ranges can be merged here.
+                    let ranges = &mut scope_ranges[scope];
+                    match ranges.last_mut() {
+                        Some((_, last_end)) if *last_end >= start => *last_end = end,
+                        _ => ranges.push((start, end)),
+                    }
+                }
+                match self.scope_parents[scope] {
+                    Some(parent) => scope = parent,
+                    None => break,
+                }
+            }
+        }
+
+        for (scope, ranges) in scope_ranges.into_iter_enumerated() {
+            if !has_lexical_block(scope) {
+                continue;
+            }
+            let ranges = ranges
+                .into_iter()
+                .map(|(start, end)| Range::StartEnd {
+                    begin: Address::Symbol { symbol, addend: i64::from(start) },
+                    end: Address::Symbol { symbol, addend: i64::from(end) },
+                })
+                .collect();
+            let range_list_id = debug_context.dwarf.unit.ranges.add(RangeList(ranges));
+            let entry = debug_context.dwarf.unit.get_mut(self.scope_entries[scope].unwrap());
+            entry.set(gimli::DW_AT_ranges, AttributeValue::RangeListRef(range_list_id));
+        }
+    }
+
+    /// Set the locations of the variables of the function, now that it is compiled.
+    pub(super) fn finalize_variables(
+        &mut self,
+        debug_context: &mut DebugContext,
+        isa: &dyn TargetIsa,
+        symbol: usize,
+        context: &Context,
+    ) {
+        let compiled_code = context.compiled_code().unwrap();
+        let value_labels_ranges = &compiled_code.value_labels_ranges;
+
+        for (var_id, location) in std::mem::take(&mut self.variables) {
+            let location = match location {
+                VariableLocation::Value(label) => {
+                    let ranges = label_ranges(value_labels_ranges, label);
+                    location_list(debug_context, symbol, &ranges, |expr, loc| {
+                        value_location(isa, expr, loc)
+                    })
+                }
+                VariableLocation::ValuePair((label_a, size_a), padding, (label_b, size_b)) => {
+                    // Only describe the variable where both of its halves are known.
+                    let ranges = intersect_ranges(
+                        &label_ranges(value_labels_ranges, label_a),
+                        &label_ranges(value_labels_ranges, label_b),
+                    );
+                    location_list(debug_context, symbol, &ranges, |expr, (loc_a, loc_b)| {
+                        value_location(isa, expr, loc_a)?;
+                        expr.op_piece(size_a);
+                        if padding != 0 {
+                            expr.op_piece(padding);
+                        }
+                        value_location(isa, expr, loc_b)?;
+                        expr.op_piece(size_b);
+                        Some(())
+                    })
+                }
+                VariableLocation::Indirect(label, offset) => {
+                    let ranges = label_ranges(value_labels_ranges, label);
+                    location_list(debug_context, symbol, &ranges, |expr, loc| {
+                        match loc {
+                            LabelValueLoc::Reg(reg) => {
+                                let reg = isa.map_regalloc_reg_to_dwarf(reg).ok()?;
+                                expr.op_breg(Register(reg), offset);
+                            }
+                            LabelValueLoc::CFAOffset(cfa_offset) => {
+                                expr.op_fbreg(cfa_offset);
+                                expr.op_deref();
+                                expr.op_plus_uconst(u64::try_from(offset).unwrap());
+                            }
+                        }
+                        Some(())
+                    })
+                }
+                VariableLocation::Stack(stack_slot, offset) => {
+                    // Stack slots are addressed relative to the stack pointer once the prologue
+                    // has run, like Cranelift does itself.
+                    match stack_pointer(isa) {
+                        Some(sp) => {
+                            let slot_offset = compiled_code.sized_stackslot_offsets[stack_slot];
+                            let mut expr = Expression::new();
+                            expr.op_breg(sp, i64::from(slot_offset) + offset);
+                            AttributeValue::Exprloc(expr)
+                        }
+                        None => continue,
+                    }
+                }
+            };
+            debug_context.dwarf.unit.get_mut(var_id).set(gimli::DW_AT_location, location);
+        }
+    }
+}
+
+/// Describe where a value is stored. Returns `None` if the location can't be described.
+fn value_location(isa: &dyn TargetIsa, expr: &mut Expression, loc: LabelValueLoc) -> Option<()> {
+    match loc {
+        LabelValueLoc::Reg(reg) => {
+            let reg = isa.map_regalloc_reg_to_dwarf(reg).ok()?;
+            expr.op_reg(Register(reg));
+        }
+        LabelValueLoc::CFAOffset(cfa_offset) => expr.op_fbreg(cfa_offset),
+    }
+    Some(())
+}
+
+/// The code ranges in which a labeled value is known, with its location.
+fn label_ranges(
+    value_labels_ranges: &ValueLabelsRanges,
+    label: ValueLabel,
+) -> Vec<(u32, u32, LabelValueLoc)> {
+    value_labels_ranges
+        .get(&label)
+        .map_or(&[][..], |ranges| &ranges[..])
+        .iter()
+        .map(|&ValueLocRange { loc, start, end }| (start, end, loc))
+        .collect()
+}
+
+fn location_list<L: Copy>(
+    debug_context: &mut DebugContext,
+    symbol: usize,
+    ranges: &[(u32, u32, L)],
+    describe: impl Fn(&mut Expression, L) -> Option<()>,
+) -> AttributeValue {
+    let locations = ranges
+        .iter()
+        .filter_map(|&(start, end, loc)| {
+            let mut data = Expression::new();
+            describe(&mut data, loc)?;
+            Some(Location::StartEnd {
+                begin: Address::Symbol { symbol, addend: i64::from(start) },
+                end: Address::Symbol { symbol, addend: i64::from(end) },
+                data,
+            })
+        })
+        .collect();
+    let location_list_id = debug_context.dwarf.unit.locations.add(LocationList(locations));
+    AttributeValue::LocationListRef(location_list_id)
+}
+
+/// The ranges in which both values are known, with their locations.
+fn intersect_ranges(
+    a: &[(u32, u32, LabelValueLoc)],
+    b: &[(u32, u32, LabelValueLoc)],
+) -> Vec<(u32, u32, (LabelValueLoc, LabelValueLoc))> {
+    let mut ranges = vec![];
+    for &(start_a, end_a, loc_a) in a {
+        for &(start_b, end_b, loc_b) in b {
+            let start = start_a.max(start_b);
+            let end = end_a.min(end_b);
+            if start < end {
+                ranges.push((start, end, (loc_a, loc_b)));
+            }
+        }
+    }
+    ranges.sort_by_key(|&(start, _, _)| start);
+    ranges
+}
+
+fn stack_pointer(isa: &dyn TargetIsa) -> Option<Register> {
+    use target_lexicon::Architecture;
+    match isa.triple().architecture {
+        Architecture::X86_64 => Some(gimli::X86_64::RSP),
+        Architecture::Aarch64(_) => Some(gimli::AArch64::SP),
+        Architecture::Riscv64(_) => Some(gimli::RiscV::SP),
+        Architecture::S390x => Some(Register(15)),
+        _ => None,
+    }
+}
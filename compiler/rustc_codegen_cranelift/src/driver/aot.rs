@@ -20,6 +20,8 @@
 use rustc_session::Session;
 
 use crate::concurrency_limiter::{ConcurrencyLimiter, ConcurrencyLimiterToken};
+use crate::coverageinfo::CoverageMap;
+use crate::debuginfo::TypeDebugContext;
 use crate::global_asm::GlobalAsmConfig;
 use crate::{prelude::*, BackendConfig};
 
@@ -142,6 +144,7 @@ fn emit_cgu(
     module: ObjectModule,
     debug: Option<DebugContext>,
     unwind_context: UnwindContext,
+    coverage_map: Option<CoverageMap>,
     global_asm_object_file: Option<PathBuf>,
 ) -> Result<ModuleCodegenResult, String> {
     let mut product = module.finish();
@@ -150,6 +153,10 @@ fn emit_cgu(
         debug.emit(&mut product);
     }
 
+    if let Some(coverage_map) = coverage_map {
+        coverage_map.emit(&mut product);
+    }
+
     unwind_context.emit(&mut product);
 
     let module_regular =
@@ -268,7 +275,7 @@ fn module_codegen(
         ConcurrencyLimiterToken,
     ),
 ) -> OngoingModuleCodegen {
-    let (cgu_name, mut cx, mut module, codegened_functions) =
+    let (cgu_name, mut cx, mut module, codegened_functions, coverage_map) =
         tcx.prof.generic_activity_with_arg("codegen cgu", cgu_name.as_str()).run(|| {
             let cgu = tcx.codegen_unit(cgu_name);
             let mono_items = cgu.items_in_deterministic_order(tcx);
@@ -282,6 +289,7 @@ fn module_codegen(
                 tcx.sess.opts.debuginfo != DebugInfo::None,
                 cgu_name,
             );
+            let mut type_dbg = TypeDebugContext::default();
             super::predefine_mono_items(tcx, &mut module, &mono_items);
             let mut codegened_functions = vec![];
             for (mono_item, _) in mono_items {
@@ -290,6 +298,7 @@ fn module_codegen(
                         let codegened_function = crate::base::codegen_fn(
                             tcx,
                             &mut cx,
+                            &mut type_dbg,
                             Function::new(),
                             &mut module,
                             inst,
@@ -316,9 +325,13 @@ fn module_codegen(
                 cgu.is_primary(),
             );
 
+            // The coverage map is encoded here since it needs the interned file names.
+            let coverage_map =
+                cx.coverage_context.take().and_then(|coverage| coverage.finalize(tcx, cgu));
+
             let cgu_name = cgu.name().as_str().to_owned();
 
-            (cgu_name, cx, module, codegened_functions)
+            (cgu_name, cx, module, codegened_functions, coverage_map)
         });
 
     OngoingModuleCodegen::Async(std::thread::spawn(move || {
@@ -347,6 +360,7 @@ fn module_codegen(
                     module,
                     cx.debug_context,
                     cx.unwind_context,
+                    coverage_map,
                     global_asm_object_file,
                 )
             });
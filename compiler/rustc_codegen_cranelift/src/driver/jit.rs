@@ -12,6 +12,7 @@
 use rustc_session::Session;
 use rustc_span::Symbol;
 
+use crate::debuginfo::TypeDebugContext;
 use crate::{prelude::*, BackendConfig};
 use crate::{CodegenCx, CodegenMode};
 
@@ -229,7 +230,9 @@ pub(crate) fn codegen_and_compile_fn<'tcx>(
             crate::PrintOnPanic(|| format!("{:?} {}", instance, tcx.symbol_name(instance).name));
 
         let cached_func = std::mem::replace(&mut cached_context.func, Function::new());
-        let codegened_func = crate::base::codegen_fn(tcx, cx, cached_func, module, instance);
+        let mut type_dbg = TypeDebugContext::default();
+        let codegened_func =
+            crate::base::codegen_fn(tcx, cx, &mut type_dbg, cached_func, module, instance);
 
         crate::base::compile_fn(cx, cached_context, module, codegened_func);
     });
@@ -59,6 +59,7 @@
 mod concurrency_limiter;
 mod config;
 mod constant;
+mod coverageinfo;
 mod debuginfo;
 mod discriminant;
 mod driver;
@@ -130,6 +131,7 @@ struct CodegenCx {
     inline_asm_index: Cell<usize>,
     debug_context: Option<DebugContext>,
     unwind_context: UnwindContext,
+    coverage_context: Option<crate::coverageinfo::CoverageContext>,
     cgu_name: Symbol,
 }
 
@@ -150,6 +152,11 @@ fn new(
         } else {
             None
         };
+        let coverage_context = if tcx.sess.instrument_coverage() {
+            Some(crate::coverageinfo::CoverageContext::new(tcx, &backend_config))
+        } else {
+            None
+        };
         CodegenCx {
             profiler: tcx.prof.clone(),
             output_filenames: tcx.output_filenames(()).clone(),
@@ -158,6 +165,7 @@ fn new(
             inline_asm_index: Cell::new(0),
             debug_context,
             unwind_context,
+            coverage_context,
             cgu_name,
         }
     }
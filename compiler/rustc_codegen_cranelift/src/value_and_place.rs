@@ -4,6 +4,7 @@
 use cranelift_codegen::ir::immediates::Offset32;
 use rustc_middle::ty::FnSig;
 
+use crate::debuginfo::{var_label, PlaceLocation};
 use crate::prelude::*;
 
 fn codegen_field<'tcx>(
@@ -452,14 +453,14 @@ pub(crate) fn to_cvalue(self, fx: &mut FunctionCx<'_, '_, 'tcx>) -> CValue<'tcx>
         match self.inner {
             CPlaceInner::Var(_local, var) => {
                 let val = fx.bcx.use_var(var);
-                //fx.bcx.set_val_label(val, cranelift_codegen::ir::ValueLabel::new(var.index()));
+                fx.bcx.set_val_label(val, var_label(var));
                 CValue::by_val(val, layout)
             }
             CPlaceInner::VarPair(_local, var1, var2) => {
                 let val1 = fx.bcx.use_var(var1);
-                //fx.bcx.set_val_label(val1, cranelift_codegen::ir::ValueLabel::new(var1.index()));
+                fx.bcx.set_val_label(val1, var_label(var1));
                 let val2 = fx.bcx.use_var(var2);
-                //fx.bcx.set_val_label(val2, cranelift_codegen::ir::ValueLabel::new(var2.index()));
+                fx.bcx.set_val_label(val2, var_label(var2));
                 CValue::by_val_pair(val1, val2, layout)
             }
             CPlaceInner::Addr(ptr, extra) => {
@@ -496,6 +497,25 @@ pub(crate) fn debug_comment(self) -> (&'static str, String) {
         }
     }
 
+    /// Where the value of this place lives, for the debuginfo of the variables stored in it.
+    /// Returns `None` for zero-sized and unsized places.
+    pub(crate) fn debug_location(self) -> Option<PlaceLocation> {
+        match self.inner {
+            CPlaceInner::Var(_local, var) => Some(PlaceLocation::Var(var)),
+            CPlaceInner::VarPair(_local, var1, var2) => Some(PlaceLocation::VarPair(var1, var2)),
+            CPlaceInner::Addr(ptr, None) => match ptr.debug_base_and_offset() {
+                (crate::pointer::PointerBase::Addr(addr), offset) => {
+                    Some(PlaceLocation::Addr(addr, offset.into()))
+                }
+                (crate::pointer::PointerBase::Stack(stack_slot), offset) => {
+                    Some(PlaceLocation::Stack(stack_slot, offset.into()))
+                }
+                (crate::pointer::PointerBase::Dangling(_), _) => None,
+            },
+            CPlaceInner::Addr(_, Some(_)) => None,
+        }
+    }
+
     #[track_caller]
     pub(crate) fn to_ptr(self) -> Pointer {
         match self.inner {
@@ -580,7 +600,7 @@ fn transmute_scalar<'tcx>(
                 // calling `write_cvalue` you need to add a `bint` instruction.
                 _ => unreachable!("write_cvalue_transmute: {:?} -> {:?}", src_ty, dst_ty),
             };
-            //fx.bcx.set_val_label(data, cranelift_codegen::ir::ValueLabel::new(var.index()));
+            fx.bcx.set_val_label(data, var_label(var));
             fx.bcx.def_var(var, data);
         }
 
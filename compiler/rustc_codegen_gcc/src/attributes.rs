@@ -73,6 +73,8 @@ pub fn from_fn_attrs<'gcc, 'tcx>(
         }
     }
 
+    // FIXME: `#[target_clones]` is not supported yet, so only the `default` clone is generated and
+    // called directly.
     let function_features =
         codegen_fn_attrs.target_features.iter().map(|features| features.as_str()).collect::<Vec<&str>>();
 
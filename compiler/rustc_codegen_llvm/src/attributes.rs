@@ -1,8 +1,11 @@
 //! Set and unset common attributes on LLVM values.
 
+use rustc_codegen_ssa::common::{self, AtomicOrdering, IntPredicate, TypeKind};
 use rustc_codegen_ssa::traits::*;
 use rustc_hir::def_id::DefId;
+use rustc_hir::LangItem;
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::ty::layout::FnAbiOf;
 use rustc_middle::ty::{self, TyCtxt};
 use rustc_session::config::{FunctionReturn, OptLevel};
 use rustc_span::symbol::sym;
@@ -10,7 +13,9 @@
 use rustc_target::spec::{FramePointer, SanitizerSet, StackProbeType, StackProtector};
 use smallvec::SmallVec;
 
+use crate::abi::FnAbiLlvmExt;
 use crate::attributes;
+use crate::builder::Builder;
 use crate::errors::{MissingFeatures, SanitizerMemtagRequiresMte, TargetFeatureDisableOrEnable};
 use crate::llvm::AttributePlace::Function;
 use crate::llvm::{self, AllocKindFlags, Attribute, AttributeKind, AttributePlace, MemoryEffects};
@@ -503,3 +508,148 @@ pub fn from_fn_attrs<'ll, 'tcx>(
 fn wasm_import_module(tcx: TyCtxt<'_>, id: DefId) -> Option<&String> {
     tcx.wasm_import_module_map(id.krate).get(&id)
 }
+
+/// Creates the clones of a `#[target_clones]` function once its body has been defined.
+///
+/// The body is cloned once per set of features, and the original function becomes the
+/// `default` clone. Calls through the symbol of the function go through a thunk that calls the
+/// clone selected by the resolver, the first one whose features the CPU supports. The resolver
+/// runs on the first call and its result is cached in a global.
+///
+/// We don't use an `ifunc` on ELF targets: the dynamic loader runs its resolver while processing
+/// relocations, when the code detecting the CPU features may not be relocated yet itself.
+pub fn create_target_clones<'ll, 'tcx>(cx: &CodegenCx<'ll, 'tcx>, instance: ty::Instance<'tcx>) {
+    let ty::InstanceDef::Item(def_id) = instance.def else { return };
+    if !cx.tcx.def_kind(def_id).has_codegen_attrs() {
+        return;
+    }
+    let codegen_fn_attrs = cx.tcx.codegen_fn_attrs(def_id);
+    if codegen_fn_attrs.target_clones.is_empty() {
+        return;
+    }
+    // Without a way to detect the CPU features, only the `default` clone is used, as
+    // `codegen_fn_attrs` warned.
+    if cx.tcx.lang_items().target_clone_supported_fn().is_none() {
+        return;
+    }
+
+    let llfn = cx.get_fn(instance);
+    let name = String::from_utf8(llvm::get_value_name(llfn).to_vec()).unwrap();
+    let linkage = unsafe { llvm::LLVMRustGetLinkage(llfn) };
+    let visibility = unsafe { llvm::LLVMRustGetVisibility(llfn) };
+    let fn_abi = cx.fn_abi_of_instance(instance, ty::List::empty());
+    let fn_ty = fn_abi.llvm_type(cx);
+    let mut new_globals = vec![];
+
+    let global_features = cx.tcx.global_backend_features(());
+    let clones: Vec<_> = codegen_fn_attrs
+        .target_clones
+        .iter()
+        .map(|clone| {
+            let suffix = clone.features.iter().map(|f| f.as_str()).intersperse("_");
+            let clone_name = format!("{name}.{}", suffix.collect::<String>());
+            let llclone = unsafe {
+                llvm::LLVMRustCloneFunction(llfn, clone_name.as_ptr().cast(), clone_name.len())
+            };
+            let function_features = codegen_fn_attrs
+                .target_features
+                .iter()
+                .chain(&clone.features)
+                .flat_map(|feat| llvm_util::to_llvm_features(cx.tcx.sess, feat.as_str()))
+                .map(|f| format!("+{f}"));
+            let target_features: String = global_features
+                .iter()
+                .cloned()
+                .chain(function_features)
+                .intersperse(",".to_string())
+                .collect();
+            let features =
+                llvm::CreateAttrStringValue(cx.llcx, "target-features", &target_features);
+            apply_to_llfn(llclone, Function, &[features]);
+            llvm::set_linkage(llclone, llvm::Linkage::InternalLinkage);
+            new_globals.push(llclone);
+            (clone, llclone)
+        })
+        .collect();
+
+    llvm::set_value_name(llfn, format!("{name}.default").as_bytes());
+    llvm::set_linkage(llfn, llvm::Linkage::InternalLinkage);
+
+    let resolver = cx.declare_cfn(
+        &format!("{name}.resolver"),
+        llvm::UnnamedAddr::Global,
+        cx.type_func(&[], cx.type_ptr()),
+    );
+    llvm::set_linkage(resolver, llvm::Linkage::InternalLinkage);
+    new_globals.push(resolver);
+
+    let cache = llvm::add_global(cx.llmod, cx.type_ptr(), &format!("{name}.cache"));
+    llvm::set_initializer(cache, cx.const_null(cx.type_ptr()));
+    llvm::set_linkage(cache, llvm::Linkage::InternalLinkage);
+    new_globals.push(cache);
+
+    // The resolver may run concurrently in several threads, which then store the same clone.
+    let dispatcher = cx.declare_fn(&name, fn_abi, Some(instance));
+    let ptr_size = cx.tcx.data_layout.pointer_size;
+    let start = Builder::append_block(cx, dispatcher, "start");
+    let resolve = Builder::append_block(cx, dispatcher, "resolve");
+    let call = Builder::append_block(cx, dispatcher, "call");
+    let mut bx = Builder::build(cx, start);
+    let cached = bx.atomic_load(cx.type_ptr(), cache, AtomicOrdering::Acquire, ptr_size);
+    let is_null = bx.icmp(IntPredicate::IntEQ, cached, cx.const_null(cx.type_ptr()));
+    bx.cond_br(is_null, resolve, call);
+
+    bx.switch_to_block(resolve);
+    let resolved = bx.call(cx.type_func(&[], cx.type_ptr()), None, None, resolver, &[], None);
+    bx.atomic_store(resolved, cache, AtomicOrdering::Release, ptr_size);
+    bx.br(call);
+
+    bx.switch_to_block(call);
+    let selected = bx.phi(cx.type_ptr(), &[cached, resolved], &[start, resolve]);
+    let args: Vec<_> = (0..unsafe { llvm::LLVMCountParams(dispatcher) })
+        .map(|i| llvm::get_param(dispatcher, i))
+        .collect();
+    let ret = bx.call(fn_ty, None, Some(fn_abi), selected, &args, None);
+    unsafe { llvm::LLVMRustSetTailCallKind(ret, llvm::TailCallKind::MustTail) };
+    if cx.type_kind(cx.val_ty(ret)) == TypeKind::Void {
+        bx.ret_void();
+    } else {
+        bx.ret(ret);
+    }
+
+    llvm::set_linkage(dispatcher, linkage);
+    llvm::set_visibility(dispatcher, visibility);
+    if matches!(linkage, llvm::Linkage::LinkOnceODRLinkage | llvm::Linkage::WeakODRLinkage) {
+        llvm::set_comdat(cx.llmod, dispatcher, &name);
+        for &global in &new_globals {
+            llvm::set_comdat(cx.llmod, global, &name);
+        }
+    }
+
+    // Calls in this module, including the recursive calls of the clones, now go through the
+    // dispatcher. The resolver is filled afterwards, so it still returns the `default` clone.
+    unsafe { llvm::LLVMReplaceAllUsesWith(llfn, dispatcher) };
+
+    let mut bx = Builder::build(cx, Builder::append_block(cx, resolver, "start"));
+    let (supported_abi, supported_fn) =
+        common::build_langcall(&bx, None, LangItem::TargetCloneSupported);
+    for (clone, llclone) in clones {
+        let features = clone.features.iter().map(|f| f.as_str()).intersperse(",");
+        let (features, len) = cx.const_str(&features.collect::<String>());
+        let supported = bx.call(
+            supported_abi.llvm_type(cx),
+            None,
+            Some(supported_abi),
+            supported_fn,
+            &[features, len],
+            None,
+        );
+        let selected = bx.append_sibling_block("selected");
+        let next = bx.append_sibling_block("next");
+        bx.cond_br(supported, selected, next);
+        bx.switch_to_block(selected);
+        bx.ret(llclone);
+        bx.switch_to_block(next);
+    }
+    bx.ret(llfn);
+}
@@ -210,7 +210,8 @@ pub fn target_machine_factory(
     let abi = SmallCStr::new(&sess.target.llvm_abiname);
     let trap_unreachable =
         sess.opts.unstable_opts.trap_unreachable.unwrap_or(sess.target.trap_unreachable);
-    let emit_stack_size_section = sess.opts.unstable_opts.emit_stack_sizes;
+    let emit_stack_size_section =
+        sess.opts.unstable_opts.emit_stack_sizes || sess.opts.unstable_opts.stack_report.enabled();
 
     let asm_comments = sess.opts.unstable_opts.asm_comments;
     let relax_elf_relocations =
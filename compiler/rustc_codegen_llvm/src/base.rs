@@ -26,7 +26,7 @@
 use rustc_data_structures::small_c_str::SmallCStr;
 use rustc_middle::dep_graph;
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrs;
-use rustc_middle::mir::mono::{Linkage, Visibility};
+use rustc_middle::mir::mono::{Linkage, MonoItem, Visibility};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::config::DebugInfo;
 use rustc_span::symbol::Symbol;
@@ -93,6 +93,13 @@ fn module_codegen(tcx: TyCtxt<'_>, cgu_name: Symbol) -> ModuleCodegen<ModuleLlvm
                 mono_item.define::<Builder<'_, '_, '_>>(&cx);
             }
 
+            // The clones of `#[target_clones]` functions are created from their definitions.
+            for &(mono_item, _) in &mono_items {
+                if let MonoItem::Fn(instance) = mono_item {
+                    attributes::create_target_clones(&cx, instance);
+                }
+            }
+
             // If this codegen unit contains the main function, also create the
             // wrapper here
             if let Some(entry) = maybe_create_entry_wrapper::<Builder<'_, '_, '_>>(&cx) {
@@ -1,4 +1,4 @@
-use rustc_middle::mir::coverage::{CounterId, CovTerm, ExpressionId};
+use rustc_middle::mir::coverage::{CodeRegion, CounterId, CovTerm, ExpressionId, MappingKind};
 
 /// Must match the layout of `LLVMRustCounterKind`.
 #[derive(Copy, Clone, Debug)]
@@ -99,6 +99,32 @@ pub enum RegionKind {
     /// associated with two counters, each representing the number of times the
     /// expression evaluates to true or false.
     BranchRegion = 4,
+
+    /// A DecisionRegion represents a top-level boolean expression and is
+    /// associated with a variable length bitmap index and condition number.
+    MCDCDecisionRegion = 5,
+
+    /// A Branch Region can be extended to include IDs to facilitate MC/DC.
+    MCDCBranchRegion = 6,
+}
+
+/// Parameters of the MC/DC decision and branch regions.
+///
+/// Corresponds to struct `llvm::coverage::CounterMappingRegion::MCDCParameters`.
+///
+/// Must match the layout of `LLVMRustMCDCParameters`.
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+pub struct MCDCParameters {
+    /// Byte index of the bitmap of a decision region.
+    bitmap_idx: u32,
+    /// Number of conditions of a decision region.
+    num_conditions: u32,
+    /// IDs of a branch region, and of the branch regions that are evaluated
+    /// next for each of its outcomes, if any.
+    id: u32,
+    true_id: u32,
+    false_id: u32,
 }
 
 /// This struct provides LLVM's representation of a "CoverageMappingRegion", encoded into the
@@ -122,6 +148,10 @@ pub struct CounterMappingRegion {
     /// for the false branch of the region.
     false_counter: Counter,
 
+    /// If the `RegionKind` is an `MCDCDecisionRegion` or an `MCDCBranchRegion`,
+    /// the parameters of the decision or of the branch.
+    mcdc_params: MCDCParameters,
+
     /// An indirect reference to the source filename. In the LLVM Coverage Mapping Format, the
     /// file_id is an index into a function-specific `virtual_file_mapping` array of indexes
     /// that, in turn, are used to look up the filename for this region.
@@ -149,6 +179,66 @@ pub struct CounterMappingRegion {
 }
 
 impl CounterMappingRegion {
+    pub(crate) fn from_mapping(
+        mapping_kind: &MappingKind,
+        local_file_id: u32,
+        code_region: &CodeRegion,
+    ) -> Self {
+        let &CodeRegion { file_name: _, start_line, start_col, end_line, end_col } = code_region;
+        match *mapping_kind {
+            MappingKind::Code(term) => Self::code_region(
+                Counter::from_term(term),
+                local_file_id,
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+            ),
+            MappingKind::Branch { true_term, false_term } => Self::branch_region(
+                Counter::from_term(true_term),
+                Counter::from_term(false_term),
+                local_file_id,
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+            ),
+            MappingKind::MCDCBranch { true_term, false_term, mcdc_params } => Self {
+                counter: Counter::from_term(true_term),
+                false_counter: Counter::from_term(false_term),
+                mcdc_params: MCDCParameters {
+                    id: mcdc_params.condition_id.as_u32(),
+                    true_id: mcdc_params.true_next_id.as_u32(),
+                    false_id: mcdc_params.false_next_id.as_u32(),
+                    ..MCDCParameters::default()
+                },
+                file_id: local_file_id,
+                expanded_file_id: 0,
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+                kind: RegionKind::MCDCBranchRegion,
+            },
+            MappingKind::MCDCDecision(decision_info) => Self {
+                counter: Counter::ZERO,
+                false_counter: Counter::ZERO,
+                mcdc_params: MCDCParameters {
+                    bitmap_idx: decision_info.bitmap_idx,
+                    num_conditions: decision_info.conditions_num as u32,
+                    ..MCDCParameters::default()
+                },
+                file_id: local_file_id,
+                expanded_file_id: 0,
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+                kind: RegionKind::MCDCDecisionRegion,
+            },
+        }
+    }
+
     pub(crate) fn code_region(
         counter: Counter,
         file_id: u32,
@@ -160,6 +250,7 @@ pub(crate) fn code_region(
         Self {
             counter,
             false_counter: Counter::ZERO,
+            mcdc_params: MCDCParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
@@ -170,9 +261,6 @@ pub(crate) fn code_region(
         }
     }
 
-    // This function might be used in the future; the LLVM API is still evolving, as is coverage
-    // support.
-    #[allow(dead_code)]
     pub(crate) fn branch_region(
         counter: Counter,
         false_counter: Counter,
@@ -185,6 +273,7 @@ pub(crate) fn branch_region(
         Self {
             counter,
             false_counter,
+            mcdc_params: MCDCParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
@@ -209,6 +298,7 @@ pub(crate) fn expansion_region(
         Self {
             counter: Counter::ZERO,
             false_counter: Counter::ZERO,
+            mcdc_params: MCDCParameters::default(),
             file_id,
             expanded_file_id,
             start_line,
@@ -232,6 +322,7 @@ pub(crate) fn skipped_region(
         Self {
             counter: Counter::ZERO,
             false_counter: Counter::ZERO,
+            mcdc_params: MCDCParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
@@ -256,6 +347,7 @@ pub(crate) fn gap_region(
         Self {
             counter,
             false_counter: Counter::ZERO,
+            mcdc_params: MCDCParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
@@ -32,6 +32,10 @@ pub struct CrateCoverageContext<'ll, 'tcx> {
     pub(crate) function_coverage_map:
         RefCell<FxHashMap<Instance<'tcx>, FunctionCoverageCollector<'tcx>>>,
     pub(crate) pgo_func_name_var_map: RefCell<FxHashMap<Instance<'tcx>, &'ll llvm::Value>>,
+    /// The condition bitmap of each function instrumented for MC/DC, along
+    /// with the LLVM function it was allocated in.
+    pub(crate) mcdc_condition_bitmap_map:
+        RefCell<FxHashMap<Instance<'tcx>, (&'ll llvm::Value, &'ll llvm::Value)>>,
 }
 
 impl<'ll, 'tcx> CrateCoverageContext<'ll, 'tcx> {
@@ -39,6 +43,7 @@ pub fn new() -> Self {
         Self {
             function_coverage_map: Default::default(),
             pgo_func_name_var_map: Default::default(),
+            mcdc_condition_bitmap_map: Default::default(),
         }
     }
 
@@ -74,6 +79,30 @@ fn get_pgo_func_name_var(&self, instance: Instance<'tcx>) -> &'ll llvm::Value {
 }
 
 impl<'tcx> CoverageInfoBuilderMethods<'tcx> for Builder<'_, '_, 'tcx> {
+    fn init_coverage(&mut self, instance: Instance<'tcx>) {
+        let Some(function_coverage_info) =
+            self.tcx.instance_mir(instance.def).function_coverage_info.as_deref()
+        else {
+            return;
+        };
+
+        // If there are no MC/DC bitmaps to set up, return immediately.
+        if function_coverage_info.mcdc_bitmap_bytes == 0 {
+            return;
+        }
+
+        let fn_name = self.get_pgo_func_name_var(instance);
+        let hash = self.const_u64(function_coverage_info.function_source_hash);
+        let bitmap_bytes = self.const_u32(function_coverage_info.mcdc_bitmap_bytes);
+        let cond_bitmap = self.mcdc_parameters(fn_name, hash, bitmap_bytes);
+        let llfn = self.llfn();
+        self.coverage_context()
+            .expect("always present when coverage is enabled")
+            .mcdc_condition_bitmap_map
+            .borrow_mut()
+            .insert(instance, (llfn, cond_bitmap));
+    }
+
     #[instrument(level = "debug", skip(self))]
     fn add_coverage(&mut self, instance: Instance<'tcx>, coverage: &Coverage) {
         // Our caller should have already taken care of inlining subtleties,
@@ -88,9 +117,12 @@ fn add_coverage(&mut self, instance: Instance<'tcx>, coverage: &Coverage) {
         match coverage.kind {
             // Marker statements have no effect during codegen,
             // so return early and don't create `func_coverage`.
-            CoverageKind::SpanMarker => return,
+            CoverageKind::SpanMarker | CoverageKind::BlockMarker { .. } => return,
             // Match exhaustively to ensure that newly-added kinds are classified correctly.
-            CoverageKind::CounterIncrement { .. } | CoverageKind::ExpressionUsed { .. } => {}
+            CoverageKind::CounterIncrement { .. }
+            | CoverageKind::ExpressionUsed { .. }
+            | CoverageKind::CondBitmapUpdate { .. }
+            | CoverageKind::TestVectorBitmapUpdate { .. } => {}
         }
 
         let Some(function_coverage_info) =
@@ -108,7 +140,7 @@ fn add_coverage(&mut self, instance: Instance<'tcx>, coverage: &Coverage) {
 
         let Coverage { kind } = coverage;
         match *kind {
-            CoverageKind::SpanMarker => unreachable!(
+            CoverageKind::SpanMarker | CoverageKind::BlockMarker { .. } => unreachable!(
                 "unexpected marker statement {kind:?} should have caused an early return"
             ),
             CoverageKind::CounterIncrement { id } => {
@@ -142,10 +174,43 @@ fn add_coverage(&mut self, instance: Instance<'tcx>, coverage: &Coverage) {
             CoverageKind::ExpressionUsed { id } => {
                 func_coverage.mark_expression_id_seen(id);
             }
+            CoverageKind::CondBitmapUpdate { id, value } => {
+                drop(coverage_map);
+                // The condition bitmap lives in the function it was allocated
+                // in, so bitmap updates of instances inlined into another
+                // function are skipped.
+                let Some(cond_bitmap) = bx.mcdc_condition_bitmap(instance) else { return };
+                let fn_name = bx.get_pgo_func_name_var(instance);
+                let hash = bx.const_u64(function_coverage_info.function_source_hash);
+                // Condition IDs start from 1 in MIR, but from 0 in LLVM.
+                let cond_loc = bx.const_i32(id.as_u32() as i32 - 1);
+                let bool_value = bx.const_bool(value);
+                bx.mcdc_condbitmap_update(fn_name, hash, cond_loc, cond_bitmap, bool_value);
+            }
+            CoverageKind::TestVectorBitmapUpdate { bitmap_idx } => {
+                drop(coverage_map);
+                let Some(cond_bitmap) = bx.mcdc_condition_bitmap(instance) else { return };
+                let fn_name = bx.get_pgo_func_name_var(instance);
+                let hash = bx.const_u64(function_coverage_info.function_source_hash);
+                let bitmap_bytes = bx.const_u32(function_coverage_info.mcdc_bitmap_bytes);
+                let bitmap_idx = bx.const_u32(bitmap_idx);
+                bx.mcdc_tvbitmap_update(fn_name, hash, bitmap_bytes, bitmap_idx, cond_bitmap);
+            }
         }
     }
 }
 
+impl<'ll, 'tcx> Builder<'_, 'll, 'tcx> {
+    /// Returns the MC/DC condition bitmap of the given instance, if it was
+    /// allocated in the function being built.
+    fn mcdc_condition_bitmap(&self, instance: Instance<'tcx>) -> Option<&'ll llvm::Value> {
+        let coverage_context = self.coverage_context()?;
+        let &(llfn, cond_bitmap) =
+            coverage_context.mcdc_condition_bitmap_map.borrow().get(&instance)?;
+        (llfn == self.llfn()).then_some(cond_bitmap)
+    }
+}
+
 /// Calls llvm::createPGOFuncNameVar() with the given function instance's
 /// mangled function name. The LLVM API returns an llvm::GlobalVariable
 /// containing the function name, with the specific variable name and linkage
@@ -152,6 +152,10 @@ fn into_diagnostic(self, dcx: &'_ DiagCtxt, level: Level) -> DiagnosticBuilder<'
 #[diag(codegen_llvm_lto_proc_macro)]
 pub(crate) struct LtoProcMacro;
 
+#[derive(Diagnostic)]
+#[diag(codegen_llvm_mcdc_requires_llvm_18)]
+pub(crate) struct McdcRequiresLlvm18;
+
 #[derive(Diagnostic)]
 #[diag(codegen_llvm_lto_bitcode_from_rlib)]
 pub(crate) struct LtoBitcodeFromRlib {
@@ -1564,6 +1564,11 @@ pub fn LLVMRustGetOrInsertGlobal<'a>(
         T: &'a Type,
     ) -> &'a Value;
     pub fn LLVMRustInsertPrivateGlobal<'a>(M: &'a Module, T: &'a Type) -> &'a Value;
+    pub fn LLVMRustCloneFunction<'a>(
+        Fn: &'a Value,
+        Name: *const c_char,
+        NameLen: size_t,
+    ) -> &'a Value;
     pub fn LLVMRustGetNamedValue(
         M: &Module,
         Name: *const c_char,
@@ -1623,6 +1628,9 @@ pub fn LLVMRustBuildInvoke<'a>(
 
     // Miscellaneous instructions
     pub fn LLVMRustGetInstrProfIncrementIntrinsic(M: &Module) -> &Value;
+    pub fn LLVMRustGetInstrProfMCDCParametersIntrinsic(M: &Module) -> &Value;
+    pub fn LLVMRustGetInstrProfMCDCTVBitmapUpdateIntrinsic(M: &Module) -> &Value;
+    pub fn LLVMRustGetInstrProfMCDCCondBitmapUpdateIntrinsic(M: &Module) -> &Value;
     pub fn LLVMRustBuildCall<'a>(
         B: &Builder<'a>,
         Ty: &'a Type,
@@ -1,6 +1,6 @@
 use crate::back::write::create_informational_target_machine;
 use crate::errors::{
-    PossibleFeature, TargetFeatureDisableOrEnable, UnknownCTargetFeature,
+    McdcRequiresLlvm18, PossibleFeature, TargetFeatureDisableOrEnable, UnknownCTargetFeature,
     UnknownCTargetFeaturePrefix, UnstableCTargetFeature,
 };
 use crate::llvm;
@@ -35,6 +35,11 @@ pub(crate) fn init(sess: &Session) {
             configure_llvm(sess);
         });
     }
+
+    // The MC/DC intrinsics and coverage mapping regions were added in LLVM 18.
+    if sess.instrument_coverage_mcdc() && get_version() < (18, 0, 0) {
+        sess.dcx().emit_fatal(McdcRequiresLlvm18);
+    }
 }
 
 fn require_inited() {
@@ -211,8 +211,27 @@ codegen_ssa_select_cpp_build_tool_workload = in the Visual Studio installer, ens
 
 codegen_ssa_shuffle_indices_evaluation = could not evaluate shuffle_indices at compile time
 
+codegen_ssa_size_attribution_read_map =
+    failed to read the linker map file `{$path}` for `--print=size-attribution`: {$error}
+
+codegen_ssa_size_attribution_unsupported_linker =
+    `--print=size-attribution` is only supported by linkers with a GNU-like command line
+
+codegen_ssa_size_attribution_write_report =
+    failed to write size attribution report to `{$path}`: {$error}
+
 codegen_ssa_specify_libraries_to_link = use the `-l` flag to specify native libraries to link
 
+codegen_ssa_stack_report_no_stack_sizes =
+    no stack sizes were found for `-Z stack-report`
+    .note = stack sizes are only emitted in ELF object files
+
+codegen_ssa_stack_report_read_object =
+    failed to read the stack sizes of `{$path}` for `-Z stack-report`: {$error}
+
+codegen_ssa_stack_report_write =
+    failed to write the stack report to `{$path}`: {$error}
+
 codegen_ssa_static_library_native_artifacts = Link against the following native artifacts when linking against this static library. The order and any duplication can be significant on some platforms.
 
 codegen_ssa_static_library_native_artifacts_to_file = Native artifacts to link against have been written to {$path}. The order and any duplication can be significant on some platforms.
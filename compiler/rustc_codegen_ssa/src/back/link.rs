@@ -13,7 +13,9 @@
 use rustc_middle::middle::dependency_format::Linkage;
 use rustc_middle::middle::exported_symbols::SymbolExportKind;
 use rustc_session::config::{self, CFGuard, CrateType, DebugInfo, OutFileName, Strip};
-use rustc_session::config::{OutputFilenames, OutputType, PrintKind, SplitDwarfKind};
+use rustc_session::config::{
+    OutputFilenames, OutputType, PrintKind, SplitDwarfKind, SwitchWithOptPath,
+};
 use rustc_session::cstore::DllImport;
 use rustc_session::output::{check_file_is_writeable, invalid_output_for_target, out_filename};
 use rustc_session::search_paths::PathKind;
@@ -33,6 +35,8 @@
 use super::linker::{self, Linker};
 use super::metadata::{create_wrapper_file, MetadataPosition};
 use super::rpath::{self, RPathConfig};
+use super::size_attribution;
+use super::stack_report;
 use crate::{
     errors, looks_like_rust_object_file, CodegenResults, CompiledModule, CrateInfo, NativeLib,
 };
@@ -70,6 +74,9 @@ pub fn link_binary<'a>(
 ) -> Result<(), ErrorGuaranteed> {
     let _timer = sess.timer("link_binary");
     let output_metadata = sess.opts.output_types.contains_key(&OutputType::Metadata);
+    if let SwitchWithOptPath::Enabled(ref directory) = sess.opts.unstable_opts.stack_report {
+        stack_report::write(sess, codegen_results, directory);
+    }
     let mut tempfiles_for_stdout_output: Vec<PathBuf> = Vec::new();
     for &crate_type in &codegen_results.crate_info.crate_types {
         // Ignore executable crates if we have -Z no-codegen, as they will error.
@@ -449,6 +456,15 @@ fn link_rlib<'a>(
         ab.add_file(&lib)
     }
 
+    // The call graph for `-Z stack-report`, read back when linking the crates
+    // depending on this one. Added at the end too, like the metadata.
+    if flavor == RlibFlavor::Normal
+        && let Some(graph) = &codegen_results.crate_info.stack_call_graph
+        && let Some(call_graph) = stack_report::emit_call_graph(sess, graph, tmpdir)
+    {
+        ab.add_file(&call_graph);
+    }
+
     return Ok(ab);
 }
 
@@ -537,7 +553,7 @@ fn link_staticlib<'a>(
                 path,
                 Box::new(move |fname: &str| {
                     // Ignore metadata files, no matter the name.
-                    if fname == METADATA_FILENAME {
+                    if fname == METADATA_FILENAME || fname == stack_report::CALL_GRAPH_FILENAME {
                         return true;
                     }
 
@@ -757,6 +773,13 @@ fn link_natively<'a>(
         cmd.env_remove(k.as_ref());
     }
 
+    let size_attribution_map =
+        if sess.opts.prints.iter().any(|print| print.kind == PrintKind::SizeAttribution) {
+            size_attribution::add_map_file_arg(sess, flavor, tmpdir, &mut cmd)
+        } else {
+            None
+        };
+
     for print in &sess.opts.prints {
         if print.kind == PrintKind::LinkArgs {
             let content = format!("{cmd:?}");
@@ -997,6 +1020,14 @@ fn is_illegal_instruction(_status: &ExitStatus) -> bool {
         }
     }
 
+    if let Some(map_file) = &size_attribution_map {
+        for print in &sess.opts.prints {
+            if print.kind == PrintKind::SizeAttribution {
+                size_attribution::print(sess, &print.out, map_file, out_filename, codegen_results);
+            }
+        }
+    }
+
     match sess.split_debuginfo() {
         // If split debug information is disabled or located in individual files
         // there's nothing to do here.
@@ -2770,7 +2801,7 @@ fn add_static_crate<'a>(
         if let Err(error) = archive.add_archive(
             cratepath,
             Box::new(move |f| {
-                if f == METADATA_FILENAME {
+                if f == METADATA_FILENAME || f == stack_report::CALL_GRAPH_FILENAME {
                     return true;
                 }
 
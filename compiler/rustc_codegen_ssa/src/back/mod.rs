@@ -5,5 +5,7 @@
 pub mod lto;
 pub mod metadata;
 pub mod rpath;
+pub(crate) mod size_attribution;
+mod stack_report;
 pub mod symbol_export;
 pub mod write;
@@ -0,0 +1,416 @@
+//! Attributing the size of a linked binary to the crates and functions it was
+//! built from (`--print=size-attribution`).
+//!
+//! The linker is asked for a map file, which lists every input section that
+//! ended up in the binary with its size and the object file it comes from.
+//! Since functions are emitted in sections of their own, the name of a
+//! section gives the symbol of the function in it, and the object file (or
+//! the rlib it is a member of) gives the crate that codegened it. The crate
+//! defining the function is known from the mono items the symbols were
+//! instantiated from, see [`symbol_def_crates`].
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::middle::exported_symbols::ExportedSymbol;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::OutFileName;
+use rustc_session::Session;
+use rustc_span::Symbol;
+use rustc_target::spec::{Cc, LinkerFlavor};
+use std::cmp::Reverse;
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::command::Command;
+use super::symbol_export::symbol_name_for_instance_in_crate;
+use crate::{errors, CodegenResults};
+
+#[cfg(test)]
+mod tests;
+
+/// The number of functions listed in the table, the largest first. The JSON
+/// report lists all of them.
+const TABLE_FUNCTIONS: usize = 100;
+
+/// Asks the linker to write a map file in `tmpdir`, returning its path, or
+/// `None` if the linker doesn't support it.
+pub(super) fn add_map_file_arg(
+    sess: &Session,
+    flavor: LinkerFlavor,
+    tmpdir: &Path,
+    cmd: &mut Command,
+) -> Option<PathBuf> {
+    let path = tmpdir.join("size-attribution.map");
+    let mut arg = OsString::from(match flavor {
+        LinkerFlavor::Gnu(Cc::Yes, _) => "-Wl,-Map=",
+        LinkerFlavor::Gnu(Cc::No, _) => "-Map=",
+        _ => {
+            sess.dcx().emit_warning(errors::SizeAttributionUnsupportedLinker);
+            return None;
+        }
+    });
+    arg.push(&path);
+    cmd.arg(arg);
+    Some(path)
+}
+
+/// Maps the symbols that may end up in the binary to the name of the crate
+/// defining the item they were instantiated from.
+///
+/// These are the symbols of the mono items of the local crate, and the ones
+/// exported by upstream crates, which include the generic instantiations they
+/// share with `-Z share-generics`. Other symbols of upstream crates are not
+/// known, e.g. their private instantiations of generic functions.
+pub(crate) fn symbol_def_crates(tcx: TyCtxt<'_>) -> FxHashMap<String, Symbol> {
+    let mut def_crates = FxHashMap::default();
+    for cgu in tcx.collect_and_partition_mono_items(()).1 {
+        for item in cgu.items().keys() {
+            let def_id = match *item {
+                MonoItem::Fn(instance) => instance.def_id(),
+                MonoItem::Static(def_id) => def_id,
+                MonoItem::GlobalAsm(_) => continue,
+            };
+            def_crates.insert(item.symbol_name(tcx).name.to_string(), tcx.crate_name(def_id.krate));
+        }
+    }
+    for &cnum in tcx.crates(()) {
+        for &(symbol, _) in tcx.exported_symbols(cnum) {
+            let def_id = match symbol {
+                ExportedSymbol::NonGeneric(def_id)
+                | ExportedSymbol::Generic(def_id, _)
+                | ExportedSymbol::ThreadLocalShim(def_id) => def_id,
+                ExportedSymbol::DropGlue(_) => {
+                    let Some(def_id) = tcx.lang_items().drop_in_place_fn() else { continue };
+                    def_id
+                }
+                ExportedSymbol::NoDefId(_) => continue,
+            };
+            let name = symbol_name_for_instance_in_crate(tcx, symbol, cnum);
+            def_crates.insert(name, tcx.crate_name(def_id.krate));
+        }
+    }
+    def_crates
+}
+
+/// An input section of the map file.
+#[derive(Debug, PartialEq)]
+struct InputSection {
+    /// The object file, either a path or `archive(member)`.
+    object: String,
+    name: String,
+    size: u64,
+}
+
+#[derive(serde::Serialize)]
+struct Report {
+    total_size: u64,
+    crates: Vec<CrateSize>,
+    functions: Vec<FunctionSize>,
+}
+
+#[derive(serde::Serialize)]
+struct CrateSize {
+    /// The name of the crate, or of the object file or library for code that
+    /// wasn't compiled by rustc.
+    name: String,
+    rust: bool,
+    size: u64,
+    /// The size of the functions of other crates that were codegened by this
+    /// one, i.e. instantiations of their generics and copies of their inline
+    /// functions.
+    other_crates_size: u64,
+}
+
+#[derive(serde::Serialize)]
+struct FunctionSize {
+    /// The demangled path of the function, without its hash.
+    name: String,
+    /// The crate that codegened the function.
+    #[serde(rename = "crate")]
+    krate: String,
+    /// The crate that defines the function, if its symbol is known (see
+    /// [`symbol_def_crates`]).
+    def_crate: Option<String>,
+    size: u64,
+    /// The number of symbols with this path codegened by the crate, e.g. one
+    /// per instantiation of a generic function with the legacy mangling.
+    symbols: usize,
+}
+
+/// Reads the map file written by the linker, and prints the size of the
+/// crates and functions of the binary to `out`. A JSON report with all the
+/// functions is written next to the binary.
+pub(super) fn print(
+    sess: &Session,
+    out: &OutFileName,
+    map_file: &Path,
+    out_filename: &Path,
+    codegen_results: &CodegenResults,
+) {
+    let map = match fs::read_to_string(map_file) {
+        Ok(map) => map,
+        Err(error) => {
+            sess.dcx().emit_warning(errors::SizeAttributionReadMap { path: map_file, error });
+            return;
+        }
+    };
+    let sections = if map.lines().next().is_some_and(|header| header.contains("VMA")) {
+        parse_lld_map(&map)
+    } else {
+        parse_gnu_map(&map)
+    };
+    let def_crates = codegen_results.crate_info.symbol_def_crates.as_ref();
+    let report = attribute(
+        &sections,
+        &rust_files(codegen_results),
+        def_crates.unwrap_or(&Default::default()),
+    );
+
+    let json_path = PathBuf::from(format!("{}.size-attribution.json", out_filename.display()));
+    let written = serde_json::to_string(&report)
+        .map_err(Into::into)
+        .and_then(|json| fs::write(&json_path, json));
+    if let Err(error) = written {
+        sess.dcx().emit_warning(errors::SizeAttributionWriteReport { path: &json_path, error });
+    }
+
+    out.overwrite(&format_table(&report), sess);
+}
+
+/// Returns the crates of the Rust objects and rlibs by file name. Objects are
+/// recognized by their file name, or the one of the rlib they are a member of,
+/// as rlibs may be copied before linking.
+fn rust_files(codegen_results: &CodegenResults) -> FxHashMap<String, String> {
+    let crate_info = &codegen_results.crate_info;
+    let mut rust_files: FxHashMap<String, String> = FxHashMap::default();
+    for (cnum, source) in &crate_info.used_crate_source {
+        if let Some((path, _)) = &source.rlib {
+            rust_files.insert(file_name(path), crate_info.crate_name[cnum].to_string());
+        }
+    }
+    let local_modules = codegen_results
+        .modules
+        .iter()
+        .chain(&codegen_results.allocator_module)
+        .chain(&codegen_results.metadata_module);
+    for module in local_modules {
+        if let Some(object) = &module.object {
+            rust_files.insert(file_name(object), crate_info.local_crate_name.to_string());
+        }
+    }
+    rust_files
+}
+
+fn attribute(
+    sections: &[InputSection],
+    rust_files: &FxHashMap<String, String>,
+    def_crates: &FxHashMap<String, Symbol>,
+) -> Report {
+    let mut crates: FxHashMap<String, CrateSize> = FxHashMap::default();
+    let mut functions: FxHashMap<(String, String), FunctionSize> = FxHashMap::default();
+    let mut total_size = 0;
+    for section in sections {
+        total_size += section.size;
+        let archive =
+            section.object.split_once('(').map_or(&*section.object, |(archive, _)| archive);
+        let archive = file_name(Path::new(archive));
+        let (krate, rust) = match rust_files.get(&archive) {
+            Some(krate) => (krate.clone(), true),
+            None => (archive, false),
+        };
+        let crate_size = crates.entry(krate.clone()).or_insert_with(|| CrateSize {
+            name: krate.clone(),
+            rust,
+            size: 0,
+            other_crates_size: 0,
+        });
+        crate_size.size += section.size;
+
+        let Some(symbol) = section_symbol(&section.name) else {
+            continue;
+        };
+        let Ok(demangled) = rustc_demangle::try_demangle(symbol) else {
+            continue;
+        };
+        // Without the hash.
+        let name = format!("{demangled:#}");
+        let def_crate = def_crates.get(symbol).map(Symbol::to_string);
+        if rust && def_crate.as_ref().is_some_and(|def_crate| *def_crate != krate) {
+            crate_size.other_crates_size += section.size;
+        }
+        let function = functions
+            .entry((krate.clone(), name.clone()))
+            .or_insert_with(|| FunctionSize { name, krate, def_crate, size: 0, symbols: 0 });
+        function.size += section.size;
+        function.symbols += 1;
+    }
+
+    let mut crates: Vec<_> = crates.into_values().collect();
+    crates.sort_unstable_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    let mut functions: Vec<_> = functions.into_values().collect();
+    functions.sort_unstable_by(|a, b| {
+        (Reverse(a.size), &a.name, &a.krate).cmp(&(Reverse(b.size), &b.name, &b.krate))
+    });
+    Report { total_size, crates, functions }
+}
+
+fn format_table(report: &Report) -> String {
+    let mut table = String::new();
+    let share = |size: u64| size as f64 * 100.0 / report.total_size.max(1) as f64;
+
+    writeln!(table, "| Crate | Size | Share | From Other Crates |").unwrap();
+    writeln!(table, "| --- | ---: | ---: | ---: |").unwrap();
+    for krate in &report.crates {
+        let name = if krate.rust { format!("`{}`", krate.name) } else { krate.name.clone() };
+        writeln!(
+            table,
+            "| {name} | {} | {:.1}% | {} |",
+            krate.size,
+            share(krate.size),
+            krate.other_crates_size
+        )
+        .unwrap();
+    }
+    writeln!(table, "| Total | {} | 100.0% | |", report.total_size).unwrap();
+
+    writeln!(table).unwrap();
+    writeln!(table, "| Function | Crate | Size | Share |").unwrap();
+    writeln!(table, "| --- | --- | ---: | ---: |").unwrap();
+    for function in report.functions.iter().take(TABLE_FUNCTIONS) {
+        writeln!(
+            table,
+            "| `{}` | `{}` | {} | {:.1}% |",
+            function.name,
+            function.krate,
+            function.size,
+            share(function.size)
+        )
+        .unwrap();
+    }
+    table
+}
+
+/// Parses a map file in the format of GNU ld:
+///
+/// ```text
+/// Linker script and memory map
+///
+/// .text           0x0000000000001040      0x185
+///  .text._ZN3foo3bar17h0123456789abcdefE
+///                 0x0000000000001140       0x2a /tmp/foo.foo.1234-cgu.0.rcgu.o
+///                 0x0000000000001140                _ZN3foo3bar17h0123456789abcdefE
+/// ```
+fn parse_gnu_map(map: &str) -> Vec<InputSection> {
+    let mut sections = vec![];
+    let mut output_section = "";
+    let mut pending_name = None;
+    // Sections listed before the memory map were discarded.
+    let lines = map.lines().skip_while(|line| !line.starts_with("Linker script and memory map"));
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if !line.starts_with(' ') {
+            output_section = fields.first().copied().unwrap_or_default();
+            pending_name = None;
+            continue;
+        }
+        let (name, fields) = if line.starts_with("  ") {
+            // The address, size and object of an input section whose name
+            // didn't leave room for them on its line. Otherwise a symbol.
+            let Some(name) = pending_name.take() else { continue };
+            (name, &fields[..])
+        } else {
+            // Patterns of the linker script and fill bytes start with `*`.
+            let Some((&name, fields)) = fields.split_first() else { continue };
+            if name.starts_with('*') {
+                continue;
+            }
+            if fields.is_empty() {
+                pending_name = Some(name);
+                continue;
+            }
+            (name, fields)
+        };
+        let [address, size, object @ ..] = fields else { continue };
+        if !address.starts_with("0x") || object.is_empty() || !is_loaded(output_section) {
+            continue;
+        }
+        let Some(size) = size.strip_prefix("0x").and_then(|s| u64::from_str_radix(s, 16).ok())
+        else {
+            continue;
+        };
+        if size > 0 {
+            sections.push(InputSection { object: object.join(" "), name: name.to_string(), size });
+        }
+    }
+    sections
+}
+
+/// Parses a map file in the format of LLD:
+///
+/// ```text
+///              VMA              LMA     Size Align Out     In      Symbol
+///           2012c0           2012c0      18f    16 .text
+///           2012c0           2012c0       2b     4         /tmp/foo.o:(.text._ZN3foo3barE)
+///           2012c0           2012c0        0     1                 _ZN3foo3barE
+/// ```
+fn parse_lld_map(map: &str) -> Vec<InputSection> {
+    let mut lines = map.lines();
+    let Some(header) = lines.next() else {
+        return vec![];
+    };
+    let (Some(out_column), Some(in_column)) = (header.find(" Out "), header.find(" In ")) else {
+        return vec![];
+    };
+
+    let (out_column, in_column) = (out_column + 1, in_column + 1);
+
+    let mut sections = vec![];
+    let mut output_section = "";
+    for line in lines {
+        let Some(size) = line.split_whitespace().nth(2) else { continue };
+        let Ok(size) = u64::from_str_radix(size, 16) else { continue };
+        let entry = line.get(out_column..).unwrap_or_default();
+        if !entry.starts_with(' ') {
+            output_section = entry.trim_end();
+            continue;
+        }
+        let Some(entry) = line.get(in_column..).filter(|entry| !entry.starts_with(' ')) else {
+            continue;
+        };
+        let Some((object, name)) = entry.trim_end().rsplit_once(":(") else { continue };
+        let name = name.strip_suffix(')').unwrap_or(name);
+        if size > 0 && is_loaded(output_section) {
+            sections.push(InputSection {
+                object: object.to_string(),
+                name: name.to_string(),
+                size,
+            });
+        }
+    }
+    sections
+}
+
+/// Whether an output section takes space when the binary is loaded, as
+/// opposed to debug information and discarded sections.
+fn is_loaded(output_section: &str) -> bool {
+    !output_section.starts_with(".debug")
+        && !output_section.starts_with(".comment")
+        && output_section != "/DISCARD/"
+}
+
+/// Returns the symbol of the Rust function or static whose section is
+/// `section`, e.g. `_ZN3foo3bar17h0123456789abcdefE` for
+/// `.text._ZN3foo3bar17h0123456789abcdefE`, without the suffix ThinLTO adds
+/// to the symbols it promotes.
+fn section_symbol(section: &str) -> Option<&str> {
+    let start = section.find("._ZN").or_else(|| section.find("._R"))?;
+    let symbol = &section[start + 1..];
+    Some(symbol.split_once(".llvm.").map_or(symbol, |(symbol, _)| symbol))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
@@ -0,0 +1,127 @@
+use super::*;
+
+use rustc_span::create_default_session_globals_then;
+
+const GNU_MAP: &str = include_str!("gnu.map");
+const LLD_MAP: &str = include_str!("lld.map");
+
+fn section(object: &str, name: &str, size: u64) -> InputSection {
+    InputSection { object: object.to_string(), name: name.to_string(), size }
+}
+
+#[test]
+fn gnu_map() {
+    assert_eq!(
+        parse_gnu_map(GNU_MAP),
+        [
+            section(
+                "/tmp/foo.foo.1234-cgu.0.rcgu.o",
+                ".text._ZN3foo4main17h0123456789abcdefE",
+                0x40,
+            ),
+            section(
+                "/tmp/libstd-abc.rlib(std-abc.std.1234-cgu.0.rcgu.o)",
+                ".text._ZN3std2rt10lang_start17h2222222222222222E",
+                0x60,
+            ),
+            section("/usr/lib/crt1.o", ".text", 0x26),
+        ]
+    );
+}
+
+#[test]
+fn lld_map() {
+    assert_eq!(
+        parse_lld_map(LLD_MAP),
+        [
+            section("<internal>", ".interp", 0x1c),
+            section(
+                "/tmp/foo.foo.1234-cgu.0.rcgu.o",
+                ".text._ZN3foo4main17h0123456789abcdefE",
+                0x40,
+            ),
+            section(
+                "/tmp/libstd-abc.rlib(std-abc.std.1234-cgu.0.rcgu.o)",
+                ".text._ZN3std2rt10lang_start17h2222222222222222E",
+                0x60,
+            ),
+        ]
+    );
+}
+
+#[test]
+fn section_symbols() {
+    assert_eq!(
+        section_symbol(".text._ZN3foo3bar17h0123456789abcdefE"),
+        Some("_ZN3foo3bar17h0123456789abcdefE")
+    );
+    assert_eq!(
+        section_symbol(".text._ZN3foo3bar17h0123456789abcdefE.llvm.1234"),
+        Some("_ZN3foo3bar17h0123456789abcdefE")
+    );
+    assert_eq!(section_symbol(".rodata._RNvCs1234_3foo3BAR"), Some("_RNvCs1234_3foo3BAR"));
+    assert_eq!(section_symbol(".text.memcpy"), None);
+}
+
+#[test]
+fn attribution() {
+    create_default_session_globals_then(|| {
+        let foo_object = "/tmp/foo.foo.1234-cgu.0.rcgu.o";
+        let std_object = "/tmp/libstd-abc.rlib(std-abc.std.1234-cgu.0.rcgu.o)";
+        let sections = [
+            section(foo_object, ".text._ZN3foo4main17h0123456789abcdefE", 0x40),
+            // An instantiation of a generic function of `alloc`.
+            section(
+                foo_object,
+                ".text._ZN5alloc3vec16Vec$LT$T$C$A$GT$4push17h3333333333333333E",
+                0x20,
+            ),
+            section(std_object, ".text._ZN3std2rt10lang_start17h2222222222222222E", 0x60),
+            // A private function of `std`, whose symbol isn't known.
+            section(std_object, ".text._ZN3std2io5stdio6_print17h5555555555555555E", 0x10),
+            section("/usr/lib/crt1.o", ".text", 0x26),
+        ];
+        let rust_files = [
+            ("foo.foo.1234-cgu.0.rcgu.o".to_string(), "foo".to_string()),
+            ("libstd-abc.rlib".to_string(), "std".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let def_crates = [
+            ("_ZN3foo4main17h0123456789abcdefE", "foo"),
+            ("_ZN5alloc3vec16Vec$LT$T$C$A$GT$4push17h3333333333333333E", "alloc"),
+            ("_ZN3std2rt10lang_start17h2222222222222222E", "std"),
+        ]
+        .into_iter()
+        .map(|(symbol, krate)| (symbol.to_string(), Symbol::intern(krate)))
+        .collect();
+
+        let report = attribute(&sections, &rust_files, &def_crates);
+        assert_eq!(report.total_size, 0x40 + 0x20 + 0x60 + 0x10 + 0x26);
+
+        let crates: Vec<_> = report
+            .crates
+            .iter()
+            .map(|krate| (&*krate.name, krate.rust, krate.size, krate.other_crates_size))
+            .collect();
+        assert_eq!(
+            crates,
+            [("std", true, 0x70, 0), ("foo", true, 0x60, 0x20), ("crt1.o", false, 0x26, 0)]
+        );
+
+        let functions: Vec<_> = report
+            .functions
+            .iter()
+            .map(|function| (&*function.name, &*function.krate, function.def_crate.as_deref()))
+            .collect();
+        assert_eq!(
+            functions,
+            [
+                ("std::rt::lang_start", "std", Some("std")),
+                ("foo::main", "foo", Some("foo")),
+                ("alloc::vec::Vec<T,A>::push", "foo", Some("alloc")),
+                ("std::io::stdio::_print", "std", None),
+            ]
+        );
+    });
+}
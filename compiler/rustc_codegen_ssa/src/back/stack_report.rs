@@ -0,0 +1,570 @@
+//! The worst-case stack usage of the entry points of a crate
+//! (`-Z stack-report`).
+//!
+//! LLVM writes the size of the frame of each function to the `.stack_sizes`
+//! sections of ELF object files. Combined with the call graph of the mono
+//! items, this gives the deepest chain of calls from each entry point, and
+//! the stack it uses. The calls found in the relocations of the code of each
+//! function are added to the call graph, as LLVM adds calls that aren't mono
+//! items, e.g. to `memcpy` or `__rust_probestack`. The usage of an entry
+//! point is not bounded when it can reach a recursive function, a call
+//! through a function pointer or a call whose callees are not all known, e.g.
+//! a function of a C library.
+//!
+//! The rlibs of the crates compiled with `-Z stack-report` contain their call
+//! graph, which is combined with the one of the crates linking them, along
+//! with the stack sizes of their objects, so that the calls into dependencies
+//! are followed too.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::stack::ensure_sufficient_stack;
+use rustc_data_structures::temp_dir::MaybeTempDir;
+use rustc_metadata::fs::emit_wrapper_file;
+use rustc_middle::mir::mono::{CallGraph, Callee};
+use rustc_serialize::opaque::{FileEncoder, MemDecoder};
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use rustc_session::Session;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use object::read::archive::ArchiveFile;
+use object::{
+    elf, Architecture, Object, ObjectSection, ObjectSymbol, RelocationKind, RelocationTarget,
+    SectionIndex, SectionKind, SymbolKind,
+};
+
+use super::metadata::{create_wrapper_file, search_for_section};
+use crate::{errors, looks_like_rust_object_file, CodegenResults};
+
+/// The name of the member of an rlib containing the call graph of its crate.
+pub(super) const CALL_GRAPH_FILENAME: &str = "lib.stack-call-graph";
+const CALL_GRAPH_SECTION: &str = ".rust-stack-call-graph";
+
+/// The stack usage of an entry point, with the deepest chain of calls.
+#[derive(serde::Serialize)]
+struct EntryPoint {
+    name: String,
+    symbol: String,
+    stack_size: u64,
+    /// Whether `stack_size` is an upper bound of the stack usage, i.e. there
+    /// is no reason in `unbounded`.
+    bounded: bool,
+    deepest_path: Vec<Frame>,
+    unbounded: Vec<Unbounded>,
+}
+
+#[derive(serde::Serialize)]
+struct Frame {
+    name: String,
+    /// The size of the frame of the function, `None` if it is unknown.
+    size: Option<u64>,
+}
+
+/// A reason why the stack usage of an entry point isn't bounded.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+enum Unbounded {
+    /// The function calls a function that it was (indirectly) called by.
+    Recursion { function: String, callee: String },
+    /// The function calls a function pointer.
+    FnPtr { function: String },
+    /// The function calls a trait method that may be implemented by another
+    /// crate.
+    Virtual { function: String },
+    /// The function calls a function that isn't defined by the objects of
+    /// the crates linked, e.g. a function of a C library like `memcpy`, or
+    /// of a dylib.
+    External { function: String, callee: String },
+    /// The size of the frame of the function is missing from the objects.
+    UnknownFrame { function: String },
+}
+
+/// Writes the call graph of the local crate to a file wrapped in an object,
+/// to be added to its rlib. Returns `None` if the file couldn't be written.
+pub(super) fn emit_call_graph(
+    sess: &Session,
+    graph: &CallGraph,
+    tmpdir: &MaybeTempDir,
+) -> Option<PathBuf> {
+    let path = tmpdir.as_ref().join("stack-call-graph.bin");
+    let data = FileEncoder::new(&path)
+        .and_then(|mut encoder| {
+            encoder.emit_str(sess.cfg_version);
+            graph.encode(&mut encoder);
+            encoder.finish().map_err(|(_path, error)| error)
+        })
+        .and_then(|_| fs::read(&path));
+    let data = match data {
+        Ok(data) => data,
+        Err(error) => {
+            sess.dcx().emit_warning(errors::StackReportWrite { path: &path, error });
+            return None;
+        }
+    };
+    let (wrapper, _) = create_wrapper_file(sess, CALL_GRAPH_SECTION.as_bytes().to_vec(), &data);
+    Some(emit_wrapper_file(sess, &wrapper, tmpdir, CALL_GRAPH_FILENAME))
+}
+
+/// Reads the stack sizes from the objects of the local crate and of the
+/// upstream rlibs, and writes the stack usage of the entry points of the
+/// local crate to the given output directory.
+pub(super) fn write(
+    sess: &Session,
+    codegen_results: &CodegenResults,
+    output_directory: &Option<PathBuf>,
+) {
+    let crate_info = &codegen_results.crate_info;
+    let Some(local_graph) = &crate_info.stack_call_graph else { return };
+
+    let mut graph = local_graph.clone();
+    let mut stack_sizes = StackSizes::default();
+    let objects = codegen_results
+        .modules
+        .iter()
+        .chain(&codegen_results.allocator_module)
+        .filter_map(|module| module.object.as_deref());
+    for path in objects {
+        let read =
+            fs::read(path).map_err(Into::into).and_then(|data| stack_sizes.read_object(&data));
+        if let Err(error) = read {
+            let error = error.to_string();
+            sess.dcx().emit_warning(errors::StackReportReadObject { path, error });
+        }
+    }
+    for cnum in &crate_info.used_crates {
+        let Some((path, _)) = &crate_info.used_crate_source[cnum].rlib else { continue };
+        if let Err(error) = read_rlib(sess, path, &mut graph, &mut stack_sizes) {
+            let error = error.to_string();
+            sess.dcx().emit_warning(errors::StackReportReadObject { path, error });
+        }
+    }
+    if stack_sizes.sizes.is_empty() {
+        sess.dcx().emit_warning(errors::StackReportNoStackSizes);
+        return;
+    }
+
+    let mut analysis = Analysis {
+        graph: &graph,
+        stack_sizes: &stack_sizes,
+        worst: FxHashMap::default(),
+        in_progress: FxHashSet::default(),
+        recursive_calls: FxHashMap::default(),
+    };
+    let entry_points: Vec<_> =
+        graph.entry_points.iter().map(|entry| analysis.entry_point(entry)).collect();
+
+    let crate_name = crate_info.local_crate_name;
+    let output_directory = output_directory.as_deref().unwrap_or(Path::new("."));
+    let written = fs::create_dir_all(output_directory)
+        .and_then(|()| {
+            let path = output_directory.join(format!("{crate_name}.stack_report.md"));
+            fs::write(path, format_report(&entry_points))
+        })
+        .and_then(|()| {
+            let path = output_directory.join(format!("{crate_name}.stack_report.json"));
+            let json = serde_json::to_string(&entry_points)?;
+            fs::write(path, json)
+        });
+    if let Err(error) = written {
+        sess.dcx().emit_warning(errors::StackReportWrite { path: output_directory, error });
+    }
+}
+
+/// Reads the objects of an upstream rlib, and adds the call graph of its
+/// crate if it was compiled with `-Z stack-report`.
+fn read_rlib(
+    sess: &Session,
+    path: &Path,
+    graph: &mut CallGraph,
+    stack_sizes: &mut StackSizes,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let data = fs::read(path)?;
+    let archive = ArchiveFile::parse(&*data)?;
+    for member in archive.members() {
+        let member = member?;
+        let name = std::str::from_utf8(member.name())?;
+        let member_data = member.data(&*data)?;
+        if name == CALL_GRAPH_FILENAME {
+            let section = search_for_section(path, member_data, CALL_GRAPH_SECTION)?;
+            let mut decoder = MemDecoder::new(section, 0);
+            if decoder.read_str() != sess.cfg_version {
+                return Err("the call graph was written by another version of rustc".into());
+            }
+            // Only the entry points of the local crate are reported.
+            for (symbol, node) in CallGraph::decode(&mut decoder).functions {
+                graph.functions.entry(symbol).or_insert(node);
+            }
+        } else if looks_like_rust_object_file(name) {
+            stack_sizes.read_object(member_data)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct StackSizes {
+    /// The largest frame size of each function symbol, as functions with a
+    /// local copy in several codegen units may be optimized differently.
+    sizes: FxHashMap<String, u64>,
+    /// The functions defined by the objects, with or without a stack size.
+    defined: FxHashSet<String>,
+    /// The functions called by each function, found in the relocations of
+    /// its code.
+    calls: FxHashMap<String, FxHashSet<String>>,
+}
+
+impl StackSizes {
+    /// Reads the `.stack_sizes` sections of an object file, and the calls in
+    /// its code sections. Each entry of a `.stack_sizes` section is the
+    /// address of a function, relocated against its symbol or section, and
+    /// the size of its frame in ULEB128.
+    fn read_object(&mut self, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let file = object::File::parse(data)?;
+        let mut functions = Functions::default();
+        for symbol in file.symbols() {
+            if symbol.kind() == SymbolKind::Text && symbol.is_definition() {
+                let name = function_name(symbol.name()?);
+                self.defined.insert(name.to_string());
+                if let Some(section) = symbol.section_index() {
+                    functions.0.entry(section).or_default().push((
+                        symbol.address(),
+                        symbol.size(),
+                        name,
+                    ));
+                }
+            }
+        }
+        for functions in functions.0.values_mut() {
+            functions.sort_unstable();
+        }
+
+        let address_size = if file.is_64() { 8 } else { 4 };
+        for section in file.sections().filter(|section| section.name() == Ok(".stack_sizes")) {
+            let contents = section.data()?;
+            for (offset, relocation) in section.relocations() {
+                let RelocationTarget::Symbol(index) = relocation.target() else { continue };
+                let target = file.symbol_by_index(index)?;
+                let Some(address) = contents.get(offset as usize..) else { continue };
+                let addend = if relocation.has_implicit_addend() {
+                    read_address(address, address_size, file.is_little_endian())
+                } else {
+                    relocation.addend() as u64
+                };
+                // Local functions are relocated against the start of their
+                // section, plus their offset in it.
+                let function = if target.kind() == SymbolKind::Section {
+                    target.section_index().and_then(|section| functions.at(section, addend))
+                } else {
+                    Some(function_name(target.name()?))
+                };
+                let Some(function) = function else { continue };
+                let Some(size) = address.get(address_size..).and_then(read_uleb128) else {
+                    continue;
+                };
+                let max = self.sizes.entry(function.to_string()).or_default();
+                *max = (*max).max(size);
+            }
+        }
+
+        let architecture = file.architecture();
+        for section in file.sections().filter(|section| section.kind() == SectionKind::Text) {
+            for (offset, relocation) in section.relocations() {
+                if !is_call(architecture, relocation.kind()) {
+                    continue;
+                }
+                let RelocationTarget::Symbol(index) = relocation.target() else { continue };
+                let target = file.symbol_by_index(index)?;
+                let Some(caller) = functions.at(section.index(), offset) else { continue };
+                // A call to a local function may be relocated against the
+                // start of its section. As each function has its own section,
+                // it is the function at the start of the section, whatever the
+                // adjustment of the displacement in the addend.
+                let callee = if target.kind() == SymbolKind::Section {
+                    target.section_index().and_then(|section| functions.at(section, 0))
+                } else {
+                    Some(function_name(target.name()?))
+                };
+                let Some(callee) = callee else { continue };
+                self.calls.entry(caller.to_string()).or_default().insert(callee.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the size of the frame of the function, or `Some(0)` if it
+    /// doesn't have a symbol, as it was inlined into all its callers.
+    fn frame_size(&self, symbol: &str) -> Option<u64> {
+        match self.sizes.get(symbol) {
+            Some(&size) => Some(size),
+            None if self.defined.contains(symbol) => None,
+            None => Some(0),
+        }
+    }
+}
+
+/// The functions defined in each section of an object, sorted by address, to
+/// find the function at an address without going through all the symbols.
+#[derive(Default)]
+struct Functions<'data>(FxHashMap<SectionIndex, Vec<(u64, u64, &'data str)>>);
+
+impl<'data> Functions<'data> {
+    fn at(&self, section: SectionIndex, address: u64) -> Option<&'data str> {
+        let functions = self.0.get(&section)?;
+        let index = functions.partition_point(|&(start, _, _)| start <= address).checked_sub(1)?;
+        let (start, size, name) = functions[index];
+        (address < start + size.max(1)).then_some(name)
+    }
+}
+
+/// Returns true if the relocation is the one of the target of a call or a
+/// tail call.
+fn is_call(architecture: Architecture, kind: RelocationKind) -> bool {
+    match (architecture, kind) {
+        (_, RelocationKind::PltRelative) => true,
+        (Architecture::Arm, RelocationKind::Elf(r_type)) => matches!(
+            r_type,
+            elf::R_ARM_CALL | elf::R_ARM_JUMP24 | elf::R_ARM_THM_PC22 | elf::R_ARM_THM_JUMP24
+        ),
+        (Architecture::Aarch64, RelocationKind::Elf(r_type)) => {
+            matches!(r_type, elf::R_AARCH64_CALL26 | elf::R_AARCH64_JUMP26)
+        }
+        (Architecture::Riscv32 | Architecture::Riscv64, RelocationKind::Elf(r_type)) => {
+            matches!(r_type, elf::R_RISCV_CALL | elf::R_RISCV_CALL_PLT)
+        }
+        _ => false,
+    }
+}
+
+/// Strips the suffix of the functions promoted by ThinLTO.
+fn function_name(symbol: &str) -> &str {
+    symbol.split_once(".llvm.").map_or(symbol, |(name, _)| name)
+}
+
+fn read_address(data: &[u8], size: usize, little_endian: bool) -> u64 {
+    let Some(bytes) = data.get(..size) else { return 0 };
+    let fold = |value: u64, &byte: &u8| (value << 8) | u64::from(byte);
+    if little_endian {
+        bytes.iter().rev().fold(0, fold)
+    } else {
+        bytes.iter().fold(0, fold)
+    }
+}
+
+fn read_uleb128(data: &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for (i, &byte) in data.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+struct Analysis<'a> {
+    graph: &'a CallGraph,
+    stack_sizes: &'a StackSizes,
+    /// The stack usage of each function visited, and the callee through which
+    /// it is reached.
+    worst: FxHashMap<&'a str, (u64, Option<&'a str>)>,
+    /// The functions on the current chain of calls.
+    in_progress: FxHashSet<&'a str>,
+    /// The calls to a function on the chain of calls that led to the caller,
+    /// by caller.
+    recursive_calls: FxHashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> Analysis<'a> {
+    fn entry_point(&mut self, symbol: &'a str) -> EntryPoint {
+        let (stack_size, _) = self.visit(symbol);
+
+        let mut deepest_path = vec![];
+        let mut next = Some(symbol);
+        while let Some(symbol) = next {
+            deepest_path
+                .push(Frame { name: self.name(symbol), size: self.stack_sizes.frame_size(symbol) });
+            next = self.worst.get(symbol).and_then(|&(_, callee)| callee);
+        }
+
+        let mut unbounded: Vec<_> = self.unbounded(symbol).into_iter().collect();
+        unbounded.sort_unstable();
+        EntryPoint {
+            name: self.name(symbol),
+            symbol: symbol.to_string(),
+            stack_size,
+            bounded: unbounded.is_empty(),
+            deepest_path,
+            unbounded,
+        }
+    }
+
+    /// Returns the largest stack usage of a chain of calls starting with
+    /// `symbol`, and the callee that chain goes through.
+    fn visit(&mut self, symbol: &'a str) -> (u64, Option<&'a str>) {
+        if let Some(&worst) = self.worst.get(symbol) {
+            return worst;
+        }
+        self.in_progress.insert(symbol);
+
+        let mut deepest: (u64, Option<&'a str>) = (0, None);
+        for callee in self.callees(symbol) {
+            if self.in_progress.contains(callee) {
+                self.recursive_calls.entry(symbol).or_default().push(callee);
+                continue;
+            }
+            let (usage, _) = ensure_sufficient_stack(|| self.visit(callee));
+            if usage > deepest.0 || deepest.1.is_none() {
+                deepest = (usage, Some(callee));
+            }
+        }
+
+        self.in_progress.remove(symbol);
+        let frame_size = self.stack_sizes.frame_size(symbol).unwrap_or(0);
+        let worst = (frame_size + deepest.0, deepest.1);
+        self.worst.insert(symbol, worst);
+        worst
+    }
+
+    /// Returns the functions `symbol` may call: its callees in the call
+    /// graph, then the other functions its code calls.
+    fn callees(&self, symbol: &'a str) -> Vec<&'a str> {
+        let mut callees = vec![];
+        for callee in self.graph.functions.get(symbol).into_iter().flat_map(|node| &node.callees) {
+            match callee {
+                Callee::Direct(callee) => callees.push(callee.as_str()),
+                Callee::Virtual { candidates, .. } => {
+                    callees.extend(candidates.iter().map(|callee| callee.as_str()))
+                }
+                Callee::FnPtr => {}
+            }
+        }
+        let mut calls: Vec<_> = self
+            .stack_sizes
+            .calls
+            .get(symbol)
+            .into_iter()
+            .flatten()
+            .map(|callee| callee.as_str())
+            .filter(|callee| !callees.contains(callee))
+            .collect();
+        calls.sort_unstable();
+        callees.extend(calls);
+        callees
+    }
+
+    /// Returns the reasons why the stack usage of the functions reachable
+    /// from `entry` is not bounded.
+    fn unbounded(&self, entry: &'a str) -> FxHashSet<Unbounded> {
+        let mut unbounded = FxHashSet::default();
+        let mut visited = FxHashSet::default();
+        let mut queue = vec![entry];
+        while let Some(symbol) = queue.pop() {
+            if !visited.insert(symbol) {
+                continue;
+            }
+            let function = self.name(symbol);
+            if self.stack_sizes.frame_size(symbol).is_none() {
+                unbounded.insert(Unbounded::UnknownFrame { function: function.clone() });
+            }
+            for &callee in self.recursive_calls.get(symbol).into_iter().flatten() {
+                let callee = self.name(callee);
+                unbounded.insert(Unbounded::Recursion { function: function.clone(), callee });
+            }
+            // The calls LLVM added, and the calls of the functions it inlined.
+            for callee in self.stack_sizes.calls.get(symbol).into_iter().flatten() {
+                if self.graph.functions.contains_key(callee.as_str())
+                    || self.stack_sizes.defined.contains(callee)
+                {
+                    queue.push(callee.as_str());
+                } else {
+                    let callee = self.name(callee);
+                    unbounded.insert(Unbounded::External { function: function.clone(), callee });
+                }
+            }
+            let Some(node) = self.graph.functions.get(symbol) else { continue };
+            for callee in &node.callees {
+                match callee {
+                    Callee::Direct(callee)
+                        if !self.graph.functions.contains_key(callee)
+                            && !self.stack_sizes.defined.contains(callee) =>
+                    {
+                        let callee = self.name(callee);
+                        unbounded
+                            .insert(Unbounded::External { function: function.clone(), callee });
+                    }
+                    Callee::Direct(callee) => queue.push(callee.as_str()),
+                    Callee::Virtual { candidates, complete } => {
+                        if !complete {
+                            unbounded.insert(Unbounded::Virtual { function: function.clone() });
+                        }
+                        queue.extend(candidates.iter().map(|callee| callee.as_str()));
+                    }
+                    Callee::FnPtr => {
+                        unbounded.insert(Unbounded::FnPtr { function: function.clone() });
+                    }
+                }
+            }
+        }
+        unbounded
+    }
+
+    fn name(&self, symbol: &str) -> String {
+        match self.graph.functions.get(symbol) {
+            Some(node) => node.name.clone(),
+            None => match rustc_demangle::try_demangle(symbol) {
+                Ok(demangled) => format!("{demangled:#}"),
+                Err(_) => symbol.to_string(),
+            },
+        }
+    }
+}
+
+fn format_report(entry_points: &[EntryPoint]) -> String {
+    let mut report = String::new();
+    writeln!(report, "| Entry Point | Stack Size | Bounded |").unwrap();
+    writeln!(report, "| --- | ---: | --- |").unwrap();
+    for entry in entry_points {
+        let bounded = if entry.bounded { "yes" } else { "no" };
+        writeln!(report, "| `{}` | {} | {bounded} |", entry.name, entry.stack_size).unwrap();
+    }
+
+    for entry in entry_points {
+        writeln!(report).unwrap();
+        writeln!(report, "## `{}`", entry.name).unwrap();
+        writeln!(report).unwrap();
+        writeln!(report, "Deepest path:").unwrap();
+        writeln!(report).unwrap();
+        for (i, frame) in entry.deepest_path.iter().enumerate() {
+            let size = frame.size.map_or_else(|| "unknown".to_string(), |size| size.to_string());
+            writeln!(report, "{}. `{}` ({size} bytes)", i + 1, frame.name).unwrap();
+        }
+        if entry.bounded {
+            continue;
+        }
+        writeln!(report).unwrap();
+        writeln!(report, "Not bounded because:").unwrap();
+        writeln!(report).unwrap();
+        for reason in &entry.unbounded {
+            let reason = match reason {
+                Unbounded::Recursion { function, callee } => {
+                    format!("`{function}` recursively calls `{callee}`")
+                }
+                Unbounded::FnPtr { function } => format!("`{function}` calls a function pointer"),
+                Unbounded::Virtual { function } => format!(
+                    "`{function}` calls a trait method that may be implemented by another crate"
+                ),
+                Unbounded::External { function, callee } => {
+                    format!(
+                        "`{function}` calls `{callee}`, which isn't defined by the crates linked"
+                    )
+                }
+                Unbounded::UnknownFrame { function } => {
+                    format!("the frame size of `{function}` is unknown")
+                }
+            };
+            writeln!(report, "- {reason}").unwrap();
+        }
+    }
+    report
+}
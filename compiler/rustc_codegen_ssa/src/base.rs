@@ -32,7 +32,7 @@
 use rustc_middle::query::Providers;
 use rustc_middle::ty::layout::{HasTyCtxt, LayoutOf, TyAndLayout};
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
-use rustc_session::config::{self, CrateType, EntryFnType, OutputType};
+use rustc_session::config::{self, CrateType, EntryFnType, OutputType, PrintKind};
 use rustc_session::Session;
 use rustc_span::symbol::sym;
 use rustc_span::Symbol;
@@ -851,6 +851,9 @@ pub fn new(tcx: TyCtxt<'_>, target_cpu: String) -> CrateInfo {
         // `compiler_builtins` are always placed last to ensure that they're linked correctly.
         used_crates.extend(compiler_builtins);
 
+        let stack_report = tcx.sess.opts.unstable_opts.stack_report.enabled();
+        let size_attribution =
+            tcx.sess.opts.prints.iter().any(|print| print.kind == PrintKind::SizeAttribution);
         let mut info = CrateInfo {
             target_cpu,
             crate_types,
@@ -867,6 +870,9 @@ pub fn new(tcx: TyCtxt<'_>, target_cpu: String) -> CrateInfo {
             dependency_formats: tcx.dependency_formats(()).clone(),
             windows_subsystem,
             natvis_debugger_visualizers: Default::default(),
+            stack_call_graph: stack_report.then(|| tcx.stack_call_graph(()).clone()),
+            symbol_def_crates: size_attribution
+                .then(|| crate::back::size_attribution::symbol_def_crates(tcx)),
         };
         let crates = tcx.crates(());
 
@@ -15,7 +15,7 @@
 use rustc_target::spec::{abi, SanitizerSet};
 
 use crate::errors;
-use crate::target_features::from_target_feature;
+use crate::target_features::{from_target_clones, from_target_feature};
 use crate::{
     errors::{ExpectedCoverageSymbol, ExpectedUsedSymbol},
     target_features::check_target_feature_trait_unsafe,
@@ -76,6 +76,7 @@ fn codegen_fn_attrs(tcx: TyCtxt<'_>, did: LocalDefId) -> CodegenFnAttrs {
     let mut inline_span = None;
     let mut link_ordinal_span = None;
     let mut no_sanitize_span = None;
+    let mut target_clones_span = None;
 
     for attr in attrs.iter() {
         // In some cases, attribute are only valid on functions, but it's the `check_attr`
@@ -322,6 +323,15 @@ fn codegen_fn_attrs(tcx: TyCtxt<'_>, did: LocalDefId) -> CodegenFnAttrs {
                     &mut codegen_fn_attrs.target_features,
                 );
             }
+            sym::target_clones => {
+                target_clones_span = Some(attr.span);
+                from_target_clones(
+                    tcx,
+                    attr,
+                    supported_target_features,
+                    &mut codegen_fn_attrs.target_clones,
+                );
+            }
             sym::linkage => {
                 if let Some(val) = attr.value_str() {
                     let linkage = Some(linkage_by_name(tcx, did, val.as_str()));
@@ -558,6 +568,43 @@ fn codegen_fn_attrs(tcx: TyCtxt<'_>, did: LocalDefId) -> CodegenFnAttrs {
         }
     }
 
+    // The clone of a `#[target_clones]` function is selected when calling it through its symbol,
+    // so inlining it would always use the default clone.
+    if !codegen_fn_attrs.target_clones.is_empty() {
+        if codegen_fn_attrs.inline == InlineAttr::Always
+            && let Some(span) = inline_span
+        {
+            tcx.dcx().span_err(span, "cannot use `#[inline(always)]` with `#[target_clones]`");
+        }
+        codegen_fn_attrs.inline = InlineAttr::Never;
+
+        // The dispatcher forwards its arguments to the selected clone with a `musttail` call,
+        // which LLVM can't lower for these targets. Elsewhere, the CPU features are detected by
+        // `std`.
+        if matches!(&*tcx.sess.target.arch, "wasm32" | "wasm64" | "nvptx64" | "bpf")
+            && let Some(span) = target_clones_span
+        {
+            tcx.dcx().span_err(
+                span,
+                format!(
+                    "`#[target_clones]` is not supported on the `{}` architecture",
+                    tcx.sess.target.arch
+                ),
+            );
+        } else if tcx.lang_items().target_clone_supported_fn().is_none()
+            && let Some(span) = target_clones_span
+        {
+            tcx.dcx()
+                .struct_span_warn(
+                    span,
+                    "`#[target_clones]` cannot select a clone without the `target_clone_supported` \
+                     lang item",
+                )
+                .note("only the `default` clone is called, as `std` detects the CPU features")
+                .emit();
+        }
+    }
+
     if !codegen_fn_attrs.no_sanitize.is_empty() {
         if codegen_fn_attrs.inline == InlineAttr::Always {
             if let (Some(no_sanitize_span), Some(inline_span)) = (no_sanitize_span, inline_span) {
@@ -604,6 +604,43 @@ pub struct ReadFileError {
     pub message: std::io::Error,
 }
 
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_size_attribution_unsupported_linker)]
+pub struct SizeAttributionUnsupportedLinker;
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_size_attribution_read_map)]
+pub struct SizeAttributionReadMap<'a> {
+    pub path: &'a Path,
+    pub error: std::io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_size_attribution_write_report)]
+pub struct SizeAttributionWriteReport<'a> {
+    pub path: &'a Path,
+    pub error: std::io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_report_no_stack_sizes)]
+#[note]
+pub struct StackReportNoStackSizes;
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_report_read_object)]
+pub struct StackReportReadObject<'a> {
+    pub path: &'a Path,
+    pub error: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_report_write)]
+pub struct StackReportWrite<'a> {
+    pub path: &'a Path,
+    pub error: std::io::Error,
+}
+
 #[derive(Diagnostic)]
 #[diag(codegen_ssa_unsupported_link_self_contained)]
 pub struct UnsupportedLinkSelfContained;
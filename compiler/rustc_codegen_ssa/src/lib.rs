@@ -32,6 +32,7 @@
 use rustc_middle::middle::debugger_visualizer::DebuggerVisualizerFile;
 use rustc_middle::middle::dependency_format::Dependencies;
 use rustc_middle::middle::exported_symbols::SymbolExportKind;
+use rustc_middle::mir::mono::CallGraph;
 use rustc_middle::util::Providers;
 use rustc_serialize::opaque::{FileEncoder, MemDecoder};
 use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
@@ -49,6 +50,7 @@
 pub mod base;
 pub mod codegen_attrs;
 pub mod common;
+pub mod coverageinfo;
 pub mod debuginfo;
 pub mod errors;
 pub mod meth;
@@ -165,6 +167,11 @@ pub struct CrateInfo {
     pub dependency_formats: Lrc<Dependencies>,
     pub windows_subsystem: Option<String>,
     pub natvis_debugger_visualizers: BTreeSet<DebuggerVisualizerFile>,
+    /// The call graph of the local crate, for `-Z stack-report`.
+    pub stack_call_graph: Option<CallGraph>,
+    /// The crate defining the item of each known symbol, for
+    /// `--print=size-attribution`.
+    pub symbol_def_crates: Option<FxHashMap<String, Symbol>>,
 }
 
 #[derive(Encodable, Decodable)]
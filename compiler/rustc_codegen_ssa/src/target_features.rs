@@ -8,11 +8,12 @@
 use rustc_hir::def_id::DefId;
 use rustc_hir::def_id::LocalDefId;
 use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::middle::codegen_fn_attrs::TargetClone;
 use rustc_middle::query::Providers;
 use rustc_middle::ty::TyCtxt;
 use rustc_session::parse::feature_err;
-use rustc_span::symbol::sym;
 use rustc_span::symbol::Symbol;
+use rustc_span::symbol::{kw, sym};
 use rustc_span::Span;
 
 pub fn from_target_feature(
@@ -30,7 +31,6 @@ pub fn from_target_feature(
             .span_suggestion(span, "must be of the form", code, Applicability::HasPlaceholders)
             .emit();
     };
-    let rust_features = tcx.features();
     for item in list {
         // Only `enable = ...` is accepted in the meta-item list.
         if !item.has_name(sym::enable) {
@@ -46,52 +46,111 @@ pub fn from_target_feature(
 
         // We allow comma separation to enable multiple features.
         target_features.extend(value.as_str().split(',').filter_map(|feature| {
-            let Some(feature_gate) = supported_target_features.get(feature) else {
-                let msg = format!("the feature named `{feature}` is not valid for this target");
-                let mut err = tcx.dcx().struct_span_err(item.span(), msg);
-                err.span_label(item.span(), format!("`{feature}` is not valid for this target"));
-                if let Some(stripped) = feature.strip_prefix('+') {
-                    let valid = supported_target_features.contains_key(stripped);
-                    if valid {
-                        err.help("consider removing the leading `+` in the feature name");
-                    }
-                }
-                err.emit();
-                return None;
-            };
+            check_target_feature(tcx, item.span(), supported_target_features, feature)
+        }));
+    }
+}
 
-            // Only allow features whose feature gates have been enabled.
-            let allowed = match feature_gate.as_ref().copied() {
-                Some(sym::arm_target_feature) => rust_features.arm_target_feature,
-                Some(sym::hexagon_target_feature) => rust_features.hexagon_target_feature,
-                Some(sym::powerpc_target_feature) => rust_features.powerpc_target_feature,
-                Some(sym::mips_target_feature) => rust_features.mips_target_feature,
-                Some(sym::riscv_target_feature) => rust_features.riscv_target_feature,
-                Some(sym::avx512_target_feature) => rust_features.avx512_target_feature,
-                Some(sym::sse4a_target_feature) => rust_features.sse4a_target_feature,
-                Some(sym::tbm_target_feature) => rust_features.tbm_target_feature,
-                Some(sym::wasm_target_feature) => rust_features.wasm_target_feature,
-                Some(sym::rtm_target_feature) => rust_features.rtm_target_feature,
-                Some(sym::ermsb_target_feature) => rust_features.ermsb_target_feature,
-                Some(sym::bpf_target_feature) => rust_features.bpf_target_feature,
-                Some(sym::aarch64_ver_target_feature) => rust_features.aarch64_ver_target_feature,
-                Some(sym::csky_target_feature) => rust_features.csky_target_feature,
-                Some(sym::loongarch_target_feature) => rust_features.loongarch_target_feature,
-                Some(name) => bug!("unknown target feature gate {}", name),
-                None => true,
-            };
-            if !allowed {
-                feature_err(
-                    &tcx.sess.parse_sess,
-                    feature_gate.unwrap(),
-                    item.span(),
-                    format!("the target feature `{feature}` is currently unstable"),
-                )
+/// Parses `#[target_clones("avx2", "sse4.1,popcnt", "default")]`: each string is the comma
+/// separated list of the target features enabled in a clone of the function, `default` being the
+/// function compiled normally, which must come last.
+pub fn from_target_clones(
+    tcx: TyCtxt<'_>,
+    attr: &ast::Attribute,
+    supported_target_features: &FxHashMap<String, Option<Symbol>>,
+    target_clones: &mut Vec<TargetClone>,
+) {
+    let Some(list) = attr.meta_item_list() else { return };
+    let mut default_span = None;
+    for item in list {
+        let Some(value) = item.lit().and_then(|lit| lit.kind.str()) else {
+            tcx.dcx()
+                .struct_span_err(item.span(), "malformed `target_clones` attribute input")
+                .span_label(item.span(), "expected a string listing target features")
+                .emit();
+            continue;
+        };
+        if let Some(default_span) = default_span {
+            tcx.dcx()
+                .struct_span_err(item.span(), "clones after the `default` clone are never selected")
+                .span_label(default_span, "`default` is always supported")
                 .emit();
+            continue;
+        }
+        if value == kw::Default {
+            default_span = Some(item.span());
+            continue;
+        }
+
+        let features = value
+            .as_str()
+            .split(',')
+            .filter_map(|feature| {
+                check_target_feature(tcx, item.span(), supported_target_features, feature)
+            })
+            .collect();
+        target_clones.push(TargetClone { features });
+    }
+    if default_span.is_none() {
+        tcx.dcx()
+            .struct_span_err(attr.span, "`#[target_clones]` requires a `default` clone")
+            .help("add `\"default\"` at the end of the list, for the CPUs without these features")
+            .emit();
+    }
+}
+
+/// Checks that `feature` is a target feature of this target that is enabled for this crate.
+fn check_target_feature(
+    tcx: TyCtxt<'_>,
+    span: Span,
+    supported_target_features: &FxHashMap<String, Option<Symbol>>,
+    feature: &str,
+) -> Option<Symbol> {
+    let Some(feature_gate) = supported_target_features.get(feature) else {
+        let msg = format!("the feature named `{feature}` is not valid for this target");
+        let mut err = tcx.dcx().struct_span_err(span, msg);
+        err.span_label(span, format!("`{feature}` is not valid for this target"));
+        if let Some(stripped) = feature.strip_prefix('+') {
+            let valid = supported_target_features.contains_key(stripped);
+            if valid {
+                err.help("consider removing the leading `+` in the feature name");
             }
-            Some(Symbol::intern(feature))
-        }));
+        }
+        err.emit();
+        return None;
+    };
+
+    // Only allow features whose feature gates have been enabled.
+    let rust_features = tcx.features();
+    let allowed = match feature_gate.as_ref().copied() {
+        Some(sym::arm_target_feature) => rust_features.arm_target_feature,
+        Some(sym::hexagon_target_feature) => rust_features.hexagon_target_feature,
+        Some(sym::powerpc_target_feature) => rust_features.powerpc_target_feature,
+        Some(sym::mips_target_feature) => rust_features.mips_target_feature,
+        Some(sym::riscv_target_feature) => rust_features.riscv_target_feature,
+        Some(sym::avx512_target_feature) => rust_features.avx512_target_feature,
+        Some(sym::sse4a_target_feature) => rust_features.sse4a_target_feature,
+        Some(sym::tbm_target_feature) => rust_features.tbm_target_feature,
+        Some(sym::wasm_target_feature) => rust_features.wasm_target_feature,
+        Some(sym::rtm_target_feature) => rust_features.rtm_target_feature,
+        Some(sym::ermsb_target_feature) => rust_features.ermsb_target_feature,
+        Some(sym::bpf_target_feature) => rust_features.bpf_target_feature,
+        Some(sym::aarch64_ver_target_feature) => rust_features.aarch64_ver_target_feature,
+        Some(sym::csky_target_feature) => rust_features.csky_target_feature,
+        Some(sym::loongarch_target_feature) => rust_features.loongarch_target_feature,
+        Some(name) => bug!("unknown target feature gate {}", name),
+        None => true,
+    };
+    if !allowed {
+        feature_err(
+            &tcx.sess.parse_sess,
+            feature_gate.unwrap(),
+            span,
+            format!("the target feature `{feature}` is currently unstable"),
+        )
+        .emit();
     }
+    Some(Symbol::intern(feature))
 }
 
 /// Computes the set of target features used in a function for the purposes of
@@ -0,0 +1,146 @@
+//! Finding the natural loops of a control-flow graph, and how they nest.
+//!
+//! An edge whose target dominates its source is a back edge. Its target is the header of a natural
+//! loop, made of the nodes that can reach the source of one of the back edges of the header
+//! without going through the header. Two natural loops with different headers are either disjoint
+//! or nested, so the loops of a graph form a forest.
+//!
+//! Note that cycles that can be entered through several nodes, which only occur in irreducible
+//! control-flow graphs, have no back edge and thus aren't found.
+
+use super::dominators::Dominators;
+use super::iterate::reverse_post_order;
+use super::ControlFlowGraph;
+use rustc_index::bit_set::BitSet;
+use rustc_index::{Idx, IndexSlice, IndexVec};
+
+#[cfg(test)]
+mod tests;
+
+rustc_index::newtype_index! {
+    #[orderable]
+    pub struct LoopIdx {}
+}
+
+#[derive(Clone, Debug)]
+pub struct Loop<Node: Idx> {
+    /// The only node of the loop that is entered from outside of the loop. It dominates the other
+    /// nodes of the loop.
+    pub header: Node,
+    /// The innermost loop containing this one.
+    pub parent: Option<LoopIdx>,
+    /// The number of loops containing this one, including itself.
+    pub depth: u32,
+    /// The nodes of the loop that jump back to the header.
+    pub latches: Vec<Node>,
+    /// The nodes of the loop, including the nodes of the loops nested in it, in reverse
+    /// postorder. The header is the first one.
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LoopNest<Node: Idx> {
+    /// The loops, ordered such that a loop comes before the loops nested in it.
+    loops: IndexVec<LoopIdx, Loop<Node>>,
+    innermost_loop: IndexVec<Node, Option<LoopIdx>>,
+}
+
+pub fn loop_nest<G: ControlFlowGraph>(
+    g: &G,
+    dominators: &Dominators<G::Node>,
+) -> LoopNest<G::Node> {
+    let rpo = reverse_post_order(g, g.start_node());
+    let mut rpo_rank = IndexVec::from_elem_n(usize::MAX, g.num_nodes());
+    for (rank, &node) in rpo.iter().enumerate() {
+        rpo_rank[node] = rank;
+    }
+
+    let mut latches = IndexVec::from_elem_n(Vec::new(), g.num_nodes());
+    for &node in &rpo {
+        for succ in g.successors(node) {
+            if dominators.dominates(succ, node) {
+                latches[succ].push(node);
+            }
+        }
+    }
+
+    let mut nest = LoopNest {
+        loops: IndexVec::new(),
+        innermost_loop: IndexVec::from_elem_n(None, g.num_nodes()),
+    };
+    let mut in_loop = BitSet::new_empty(g.num_nodes());
+    // The header of a loop dominates the headers of the loops nested in it, so visiting the
+    // headers in reverse postorder finds a loop before the loops nested in it.
+    for &header in &rpo {
+        if latches[header].is_empty() {
+            continue;
+        }
+
+        // All the reachable predecessors of a node of the loop other than the header are dominated
+        // by the header, so the search stays in the loop.
+        in_loop.clear();
+        in_loop.insert(header);
+        let mut nodes = vec![header];
+        let mut stack = latches[header].clone();
+        while let Some(node) = stack.pop() {
+            if !in_loop.insert(node) {
+                continue;
+            }
+            nodes.push(node);
+            stack.extend(
+                g.predecessors(node)
+                    .filter(|&pred| dominators.is_reachable(pred) && !in_loop.contains(pred)),
+            );
+        }
+        nodes.sort_by_key(|&node| rpo_rank[node]);
+
+        let parent = nest.innermost_loop[header];
+        let depth = parent.map_or(1, |parent| nest.loops[parent].depth + 1);
+        let loop_idx = nest.loops.next_index();
+        for &node in &nodes {
+            nest.innermost_loop[node] = Some(loop_idx);
+        }
+        let latches = std::mem::take(&mut latches[header]);
+        nest.loops.push(Loop { header, parent, depth, latches, nodes });
+    }
+    nest
+}
+
+impl<Node: Idx> LoopNest<Node> {
+    /// The loops of the graph, ordered such that a loop comes before the loops nested in it.
+    pub fn loops(&self) -> &IndexSlice<LoopIdx, Loop<Node>> {
+        &self.loops
+    }
+
+    /// The innermost loop containing a node, if any.
+    pub fn innermost_loop(&self, node: Node) -> Option<LoopIdx> {
+        self.innermost_loop[node]
+    }
+
+    /// Returns true if `node` is in `loop_idx` or in a loop nested in it.
+    pub fn contains(&self, loop_idx: LoopIdx, node: Node) -> bool {
+        let depth = self.loops[loop_idx].depth;
+        let mut current = self.innermost_loop[node];
+        while let Some(current_idx) = current {
+            let current_loop = &self.loops[current_idx];
+            if current_loop.depth <= depth {
+                return current_idx == loop_idx;
+            }
+            current = current_loop.parent;
+        }
+        false
+    }
+
+    /// Returns true if the node is the header of a loop.
+    pub fn is_header(&self, node: Node) -> bool {
+        self.innermost_loop[node].is_some_and(|loop_idx| self.loops[loop_idx].header == node)
+    }
+}
+
+impl<Node: Idx> std::ops::Index<LoopIdx> for LoopNest<Node> {
+    type Output = Loop<Node>;
+
+    fn index(&self, index: LoopIdx) -> &Loop<Node> {
+        &self.loops[index]
+    }
+}
@@ -0,0 +1,105 @@
+use super::super::dominators::dominators;
+use super::*;
+
+use super::super::tests::TestGraph;
+
+fn loop_nest_of(graph: &TestGraph) -> LoopNest<usize> {
+    loop_nest(graph, &dominators(graph))
+}
+
+#[test]
+fn no_loop() {
+    let graph = TestGraph::new(0, &[(0, 1), (0, 2), (1, 3), (2, 3)]);
+
+    let nest = loop_nest_of(&graph);
+    assert!(nest.loops().is_empty());
+    assert_eq!(nest.innermost_loop(3), None);
+}
+
+#[test]
+fn simple_loop() {
+    let graph = TestGraph::new(0, &[(0, 1), (1, 2), (2, 1), (1, 3)]);
+
+    let nest = loop_nest_of(&graph);
+    assert_eq!(nest.loops().len(), 1);
+    let l = &nest[LoopIdx::from_u32(0)];
+    assert_eq!(l.header, 1);
+    assert_eq!(l.parent, None);
+    assert_eq!(l.depth, 1);
+    assert_eq!(l.latches, [2]);
+    assert_eq!(l.nodes, [1, 2]);
+    assert!(nest.is_header(1));
+    assert!(!nest.is_header(2));
+    assert_eq!(nest.innermost_loop(0), None);
+    assert_eq!(nest.innermost_loop(3), None);
+}
+
+#[test]
+fn self_loop() {
+    let graph = TestGraph::new(0, &[(0, 1), (1, 1), (1, 2)]);
+
+    let nest = loop_nest_of(&graph);
+    assert_eq!(nest.loops().len(), 1);
+    let l = &nest[LoopIdx::from_u32(0)];
+    assert_eq!(l.header, 1);
+    assert_eq!(l.latches, [1]);
+    assert_eq!(l.nodes, [1]);
+}
+
+#[test]
+fn nested_loops() {
+    // 1 is the header of the outer loop, 2 the header of the inner loop.
+    let graph = TestGraph::new(
+        0,
+        &[(0, 1), (1, 2), (2, 3), (3, 2), (3, 4), (4, 1), (1, 5), (2, 6), (6, 4)],
+    );
+
+    let nest = loop_nest_of(&graph);
+    assert_eq!(nest.loops().len(), 2);
+    let outer = LoopIdx::from_u32(0);
+    let inner = LoopIdx::from_u32(1);
+    assert_eq!(nest[outer].header, 1);
+    assert_eq!(nest[outer].depth, 1);
+    assert_eq!(nest[outer].latches, [4]);
+    assert_eq!(nest[inner].header, 2);
+    assert_eq!(nest[inner].parent, Some(outer));
+    assert_eq!(nest[inner].depth, 2);
+    assert_eq!(nest[inner].latches, [3]);
+
+    let mut outer_nodes = nest[outer].nodes.clone();
+    assert_eq!(outer_nodes[0], 1);
+    outer_nodes.sort();
+    assert_eq!(outer_nodes, [1, 2, 3, 4, 6]);
+    assert_eq!(nest[inner].nodes, [2, 3]);
+
+    assert_eq!(nest.innermost_loop(3), Some(inner));
+    assert_eq!(nest.innermost_loop(6), Some(outer));
+    assert!(nest.contains(outer, 3));
+    assert!(nest.contains(inner, 3));
+    assert!(!nest.contains(inner, 6));
+    assert!(!nest.contains(outer, 5));
+}
+
+#[test]
+fn sibling_loops() {
+    let graph = TestGraph::new(0, &[(0, 1), (1, 1), (1, 2), (2, 3), (3, 2), (3, 4)]);
+
+    let nest = loop_nest_of(&graph);
+    assert_eq!(nest.loops().len(), 2);
+    let first = LoopIdx::from_u32(0);
+    let second = LoopIdx::from_u32(1);
+    assert_eq!(nest[first].header, 1);
+    assert_eq!(nest[second].header, 2);
+    assert_eq!(nest[second].parent, None);
+    assert!(!nest.contains(first, 2));
+    assert!(!nest.contains(second, 1));
+}
+
+#[test]
+fn irreducible() {
+    // The cycle between 1 and 2 can be entered through both nodes, so it has no header.
+    let graph = TestGraph::new(0, &[(0, 1), (0, 2), (1, 2), (2, 1)]);
+
+    let nest = loop_nest_of(&graph);
+    assert!(nest.loops().is_empty());
+}
@@ -40,6 +40,11 @@ pub fn borrow(&self) -> MappedReadGuard<'_, T> {
         ReadGuard::map(borrow, |opt| opt.as_ref().unwrap())
     }
 
+    /// Whether the value was stolen, in which case it can't be read anymore.
+    pub fn is_stolen(&self) -> bool {
+        self.value.borrow().is_none()
+    }
+
     #[track_caller]
     pub fn get_mut(&mut self) -> &mut T {
         self.value.get_mut().as_mut().expect("attempt to read from stolen value")
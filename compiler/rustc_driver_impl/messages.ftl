@@ -1,3 +1,17 @@
+driver_impl_fix_applied = applied {$count} {$count ->
+    [one] suggestion
+    *[other] suggestions
+    } to {$files} {$files ->
+    [one] file
+    *[other] files
+    }
+
+driver_impl_fix_source_changed = not applying suggestions to `{$path}` because it was modified during compilation
+
+driver_impl_fix_unable_to_read = failed to read `{$path}` to apply suggestions: {$err}
+
+driver_impl_fix_unable_to_write = failed to write suggestions to `{$path}`: {$err}
+
 driver_impl_ice = the compiler unexpectedly panicked. this is a bug.
 driver_impl_ice_bug_report = we would appreciate a bug report: {$bug_report_url}
 driver_impl_ice_bug_report_internal_feature = using internal features is not supported and expected to cause internal compiler errors when used incorrectly
@@ -0,0 +1,47 @@
+//! Implementation of `-Z fix`: writing the machine-applicable suggestions
+//! collected during compilation back to the source files.
+
+use rustc_errors::fix::resolve_fixes;
+use rustc_session::Session;
+
+use std::fs;
+
+use crate::session_diagnostics::{FixApplied, FixSourceChanged, FixUnableToRead, FixUnableToWrite};
+
+/// Applies the machine-applicable suggestions emitted so far and returns the
+/// number of edits that were written.
+pub(crate) fn apply_fixes(sess: &Session) -> usize {
+    let suggestions = sess.dcx().steal_fixes();
+    if suggestions.is_empty() {
+        return 0;
+    }
+
+    let mut applied = 0;
+    let mut files = 0;
+    for fix in resolve_fixes(sess.source_map(), &suggestions) {
+        let path = &fix.path;
+        let src = match fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(err) => {
+                sess.dcx().emit_warning(FixUnableToRead { path, err });
+                continue;
+            }
+        };
+        // Don't clobber edits made while we were compiling.
+        if !fix.file.src_hash.matches(&src) {
+            sess.dcx().emit_warning(FixSourceChanged { path });
+            continue;
+        }
+        if let Err(err) = fs::write(path, fix.apply(&src)) {
+            sess.dcx().emit_warning(FixUnableToWrite { path, err });
+            continue;
+        }
+        applied += fix.edits.len();
+        files += 1;
+    }
+
+    if applied > 0 {
+        sess.dcx().emit_note(FixApplied { count: applied, files });
+    }
+    applied
+}
@@ -21,6 +21,7 @@
 
 use rustc_ast as ast;
 use rustc_codegen_ssa::{traits::CodegenBackend, CodegenErrors, CodegenResults};
+use rustc_data_structures::defer;
 use rustc_data_structures::profiling::{
     get_resident_set_size, print_time_passes_entry, TimePassesFormat,
 };
@@ -81,6 +82,7 @@
 use {do_not_use_print as print, do_not_use_print as println};
 
 pub mod args;
+mod fix;
 pub mod pretty;
 #[macro_use]
 mod print;
@@ -292,6 +294,7 @@ fn run_compiler(
     using_internal_features: Arc<std::sync::atomic::AtomicBool>,
 ) -> interface::Result<()> {
     let mut default_early_dcx = EarlyDiagCtxt::new(ErrorOutputType::default());
+    let original_args = at_args;
 
     // Throw away the first argument, the name of the binary.
     // In case of at_args being empty, as might be the case by
@@ -314,6 +317,13 @@ fn run_compiler(
         return Ok(());
     }
 
+    // With `-Z fix-passes`, recompile the fixed sources to apply the suggestions uncovered by, or
+    // conflicting with, the ones applied in this pass. The file loader and codegen backend can't
+    // be recreated, so custom drivers setting them only get a single pass.
+    let fix_passes_left = if sopts.unstable_opts.fix { sopts.unstable_opts.fix_passes } else { 0 };
+    let can_rerun = file_loader.is_none() && make_codegen_backend.is_none();
+    let rerun_internal_features = using_internal_features.clone();
+
     let (odir, ofile) = make_output(&matches);
     let mut config = interface::Config {
         opts: sopts,
@@ -357,10 +367,16 @@ fn run_compiler(
     default_early_dcx.abort_if_errors();
     drop(default_early_dcx);
 
-    interface::run_compiler(config, |compiler| {
+    let mut fixes_applied = 0;
+    let result = interface::run_compiler(config, |compiler| {
         let sess = &compiler.sess;
         let codegen_backend = &*compiler.codegen_backend;
 
+        // Apply the collected suggestions however compilation ends, including
+        // when it stops early because of errors.
+        let _apply_fixes =
+            sess.opts.unstable_opts.fix.then(|| defer(|| fixes_applied = fix::apply_fixes(sess)));
+
         // This implements `-Whelp`. It should be handled very early, like
         // `--help`/`-Zhelp`/`-Chelp`. This is the earliest it can run, because
         // it must happen after lints are registered, during session creation.
@@ -480,7 +496,15 @@ fn run_compiler(
         }
 
         Ok(())
-    })
+    });
+
+    if fixes_applied > 0 && fix_passes_left > 1 && can_rerun {
+        let mut args = original_args.to_vec();
+        args.push(format!("-Zfix-passes={}", fix_passes_left - 1));
+        return run_compiler(&args, callbacks, None, None, rerun_internal_features);
+    }
+
+    result
 }
 
 // Extract output directory and file from matches.
@@ -705,9 +729,14 @@ fn print_crate_info(
     #[allow(unused_imports)]
     use {do_not_use_safe_print as safe_print, do_not_use_safe_print as safe_println};
 
-    // NativeStaticLibs and LinkArgs are special - printed during linking
+    // NativeStaticLibs, LinkArgs and SizeAttribution are special - printed during linking
     // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs) {
+    if sess
+        .opts
+        .prints
+        .iter()
+        .all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs || p.kind == SizeAttribution)
+    {
         return Compilation::Continue;
     }
 
@@ -822,6 +851,7 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            SizeAttribution => {}
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
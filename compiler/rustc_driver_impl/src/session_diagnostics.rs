@@ -1,5 +1,7 @@
 use rustc_macros::{Diagnostic, Subdiagnostic};
 
+use std::path::Path;
+
 #[derive(Diagnostic)]
 #[diag(driver_impl_rlink_unable_to_read)]
 pub(crate) struct RlinkUnableToRead {
@@ -83,3 +85,30 @@ pub(crate) struct IceFlags {
 #[derive(Diagnostic)]
 #[diag(driver_impl_ice_exclude_cargo_defaults)]
 pub(crate) struct IceExcludeCargoDefaults;
+
+#[derive(Diagnostic)]
+#[diag(driver_impl_fix_unable_to_read)]
+pub(crate) struct FixUnableToRead<'a> {
+    pub path: &'a Path,
+    pub err: std::io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(driver_impl_fix_unable_to_write)]
+pub(crate) struct FixUnableToWrite<'a> {
+    pub path: &'a Path,
+    pub err: std::io::Error,
+}
+
+#[derive(Diagnostic)]
+#[diag(driver_impl_fix_source_changed)]
+pub(crate) struct FixSourceChanged<'a> {
+    pub path: &'a Path,
+}
+
+#[derive(Diagnostic)]
+#[diag(driver_impl_fix_applied)]
+pub(crate) struct FixApplied {
+    pub count: usize,
+    pub files: usize,
+}
@@ -0,0 +1,149 @@
+//! Applying machine-applicable suggestions to source files (`-Z fix`).
+//!
+//! The `DiagCtxt` collects the `MachineApplicable` suggestions of every
+//! emitted diagnostic (see `DiagCtxtFlags::collect_fixes`). This module turns
+//! them into a set of non-overlapping edits per source file, which the driver
+//! then writes back to disk.
+//!
+//! A suggestion is applied either completely or not at all: if any of its
+//! parts overlaps an edit from a previously accepted suggestion, the whole
+//! suggestion is dropped. It may still apply on a later pass, once the source
+//! has been recompiled (see `-Z fix-passes`).
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, Pos, RealFileName, SourceFile, Span, StableSourceFileId};
+
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::CodeSuggestion;
+
+#[cfg(test)]
+mod tests;
+
+/// Replaces `range` (byte offsets into the file as it is on disk) with
+/// `replacement`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl Edit {
+    fn conflicts_with(&self, other: &Edit) -> bool {
+        if self.range.is_empty() && other.range.is_empty() {
+            // Two insertions at the same point have no well-defined order.
+            self.range.start == other.range.start
+        } else {
+            self.range.start < other.range.end && other.range.start < self.range.end
+        }
+    }
+}
+
+/// The edits to apply to a single file, sorted and non-overlapping.
+pub struct FileFix {
+    pub file: Lrc<SourceFile>,
+    pub path: PathBuf,
+    pub edits: Vec<Edit>,
+}
+
+impl FileFix {
+    /// Applies the edits to `src`, which must be the contents of the file the
+    /// suggestions were computed for.
+    pub fn apply(&self, src: &str) -> String {
+        apply_edits(src, &self.edits)
+    }
+}
+
+/// Resolves `suggestions` to edits grouped by file, dropping the ones that
+/// can't be applied: suggestions offering several alternatives, suggestions
+/// touching files that don't live on the local file system (e.g. code from
+/// other crates), and suggestions that conflict with an earlier one.
+pub fn resolve_fixes(sm: &SourceMap, suggestions: &[CodeSuggestion]) -> Vec<FileFix> {
+    let mut fixes: FxIndexMap<StableSourceFileId, FileFix> = FxIndexMap::default();
+
+    'suggestions: for suggestion in suggestions {
+        let [substitution] = &suggestion.substitutions[..] else {
+            continue;
+        };
+
+        let mut edits: Vec<(Lrc<SourceFile>, PathBuf, Edit)> =
+            Vec::with_capacity(substitution.parts.len());
+        for part in &substitution.parts {
+            let Some((file, path, range)) = resolve_span(sm, part.span) else {
+                continue 'suggestions;
+            };
+            let edit = Edit { range, replacement: part.snippet.clone() };
+            if edits
+                .iter()
+                .any(|(f, _, e)| f.stable_id == file.stable_id && e.conflicts_with(&edit))
+            {
+                continue 'suggestions;
+            }
+            edits.push((file, path, edit));
+        }
+
+        // Identical edits from duplicated suggestions are applied once, any
+        // other overlap rejects the whole suggestion.
+        let mut new_edits = vec![];
+        for (file, path, edit) in edits {
+            let accepted = fixes.get(&file.stable_id).map_or(&[][..], |fix| &fix.edits[..]);
+            if accepted.contains(&edit) {
+                continue;
+            }
+            if accepted.iter().any(|e| e.conflicts_with(&edit)) {
+                continue 'suggestions;
+            }
+            new_edits.push((file, path, edit));
+        }
+        for (file, path, edit) in new_edits {
+            fixes
+                .entry(file.stable_id)
+                .or_insert_with(|| FileFix { file, path, edits: vec![] })
+                .edits
+                .push(edit);
+        }
+    }
+
+    fixes
+        .into_values()
+        .map(|mut fix| {
+            fix.edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+            fix
+        })
+        .collect()
+}
+
+/// Returns the file `span` points into, its path on disk and the range it
+/// covers in the original (i.e. not normalized) source.
+fn resolve_span(sm: &SourceMap, span: Span) -> Option<(Lrc<SourceFile>, PathBuf, Range<usize>)> {
+    if span.is_dummy() {
+        return None;
+    }
+    let file = sm.lookup_source_file(span.lo());
+    if file.is_imported() || !file.contains(span.hi()) {
+        return None;
+    }
+    let FileName::Real(RealFileName::LocalPath(path)) = &file.name else {
+        return None;
+    };
+    let path = path.clone();
+    let lo = file.original_relative_byte_pos(span.lo()).to_usize();
+    let hi = file.original_relative_byte_pos(span.hi()).to_usize();
+    Some((file, path, lo..hi))
+}
+
+/// Applies sorted, non-overlapping `edits` to `src`.
+pub fn apply_edits(src: &str, edits: &[Edit]) -> String {
+    let mut fixed = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits {
+        fixed.push_str(&src[pos..edit.range.start]);
+        fixed.push_str(&edit.replacement);
+        pos = edit.range.end;
+    }
+    fixed.push_str(&src[pos..]);
+    fixed
+}
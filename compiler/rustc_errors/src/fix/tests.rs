@@ -0,0 +1,102 @@
+use super::*;
+
+use crate::{Applicability, Substitution, SubstitutionPart, SuggestionStyle};
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::BytePos;
+
+use std::path::Path;
+
+fn suggestion(parts: &[(u32, u32, &str)]) -> CodeSuggestion {
+    CodeSuggestion {
+        substitutions: vec![Substitution {
+            parts: parts
+                .iter()
+                .map(|&(lo, hi, snippet)| SubstitutionPart {
+                    span: Span::with_root_ctxt(BytePos(lo), BytePos(hi)),
+                    snippet: snippet.to_owned(),
+                })
+                .collect(),
+        }],
+        msg: "fix it".into(),
+        style: SuggestionStyle::ShowCode,
+        applicability: Applicability::MachineApplicable,
+    }
+}
+
+/// Resolves `suggestions` against a single file containing `code` and
+/// returns the fixed source.
+fn fix(code: &str, suggestions: &[CodeSuggestion]) -> String {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = SourceMap::new(FilePathMapping::empty());
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fixes = resolve_fixes(&sm, suggestions);
+        match &fixes[..] {
+            [] => code.to_owned(),
+            [fix] => {
+                assert_eq!(fix.path, Path::new("test.rs"));
+                fix.apply(code)
+            }
+            _ => panic!("expected a single file"),
+        }
+    })
+}
+
+#[test]
+fn single_replacement() {
+    assert_eq!(fix("let x = 1;", &[suggestion(&[(4, 5, "_x")])]), "let _x = 1;");
+}
+
+#[test]
+fn multipart() {
+    assert_eq!(
+        fix("foo(a, b)", &[suggestion(&[(0, 0, "self."), (4, 5, "&a")])]),
+        "self.foo(&a, b)"
+    );
+}
+
+#[test]
+fn overlapping_suggestion_is_dropped() {
+    assert_eq!(
+        fix("let x = 1;", &[suggestion(&[(4, 5, "_x")]), suggestion(&[(4, 9, "y = 2")])]),
+        "let _x = 1;"
+    );
+    // A suggestion is applied completely or not at all.
+    assert_eq!(
+        fix(
+            "let x = 1;",
+            &[suggestion(&[(4, 5, "_x")]), suggestion(&[(0, 3, "const"), (4, 5, "X")])]
+        ),
+        "let _x = 1;"
+    );
+}
+
+#[test]
+fn insertions_at_same_point() {
+    assert_eq!(
+        fix("fn f() {}", &[suggestion(&[(0, 0, "pub ")]), suggestion(&[(0, 0, "unsafe ")])]),
+        "pub fn f() {}"
+    );
+    // Duplicated suggestions are applied once.
+    assert_eq!(
+        fix("fn f() {}", &[suggestion(&[(0, 0, "pub ")]), suggestion(&[(0, 0, "pub ")])]),
+        "pub fn f() {}"
+    );
+}
+
+#[test]
+fn adjacent_edits() {
+    assert_eq!(fix("a+b", &[suggestion(&[(0, 1, "x")]), suggestion(&[(1, 2, " - ")])]), "x - b");
+}
+
+#[test]
+fn alternatives_are_not_applied() {
+    let mut sugg = suggestion(&[(4, 5, "_x")]);
+    sugg.substitutions.push(sugg.substitutions[0].clone());
+    assert_eq!(fix("let x = 1;", &[sugg]), "let x = 1;");
+}
+
+#[test]
+fn crlf_offsets() {
+    // Spans point into the normalized source, edits into the file on disk.
+    assert_eq!(fix("\r\nlet x = 1;\r\n", &[suggestion(&[(5, 6, "_x")])]), "\r\nlet _x = 1;\r\n");
+}
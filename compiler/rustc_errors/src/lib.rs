@@ -80,10 +80,12 @@
 mod diagnostic_impls;
 pub mod emitter;
 pub mod error;
+pub mod fix;
 pub mod json;
 mod lock;
 pub mod markdown;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 #[cfg(test)]
@@ -484,6 +486,10 @@ struct DiagCtxtInner {
     /// The file where the ICE information is stored. This allows delayed_span_bug backtraces to be
     /// stored along side the main panic backtrace.
     ice_file: Option<PathBuf>,
+
+    /// Machine-applicable suggestions of the emitted diagnostics, collected
+    /// when [`DiagCtxtFlags::collect_fixes`] is set.
+    fixes: Vec<CodeSuggestion>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -535,6 +541,10 @@ pub struct DiagCtxtFlags {
     pub deduplicate_diagnostics: bool,
     /// Track where errors are created. Enabled with `-Ztrack-diagnostics`.
     pub track_diagnostics: bool,
+    /// If true, machine-applicable suggestions of emitted diagnostics are kept
+    /// so that they can be applied to the source files.
+    /// (rustc: see `-Z fix`)
+    pub collect_fixes: bool,
 }
 
 impl Drop for DiagCtxtInner {
@@ -614,6 +624,7 @@ pub fn with_emitter(emitter: Box<DynEmitter>) -> Self {
                 unstable_expect_diagnostics: Vec::new(),
                 fulfilled_expectations: Default::default(),
                 ice_file: None,
+                fixes: Vec::new(),
             }),
         }
     }
@@ -1293,6 +1304,12 @@ pub fn create_note<'a>(
         note.into_diagnostic(self, Note)
     }
 
+    /// Takes the machine-applicable suggestions collected so far, see
+    /// [`DiagCtxtFlags::collect_fixes`].
+    pub fn steal_fixes(&self) -> Vec<CodeSuggestion> {
+        std::mem::take(&mut self.inner.borrow_mut().fixes)
+    }
+
     pub fn emit_artifact_notification(&self, path: &Path, artifact_type: &str) {
         self.inner.borrow_mut().emitter.emit_artifact_notification(path, artifact_type);
     }
@@ -1502,6 +1519,16 @@ fn emit_diagnostic_without_consuming(
                 }
 
                 self.emitter.emit_diagnostic(diagnostic);
+                if self.flags.collect_fixes {
+                    self.fixes.extend(
+                        diagnostic
+                            .suggestions
+                            .iter()
+                            .flatten()
+                            .filter(|sugg| sugg.applicability == Applicability::MachineApplicable)
+                            .cloned(),
+                    );
+                }
                 if diagnostic.is_error() {
                     self.deduplicated_err_count += 1;
                 } else if let Warning(_) = diagnostic.level {
@@ -0,0 +1,669 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF 2.1.0] is the interchange format consumed by most code-scanning
+//! services. Unlike the JSON emitter, which writes one object per diagnostic,
+//! a SARIF log is a single document, so this emitter buffers every result and
+//! writes the whole log when it is dropped (i.e. when the `DiagCtxt` owning it
+//! goes away at the end of the session). The emitters of the `DiagCtxt`s used
+//! before the session exists add their results to the session's log, so that
+//! stderr only ever gets one document (see [`STDERR_LOG`]).
+//!
+//! The mapping is:
+//!
+//! - error codes and lint names become `rules`, referenced by `ruleId`,
+//! - primary spans become `locations`, secondary spans, sub-diagnostics and
+//!   macro backtraces become `relatedLocations`,
+//! - suggestions become `fixes`, with their `Applicability` recorded as a
+//!   property so that consumers can decide which ones to apply automatically.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{to_fluent_args, Translate};
+use crate::{
+    CodeSuggestion, DiagnosticId, FluentBundle, LazyFallbackBundle, Level, MultiSpan, SubDiagnostic,
+};
+use rustc_lint_defs::Applicability;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
+use rustc_error_messages::FluentArgs;
+use rustc_span::{FileName, Span};
+use std::error::Report;
+use std::io::{self, Write};
+use std::mem;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// `uriBaseId` used for files given by a relative path, i.e. relative to the
+/// directory the compiler was invoked from.
+const SRCROOT: &str = "%SRCROOT%";
+
+pub struct SarifEmitter {
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    fallback_bundle: LazyFallbackBundle,
+    pretty: bool,
+    tool_name: &'static str,
+    tool_version: Option<String>,
+    log: LogRef,
+}
+
+/// The log written to stderr.
+///
+/// Besides the session's `DiagCtxt`, rustc creates short-lived ones for the
+/// diagnostics emitted before the session exists, e.g. while parsing options.
+/// All their emitters add their results to this log, which is written once:
+/// when the last session emitter is dropped or, if compilation aborts before
+/// there is a session, when the early emitter reporting the error is dropped.
+static STDERR_LOG: OnceLock<Mutex<SharedLog>> = OnceLock::new();
+
+/// The rules and results of a log.
+#[derive(Default)]
+struct LogContents {
+    /// Rules referenced so far, keyed by their id, in order of first use.
+    rules: FxIndexMap<String, Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl LogContents {
+    fn push(&mut self, rule: Option<Rule>, mut result: SarifResult) {
+        if let Some(rule) = rule {
+            let entry = self.rules.entry(rule.id.clone());
+            result.rule_index = Some(entry.index());
+            entry.or_insert(rule);
+        }
+        self.results.push(result);
+    }
+}
+
+#[derive(Default)]
+struct SharedLog {
+    contents: LogContents,
+    /// The number of live session emitters using the log.
+    sessions: usize,
+}
+
+enum LogRef {
+    /// A log of the emitter's own, written when it is dropped.
+    Owned(LogContents),
+    /// A log shared with other emitters, which is written when the last
+    /// session emitter using it is dropped.
+    Shared { log: &'static Mutex<SharedLog>, session: bool },
+}
+
+impl LogRef {
+    fn shared(log: &'static Mutex<SharedLog>, session: bool) -> LogRef {
+        if session {
+            lock_shared_log(log).sessions += 1;
+        }
+        LogRef::Shared { log, session }
+    }
+
+    fn push(&mut self, rule: Option<Rule>, result: SarifResult) {
+        match self {
+            LogRef::Owned(contents) => contents.push(rule, result),
+            LogRef::Shared { log, .. } => lock_shared_log(log).contents.push(rule, result),
+        }
+    }
+
+    /// Returns the contents of the log if it is the emitter's job to write it
+    /// now that it is being dropped.
+    fn take_contents_to_write(&mut self) -> Option<LogContents> {
+        match self {
+            LogRef::Owned(contents) => Some(mem::take(contents)),
+            LogRef::Shared { log, session: true } => {
+                let mut log = lock_shared_log(log);
+                log.sessions -= 1;
+                (log.sessions == 0).then(|| mem::take(&mut log.contents))
+            }
+            LogRef::Shared { log, session: false } => {
+                // The results of early emitters are written by the session
+                // emitter, unless compilation is aborting before there is one.
+                let mut log = lock_shared_log(log);
+                let aborting = std::thread::panicking() && log.sessions == 0;
+                (aborting && !log.contents.results.is_empty()).then(|| mem::take(&mut log.contents))
+            }
+        }
+    }
+}
+
+fn lock_shared_log(log: &Mutex<SharedLog>) -> MutexGuard<'_, SharedLog> {
+    // The log is only modified while holding the lock, so it is consistent
+    // even if another thread panicked.
+    log.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl SarifEmitter {
+    /// The emitter of a session, writing to stderr.
+    pub fn stderr(
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+        pretty: bool,
+    ) -> SarifEmitter {
+        SarifEmitter::with_log(
+            Box::new(io::BufWriter::new(io::stderr())),
+            registry,
+            source_map,
+            fluent_bundle,
+            fallback_bundle,
+            pretty,
+            LogRef::shared(STDERR_LOG.get_or_init(Default::default), true),
+        )
+    }
+
+    /// The emitter of a `DiagCtxt` used before the session exists, whose
+    /// results end up in the log of the session.
+    pub fn early(pretty: bool, fallback_bundle: LazyFallbackBundle) -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::with_log(
+            Box::new(io::BufWriter::new(io::stderr())),
+            None,
+            Lrc::new(SourceMap::new(file_path_mapping)),
+            None,
+            fallback_bundle,
+            pretty,
+            LogRef::shared(STDERR_LOG.get_or_init(Default::default), false),
+        )
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+        pretty: bool,
+    ) -> SarifEmitter {
+        SarifEmitter::with_log(
+            dst,
+            registry,
+            source_map,
+            fluent_bundle,
+            fallback_bundle,
+            pretty,
+            LogRef::Owned(LogContents::default()),
+        )
+    }
+
+    fn with_log(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+        pretty: bool,
+        log: LogRef,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst: IntoDynSyncSend(dst),
+            registry,
+            sm: source_map,
+            fluent_bundle,
+            fallback_bundle,
+            pretty,
+            tool_name: "rustc",
+            tool_version: None,
+            log,
+        }
+    }
+
+    /// Sets the name of the tool recorded in the log, e.g. `rustdoc`.
+    pub fn tool_name(mut self, tool_name: &'static str) -> Self {
+        self.tool_name = tool_name;
+        self
+    }
+
+    pub fn tool_version(mut self, tool_version: Option<String>) -> Self {
+        self.tool_version = tool_version;
+        self
+    }
+
+    /// Writes a log with the given rules and results.
+    fn write_log(&mut self, contents: LogContents) -> io::Result<()> {
+        let LogContents { rules, results } = contents;
+        let rules = rules.into_values().collect();
+        let log = Log {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: self.tool_name,
+                        information_uri: "https://www.rust-lang.org/",
+                        version: self.tool_version.clone(),
+                        rules,
+                    },
+                },
+                column_kind: "unicodeCodePoints",
+                results,
+            }],
+        };
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut *self.dst, &log)?
+        } else {
+            serde_json::to_writer(&mut *self.dst, &log)?
+        };
+        self.dst.write_all(b"\n")?;
+        self.dst.flush()
+    }
+
+    /// Returns the rule for `code`, which the log adds to its `rules` table on
+    /// first use.
+    fn rule(&self, code: &DiagnosticId) -> Rule {
+        let (id, is_lint) = match code {
+            DiagnosticId::Error(code) => (code, false),
+            DiagnosticId::Lint { name, .. } => (name, true),
+        };
+        let explanation =
+            self.registry.as_ref().and_then(|registry| registry.try_find_description(id).ok());
+        Rule {
+            id: id.clone(),
+            full_description: explanation
+                .map(|text| MultiformatMessage { text: text.trim().to_owned() }),
+            help_uri: (!is_lint && explanation.is_some())
+                .then(|| format!("https://doc.rust-lang.org/error_codes/{id}.html")),
+            properties: RuleProperties { tags: vec![if is_lint { "lint" } else { "error" }] },
+        }
+    }
+
+    /// Returns the result for `diag`, along with its rule. The index of the
+    /// rule is filled in when the result is added to the log.
+    fn result_from_diagnostic(
+        &self,
+        diag: &crate::Diagnostic,
+    ) -> Option<(Option<Rule>, SarifResult)> {
+        let level = match diag.level {
+            Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => "error",
+            Level::Warning(_) => "warning",
+            Level::Note | Level::OnceNote | Level::Help | Level::OnceHelp => "note",
+            // "aborting due to N previous errors" and friends only summarize
+            // the other results, they don't carry any information of their own.
+            Level::FailureNote => return None,
+            Level::Allow | Level::Expect(_) => return None,
+        };
+        let args = to_fluent_args(diag.args());
+        let rule = diag.code.as_ref().map(|code| self.rule(code));
+
+        let mut message = self.translate_messages(&diag.messages, &args).into_owned();
+        let mut locations = vec![];
+        let mut related_locations = vec![];
+        self.add_multispan(&diag.span, &args, &mut locations, &mut related_locations);
+        for child in &diag.children {
+            self.add_sub_diagnostic(child, &args, &mut message, &mut related_locations);
+        }
+        let fixes = diag
+            .suggestions
+            .iter()
+            .flatten()
+            .flat_map(|sugg| self.fixes_from_suggestion(sugg, &args))
+            .collect();
+
+        let result = SarifResult {
+            rule_id: rule.as_ref().map(|rule| rule.id.clone()),
+            rule_index: None,
+            level,
+            message: Message { text: message },
+            locations,
+            related_locations,
+            fixes,
+        };
+        Some((rule, result))
+    }
+
+    /// Adds the spans of a sub-diagnostic (a note or help) as related
+    /// locations, or appends its message to `message` if it has no span.
+    fn add_sub_diagnostic(
+        &self,
+        child: &SubDiagnostic,
+        args: &FluentArgs<'_>,
+        message: &mut String,
+        related_locations: &mut Vec<Location>,
+    ) {
+        let text = self.translate_messages(&child.messages, args);
+        let text = format!("{}: {text}", child.level.to_str());
+        if child.span.primary_spans().is_empty() {
+            message.push('\n');
+            message.push_str(&text);
+            return;
+        }
+        for &span in child.span.primary_spans() {
+            let id = related_locations.len();
+            related_locations.push(self.location(span, Some(text.clone()), Some(id)));
+        }
+    }
+
+    /// Splits `msp` into primary `locations` and labelled `related_locations`.
+    /// The macro backtrace of every primary span is recorded as related
+    /// locations pointing at each invocation.
+    fn add_multispan(
+        &self,
+        msp: &MultiSpan,
+        args: &FluentArgs<'_>,
+        locations: &mut Vec<Location>,
+        related_locations: &mut Vec<Location>,
+    ) {
+        for span_label in msp.span_labels() {
+            if span_label.span.is_dummy() {
+                continue;
+            }
+            let label = span_label
+                .label
+                .as_ref()
+                .map(|m| self.translate_message(m, args).map_err(Report::new).unwrap().to_string());
+            if span_label.is_primary {
+                locations.push(self.location(span_label.span, label, None));
+                for expn in span_label.span.macro_backtrace() {
+                    let id = related_locations.len();
+                    let text = format!("in this expansion of {}", expn.kind.descr());
+                    related_locations.push(self.location(expn.call_site, Some(text), Some(id)));
+                }
+            } else {
+                let id = related_locations.len();
+                related_locations.push(self.location(span_label.span, label, Some(id)));
+            }
+        }
+    }
+
+    fn location(&self, span: Span, message: Option<String>, id: Option<usize>) -> Location {
+        Location {
+            id,
+            physical_location: self.physical_location(span),
+            message: message.map(|text| Message { text }),
+        }
+    }
+
+    fn physical_location(&self, span: Span) -> PhysicalLocation {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_offset = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        PhysicalLocation {
+            artifact_location: self.artifact_location(&start.file.name),
+            region: Region {
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+                byte_offset,
+                byte_length: byte_end.saturating_sub(byte_offset),
+            },
+        }
+    }
+
+    fn artifact_location(&self, name: &FileName) -> ArtifactLocation {
+        let name = self.sm.filename_for_diagnostics(name).to_string();
+        let (uri, uri_base_id) = artifact_uri(&name);
+        ArtifactLocation { uri, uri_base_id }
+    }
+
+    /// Every substitution of a suggestion is an alternative fix.
+    fn fixes_from_suggestion(&self, sugg: &CodeSuggestion, args: &FluentArgs<'_>) -> Vec<Fix> {
+        let description =
+            self.translate_message(&sugg.msg, args).map_err(Report::new).unwrap().to_string();
+        sugg.substitutions
+            .iter()
+            .map(|substitution| {
+                let mut changes: FxIndexMap<String, ArtifactChange> = FxIndexMap::default();
+                for part in &substitution.parts {
+                    let PhysicalLocation { artifact_location, region } =
+                        self.physical_location(part.span);
+                    changes
+                        .entry(artifact_location.uri.clone())
+                        .or_insert_with(|| ArtifactChange {
+                            artifact_location,
+                            replacements: vec![],
+                        })
+                        .replacements
+                        .push(Replacement {
+                            deleted_region: region,
+                            inserted_content: ArtifactContent { text: part.snippet.clone() },
+                        });
+                }
+                Fix {
+                    description: Message { text: description.clone() },
+                    artifact_changes: changes.into_values().collect(),
+                    properties: FixProperties { applicability: sugg.applicability },
+                }
+            })
+            .collect()
+    }
+}
+
+/// Turns a file name as printed in diagnostics into a URI reference.
+///
+/// Absolute paths become `file` URIs, relative paths are kept relative and
+/// resolved against [`SRCROOT`], and anything else (e.g. `<anon>`) is kept
+/// as an opaque relative reference.
+fn artifact_uri(name: &str) -> (String, Option<&'static str>) {
+    let mut encoded = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' => encoded.push('/'),
+            ' ' => encoded.push_str("%20"),
+            '%' => encoded.push_str("%25"),
+            '#' => encoded.push_str("%23"),
+            '?' => encoded.push_str("%3F"),
+            '<' => encoded.push_str("%3C"),
+            '>' => encoded.push_str("%3E"),
+            c => encoded.push(c),
+        }
+    }
+    if Path::new(name).is_absolute() {
+        if encoded.starts_with('/') {
+            (format!("file://{encoded}"), None)
+        } else {
+            // Windows paths such as `C:/foo`.
+            (format!("file:///{encoded}"), None)
+        }
+    } else {
+        (encoded, Some(SRCROOT))
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        if let Some((rule, result)) = self.result_from_diagnostic(diag) {
+            self.log.push(rule, result);
+        }
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        let Some(contents) = self.log.take_contents_to_write() else { return };
+        if let Err(e) = self.write_log(contents) {
+            // Don't double panic, the log is lost either way.
+            if !std::thread::panicking() {
+                panic!("failed to print diagnostics: {e:?}");
+            }
+        }
+    }
+}
+
+// The following data types are provided just for serialisation. Field names
+// follow the SARIF 2.1.0 specification.
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    /// How `startColumn`/`endColumn` are counted; rustc counts chars.
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent {
+    name: &'static str,
+    information_uri: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code (`E0308`) or lint name (`unused_variables`,
+    /// `clippy::needless_return`).
+    id: String,
+    /// The long explanation of an error code, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<MultiformatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+    properties: RuleProperties,
+}
+
+#[derive(Serialize)]
+struct RuleProperties {
+    /// Either `error` or `lint`.
+    tags: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct MultiformatMessage {
+    /// Explanations are written in markdown, which is also valid plain text.
+    text: String,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning" or "note".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    /// Only related locations are numbered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    /// 0-based, relative to the start of the original file.
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
@@ -0,0 +1,187 @@
+use super::*;
+
+use crate::DiagCtxt;
+use rustc_span::BytePos;
+
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` with a `DiagCtxt` emitting SARIF for a single source file
+/// containing `code`, and returns the parsed log once it has been dropped.
+fn with_sarif_log(code: &str, f: impl FnOnce(&DiagCtxt)) -> Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let se = SarifEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            None,
+            sm,
+            None,
+            fallback_bundle,
+            false,
+        );
+
+        let dcx = DiagCtxt::with_emitter(Box::new(se));
+        f(&dcx);
+        drop(dcx);
+
+        let bytes = output.lock().unwrap();
+        serde_json::from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+    })
+}
+
+#[test]
+fn empty_log() {
+    let log = with_sarif_log("", |_| {});
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(log["runs"][0]["tool"]["driver"]["name"], "rustc");
+    assert_eq!(log["runs"][0]["results"], Value::Array(vec![]));
+}
+
+#[test]
+fn result_with_rule_and_region() {
+    let log = with_sarif_log("\nmod foo;\nmod bar;\n", |dcx| {
+        let span = Span::with_root_ctxt(BytePos(5), BytePos(12));
+        dcx.struct_span_err(span, "foo").code(DiagnosticId::Error("E0001".to_owned())).emit();
+    });
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "E0001");
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0001");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "foo");
+
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "test.rs");
+    assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+    let region = &location["region"];
+    assert_eq!(region["startLine"], 2);
+    assert_eq!(region["startColumn"], 5);
+    assert_eq!(region["endLine"], 3);
+    assert_eq!(region["endColumn"], 3);
+    assert_eq!(region["byteOffset"], 5);
+    assert_eq!(region["byteLength"], 7);
+}
+
+#[test]
+fn suggestion_becomes_fix() {
+    let log = with_sarif_log("let x = 1;", |dcx| {
+        let span = Span::with_root_ctxt(BytePos(4), BytePos(5));
+        dcx.struct_span_warn(span, "unused variable")
+            .span_suggestion(span, "prefix it", "_x", Applicability::MachineApplicable)
+            .emit();
+    });
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!(result["level"], "warning");
+
+    let fix = &result["fixes"][0];
+    assert_eq!(fix["description"]["text"], "prefix it");
+    assert_eq!(fix["properties"]["applicability"], "MachineApplicable");
+    let replacement = &fix["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["insertedContent"]["text"], "_x");
+    assert_eq!(replacement["deletedRegion"]["byteOffset"], 4);
+    assert_eq!(replacement["deletedRegion"]["byteLength"], 1);
+}
+
+/// Runs `f` with a function creating emitters that share a log, as the early
+/// and session emitters writing to stderr do, and returns what they wrote.
+fn with_shared_log(f: impl FnOnce(&dyn Fn(bool) -> DiagCtxt)) -> String {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let log: &'static Mutex<SharedLog> = Box::leak(Box::default());
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        let new_dcx = |session| {
+            let fallback_bundle =
+                crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+            let se = SarifEmitter::with_log(
+                Box::new(Shared { data: output.clone() }),
+                None,
+                sm.clone(),
+                None,
+                fallback_bundle,
+                false,
+                LogRef::shared(log, session),
+            );
+            DiagCtxt::with_emitter(Box::new(se))
+        };
+        f(&new_dcx);
+    });
+    let bytes = output.lock().unwrap();
+    str::from_utf8(&bytes).unwrap().to_owned()
+}
+
+fn result_messages(log: &Value) -> Vec<&str> {
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    results.iter().map(|result| result["message"]["text"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn early_results_go_to_session_log() {
+    let output = with_shared_log(|new_dcx| {
+        let early = new_dcx(false);
+        early.struct_warn("before the session").emit();
+        drop(early);
+
+        let session = new_dcx(true);
+        let early = new_dcx(false);
+        session.struct_err("in the session").emit();
+        early.struct_warn("next to the session").emit();
+        drop(early);
+        drop(session);
+    });
+    // `from_str` rejects trailing documents.
+    let log: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        result_messages(&log),
+        ["before the session", "in the session", "next to the session"]
+    );
+}
+
+#[test]
+fn early_results_written_when_aborting() {
+    let output = with_shared_log(|new_dcx| {
+        let early = new_dcx(false);
+        early.struct_warn("unused").emit();
+        drop(early);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let early = new_dcx(false);
+            early.struct_err("invalid option").emit();
+            crate::FatalError.raise()
+        }));
+        assert!(result.is_err());
+    });
+    let log: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(result_messages(&log), ["unused", "invalid option"]);
+}
+
+#[test]
+fn uris() {
+    assert_eq!(artifact_uri("src/main.rs"), ("src/main.rs".to_owned(), Some(SRCROOT)));
+    assert_eq!(artifact_uri("src\\a b.rs"), ("src/a%20b.rs".to_owned(), Some(SRCROOT)));
+    assert_eq!(artifact_uri("<anon>"), ("%3Canon%3E".to_owned(), Some(SRCROOT)));
+    #[cfg(unix)]
+    assert_eq!(artifact_uri("/src/lib.rs"), ("file:///src/lib.rs".to_owned(), None));
+}
@@ -390,6 +390,10 @@ pub struct BuiltinAttribute {
         target_feature, Normal, template!(List: r#"enable = "name""#),
         DuplicatesOk, @only_local: true,
     ),
+    gated!(
+        target_clones, Normal, template!(List: r#""feature1,feature2", "default""#),
+        ErrorFollowing, @only_local: true, experimental!(target_clones)
+    ),
     ungated!(track_caller, Normal, template!(Word), WarnFollowing),
     ungated!(instruction_set, Normal, template!(List: "set"), ErrorPreceding),
     gated!(
@@ -576,6 +576,9 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, strict_provenance, "1.61.0", Some(95228)),
     /// Allows string patterns to dereference values to match them.
     (unstable, string_deref_patterns, "1.67.0", Some(87121)),
+    /// Allows `#[target_clones]`, generating clones of a function for several sets of target
+    /// features and selecting the best one at runtime.
+    (unstable, target_clones, "CURRENT_RUSTC_VERSION", None),
     /// Allows the use of `#[target_feature]` on safe functions.
     (unstable, target_feature_11, "1.45.0", Some(69098)),
     /// Allows using `#[thread_local]` on `static` items.
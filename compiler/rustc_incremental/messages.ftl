@@ -78,6 +78,10 @@ incremental_not_loaded = `{$dep_node_str}` should have been loaded from disk but
 
 incremental_ok = OK
 
+incremental_relocatable_working_dir =
+    the incremental compilation cache depends on the working directory `{$path}`
+    .note = remap it with `--remap-path-prefix` to use the cache in other directories
+
 incremental_repeated_depnode_label = dep-node label `{$label}` is repeated
 
 incremental_session_gc_failed =
@@ -99,4 +103,7 @@ incremental_unrecognized_depnode_label = dep-node label `{$label}` not recognize
 
 incremental_write_dep_graph = failed to write dependency graph to `{$path}`: {$err}
 
+incremental_write_invalidation_report =
+    failed to write incremental invalidation report to `{$path}`: {$err}
+
 incremental_write_new = failed to write {$name} to `{$path}`: {$err}
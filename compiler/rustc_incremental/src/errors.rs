@@ -270,6 +270,13 @@ pub struct LoadDepGraph {
     pub err: std::io::Error,
 }
 
+#[derive(Diagnostic)]
+#[diag(incremental_relocatable_working_dir)]
+#[note]
+pub struct RelocatableWorkingDir<'a> {
+    pub path: &'a Path,
+}
+
 #[derive(Diagnostic)]
 #[diag(incremental_write_dep_graph)]
 pub struct WriteDepGraph<'a> {
@@ -292,6 +299,13 @@ pub struct CreateDepGraph<'a> {
     pub err: std::io::Error,
 }
 
+#[derive(Diagnostic)]
+#[diag(incremental_write_invalidation_report)]
+pub struct WriteInvalidationReport<'a> {
+    pub path: &'a Path,
+    pub err: std::io::Error,
+}
+
 #[derive(Diagnostic)]
 #[diag(incremental_copy_workproduct_to_cache)]
 pub struct CopyWorkProductToCache<'a> {
@@ -1,5 +1,7 @@
 //! The data that we will serialize and deserialize.
 
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::unord::UnordMap;
 use rustc_macros::{Decodable, Encodable};
 use rustc_middle::dep_graph::{WorkProduct, WorkProductId};
 
@@ -10,4 +12,8 @@ pub struct SerializedWorkProduct {
 
     /// work-product data itself
     pub work_product: WorkProduct,
+
+    /// hashes of the contents of the saved files, keyed like
+    /// `WorkProduct::saved_files`; only recorded with `-Z incremental-relocatable`
+    pub file_hashes: UnordMap<String, Fingerprint>,
 }
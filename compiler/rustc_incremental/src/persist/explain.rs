@@ -0,0 +1,296 @@
+//! Explaining why incremental compilation redid work of the previous session
+//! (`-Z incremental-explain`).
+//!
+//! While trying to mark nodes of the previous dep-graph green, the dep-graph
+//! remembers which dependency was red for every node that failed (see
+//! `InvalidationRecorder`). At the end of the session, these red dependencies
+//! are followed back to the inputs that changed, and the result is written to
+//! a JSON report and summarized on stderr.
+//!
+//! A change of the tracked command-line options discards the previous
+//! dep-graph as a whole. To tell which options changed, the hash of every
+//! tracked option is saved in the session directory.
+
+use crate::errors;
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
+use rustc_middle::dep_graph::{
+    dep_kinds, DepNode, DepNodeExt, Invalidation, InvalidationRecorder, InvalidationRoot,
+};
+use rustc_middle::ty::TyCtxt;
+use rustc_serialize::opaque::MemDecoder;
+use rustc_serialize::{Decodable, Encodable};
+use rustc_session::config::SwitchWithOptPath;
+use rustc_session::Session;
+use rustc_span::def_id::LOCAL_CRATE;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::file_format;
+use super::fs::*;
+use super::load::{load_data, LoadResult};
+use super::relocatable;
+
+/// The number of invalidation roots listed in the summary on stderr.
+const SUMMARY_ROOTS: usize = 10;
+
+/// Creates the recorder for the dep-graph if `-Z incremental-explain` is
+/// enabled. This compares the command-line options with the ones of the
+/// previous session, so it must run before the files of the previous session
+/// are deleted.
+pub(crate) fn invalidation_recorder(sess: &Session) -> Option<InvalidationRecorder> {
+    if !sess.opts.unstable_opts.incremental_explain.enabled() {
+        return None;
+    }
+    Some(InvalidationRecorder::new(changed_options(sess)))
+}
+
+fn changed_options(sess: &Session) -> Vec<String> {
+    if sess.incr_comp_session_dir_opt().is_none() {
+        return vec![];
+    }
+    // The previous session may not have recorded its options, in which case
+    // we can't tell what changed.
+    let LoadResult::Ok { data: (bytes, start_pos) } = load_data(&option_hashes_path(sess), sess)
+    else {
+        return vec![];
+    };
+    let mut decoder = MemDecoder::new(&bytes, start_pos);
+    let previous: BTreeMap<String, u64> = Decodable::decode(&mut decoder);
+    let current = relocatable::hashed_options(sess).dep_tracking_hashes();
+
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|&(name, hash)| previous.get(name) != Some(hash))
+        .map(|(name, _)| name.clone())
+        .collect();
+    changed.extend(previous.into_keys().filter(|name| !current.contains_key(name)));
+    changed.sort();
+    changed
+}
+
+/// Saves the hash of every tracked command-line option, for the next session
+/// to tell which options changed.
+pub(crate) fn save_option_hashes(sess: &Session) {
+    file_format::save_in(sess, option_hashes_path(sess), "command-line option hashes", |mut e| {
+        relocatable::hashed_options(sess).dep_tracking_hashes().encode(&mut e);
+        e.finish()
+    });
+}
+
+/// What changed to start an invalidation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum RootKind {
+    /// The source code of an item.
+    Source,
+    /// The span of an item, without the item changing otherwise.
+    Span,
+    /// An item that existed in the previous session.
+    Removed,
+    /// Any other input, e.g. the metadata of a dependency.
+    Input,
+}
+
+#[derive(serde::Serialize)]
+struct Report {
+    crate_name: String,
+    /// The tracked command-line options that changed. If there are any, the
+    /// whole previous session was discarded.
+    changed_options: Vec<String>,
+    /// The inputs that changed, the ones causing the most work first.
+    roots: Vec<Root>,
+    /// Every query and codegen unit of the previous session that was redone.
+    redone: Vec<Redone>,
+}
+
+#[derive(serde::Serialize)]
+struct Root {
+    kind: RootKind,
+    node: String,
+    /// The item whose source or span changed.
+    item: Option<String>,
+    file: Option<String>,
+    queries: usize,
+    codegen_units: usize,
+}
+
+#[derive(serde::Serialize)]
+struct Redone {
+    node: String,
+    /// The name of the codegen unit, if the node is the codegen of one.
+    codegen_unit: Option<String>,
+    /// Whether redoing the work produced a different result.
+    changed: bool,
+    /// The red dependencies leading from `node` to the input that changed.
+    chain: Vec<String>,
+    /// The index of the root in `Report::roots`.
+    root: usize,
+}
+
+/// Writes the report of `-Z incremental-explain` and prints a summary of the
+/// inputs that caused the most work to be redone.
+pub(crate) fn explain_invalidations(tcx: TyCtxt<'_>) {
+    let SwitchWithOptPath::Enabled(ref directory) = tcx.sess.opts.unstable_opts.incremental_explain
+    else {
+        return;
+    };
+    let Some(invalidations) = tcx.dep_graph.invalidations() else {
+        return;
+    };
+
+    // Codegen units are keyed by an opaque hash of their name.
+    let cgu_names: FxHashMap<DepNode, String> = if invalidations
+        .nodes
+        .iter()
+        .any(|invalidation| invalidation.node.kind == dep_kinds::CompileCodegenUnit)
+    {
+        let (_, codegen_units) = tcx.collect_and_partition_mono_items(());
+        codegen_units
+            .iter()
+            .map(|cgu| (cgu.codegen_dep_node(tcx), cgu.name().to_string()))
+            .collect()
+    } else {
+        Default::default()
+    };
+    let describe = |node: &DepNode| match cgu_names.get(node) {
+        Some(name) => format!("{:?}({name})", node.kind),
+        None => format!("{node:?}"),
+    };
+
+    let mut roots: FxIndexMap<(RootKind, DepNode), Root> = FxIndexMap::default();
+    let mut redone = Vec::with_capacity(invalidations.nodes.len());
+    for invalidation in &invalidations.nodes {
+        let (kind, root_node) = find_root(invalidation);
+        let entry = roots.entry((kind, root_node));
+        let root_index = entry.index();
+        let root = entry.or_insert_with(|| {
+            let def_id = root_node.extract_def_id(tcx);
+            let file = def_id.filter(|def_id| def_id.is_local()).map(|def_id| {
+                let span = tcx.def_span(def_id);
+                tcx.sess.source_map().span_to_filename(span).prefer_local().to_string()
+            });
+            Root {
+                kind,
+                node: describe(&root_node),
+                item: def_id.map(|def_id| tcx.def_path_str(def_id)),
+                file,
+                queries: 0,
+                codegen_units: 0,
+            }
+        });
+        if invalidation.node.kind == dep_kinds::CompileCodegenUnit {
+            root.codegen_units += 1;
+        } else {
+            root.queries += 1;
+        }
+        redone.push(Redone {
+            node: describe(&invalidation.node),
+            codegen_unit: cgu_names.get(&invalidation.node).cloned(),
+            changed: invalidation.changed,
+            chain: invalidation.chain.iter().map(describe).collect(),
+            root: root_index,
+        });
+    }
+
+    // Sort the roots by the work they caused, and renumber them accordingly.
+    let mut roots: Vec<(usize, Root)> = roots.into_values().enumerate().collect();
+    roots.sort_by_key(|(_, root)| Reverse(root.queries + root.codegen_units));
+    let mut new_index = vec![0; roots.len()];
+    for (new, &(old, _)) in roots.iter().enumerate() {
+        new_index[old] = new;
+    }
+    for redone in &mut redone {
+        redone.root = new_index[redone.root];
+    }
+    let roots = roots.into_iter().map(|(_, root)| root).collect();
+
+    let report = Report {
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        changed_options: invalidations.changed_options,
+        roots,
+        redone,
+    };
+    print_summary(&report);
+
+    let directory = directory.as_deref().unwrap_or(Path::new("."));
+    let path = directory.join(format!("{}.incremental-explain.json", report.crate_name));
+    if let Err(err) = write_report(directory, &path, &report) {
+        tcx.dcx().emit_err(errors::WriteInvalidationReport { path: &path, err });
+    }
+}
+
+/// Finds the input that started an invalidation.
+fn find_root(invalidation: &Invalidation) -> (RootKind, DepNode) {
+    let root_node = *invalidation.root_node();
+    if invalidation.root == InvalidationRoot::Unrecoverable {
+        return (RootKind::Removed, root_node);
+    }
+    if root_node.kind == dep_kinds::source_span {
+        return (RootKind::Span, root_node);
+    }
+    if root_node.kind == dep_kinds::hir_crate || root_node.kind == dep_kinds::hir_crate_items {
+        // Every source change reaches the rest of the crate through the
+        // HIR of the whole crate, which is not very telling. Blame the node
+        // that read the HIR instead, usually the HIR of the item that changed.
+        return match invalidation.chain.iter().rev().nth(1) {
+            Some(reader) => (RootKind::Source, *reader),
+            None if invalidation.changed => (RootKind::Source, invalidation.node),
+            // The node read the HIR of the crate itself, but is not what
+            // changed in the sources.
+            None => (RootKind::Input, root_node),
+        };
+    }
+    (RootKind::Input, root_node)
+}
+
+fn print_summary(report: &Report) {
+    if !report.changed_options.is_empty() {
+        let options: Vec<_> = report.changed_options.iter().map(|o| format!("`{o}`")).collect();
+        eprintln!(
+            "[incremental] previous session discarded, changed command-line options: {}",
+            options.join(", ")
+        );
+    }
+
+    let queries = report.roots.iter().map(|root| root.queries).sum();
+    let codegen_units = report.roots.iter().map(|root| root.codegen_units).sum();
+    eprintln!(
+        "[incremental] redid {} and {} of the previous session",
+        plural(queries, "query", "queries"),
+        plural(codegen_units, "codegen unit", "codegen units"),
+    );
+    for root in report.roots.iter().take(SUMMARY_ROOTS) {
+        let cause = match (root.kind, &root.item) {
+            (RootKind::Source, Some(item)) => format!("source of `{item}` changed"),
+            (RootKind::Span, Some(item)) => format!("span of `{item}` changed"),
+            (RootKind::Removed, _) => format!("`{}` no longer exists", root.node),
+            _ => format!("`{}` changed", root.node),
+        };
+        let file = root.file.as_ref().map(|file| format!(" ({file})")).unwrap_or_default();
+        let mut work = vec![];
+        if root.queries > 0 {
+            work.push(plural(root.queries, "query", "queries"));
+        }
+        if root.codegen_units > 0 {
+            work.push(plural(root.codegen_units, "codegen unit", "codegen units"));
+        }
+        eprintln!("[incremental]   {}: {cause}{file}", work.join(", "));
+    }
+    if report.roots.len() > SUMMARY_ROOTS {
+        eprintln!("[incremental]   ... and {} more", report.roots.len() - SUMMARY_ROOTS);
+    }
+}
+
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    format!("{count} {}", if count == 1 { singular } else { plural })
+}
+
+fn write_report(directory: &Path, path: &Path, report: &Report) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut file, report)?;
+    file.flush()
+}
@@ -128,6 +128,7 @@
 const STAGING_DEP_GRAPH_FILENAME: &str = "dep-graph.part.bin";
 const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
 const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
+const OPTION_HASHES_FILENAME: &str = "option-hashes.bin";
 
 // We encode integers using the following base, so they are shorter than decimal
 // or hexadecimal numbers (we want short file and directory names). Since these
@@ -157,6 +158,12 @@ pub fn query_cache_path(sess: &Session) -> PathBuf {
     in_incr_comp_dir_sess(sess, QUERY_CACHE_FILENAME)
 }
 
+/// Returns the path to the hashes of a session's command-line options, which
+/// are only saved with `-Z incremental-explain`.
+pub(crate) fn option_hashes_path(sess: &Session) -> PathBuf {
+    in_incr_comp_dir_sess(sess, OPTION_HASHES_FILENAME)
+}
+
 /// Locks a given session directory.
 fn lock_file_path(session_dir: &Path) -> PathBuf {
     let crate_dir = session_dir.parent().unwrap();
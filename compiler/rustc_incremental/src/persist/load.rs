@@ -13,8 +13,10 @@
 use std::path::{Path, PathBuf};
 
 use super::data::*;
+use super::explain;
 use super::file_format;
 use super::fs::*;
+use super::relocatable;
 use super::save::build_dep_graph;
 use super::work_product;
 
@@ -66,7 +68,7 @@ pub fn open(self, sess: &Session) -> T {
     }
 }
 
-fn load_data(path: &Path, sess: &Session) -> LoadResult<(Mmap, usize)> {
+pub(super) fn load_data(path: &Path, sess: &Session) -> LoadResult<(Mmap, usize)> {
     match file_format::read_file(
         path,
         sess.opts.unstable_opts.incremental_info,
@@ -101,7 +103,7 @@ fn load_dep_graph(sess: &Session) -> LoadResult<(SerializedDepGraph, WorkProduct
     // Calling `sess.incr_comp_session_dir()` will panic if `sess.opts.incremental.is_none()`.
     // Fortunately, we just checked that this isn't the case.
     let path = dep_graph_path(sess);
-    let expected_hash = sess.opts.dep_tracking_hash(false);
+    let expected_hash = relocatable::hashed_options(sess).dep_tracking_hash(false);
 
     let mut prev_work_products = UnordMap::default();
 
@@ -127,12 +129,15 @@ fn load_dep_graph(sess: &Session) -> LoadResult<(SerializedDepGraph, WorkProduct
                     exists
                 });
 
-                if all_files_exist {
-                    debug!("reconcile_work_products: all files for {:?} exist", swp);
-                    prev_work_products.insert(swp.id, swp.work_product);
-                } else {
+                if !all_files_exist {
                     debug!("reconcile_work_products: some file for {:?} does not exist", swp);
                     delete_dirty_work_product(sess, swp);
+                } else if !relocatable::validate_work_product(sess, &swp) {
+                    debug!("reconcile_work_products: some file for {:?} was modified", swp);
+                    delete_dirty_work_product(sess, swp);
+                } else {
+                    debug!("reconcile_work_products: all files for {:?} exist", swp);
+                    prev_work_products.insert(swp.id, swp.work_product);
                 }
             }
         }
@@ -198,6 +203,13 @@ pub fn setup_dep_graph(
     // `load_dep_graph` can only be called after `prepare_session_directory`.
     prepare_session_directory(sess, crate_name, stable_crate_id)?;
 
+    if sess.opts.incremental.is_some() && sess.opts.unstable_opts.incremental_relocatable {
+        relocatable::check_paths(sess);
+    }
+
+    // This must happen before the files of an incompatible previous session are deleted.
+    let invalidations = explain::invalidation_recorder(sess);
+
     let res = sess.opts.build_dep_graph().then(|| load_dep_graph(sess));
 
     if sess.opts.incremental.is_some() {
@@ -215,7 +227,7 @@ pub fn setup_dep_graph(
     Ok(res
         .and_then(|result| {
             let (prev_graph, prev_work_products) = result.open(sess);
-            build_dep_graph(sess, prev_graph, prev_work_products)
+            build_dep_graph(sess, prev_graph, prev_work_products, invalidations)
         })
         .unwrap_or_else(DepGraph::new_disabled))
 }
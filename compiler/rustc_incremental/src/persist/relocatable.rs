@@ -0,0 +1,94 @@
+//! Making the incremental compilation cache independent of where the sources
+//! and outputs are located (`-Z incremental-relocatable`), so that a session
+//! directory can be copied to another checkout of the same sources.
+//!
+//! The dep-graph and the query result cache are keyed by `DefPathHash`es,
+//! which only depend on the `StableCrateId`, and source files are identified
+//! by their remapped name. What remains are the paths in the command-line
+//! options, which are hashed as remapped by `--remap-path-prefix` here, and
+//! the work products, whose contents are validated when they are loaded
+//! since they may have been produced on another machine.
+
+use crate::errors;
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_data_structures::unord::UnordMap;
+use rustc_middle::dep_graph::WorkProduct;
+use rustc_session::config::Options;
+use rustc_session::Session;
+use rustc_span::RealFileName;
+use std::borrow::Cow;
+use std::fs;
+use std::hash::Hasher;
+use std::io;
+use std::path::PathBuf;
+
+use super::data::SerializedWorkProduct;
+use super::fs::*;
+
+/// Warns about the paths that keep the cache from being relocated.
+pub(crate) fn check_paths(sess: &Session) {
+    if let RealFileName::LocalPath(path) = &sess.opts.working_dir {
+        sess.dcx().emit_warning(errors::RelocatableWorkingDir { path });
+    }
+}
+
+/// Returns the options whose dep-tracking hash is saved with the dep-graph.
+/// With `-Z incremental-relocatable`, the local side of `--remap-path-prefix`
+/// and the location of the sysroot sources are not part of the hash.
+pub(crate) fn hashed_options(sess: &Session) -> Cow<'_, Options> {
+    if !sess.opts.unstable_opts.incremental_relocatable {
+        return Cow::Borrowed(&sess.opts);
+    }
+    let mut opts = sess.opts.clone();
+    for (from, _) in &mut opts.remap_path_prefix {
+        *from = PathBuf::new();
+    }
+    opts.real_rust_source_base_dir = None;
+    Cow::Owned(opts)
+}
+
+/// Hashes the contents of the files of a work product, to be validated by
+/// [`validate_work_product`] in the next session.
+pub(crate) fn hash_work_product_files(
+    sess: &Session,
+    work_product: &WorkProduct,
+) -> UnordMap<String, Fingerprint> {
+    if !sess.opts.unstable_opts.incremental_relocatable {
+        return UnordMap::default();
+    }
+    work_product
+        .saved_files
+        .items()
+        .filter_map(|(kind, file_name)| {
+            let hash = hash_file(sess, file_name).ok()?;
+            Some((kind.clone(), hash))
+        })
+        .collect()
+}
+
+/// Checks that the files of a work product have the contents that were saved
+/// with it. A cache produced elsewhere may have been copied partially, or
+/// mixed with the files of another session.
+pub(crate) fn validate_work_product(sess: &Session, swp: &SerializedWorkProduct) -> bool {
+    if !sess.opts.unstable_opts.incremental_relocatable {
+        return true;
+    }
+    swp.work_product.saved_files.items().all(|(kind, file_name)| {
+        let valid = match (swp.file_hashes.get(kind), hash_file(sess, file_name)) {
+            (Some(expected), Ok(actual)) => *expected == actual,
+            _ => false,
+        };
+        if !valid && sess.opts.unstable_opts.incremental_info {
+            eprintln!("incremental: contents of work product file changed: {file_name}");
+        }
+        valid
+    })
+}
+
+fn hash_file(sess: &Session, file_name: &str) -> io::Result<Fingerprint> {
+    let contents = fs::read(in_incr_comp_dir_sess(sess, file_name))?;
+    let mut hasher = StableHasher::new();
+    hasher.write(&contents);
+    Ok(hasher.finish())
+}
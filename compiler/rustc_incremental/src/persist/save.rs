@@ -3,7 +3,7 @@
 use rustc_data_structures::fx::FxIndexMap;
 use rustc_data_structures::sync::join;
 use rustc_middle::dep_graph::{
-    DepGraph, SerializedDepGraph, WorkProduct, WorkProductId, WorkProductMap,
+    DepGraph, InvalidationRecorder, SerializedDepGraph, WorkProduct, WorkProductId, WorkProductMap,
 };
 use rustc_middle::ty::TyCtxt;
 use rustc_serialize::opaque::{FileEncodeResult, FileEncoder};
@@ -13,8 +13,10 @@
 
 use super::data::*;
 use super::dirty_clean;
+use super::explain;
 use super::file_format;
 use super::fs::*;
+use super::relocatable;
 use super::work_product;
 
 /// Saves and writes the [`DepGraph`] to the file system.
@@ -47,6 +49,10 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
             tcx.dep_graph.print_incremental_info()
         }
 
+        if sess.opts.unstable_opts.incremental_explain.enabled() {
+            sess.time("incr_comp_explain_invalidations", || explain::explain_invalidations(tcx));
+        }
+
         join(
             move || {
                 sess.time("incr_comp_persist_dep_graph", || {
@@ -95,7 +101,7 @@ pub fn save_work_product_index(
     dep_graph.assert_ignored();
     let path = work_products_path(sess);
     file_format::save_in(sess, path, "work product index", |mut e| {
-        encode_work_product_index(&new_work_products, &mut e);
+        encode_work_product_index(sess, &new_work_products, &mut e);
         e.finish()
     });
 
@@ -121,6 +127,7 @@ pub fn save_work_product_index(
 }
 
 fn encode_work_product_index(
+    sess: &Session,
     work_products: &FxIndexMap<WorkProductId, WorkProduct>,
     encoder: &mut FileEncoder,
 ) {
@@ -129,6 +136,7 @@ fn encode_work_product_index(
         .map(|(id, work_product)| SerializedWorkProduct {
             id: *id,
             work_product: work_product.clone(),
+            file_hashes: relocatable::hash_work_product_files(sess, work_product),
         })
         .collect();
 
@@ -149,6 +157,7 @@ pub(crate) fn build_dep_graph(
     sess: &Session,
     prev_graph: SerializedDepGraph,
     prev_work_products: WorkProductMap,
+    invalidations: Option<InvalidationRecorder>,
 ) -> Option<DepGraph> {
     if sess.opts.incremental.is_none() {
         // No incremental compilation.
@@ -169,7 +178,11 @@ pub(crate) fn build_dep_graph(
     file_format::write_file_header(&mut encoder, sess);
 
     // First encode the commandline arguments hash
-    sess.opts.dep_tracking_hash(false).encode(&mut encoder);
+    relocatable::hashed_options(sess).dep_tracking_hash(false).encode(&mut encoder);
+
+    if invalidations.is_some() {
+        explain::save_option_hashes(sess);
+    }
 
     Some(DepGraph::new(
         &sess.prof,
@@ -178,5 +191,6 @@ pub(crate) fn build_dep_graph(
         encoder,
         sess.opts.unstable_opts.query_dep_graph,
         sess.opts.unstable_opts.incremental_info,
+        invalidations,
     ))
 }
@@ -22,10 +22,12 @@
 use rustc_middle::util::Providers;
 use rustc_mir_build as mir_build;
 use rustc_parse::{parse_crate_from_file, parse_crate_from_source_str, validate_attr};
-use rustc_passes::{abi_test, hir_stats, layout_test};
+use rustc_passes::{abi_test, api_usage, hir_stats, layout_test};
 use rustc_resolve::Resolver;
 use rustc_session::code_stats::VTableSizeInfo;
-use rustc_session::config::{CrateType, Input, OutFileName, OutputFilenames, OutputType};
+use rustc_session::config::{
+    CrateType, Input, OutFileName, OutputFilenames, OutputType, SwitchWithOptPath,
+};
 use rustc_session::cstore::Untracked;
 use rustc_session::output::filename_for_input;
 use rustc_session::search_paths::PathKind;
@@ -448,6 +450,11 @@ fn write_out_deps(tcx: TyCtxt<'_>, outputs: &OutputFilenames, out_filenames: &[P
             files.push(normalize_path(profile_sample.as_path().to_path_buf()));
         }
 
+        // The lint configuration file
+        if let Some(ref lint_config) = sess.opts.lint_config {
+            files.push(normalize_path(lint_config.path.clone()));
+        }
+
         // Debugger visualizer files
         for debugger_visualizer in tcx.debugger_visualizers(LOCAL_CRATE) {
             files.push(normalize_path(debugger_visualizer.path.clone().unwrap()));
@@ -822,6 +829,10 @@ fn analysis(tcx: TyCtxt<'_>, (): ()) -> Result<()> {
         let _ = tcx.all_diagnostic_items(());
     });
 
+    if let SwitchWithOptPath::Enabled(ref directory) = sess.opts.unstable_opts.api_usage {
+        sess.time("api_usage", || api_usage::write(tcx, directory));
+    }
+
     if sess.opts.unstable_opts.print_vtable_sizes {
         let traits = tcx.traits(LOCAL_CRATE);
 
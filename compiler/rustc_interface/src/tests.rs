@@ -2,6 +2,7 @@
 use crate::interface::parse_cfg;
 use rustc_data_structures::profiling::TimePassesFormat;
 use rustc_errors::{emitter::HumanReadableErrorType, registry, ColorConfig};
+use rustc_session::config::lint_config::LintConfig;
 use rustc_session::config::{
     build_configuration, build_session_options, rustc_optgroups, BranchProtection, CFGuard, Cfg,
     DebugInfo, DumpMonoStatsFormat, ErrorOutputType, ExternEntry, ExternLocation, Externs,
@@ -294,6 +295,21 @@ fn test_lint_cap_hash_different() {
     assert_non_crate_hash_different(&v2, &v3);
 }
 
+#[test]
+fn test_lint_config_hash_different() {
+    let mut v1 = Options::default();
+    let mut v2 = Options::default();
+    let v3 = Options::default();
+
+    let path = Path::new("lints.toml");
+    v1.lint_config = Some(LintConfig::parse(path, "[lints.rust]\nunused = \"deny\"\n").unwrap());
+    v2.lint_config = Some(LintConfig::parse(path, "[lints.rust]\nunused = \"warn\"\n").unwrap());
+
+    assert_non_crate_hash_different(&v1, &v2);
+    assert_non_crate_hash_different(&v1, &v3);
+    assert_non_crate_hash_different(&v2, &v3);
+}
+
 #[test]
 fn test_search_paths_tracking_hash_different_order() {
     let mut v1 = Options::default();
@@ -642,6 +658,28 @@ macro_rules! tracked {
     // tidy-alphabetical-end
 }
 
+#[test]
+fn test_dep_tracking_hashes_of_changed_options() {
+    let reference = Options::default();
+    let mut opts = reference.clone();
+    opts.cg.opt_level = "3".to_string();
+    opts.unstable_opts.incremental_ignore_spans = true;
+    opts.unstable_opts.incremental_info = true;
+    opts.edition = Edition::Edition2021;
+
+    let before = reference.dep_tracking_hashes();
+    let after = opts.dep_tracking_hashes();
+    let changed: Vec<_> = after
+        .iter()
+        .filter(|&(name, hash)| before.get(name) != Some(hash))
+        .map(|(name, _)| name.as_str())
+        .collect();
+
+    // Untracked options (`-Z incremental-info`) have no hash.
+    assert_eq!(changed, ["-C opt-level", "-Z incremental-ignore-spans", "edition"]);
+    assert_eq!(before.len(), after.len());
+}
+
 #[test]
 fn test_unstable_options_tracking_hash() {
     let reference = Options::default();
@@ -657,6 +695,7 @@ macro_rules! untracked {
 
     // Make sure that changing an [UNTRACKED] option leaves the hash unchanged.
     // tidy-alphabetical-start
+    untracked!(api_usage, SwitchWithOptPath::Enabled(Some("api-usage-dir/".into())));
     untracked!(assert_incr_state, Some(String::from("loaded")));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dont_buffer_diagnostics, true);
@@ -667,17 +706,23 @@ macro_rules! untracked {
     untracked!(dump_mir_exclude_pass_number, true);
     untracked!(dump_mir_graphviz, true);
     untracked!(dump_mir_spanview, Some(MirSpanview::Statement));
+    untracked!(dump_mono_bloat, SwitchWithOptPath::Enabled(Some("mono-items-dir/".into())));
     untracked!(dump_mono_stats, SwitchWithOptPath::Enabled(Some("mono-items-dir/".into())));
     untracked!(dump_mono_stats_format, DumpMonoStatsFormat::Json);
     untracked!(dylib_lto, true);
     untracked!(emit_stack_sizes, true);
+    untracked!(fix, true);
+    untracked!(fix_passes, 4);
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
     untracked!(identify_regions, true);
+    untracked!(incremental_explain, SwitchWithOptPath::Enabled(None));
     untracked!(incremental_info, true);
+    untracked!(incremental_relocatable, true);
     untracked!(incremental_verify_ich, true);
     untracked!(input_stats, true);
     untracked!(link_native_libraries, false);
+    untracked!(lint_config, Some(PathBuf::from("lints.toml")));
     untracked!(llvm_time_trace, true);
     untracked!(ls, vec!["all".to_owned()]);
     untracked!(macro_backtrace, true);
@@ -698,6 +743,7 @@ macro_rules! untracked {
     untracked!(proc_macro_execution_strategy, ProcMacroExecutionStrategy::CrossThread);
     untracked!(profile_closures, true);
     untracked!(query_dep_graph, true);
+    untracked!(record_mono_item_mentions, true);
     untracked!(self_profile, SwitchWithOptPath::Enabled(None));
     untracked!(self_profile_events, Some(vec![String::new()]));
     untracked!(span_debug, true);
@@ -778,6 +824,8 @@ macro_rules! tracked {
     tracked!(mir_enable_passes, vec![("DestProp".to_string(), false)]);
     tracked!(mir_keep_place_mention, true);
     tracked!(mir_opt_level, Some(4));
+    tracked!(mir_specialize_const_args, Some(true));
+    tracked!(mir_specialize_const_args_threshold, Some(123));
     tracked!(move_size_limit, Some(4096));
     tracked!(mutable_noalias, false);
     tracked!(
@@ -819,6 +867,7 @@ macro_rules! tracked {
     tracked!(simulate_remapped_rust_src_base, Some(PathBuf::from("/rustc/abc")));
     tracked!(split_lto_unit, Some(true));
     tracked!(src_hash_algorithm, Some(SourceFileHashAlgorithm::Sha1));
+    tracked!(stack_report, SwitchWithOptPath::Enabled(Some("stack-report-dir/".into())));
     tracked!(stack_protector, StackProtector::All);
     tracked!(teach, true);
     tracked!(thinlto, Some(true));
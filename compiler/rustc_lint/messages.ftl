@@ -162,6 +162,8 @@ lint_check_name_unknown_tool = unknown lint tool: `{$tool_name}`
 
 lint_command_line_source = `forbid` lint level was set on command line
 
+lint_config_file_source = `forbid` lint level was set by `{$path}` line {$line}
+
 lint_confusable_identifier_pair = found both `{$existing_sym}` and `{$sym}` as identifiers, which look alike
     .current_use = this identifier can be confused with `{$existing_sym}`
     .other_use = other identifier used here
@@ -492,6 +494,8 @@ lint_renamed_lint = lint `{$name}` has been renamed to `{$replace}`
 
 lint_requested_level = requested on the command line with `{$level} {$lint_name}`
 
+lint_requested_level_config_file = requested by `{$path}` line {$line} with `{$lint_name} = "{$level}"`
+
 lint_span_use_eq_ctxt = use `.eq_ctxt()` instead of `.ctxt() == .ctxt()`
 
 lint_supertrait_as_deref_target = this `Deref` implementation is covered by an implicit supertrait coercion
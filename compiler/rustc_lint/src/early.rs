@@ -84,11 +84,13 @@ fn visit_param(&mut self, param: &'a ast::Param) {
     }
 
     fn visit_item(&mut self, it: &'a ast::Item) {
+        let push = self.context.builder.push_module_overrides(it);
         self.with_lint_attrs(it.id, &it.attrs, |cx| {
             lint_callback!(cx, check_item, it);
             ast_visit::walk_item(cx, it);
             lint_callback!(cx, check_item_post, it);
-        })
+        });
+        self.context.builder.pop(push);
     }
 
     fn visit_foreign_item(&mut self, it: &'a ast::ForeignItem) {
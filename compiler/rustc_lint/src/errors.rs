@@ -21,6 +21,7 @@ pub enum OverruledAttributeSub {
     DefaultSource { id: String },
     NodeSource { span: Span, reason: Option<Symbol> },
     CommandLineSource,
+    ConfigFileSource { path: Symbol, line: u32 },
 }
 
 impl AddToDiagnostic for OverruledAttributeSub {
@@ -43,6 +44,11 @@ fn add_to_diagnostic_with<F>(self, diag: &mut Diagnostic, _: F)
             OverruledAttributeSub::CommandLineSource => {
                 diag.note(fluent::lint_command_line_source);
             }
+            OverruledAttributeSub::ConfigFileSource { path, line } => {
+                diag.note(fluent::lint_config_file_source);
+                diag.set_arg("path", path);
+                diag.set_arg("line", line);
+            }
         }
     }
 }
@@ -87,11 +93,12 @@ pub struct BuiltinEllipsisInclusiveRangePatterns {
     pub replace: String,
 }
 
-#[derive(Subdiagnostic)]
-#[note(lint_requested_level)]
-pub struct RequestedLevel<'a> {
-    pub level: Level,
-    pub lint_name: &'a str,
+#[derive(Clone, Copy, Subdiagnostic)]
+pub enum RequestedLevel<'a> {
+    #[note(lint_requested_level)]
+    CommandLine { level: Level, lint_name: &'a str },
+    #[note(lint_requested_level_config_file)]
+    ConfigFile { level: &'static str, lint_name: &'a str, path: &'a str, line: u32 },
 }
 
 #[derive(Diagnostic)]
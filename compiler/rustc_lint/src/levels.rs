@@ -29,6 +29,7 @@
 };
 use rustc_middle::query::Providers;
 use rustc_middle::ty::{RegisteredTools, TyCtxt};
+use rustc_session::config::lint_config::{LintConfig, LintConfigEntry};
 use rustc_session::lint::{
     builtin::{
         self, FORBIDDEN_LINT_GROUPS, RENAMED_AND_REMOVED_LINTS, SINGLE_USE_LIFETIMES,
@@ -39,7 +40,8 @@
 use rustc_session::parse::feature_err;
 use rustc_session::Session;
 use rustc_span::symbol::{sym, Symbol};
-use rustc_span::{Span, DUMMY_SP};
+use rustc_span::{FileName, Span, DUMMY_SP};
+use std::path::Path;
 
 use crate::errors::{
     MalformedAttribute, MalformedAttributeSub, OverruledAttribute, OverruledAttributeSub,
@@ -171,6 +173,13 @@ fn shallow_lint_levels_on(tcx: TyCtxt<'_>, owner: hir::OwnerId) -> ShallowLintLe
 
     if owner == hir::CRATE_OWNER_ID {
         levels.add_command_line();
+    } else if tcx.sess.opts.lint_config.as_ref().is_some_and(|config| !config.overrides.is_empty())
+        && let hir::OwnerNode::Item(item) = tcx.hir().owner(owner)
+        && let hir::ItemKind::Mod(module) = item.kind
+    {
+        // The overrides of the lint configuration file come before the
+        // attributes of the module, which take precedence.
+        levels.add_module_lint_config_overrides(item.span, module.spans.inner_span);
     }
 
     match attrs.map.range(..) {
@@ -524,6 +533,27 @@ pub(crate) fn push(
         BuilderPush { prev }
     }
 
+    /// Pushes the levels set by the overrides of the lint configuration file
+    /// for the module `item`, if it is one loaded from a file of its own.
+    ///
+    /// As for `push`, the result must be passed to `pop`.
+    pub(crate) fn push_module_overrides(&mut self, item: &ast::Item) -> BuilderPush {
+        let prev = self.provider.cur;
+        if let ast::ItemKind::Mod(_, ast::ModKind::Loaded(_, ast::Inline::No, spans)) = &item.kind {
+            self.provider.cur =
+                self.provider.sets.list.push(LintSet { specs: FxHashMap::default(), parent: prev });
+
+            self.add_module_lint_config_overrides(item.span, spans.inner_span);
+
+            if self.provider.current_specs().is_empty() {
+                self.provider.sets.list.pop();
+                self.provider.cur = prev;
+            }
+        }
+
+        BuilderPush { prev }
+    }
+
     /// Called after `push` when the scope of a set of attributes are exited.
     pub(crate) fn pop(&mut self, push: BuilderPush) {
         self.provider.cur = push.prev;
@@ -556,52 +586,16 @@ fn insert(&mut self, id: LintId, lvl: LevelAndSource) {
     }
 
     fn add_command_line(&mut self) {
+        let sess = self.sess;
+        if let Some(config) = &sess.opts.lint_config {
+            self.check_lint_config_names(config);
+            self.add_lint_config_entries(config, &config.lints);
+        }
+
         for &(ref lint_name, level) in &self.sess.opts.lint_opts {
             // Checks the validity of lint names derived from the command line.
-            let (tool_name, lint_name_only) = parse_lint_and_tool_name(lint_name);
-            if lint_name_only == crate::WARNINGS.name_lower()
-                && matches!(level, Level::ForceWarn(_))
-            {
-                self.sess
-                    .dcx()
-                    .emit_err(UnsupportedGroup { lint_group: crate::WARNINGS.name_lower() });
-            }
-            match self.store.check_lint_name(lint_name_only, tool_name, self.registered_tools) {
-                CheckLintNameResult::Renamed(ref replace) => {
-                    let name = lint_name.as_str();
-                    let suggestion = RenamedLintSuggestion::WithoutSpan { replace };
-                    let requested_level = RequestedLevel { level, lint_name };
-                    let lint = RenamedLintFromCommandLine { name, suggestion, requested_level };
-                    self.emit_lint(RENAMED_AND_REMOVED_LINTS, lint);
-                }
-                CheckLintNameResult::Removed(ref reason) => {
-                    let name = lint_name.as_str();
-                    let requested_level = RequestedLevel { level, lint_name };
-                    let lint = RemovedLintFromCommandLine { name, reason, requested_level };
-                    self.emit_lint(RENAMED_AND_REMOVED_LINTS, lint);
-                }
-                CheckLintNameResult::NoLint(suggestion) => {
-                    let name = lint_name.clone();
-                    let suggestion =
-                        suggestion.map(|replace| UnknownLintSuggestion::WithoutSpan { replace });
-                    let requested_level = RequestedLevel { level, lint_name };
-                    let lint = UnknownLintFromCommandLine { name, suggestion, requested_level };
-                    self.emit_lint(UNKNOWN_LINTS, lint);
-                }
-                CheckLintNameResult::Tool(Err((Some(_), ref replace))) => {
-                    let name = lint_name.clone();
-                    let requested_level = RequestedLevel { level, lint_name };
-                    let lint = DeprecatedLintNameFromCommandLine { name, replace, requested_level };
-                    self.emit_lint(RENAMED_AND_REMOVED_LINTS, lint);
-                }
-                CheckLintNameResult::NoTool => {
-                    self.sess.dcx().emit_err(CheckNameUnknownTool {
-                        tool_name: tool_name.unwrap(),
-                        sub: RequestedLevel { level, lint_name },
-                    });
-                }
-                _ => {}
-            };
+            let requested_level = RequestedLevel::CommandLine { level, lint_name };
+            self.check_lint_name_outside_attr(lint_name, level, requested_level);
 
             let orig_level = level;
             let lint_flag_val = Symbol::intern(lint_name);
@@ -624,6 +618,129 @@ fn add_command_line(&mut self) {
                 }
             }
         }
+
+        // The crate root is a module like any other for the overrides of the
+        // configuration file, they take precedence over the command line.
+        if let Some(config) = &sess.opts.lint_config
+            && let Some(crate_root) = sess.io.input.opt_path()
+        {
+            self.add_lint_config_overrides(config, crate_root);
+        }
+    }
+
+    /// Checks the validity of a lint name given outside of an attribute, i.e.
+    /// on the command line or in the lint configuration file.
+    fn check_lint_name_outside_attr(
+        &self,
+        lint_name: &str,
+        level: Level,
+        requested_level: RequestedLevel<'_>,
+    ) {
+        let (tool_name, lint_name_only) = parse_lint_and_tool_name(lint_name);
+        if lint_name_only == crate::WARNINGS.name_lower() && matches!(level, Level::ForceWarn(_)) {
+            self.sess.dcx().emit_err(UnsupportedGroup { lint_group: crate::WARNINGS.name_lower() });
+        }
+        match self.store.check_lint_name(lint_name_only, tool_name, self.registered_tools) {
+            CheckLintNameResult::Renamed(ref replace) => {
+                let suggestion = RenamedLintSuggestion::WithoutSpan { replace };
+                let lint =
+                    RenamedLintFromCommandLine { name: lint_name, suggestion, requested_level };
+                self.emit_lint(RENAMED_AND_REMOVED_LINTS, lint);
+            }
+            CheckLintNameResult::Removed(ref reason) => {
+                let lint = RemovedLintFromCommandLine { name: lint_name, reason, requested_level };
+                self.emit_lint(RENAMED_AND_REMOVED_LINTS, lint);
+            }
+            CheckLintNameResult::NoLint(suggestion) => {
+                let name = lint_name.to_owned();
+                let suggestion =
+                    suggestion.map(|replace| UnknownLintSuggestion::WithoutSpan { replace });
+                let lint = UnknownLintFromCommandLine { name, suggestion, requested_level };
+                self.emit_lint(UNKNOWN_LINTS, lint);
+            }
+            CheckLintNameResult::Tool(Err((Some(_), ref replace))) => {
+                let name = lint_name.to_owned();
+                let lint = DeprecatedLintNameFromCommandLine { name, replace, requested_level };
+                self.emit_lint(RENAMED_AND_REMOVED_LINTS, lint);
+            }
+            CheckLintNameResult::NoTool => {
+                self.sess.dcx().emit_err(CheckNameUnknownTool {
+                    tool_name: tool_name.unwrap(),
+                    sub: requested_level,
+                });
+            }
+            _ => {}
+        };
+    }
+
+    /// Checks the validity of all the lint names of the configuration file,
+    /// including the ones of overrides that may not apply to any module.
+    fn check_lint_config_names(&self, config: &LintConfig) {
+        let path = config.path.display().to_string();
+        let overrides = config.overrides.iter().flat_map(|over| &over.lints);
+        for entry in config.lints.iter().chain(overrides) {
+            let requested_level = RequestedLevel::ConfigFile {
+                level: entry.level.as_str(),
+                lint_name: &entry.name,
+                path: &path,
+                line: entry.line,
+            };
+            self.check_lint_name_outside_attr(&entry.name, entry.level, requested_level);
+        }
+    }
+
+    /// Sets the levels of `entries`, which come from `config`.
+    ///
+    /// Entries are applied by ascending priority and, for the same priority,
+    /// lint groups come first so that they don't override the individual lints
+    /// listed next to them.
+    fn add_lint_config_entries(&mut self, config: &LintConfig, entries: &[LintConfigEntry]) {
+        let path = Symbol::intern(&config.path.display().to_string());
+        let mut entries: Vec<_> =
+            entries.iter().map(|entry| (entry, Symbol::intern(&entry.name))).collect();
+        entries.sort_by_key(|&(entry, name)| (entry.priority, !self.store.is_lint_group(name)));
+
+        for (entry, name) in entries {
+            // Invalid names have been reported by `check_lint_config_names`.
+            let Ok(ids) = self.store.find_lints(&entry.name) else {
+                continue;
+            };
+            let src =
+                LintLevelSource::ConfigFile { name, level: entry.level, path, line: entry.line };
+            for id in ids {
+                if self.check_gated_lint(id, DUMMY_SP, true) {
+                    self.insert_spec(id, (entry.level, src));
+                }
+            }
+        }
+    }
+
+    /// Applies the overrides of `config` matching the module loaded from `file`.
+    fn add_lint_config_overrides(&mut self, config: &LintConfig, file: &Path) {
+        for over in config.overrides_for(file) {
+            self.add_lint_config_entries(config, &over.lints);
+        }
+    }
+
+    /// Applies the overrides of the lint configuration file to a module loaded
+    /// from a file of its own, `item_span` being the span of the `mod` item and
+    /// `inner_span` the one of the contents of the module.
+    fn add_module_lint_config_overrides(&mut self, item_span: Span, inner_span: Span) {
+        let sess = self.sess;
+        let Some(config) = &sess.opts.lint_config else { return };
+        if config.overrides.is_empty() || inner_span.is_dummy() {
+            return;
+        }
+        let file = sess.source_map().lookup_source_file(inner_span.lo());
+        if file.contains(item_span.lo()) {
+            // Inline modules inherit the levels of their parent.
+            return;
+        }
+        if let FileName::Real(name) = &file.name
+            && let Some(path) = name.local_path()
+        {
+            self.add_lint_config_overrides(config, path);
+        }
     }
 
     /// Attempts to insert the `id` to `level_src` map entry. If unsuccessful
@@ -655,6 +772,7 @@ fn insert_spec(&mut self, id: LintId, (mut level, src): LevelAndSource) {
                     LintLevelSource::Default => false,
                     LintLevelSource::Node { name, .. } => self.store.is_lint_group(name),
                     LintLevelSource::CommandLine(symbol, _) => self.store.is_lint_group(symbol),
+                    LintLevelSource::ConfigFile { name, .. } => self.store.is_lint_group(name),
                 };
                 debug!(
                     "fcw_warning={:?}, specs.get(&id) = {:?}, old_src={:?}, id_name={:?}",
@@ -671,6 +789,9 @@ fn insert_spec(&mut self, id: LintId, (mut level, src): LevelAndSource) {
                         OverruledAttributeSub::NodeSource { span, reason }
                     }
                     LintLevelSource::CommandLine(_, _) => OverruledAttributeSub::CommandLineSource,
+                    LintLevelSource::ConfigFile { path, line, .. } => {
+                        OverruledAttributeSub::ConfigFileSource { path, line }
+                    }
                 };
                 if !fcw_warning {
                     self.sess.dcx().emit_err(OverruledAttribute {
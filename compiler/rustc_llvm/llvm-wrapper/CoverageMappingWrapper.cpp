@@ -43,6 +43,10 @@ enum class LLVMRustCounterMappingRegionKind {
   SkippedRegion = 2,
   GapRegion = 3,
   BranchRegion = 4,
+#if LLVM_VERSION_GE(18, 0)
+  MCDCDecisionRegion = 5,
+  MCDCBranchRegion = 6
+#endif
 };
 
 static coverage::CounterMappingRegion::RegionKind
@@ -58,15 +62,41 @@ fromRust(LLVMRustCounterMappingRegionKind Kind) {
     return coverage::CounterMappingRegion::GapRegion;
   case LLVMRustCounterMappingRegionKind::BranchRegion:
     return coverage::CounterMappingRegion::BranchRegion;
+#if LLVM_VERSION_GE(18, 0)
+  case LLVMRustCounterMappingRegionKind::MCDCDecisionRegion:
+    return coverage::CounterMappingRegion::MCDCDecisionRegion;
+  case LLVMRustCounterMappingRegionKind::MCDCBranchRegion:
+    return coverage::CounterMappingRegion::MCDCBranchRegion;
+#endif
   }
   report_fatal_error("Bad LLVMRustCounterMappingRegionKind!");
 }
 
+// FFI equivalent of struct `llvm::coverage::CounterMappingRegion::MCDCParameters`
+// https://github.com/llvm/llvm-project/blob/llvmorg-18.1.0/llvm/include/llvm/ProfileData/Coverage/CoverageMapping.h#L253-L263
+struct LLVMRustMCDCParameters {
+  uint32_t BitmapIdx;
+  uint32_t NumConditions;
+  uint32_t ID;
+  uint32_t TrueID;
+  uint32_t FalseID;
+};
+
+#if LLVM_VERSION_GE(18, 0)
+static coverage::CounterMappingRegion::MCDCParameters
+fromRust(LLVMRustMCDCParameters Params) {
+  return coverage::CounterMappingRegion::MCDCParameters{
+      Params.BitmapIdx, Params.NumConditions,
+      Params.ID, Params.TrueID, Params.FalseID};
+}
+#endif
+
 // FFI equivalent of struct `llvm::coverage::CounterMappingRegion`
 // https://github.com/rust-lang/llvm-project/blob/ea6fa9c2/llvm/include/llvm/ProfileData/Coverage/CoverageMapping.h#L211-L304
 struct LLVMRustCounterMappingRegion {
   LLVMRustCounter Count;
   LLVMRustCounter FalseCount;
+  LLVMRustMCDCParameters MCDCParameters;
   uint32_t FileID;
   uint32_t ExpandedFileID;
   uint32_t LineStart;
@@ -140,7 +170,7 @@ extern "C" void LLVMRustCoverageWriteMappingToBuffer(
     MappingRegions.emplace_back(
         fromRust(Region.Count), fromRust(Region.FalseCount),
 #if LLVM_VERSION_GE(18, 0)
-        coverage::CounterMappingRegion::MCDCParameters{},
+        fromRust(Region.MCDCParameters),
 #endif
         Region.FileID, Region.ExpandedFileID,
         Region.LineStart, Region.ColumnStart, Region.LineEnd, Region.ColumnEnd,
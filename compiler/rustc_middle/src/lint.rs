@@ -32,6 +32,17 @@ pub enum LintLevelSource {
     /// The provided `Level` is the level specified on the command line.
     /// (The actual level may be lower due to `--cap-lints`.)
     CommandLine(Symbol, Level),
+
+    /// Lint level was set by the lint configuration file (`-Z lint-config`).
+    ConfigFile {
+        /// The lint or lint group named in the file.
+        name: Symbol,
+        /// The level specified in the file, as for `CommandLine`.
+        level: Level,
+        /// The path of the file.
+        path: Symbol,
+        line: u32,
+    },
 }
 
 impl LintLevelSource {
@@ -40,6 +51,7 @@ pub fn name(&self) -> Symbol {
             LintLevelSource::Default => symbol::kw::Default,
             LintLevelSource::Node { name, .. } => name,
             LintLevelSource::CommandLine(name, _) => name,
+            LintLevelSource::ConfigFile { name, .. } => name,
         }
     }
 
@@ -48,6 +60,7 @@ pub fn span(&self) -> Span {
             LintLevelSource::Default => DUMMY_SP,
             LintLevelSource::Node { span, .. } => span,
             LintLevelSource::CommandLine(_, _) => DUMMY_SP,
+            LintLevelSource::ConfigFile { .. } => DUMMY_SP,
         }
     }
 }
@@ -97,7 +110,9 @@ pub fn reveal_actual_level(
     }
 
     // Ensure that we never exceed the `--cap-lints` argument unless the source is a --force-warn
-    level = if let LintLevelSource::CommandLine(_, Level::ForceWarn(_)) = src {
+    level = if let LintLevelSource::CommandLine(_, Level::ForceWarn(_))
+    | LintLevelSource::ConfigFile { level: Level::ForceWarn(_), .. } = src
+    {
         level
     } else {
         cmp::min(level, sess.opts.lint_cap.unwrap_or(Level::Forbid))
@@ -228,6 +243,17 @@ pub fn explain_lint_level_source(
                 ));
             }
         }
+        LintLevelSource::ConfigFile { name: config_name, level: orig_level, path, line } => {
+            let orig_level = orig_level.as_str();
+            if config_name.as_str() == name {
+                err.note_once(format!("`{name} = \"{orig_level}\"` set by `{path}` line {line}"));
+            } else {
+                err.note_once(format!(
+                    "`{name}` implied by `{config_name} = \"{orig_level}\"` \
+                     set by `{path}` line {line}"
+                ));
+            }
+        }
         LintLevelSource::Node { name: lint_attr_name, span, reason, .. } => {
             if let Some(rationale) = reason {
                 err.note(rationale.to_string());
@@ -26,6 +26,10 @@ pub struct CodegenFnAttrs {
     /// The `#[target_feature(enable = "...")]` attribute and the enabled
     /// features (only enabled features are supported right now).
     pub target_features: Vec<Symbol>,
+    /// The `#[target_clones("...", "default")]` attribute: the clones of the function compiled
+    /// with more target features, from the most preferred to the least. The `default` clone, which
+    /// is the function compiled normally, isn't included.
+    pub target_clones: Vec<TargetClone>,
     /// The `#[linkage = "..."]` attribute on Rust-defined items and the value we found.
     pub linkage: Option<Linkage>,
     /// The `#[linkage = "..."]` attribute on foreign items and the value we found.
@@ -45,6 +49,13 @@ pub struct CodegenFnAttrs {
     pub alignment: Option<u32>,
 }
 
+/// A clone of a function generated by `#[target_clones]`.
+#[derive(Clone, TyEncodable, TyDecodable, HashStable, Debug)]
+pub struct TargetClone {
+    /// The target features enabled in the clone, in addition to the ones of the function.
+    pub features: Vec<Symbol>,
+}
+
 bitflags! {
     #[derive(TyEncodable, TyDecodable, HashStable)]
     pub struct CodegenFnAttrFlags: u32 {
@@ -117,6 +128,7 @@ pub const fn new() -> CodegenFnAttrs {
             link_name: None,
             link_ordinal: None,
             target_features: vec![],
+            target_clones: vec![],
             linkage: None,
             import_linkage: None,
             link_section: None,
@@ -4,6 +4,7 @@
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::graph;
 use rustc_data_structures::graph::dominators::{dominators, Dominators};
+use rustc_data_structures::graph::loops::{loop_nest, LoopNest};
 use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
 use rustc_data_structures::sync::OnceLock;
 use rustc_index::{IndexSlice, IndexVec};
@@ -28,6 +29,7 @@ struct Cache {
     is_cyclic: OnceLock<bool>,
     reverse_postorder: OnceLock<Vec<BasicBlock>>,
     dominators: OnceLock<Dominators<BasicBlock>>,
+    loop_nest: OnceLock<LoopNest<BasicBlock>>,
 }
 
 impl<'tcx> BasicBlocks<'tcx> {
@@ -46,6 +48,11 @@ pub fn dominators(&self) -> &Dominators<BasicBlock> {
         self.cache.dominators.get_or_init(|| dominators(self))
     }
 
+    /// Returns the natural loops of the control-flow graph, and how they nest.
+    pub fn loop_nest(&self) -> &LoopNest<BasicBlock> {
+        self.cache.loop_nest.get_or_init(|| loop_nest(self, self.dominators()))
+    }
+
     /// Returns predecessors for each basic block.
     #[inline]
     pub fn predecessors(&self) -> &Predecessors {
@@ -2,7 +2,7 @@
 
 use rustc_index::IndexVec;
 use rustc_macros::HashStable;
-use rustc_span::Symbol;
+use rustc_span::{Span, Symbol};
 
 use std::fmt::{self, Debug, Formatter};
 
@@ -50,6 +50,39 @@ impl ExpressionId {
     pub const START: Self = Self::from_u32(0);
 }
 
+rustc_index::newtype_index! {
+    /// Identifies a `CoverageKind::BlockMarker` statement injected during
+    /// MIR building, so that the coverage instrumentor can find the blocks
+    /// where the outcomes of a branch begin. Values ascend from 0.
+    ///
+    /// Block marker IDs are local to the body they were injected into, and
+    /// are only meaningful in conjunction with that body's [`BranchInfo`].
+    #[derive(HashStable)]
+    #[encodable]
+    #[orderable]
+    #[debug_format = "BlockMarkerId({})"]
+    pub struct BlockMarkerId {}
+}
+
+rustc_index::newtype_index! {
+    /// ID of a condition within an MC/DC decision. Values ascend from 1, so
+    /// that [`ConditionId::NONE`] can mark the absence of a next condition.
+    ///
+    /// Note that LLVM handles condition IDs as `int16_t`, and only supports
+    /// a handful of conditions per decision anyway.
+    #[derive(HashStable)]
+    #[encodable]
+    #[orderable]
+    #[max = 0xFFFF]
+    #[debug_format = "ConditionId({})"]
+    pub struct ConditionId {}
+}
+
+impl ConditionId {
+    pub const NONE: Self = Self::from_u32(0);
+    pub const START: Self = Self::from_u32(1);
+}
+
 /// Enum that can hold a constant zero value, the ID of an physical coverage
 /// counter, or the ID of a coverage-counter expression.
 ///
@@ -100,6 +133,30 @@ pub enum CoverageKind {
     /// mappings. Intermediate expressions with no direct mappings are
     /// retained/zeroed based on whether they are transitively used.)
     ExpressionUsed { id: ExpressionId },
+
+    /// Marks its enclosing basic block with an ID that can be referred to by
+    /// the [`BranchInfo`] of the body, to find the blocks where the outcomes
+    /// of a branch begin.
+    ///
+    /// Only used by the `InstrumentCoverage` pass, and has no effect during
+    /// codegen.
+    BlockMarker { id: BlockMarkerId },
+
+    /// Marks the point in MIR control flow where a condition of an MC/DC
+    /// decision has been evaluated to `value`.
+    ///
+    /// This is eventually lowered to `llvm.instrprof.mcdc.condbitmap.update`
+    /// in LLVM IR.
+    CondBitmapUpdate { id: ConditionId, value: bool },
+
+    /// Marks the point in MIR control flow where an MC/DC decision has been
+    /// evaluated, so that the conditions evaluated so far can be recorded as a
+    /// test vector in the bitmap of the decision, which starts at byte
+    /// `bitmap_idx` of the function's MC/DC bitmap.
+    ///
+    /// This is eventually lowered to `llvm.instrprof.mcdc.tvbitmap.update`
+    /// in LLVM IR.
+    TestVectorBitmapUpdate { bitmap_idx: u32 },
 }
 
 impl Debug for CoverageKind {
@@ -109,6 +166,13 @@ fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
             SpanMarker => write!(fmt, "SpanMarker"),
             CounterIncrement { id } => write!(fmt, "CounterIncrement({:?})", id.index()),
             ExpressionUsed { id } => write!(fmt, "ExpressionUsed({:?})", id.index()),
+            BlockMarker { id } => write!(fmt, "BlockMarker({:?})", id.index()),
+            CondBitmapUpdate { id, value } => {
+                write!(fmt, "CondBitmapUpdate({:?}, {value})", id.index())
+            }
+            TestVectorBitmapUpdate { bitmap_idx } => {
+                write!(fmt, "TestVectorBitmapUpdate({bitmap_idx})")
+            }
         }
     }
 }
@@ -158,18 +222,56 @@ pub struct Expression {
     pub rhs: CovTerm,
 }
 
+#[derive(Copy, Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub enum MappingKind {
+    /// Associates a normal region of code with a counter/expression/zero.
+    Code(CovTerm),
+    /// Associates a branch region with separate counters for true and false.
+    Branch { true_term: CovTerm, false_term: CovTerm },
+    /// Associates a branch region with separate counters for true and false,
+    /// as one of the conditions of an MC/DC decision.
+    MCDCBranch { true_term: CovTerm, false_term: CovTerm, mcdc_params: ConditionInfo },
+    /// Associates a decision region with its MC/DC bitmap and number of
+    /// conditions.
+    MCDCDecision(DecisionInfo),
+}
+
+impl MappingKind {
+    /// Iterator over all coverage terms in this mapping kind.
+    pub fn terms(&self) -> impl Iterator<Item = CovTerm> {
+        let terms = match *self {
+            Self::Code(term) => [Some(term), None],
+            Self::Branch { true_term, false_term }
+            | Self::MCDCBranch { true_term, false_term, .. } => [Some(true_term), Some(false_term)],
+            Self::MCDCDecision(_) => [None, None],
+        };
+        terms.into_iter().flatten()
+    }
+
+    /// Returns a copy of this mapping kind, in which all coverage terms have
+    /// been replaced with ones returned by the given function.
+    pub fn map_terms(&self, map_fn: impl Fn(CovTerm) -> CovTerm) -> Self {
+        match *self {
+            Self::Code(term) => Self::Code(map_fn(term)),
+            Self::Branch { true_term, false_term } => {
+                Self::Branch { true_term: map_fn(true_term), false_term: map_fn(false_term) }
+            }
+            Self::MCDCBranch { true_term, false_term, mcdc_params } => Self::MCDCBranch {
+                true_term: map_fn(true_term),
+                false_term: map_fn(false_term),
+                mcdc_params,
+            },
+            Self::MCDCDecision(decision_info) => Self::MCDCDecision(decision_info),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 #[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
 pub struct Mapping {
+    pub kind: MappingKind,
     pub code_region: CodeRegion,
-
-    /// Indicates whether this mapping uses a counter value, expression value,
-    /// or zero value.
-    ///
-    /// FIXME: When we add support for mapping kinds other than `Code`
-    /// (e.g. branch regions, expansion regions), replace this with a dedicated
-    /// mapping-kind enum.
-    pub term: CovTerm,
 }
 
 /// Stores per-function coverage information attached to a `mir::Body`,
@@ -180,7 +282,101 @@ pub struct Mapping {
 pub struct FunctionCoverageInfo {
     pub function_source_hash: u64,
     pub num_counters: usize,
+    /// Total size in bytes of the MC/DC bitmaps of the function's decisions.
+    pub mcdc_bitmap_bytes: u32,
 
     pub expressions: IndexVec<ExpressionId, Expression>,
     pub mappings: Vec<Mapping>,
 }
+
+/// Branch information recorded during THIR-to-MIR lowering, and stored in MIR,
+/// for `-Cinstrument-coverage=branch` and `-Cinstrument-coverage=mcdc`.
+///
+/// The spans refer to the blocks where the outcomes of each branch begin by
+/// the IDs of the `CoverageKind::BlockMarker` statements injected into them.
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct BranchInfo {
+    /// 1 more than the highest-numbered [`CoverageKind::BlockMarker`] that was
+    /// injected into the MIR body. This makes it possible to allocate per-ID
+    /// data structures without having to scan the entire body first.
+    pub num_block_markers: usize,
+    pub branch_spans: Vec<BranchSpan>,
+    pub match_spans: Vec<MatchSpan>,
+    pub mcdc_decision_spans: Vec<MCDCDecisionSpan>,
+}
+
+/// A two-way branch, e.g. the condition of an `if` or an operand of `&&`.
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct BranchSpan {
+    pub span: Span,
+    pub true_marker: BlockMarkerId,
+    pub false_marker: BlockMarkerId,
+}
+
+/// The arms of a `match` expression, including the ones desugared from `?`.
+/// Each arm is a branch whose true outcome is the arm being taken, and whose
+/// false outcome is one of the later arms being taken.
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct MatchSpan {
+    pub span: Span,
+    pub arms: Vec<MatchArmSpan>,
+}
+
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct MatchArmSpan {
+    pub span: Span,
+    /// Marks the block where the body of the arm begins.
+    pub marker: BlockMarkerId,
+}
+
+/// The position of a condition in the evaluation of its MC/DC decision: the
+/// condition that is evaluated next for each of its outcomes, if any.
+#[derive(Copy, Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct ConditionInfo {
+    pub condition_id: ConditionId,
+    pub true_next_id: ConditionId,
+    pub false_next_id: ConditionId,
+}
+
+impl Default for ConditionInfo {
+    fn default() -> Self {
+        Self {
+            condition_id: ConditionId::NONE,
+            true_next_id: ConditionId::NONE,
+            false_next_id: ConditionId::NONE,
+        }
+    }
+}
+
+/// A condition of an MC/DC decision.
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct MCDCBranchSpan {
+    pub span: Span,
+    pub condition_info: ConditionInfo,
+    pub true_marker: BlockMarkerId,
+    pub false_marker: BlockMarkerId,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct DecisionInfo {
+    pub bitmap_idx: u32,
+    pub conditions_num: u16,
+}
+
+/// A boolean decision made of several conditions combined with `&&` and `||`.
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, HashStable, TypeFoldable, TypeVisitable)]
+pub struct MCDCDecisionSpan {
+    pub span: Span,
+    pub conditions: Vec<MCDCBranchSpan>,
+    /// Marks the blocks where the outcome of the decision is known, i.e. the
+    /// outcomes of its conditions that aren't followed by another condition.
+    pub end_markers: Vec<BlockMarkerId>,
+}
@@ -10,6 +10,7 @@
 use rustc_index::Idx;
 use rustc_query_system::ich::StableHashingContext;
 use rustc_session::config::OptLevel;
+use rustc_span::source_map::{dummy_spanned, respan, Spanned};
 use rustc_span::symbol::Symbol;
 use rustc_span::Span;
 use std::fmt;
@@ -234,6 +235,125 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// The kind of use of a mono item by another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UsageKind {
+    /// The item is called, e.g. a function or a lang item to panic with.
+    Call,
+    /// The item is the drop glue of a value that is dropped.
+    DropGlue,
+    /// The item is in a vtable that is created, i.e. a method or the drop glue of the type.
+    Vtable,
+    /// The item is turned into a function pointer.
+    FnPointer,
+    /// The item is a static that is referred to, or the shim to access a thread local static.
+    Static,
+    /// The item is only mentioned as a function item value, e.g. when it is passed to a generic
+    /// function. It isn't collected because of this use, and it is only recorded with
+    /// `-Z record-mono-item-mentions`.
+    Mentioned,
+}
+
+/// The mono items used by each mono item of the local crate, as found by the collector of
+/// `rustc_monomorphize`.
+#[derive(Debug)]
+pub struct UsageMap<'tcx> {
+    // Maps every mono item to the mono items used by it, with the kind of each use.
+    used_map: FxHashMap<MonoItem<'tcx>, Vec<(MonoItem<'tcx>, UsageKind)>>,
+
+    // Maps every mono item to the mono items it only mentions.
+    mentioned_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
+
+    // Maps every mono item to the mono items that use it.
+    user_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
+
+    // Maps every mono item but the roots to the mono item whose use of it
+    // caused it to be collected, with the span of that use. Only recorded
+    // for `-Z dump-mono-bloat`.
+    collected_by: Option<FxHashMap<MonoItem<'tcx>, Spanned<MonoItem<'tcx>>>>,
+}
+
+impl<'tcx> UsageMap<'tcx> {
+    pub fn new(record_collected_by: bool) -> UsageMap<'tcx> {
+        UsageMap {
+            used_map: FxHashMap::default(),
+            mentioned_map: FxHashMap::default(),
+            user_map: FxHashMap::default(),
+            collected_by: record_collected_by.then(FxHashMap::default),
+        }
+    }
+
+    pub fn record_used(
+        &mut self,
+        user_item: MonoItem<'tcx>,
+        used_items: &[(Spanned<MonoItem<'tcx>>, UsageKind)],
+    ) {
+        let mut mentioned_items = vec![];
+        let mut uses = vec![];
+        for &(used_item, kind) in used_items {
+            if kind == UsageKind::Mentioned {
+                mentioned_items.push(used_item.node);
+            } else {
+                self.user_map.entry(used_item.node).or_default().push(user_item);
+                uses.push((used_item.node, kind));
+            }
+        }
+
+        assert!(self.used_map.insert(user_item, uses).is_none());
+        if !mentioned_items.is_empty() {
+            self.mentioned_map.insert(user_item, mentioned_items);
+        }
+    }
+
+    pub fn record_collected_by(&mut self, item: MonoItem<'tcx>, user: Spanned<MonoItem<'tcx>>) {
+        if let Some(collected_by) = &mut self.collected_by {
+            assert!(collected_by.insert(item, user).is_none());
+        }
+    }
+
+    /// Returns the mono items through which `item` was collected, starting
+    /// with a root and ending with `item`, each with the span of its use by
+    /// the previous one. Returns `None` if this wasn't recorded.
+    pub fn collection_chain(&self, item: MonoItem<'tcx>) -> Option<Vec<Spanned<MonoItem<'tcx>>>> {
+        let collected_by = self.collected_by.as_ref()?;
+        let mut chain = vec![];
+        let mut current = item;
+        while let Some(&Spanned { node: user, span }) = collected_by.get(&current) {
+            chain.push(respan(span, current));
+            current = user;
+        }
+        chain.push(dummy_spanned(current));
+        chain.reverse();
+        Some(chain)
+    }
+
+    pub fn get_used_items(&self, item: MonoItem<'tcx>) -> &[(MonoItem<'tcx>, UsageKind)] {
+        self.used_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn get_mentioned_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
+        self.mentioned_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
+        self.user_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
+    /// Internally iterate over all inlined items used by `item`.
+    pub fn for_each_inlined_used_item<F>(&self, tcx: TyCtxt<'tcx>, item: MonoItem<'tcx>, mut f: F)
+    where
+        F: FnMut(MonoItem<'tcx>),
+    {
+        let used_items = self.used_map.get(&item).unwrap();
+        for (used_item, _) in used_items.iter() {
+            let is_inlined = used_item.instantiation_mode(tcx) == InstantiationMode::LocalCopy;
+            if is_inlined {
+                f(*used_item);
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CodegenUnit<'tcx> {
     /// A name for this CGU. Incremental compilation requires that
@@ -405,7 +525,8 @@ fn item_sort_key<'tcx>(tcx: TyCtxt<'tcx>, item: MonoItem<'tcx>) -> ItemSortKey<'
                             | InstanceDef::DropGlue(..)
                             | InstanceDef::CloneShim(..)
                             | InstanceDef::ThreadLocalShim(..)
-                            | InstanceDef::FnPtrAddrShim(..) => None,
+                            | InstanceDef::FnPtrAddrShim(..)
+                            | InstanceDef::ConstArgShim { .. } => None,
                         }
                     }
                     MonoItem::Static(def_id) => def_id.as_local().map(Idx::index),
@@ -552,3 +673,36 @@ pub fn build_cgu_name_no_mangle<I, C, S>(
         Symbol::intern(&cgu_name)
     }
 }
+
+/// The calls made by the functions codegened in the current crate, used by
+/// `-Z stack-report` to compute the worst-case stack usage of the entry points
+/// once the stack sizes of the functions are known. Functions are identified
+/// by their symbol names, as in the object files.
+#[derive(Clone, Debug, Default, Encodable, Decodable)]
+pub struct CallGraph {
+    /// The functions the stack usage is computed from: the entry function of
+    /// the program, and the functions exported with `#[no_mangle]` or
+    /// `#[export_name]`, like interrupt handlers.
+    pub entry_points: Vec<String>,
+    pub functions: FxHashMap<String, CallGraphNode>,
+}
+
+#[derive(Clone, Debug, Encodable, Decodable)]
+pub struct CallGraphNode {
+    /// The path of the function, for the report.
+    pub name: String,
+    pub callees: Vec<Callee>,
+}
+
+#[derive(Clone, Debug, Encodable, Decodable)]
+pub enum Callee {
+    /// A direct call. The callee is not part of the call graph if it is
+    /// codegened by another crate.
+    Direct(String),
+    /// A call through a vtable, to one of the methods of the current crate
+    /// that can be in that slot of the vtable. The list is not `complete` if
+    /// implementations of the trait from other crates could be called.
+    Virtual { candidates: Vec<String>, complete: bool },
+    /// A call through a function pointer.
+    FnPtr,
+}
@@ -57,7 +57,7 @@
 use rustc_ast::expand::{allocator::AllocatorKind, StrippedCfgItem};
 use rustc_attr as attr;
 use rustc_data_structures::fingerprint::Fingerprint;
-use rustc_data_structures::fx::{FxHashMap, FxIndexMap, FxIndexSet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap, FxIndexSet};
 use rustc_data_structures::steal::Steal;
 use rustc_data_structures::svh::Svh;
 use rustc_data_structures::sync::Lrc;
@@ -1880,11 +1880,27 @@
         separate_provide_extern
     }
 
+    /// Collects the mono items of the local crate, and the items used by each of them.
+    query collect_mono_items(_: ()) -> &'tcx (FxHashSet<mir::mono::MonoItem<'tcx>>, mir::mono::UsageMap<'tcx>) {
+        arena_cache
+        eval_always
+        no_hash
+        desc { "collecting the mono items of the crate" }
+    }
+
     query collect_and_partition_mono_items(_: ()) -> (&'tcx DefIdSet, &'tcx [CodegenUnit<'tcx>]) {
         eval_always
         desc { "collect_and_partition_mono_items" }
     }
 
+    /// The calls made by the functions codegened in the current crate, for
+    /// `-Z stack-report`.
+    query stack_call_graph(_: ()) -> &'tcx mir::mono::CallGraph {
+        arena_cache
+        no_hash
+        desc { "computing the call graph of the codegened functions" }
+    }
+
     query is_codegened_item(def_id: DefId) -> bool {
         desc { |tcx| "determining whether `{}` needs codegen", tcx.def_path_str(def_id) }
     }
@@ -111,6 +111,14 @@ pub enum InstanceDef<'tcx> {
     ///
     /// The `DefId` is for `FnPtr::addr`, the `Ty` is the type `T`.
     FnPtrAddrShim(DefId, Ty<'tcx>),
+
+    /// A clone of the `fn` item `def_id` in which some arguments are replaced by constants.
+    ///
+    /// `fixed_args` has a bit set for each argument that is fixed, and `values` contains the
+    /// constants of these arguments in order. The clone keeps the signature of the item and ignores
+    /// the values passed for the fixed arguments, so it is called through a function pointer of the
+    /// item's type. These are created by the `SpecializeConstArgs` MIR pass.
+    ConstArgShim { def_id: DefId, fixed_args: u32, values: &'tcx ty::List<ty::Const<'tcx>> },
 }
 
 impl<'tcx> Instance<'tcx> {
@@ -168,7 +176,8 @@ pub fn def_id(self) -> DefId {
             | InstanceDef::ClosureOnceShim { call_once: def_id, track_caller: _ }
             | InstanceDef::DropGlue(def_id, _)
             | InstanceDef::CloneShim(def_id, _)
-            | InstanceDef::FnPtrAddrShim(def_id, _) => def_id,
+            | InstanceDef::FnPtrAddrShim(def_id, _)
+            | InstanceDef::ConstArgShim { def_id, .. } => def_id,
         }
     }
 
@@ -187,7 +196,8 @@ pub fn def_id_if_not_guaranteed_local_codegen(self) -> Option<DefId> {
             | InstanceDef::ClosureOnceShim { .. }
             | InstanceDef::DropGlue(..)
             | InstanceDef::CloneShim(..)
-            | InstanceDef::FnPtrAddrShim(..) => None,
+            | InstanceDef::FnPtrAddrShim(..)
+            | InstanceDef::ConstArgShim { .. } => None,
         }
     }
 
@@ -278,6 +288,7 @@ pub fn has_polymorphic_mir_body(&self) -> bool {
             | InstanceDef::ThreadLocalShim(..)
             | InstanceDef::FnPtrAddrShim(..)
             | InstanceDef::FnPtrShim(..)
+            | InstanceDef::ConstArgShim { .. }
             | InstanceDef::DropGlue(_, Some(_)) => false,
             InstanceDef::ClosureOnceShim { .. }
             | InstanceDef::DropGlue(..)
@@ -317,6 +328,9 @@ fn fmt_instance(
         InstanceDef::DropGlue(_, Some(ty)) => write!(f, " - shim(Some({ty}))"),
         InstanceDef::CloneShim(_, ty) => write!(f, " - shim({ty})"),
         InstanceDef::FnPtrAddrShim(_, ty) => write!(f, " - shim({ty})"),
+        InstanceDef::ConstArgShim { fixed_args, values, .. } => {
+            write!(f, " - shim(const args {fixed_args:#b}: {values:?})")
+        }
     }
 }
 
@@ -1448,7 +1448,19 @@ fn pretty_print_const_scalar_ptr(
                     self.tcx().try_get_global_alloc(prov.alloc_id())
                 {
                     self.typed_value(
-                        |this| this.print_value_path(instance.def_id(), instance.args),
+                        |this| {
+                            this.print_value_path(instance.def_id(), instance.args)?;
+                            // The clones of a function specialized for constant arguments have
+                            // its path, so show which clone the pointer is to.
+                            if let ty::InstanceDef::ConstArgShim { fixed_args, values, .. } =
+                                instance.def
+                            {
+                                write!(this, " - shim(const args {fixed_args:#b}: [")?;
+                                this.comma_sep(values.iter())?;
+                                this.write_str("])")?;
+                            }
+                            Ok(())
+                        },
                         |this| this.print_type(ty),
                         " as ",
                     )?;
@@ -114,6 +114,21 @@ pub(crate) fn push_coverage_span_marker(&mut self, block: BasicBlock, source_inf
         self.push(block, stmt);
     }
 
+    /// Adds a statement marking `block` with `id`, so that branch coverage
+    /// instrumentation can find the blocks where the outcomes of a branch begin.
+    pub(crate) fn push_coverage_block_marker(
+        &mut self,
+        block: BasicBlock,
+        source_info: SourceInfo,
+        id: coverage::BlockMarkerId,
+    ) {
+        let kind = StatementKind::Coverage(Box::new(Coverage {
+            kind: coverage::CoverageKind::BlockMarker { id },
+        }));
+        let stmt = Statement { source_info, kind };
+        self.push(block, stmt);
+    }
+
     pub(crate) fn terminate(
         &mut self,
         block: BasicBlock,
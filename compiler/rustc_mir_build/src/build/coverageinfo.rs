@@ -0,0 +1,418 @@
+use std::assert_matches::assert_matches;
+use std::collections::hash_map::Entry;
+use std::mem;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::mir::coverage::{
+    BlockMarkerId, BranchSpan, ConditionId, ConditionInfo, MCDCBranchSpan, MCDCDecisionSpan,
+    MatchArmSpan, MatchSpan,
+};
+use rustc_middle::mir::{self, BasicBlock, UnOp};
+use rustc_middle::thir::{ExprId, ExprKind, LogicalOp, Thir};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::def_id::LocalDefId;
+use rustc_span::Span;
+
+use crate::build::{BlockAnd, Builder};
+use crate::errors::MCDCExceedsConditionNumLimit;
+
+/// The maximum number of conditions in a decision that LLVM supports for MC/DC.
+const MAX_CONDITIONS_NUM_IN_DECISION: usize = 6;
+
+pub(crate) struct BranchInfoBuilder {
+    /// Maps condition expressions to their enclosing `!`, for better instrumentation.
+    nots: FxHashMap<ExprId, NotInfo>,
+
+    num_block_markers: usize,
+    branch_spans: Vec<BranchSpan>,
+    match_spans: Vec<MatchSpan>,
+
+    /// Only present if MC/DC instrumentation is enabled.
+    mcdc_state: Option<MCDCState>,
+    mcdc_decision_spans: Vec<MCDCDecisionSpan>,
+}
+
+#[derive(Clone, Copy)]
+struct NotInfo {
+    /// When visiting the associated expression as a branch condition, treat this
+    /// enclosing `!` as the branch condition instead.
+    enclosing_not: ExprId,
+    /// True if the associated expression is nested within an odd number of `!`
+    /// expressions relative to `enclosing_not` (inclusive of `enclosing_not`).
+    is_flipped: bool,
+}
+
+/// The MC/DC decision whose conditions are being lowered, if any.
+#[derive(Default)]
+struct MCDCState {
+    decision: Option<MCDCDecision>,
+    /// The conditions of the decision that haven't been lowered yet. The next
+    /// condition to be lowered is on top of the stack.
+    condition_stack: Vec<ConditionInfo>,
+}
+
+struct MCDCDecision {
+    span: Span,
+    conditions_num: usize,
+    end_markers: Vec<BlockMarkerId>,
+    /// The conditions lowered so far. They are only recorded as MC/DC branches
+    /// once the whole decision is known to be supported.
+    branch_spans: Vec<MCDCBranchSpan>,
+    /// Whether one of the conditions is a `let` expression, which can't be
+    /// instrumented for MC/DC.
+    has_let: bool,
+}
+
+impl BranchInfoBuilder {
+    /// Creates a new branch info builder, but only if branch coverage instrumentation
+    /// is enabled and `def_id` represents a function that is eligible for coverage.
+    pub(crate) fn new_if_enabled(tcx: TyCtxt<'_>, def_id: LocalDefId) -> Option<Self> {
+        if !tcx.sess.instrument_coverage_branch()
+            || !tcx.def_kind(def_id).is_fn_like()
+            || tcx.codegen_fn_attrs(def_id).flags.contains(CodegenFnAttrFlags::NO_COVERAGE)
+        {
+            return None;
+        }
+
+        Some(Self {
+            nots: FxHashMap::default(),
+            num_block_markers: 0,
+            branch_spans: vec![],
+            match_spans: vec![],
+            mcdc_state: tcx.sess.instrument_coverage_mcdc().then(MCDCState::default),
+            mcdc_decision_spans: vec![],
+        })
+    }
+
+    /// Unary `!` expressions inside an `if` condition are lowered by lowering
+    /// their argument instead, and then reversing the then/else arms of that `if`.
+    ///
+    /// That's great for efficiency, but screws up branch coverage, because the
+    /// instrumentation would report the branch outcomes of the inner expression
+    /// instead of the `!` expression. To avoid that, we record the enclosing `!`
+    /// of each expression nested in it, so that the branch can be reported for
+    /// the `!` expression instead.
+    fn visit_unary_not(&mut self, thir: &Thir<'_>, unary_not: ExprId) {
+        assert_matches!(thir[unary_not].kind, ExprKind::Unary { op: UnOp::Not, .. });
+        self.visit_with_not_info(
+            thir,
+            unary_not,
+            // Set `is_flipped: false` for the `!` itself, so that its enclosed
+            // expression will have `is_flipped: true`.
+            NotInfo { enclosing_not: unary_not, is_flipped: false },
+        );
+    }
+
+    fn visit_with_not_info(&mut self, thir: &Thir<'_>, expr_id: ExprId, not_info: NotInfo) {
+        match self.nots.entry(expr_id) {
+            // This expression has already been marked by an enclosing `!`.
+            Entry::Occupied(_) => return,
+            Entry::Vacant(entry) => entry.insert(not_info),
+        };
+
+        match thir[expr_id].kind {
+            ExprKind::Unary { op: UnOp::Not, arg } => {
+                let not_info = NotInfo { is_flipped: !not_info.is_flipped, ..not_info };
+                self.visit_with_not_info(thir, arg, not_info);
+            }
+            ExprKind::Scope { value, .. } => self.visit_with_not_info(thir, value, not_info),
+            ExprKind::Use { source } => self.visit_with_not_info(thir, source, not_info),
+            // All other expressions (including `&&` and `||`) don't need any
+            // special handling of their contents, so stop visiting.
+            _ => {}
+        }
+    }
+
+    fn next_block_marker_id(&mut self) -> BlockMarkerId {
+        let id = BlockMarkerId::from_usize(self.num_block_markers);
+        self.num_block_markers += 1;
+        id
+    }
+
+    /// Records a branch whose outcomes begin at the given markers, either as a
+    /// condition of the MC/DC decision being lowered, or as a plain branch.
+    fn record_condition(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        span: Span,
+        true_marker: BlockMarkerId,
+        false_marker: BlockMarkerId,
+    ) {
+        let Some(mcdc_state) = self.mcdc_state.as_mut() else {
+            self.branch_spans.push(BranchSpan { span, true_marker, false_marker });
+            return;
+        };
+        let Some(condition_info) = mcdc_state.condition_stack.pop() else {
+            // This condition isn't part of a decision, e.g. it's the only
+            // condition of an `if`.
+            self.branch_spans.push(BranchSpan { span, true_marker, false_marker });
+            return;
+        };
+
+        let decision = mcdc_state.decision.as_mut().expect("conditions belong to a decision");
+        if condition_info.true_next_id == ConditionId::NONE {
+            decision.end_markers.push(true_marker);
+        }
+        if condition_info.false_next_id == ConditionId::NONE {
+            decision.end_markers.push(false_marker);
+        }
+        decision.branch_spans.push(MCDCBranchSpan {
+            span,
+            condition_info,
+            true_marker,
+            false_marker,
+        });
+        self.finish_decision_if_complete(tcx);
+    }
+
+    /// Skips a condition of the MC/DC decision being lowered that can't be
+    /// instrumented, which makes the whole decision fall back to plain branches.
+    fn skip_condition(&mut self, tcx: TyCtxt<'_>) {
+        let Some(mcdc_state) = self.mcdc_state.as_mut() else { return };
+        if mcdc_state.condition_stack.pop().is_none() {
+            return;
+        }
+        mcdc_state.decision.as_mut().expect("conditions belong to a decision").has_let = true;
+        self.finish_decision_if_complete(tcx);
+    }
+
+    fn finish_decision_if_complete(&mut self, tcx: TyCtxt<'_>) {
+        let mcdc_state = self.mcdc_state.as_mut().expect("MC/DC is enabled");
+        if !mcdc_state.condition_stack.is_empty() {
+            return;
+        }
+        let Some(MCDCDecision { span, conditions_num, end_markers, branch_spans, has_let }) =
+            mcdc_state.decision.take()
+        else {
+            return;
+        };
+
+        if !has_let && conditions_num <= MAX_CONDITIONS_NUM_IN_DECISION {
+            let conditions = branch_spans;
+            self.mcdc_decision_spans.push(MCDCDecisionSpan { span, conditions, end_markers });
+            return;
+        }
+
+        if conditions_num > MAX_CONDITIONS_NUM_IN_DECISION {
+            tcx.dcx().emit_warning(MCDCExceedsConditionNumLimit {
+                span,
+                conditions_num,
+                max_conditions_num: MAX_CONDITIONS_NUM_IN_DECISION,
+            });
+        }
+        // Still report the conditions of the decision as plain branches.
+        self.branch_spans.extend(branch_spans.into_iter().map(
+            |MCDCBranchSpan { span, true_marker, false_marker, .. }| BranchSpan {
+                span,
+                true_marker,
+                false_marker,
+            },
+        ));
+    }
+
+    pub(crate) fn into_done(self) -> Option<Box<mir::coverage::BranchInfo>> {
+        let Self {
+            nots: _,
+            num_block_markers,
+            branch_spans,
+            match_spans,
+            mcdc_state,
+            mcdc_decision_spans,
+        } = self;
+
+        if let Some(mcdc_state) = mcdc_state {
+            assert!(mcdc_state.decision.is_none(), "an MC/DC decision was left unfinished");
+        }
+
+        if num_block_markers == 0 {
+            assert!(branch_spans.is_empty() && match_spans.is_empty());
+            return None;
+        }
+
+        Some(Box::new(mir::coverage::BranchInfo {
+            num_block_markers,
+            branch_spans,
+            match_spans,
+            mcdc_decision_spans,
+        }))
+    }
+}
+
+impl MCDCState {
+    /// Assigns condition IDs to the operands of a `&&` or `||` expression that
+    /// is part of a decision, starting a new decision if necessary.
+    ///
+    /// Each condition knows which condition is evaluated next for each of its
+    /// outcomes, or [`ConditionId::NONE`] if the outcome of the decision is
+    /// known at that point. This follows the algorithm used by clang, which
+    /// visits the operators in pre-order.
+    fn record_conditions(&mut self, op: LogicalOp, span: Span) {
+        let decision = self.decision.get_or_insert_with(|| MCDCDecision {
+            span,
+            conditions_num: 0,
+            end_markers: vec![],
+            branch_spans: vec![],
+            has_let: false,
+        });
+
+        let parent_condition = self.condition_stack.pop().unwrap_or_default();
+        let lhs_id = if parent_condition.condition_id == ConditionId::NONE {
+            decision.conditions_num += 1;
+            ConditionId::from_usize(decision.conditions_num)
+        } else {
+            parent_condition.condition_id
+        };
+        decision.conditions_num += 1;
+        let rhs_id = ConditionId::from_usize(decision.conditions_num);
+
+        let rhs = ConditionInfo { condition_id: rhs_id, ..parent_condition };
+        let lhs = match op {
+            LogicalOp::And => ConditionInfo {
+                condition_id: lhs_id,
+                true_next_id: rhs_id,
+                false_next_id: parent_condition.false_next_id,
+            },
+            LogicalOp::Or => ConditionInfo {
+                condition_id: lhs_id,
+                true_next_id: parent_condition.true_next_id,
+                false_next_id: rhs_id,
+            },
+        };
+        // The left-hand side is lowered first, so it goes on top of the stack.
+        self.condition_stack.push(rhs);
+        self.condition_stack.push(lhs);
+    }
+}
+
+impl<'tcx> Builder<'_, 'tcx> {
+    /// If branch coverage is enabled, records the enclosing `!` of the
+    /// expressions nested in `unary_not`. If the `!` applies to a `&&` or `||`
+    /// that is part of an MC/DC decision, also swaps the conditions that follow
+    /// each of its outcomes.
+    pub(crate) fn visit_coverage_unary_not(&mut self, unary_not: ExprId) {
+        let Some(branch_info) = self.coverage_branch_info.as_mut() else { return };
+        branch_info.visit_unary_not(self.thir, unary_not);
+
+        let mut arg = unary_not;
+        loop {
+            match self.thir[arg].kind {
+                ExprKind::Unary { op: UnOp::Not, arg: inner }
+                | ExprKind::Scope { value: inner, .. }
+                | ExprKind::Use { source: inner } => arg = inner,
+                _ => break,
+            }
+        }
+        if let ExprKind::LogicalOp { .. } = self.thir[arg].kind
+            && let Some(mcdc_state) = branch_info.mcdc_state.as_mut()
+            && let Some(condition) = mcdc_state.condition_stack.last_mut()
+        {
+            mem::swap(&mut condition.true_next_id, &mut condition.false_next_id);
+        }
+    }
+
+    /// If MC/DC is enabled, assigns condition IDs to the operands of a `&&` or
+    /// `||` expression used as a condition.
+    pub(crate) fn visit_coverage_branch_operation(&mut self, op: LogicalOp, span: Span) {
+        if let Some(mcdc_state) =
+            self.coverage_branch_info.as_mut().and_then(|info| info.mcdc_state.as_mut())
+        {
+            mcdc_state.record_conditions(op, span);
+        }
+    }
+
+    /// If branch coverage is enabled, inject marker statements into `then_block`
+    /// and `else_block`, and record their IDs in the table of branch spans.
+    pub(crate) fn visit_coverage_branch_condition(
+        &mut self,
+        mut expr_id: ExprId,
+        mut then_block: BasicBlock,
+        mut else_block: BasicBlock,
+    ) {
+        // Bail out if branch coverage is not enabled for this function.
+        let Some(branch_info) = self.coverage_branch_info.as_ref() else { return };
+
+        // If this condition expression is nested within one or more `!` expressions,
+        // replace it with the enclosing `!` collected by `visit_unary_not`.
+        if let Some(&NotInfo { enclosing_not, is_flipped }) = branch_info.nots.get(&expr_id) {
+            expr_id = enclosing_not;
+            if is_flipped {
+                mem::swap(&mut then_block, &mut else_block);
+            }
+        }
+        let span = self.thir[expr_id].span;
+
+        let true_marker = self.inject_coverage_block_marker(then_block, span);
+        let false_marker = self.inject_coverage_block_marker(else_block, span);
+        let branch_info = self.coverage_branch_info.as_mut().expect("upgrading & to &mut");
+        branch_info.record_condition(self.tcx, span, true_marker, false_marker);
+    }
+
+    /// If MC/DC is enabled, notes that the condition being lowered is a `let`
+    /// expression, which prevents instrumenting its decision for MC/DC.
+    pub(crate) fn visit_coverage_let_condition(&mut self) {
+        if let Some(branch_info) = self.coverage_branch_info.as_mut() {
+            branch_info.skip_condition(self.tcx);
+        }
+    }
+
+    /// Lowers the evaluation of a condition with `f`, hiding the MC/DC decision
+    /// being lowered from the decisions nested in the condition, such as the
+    /// one in `if f(a && b) || c`.
+    pub(crate) fn in_coverage_condition_scope<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> BlockAnd<T>,
+    ) -> BlockAnd<T> {
+        let Some(mcdc_state) =
+            self.coverage_branch_info.as_mut().and_then(|info| info.mcdc_state.as_mut())
+        else {
+            return f(self);
+        };
+        let outer_state = mem::take(mcdc_state);
+
+        let result = f(self);
+
+        let mcdc_state = self
+            .coverage_branch_info
+            .as_mut()
+            .and_then(|info| info.mcdc_state.as_mut())
+            .expect("MC/DC is still enabled");
+        assert!(mcdc_state.decision.is_none(), "a nested MC/DC decision was left unfinished");
+        *mcdc_state = outer_state;
+        result
+    }
+
+    /// If branch coverage is enabled, injects a marker statement at the start
+    /// of the body of a match arm, and adds the arm to `arms`.
+    pub(crate) fn visit_coverage_match_arm(
+        &mut self,
+        arms: &mut Vec<MatchArmSpan>,
+        span: Span,
+        arm_block: BasicBlock,
+    ) {
+        if self.coverage_branch_info.is_none() {
+            return;
+        }
+        let marker = self.inject_coverage_block_marker(arm_block, span);
+        arms.push(MatchArmSpan { span, marker });
+    }
+
+    /// If branch coverage is enabled, records the arms of a `match` expression
+    /// collected by `visit_coverage_match_arm`. A `match` with a single arm
+    /// has no branches.
+    pub(crate) fn visit_coverage_match(&mut self, span: Span, arms: Vec<MatchArmSpan>) {
+        if let Some(branch_info) = self.coverage_branch_info.as_mut()
+            && arms.len() > 1
+        {
+            branch_info.match_spans.push(MatchSpan { span, arms });
+        }
+    }
+
+    fn inject_coverage_block_marker(&mut self, block: BasicBlock, span: Span) -> BlockMarkerId {
+        let branch_info = self.coverage_branch_info.as_mut().expect("branch coverage is enabled");
+        let id = branch_info.next_block_marker_id();
+        let source_info = self.source_info(span);
+        self.cfg.push_coverage_block_marker(block, source_info, id);
+        id
+    }
+}
@@ -47,6 +47,7 @@ pub(crate) fn then_else_break(
 
         match expr.kind {
             ExprKind::LogicalOp { op: LogicalOp::And, lhs, rhs } => {
+                this.visit_coverage_branch_operation(LogicalOp::And, expr_span);
                 let lhs_then_block = unpack!(this.then_else_break(
                     block,
                     lhs,
@@ -66,6 +67,7 @@ pub(crate) fn then_else_break(
                 rhs_then_block.unit()
             }
             ExprKind::LogicalOp { op: LogicalOp::Or, lhs, rhs } => {
+                this.visit_coverage_branch_operation(LogicalOp::Or, expr_span);
                 let local_scope = this.local_scope();
                 let (lhs_success_block, failure_block) =
                     this.in_if_then_scope(local_scope, expr_span, |this| {
@@ -84,10 +86,21 @@ pub(crate) fn then_else_break(
                     break_scope,
                     variable_source_info,
                 ));
-                this.cfg.goto(lhs_success_block, variable_source_info, rhs_success_block);
-                rhs_success_block.unit()
+                // Make the LHS and RHS success arms converge to a common block.
+                // (We can't just make LHS goto RHS, because `rhs_success_block`
+                // might contain statements that we don't want on the LHS path,
+                // such as the true marker of the RHS branch condition.)
+                let success_block = this.cfg.start_new_block();
+                this.cfg.goto(lhs_success_block, variable_source_info, success_block);
+                this.cfg.goto(rhs_success_block, variable_source_info, success_block);
+                success_block.unit()
             }
             ExprKind::Unary { op: UnOp::Not, arg } => {
+                // Improve branch coverage instrumentation by noting conditions
+                // nested within one or more `!` expressions.
+                // (Skipped if branch coverage is not enabled.)
+                this.visit_coverage_unary_not(expr_id);
+
                 let local_scope = this.local_scope();
                 let (success_block, failure_block) =
                     this.in_if_then_scope(local_scope, expr_span, |this| {
@@ -126,20 +139,28 @@ pub(crate) fn then_else_break(
                 break_scope,
                 variable_source_info,
             ),
-            ExprKind::Let { expr, ref pat } => this.lower_let_expr(
-                block,
-                expr,
-                pat,
-                break_scope,
-                Some(variable_source_info.scope),
-                variable_source_info.span,
-                true,
-            ),
+            ExprKind::Let { expr, ref pat } => {
+                this.visit_coverage_let_condition();
+                this.in_coverage_condition_scope(|this| {
+                    this.lower_let_expr(
+                        block,
+                        expr,
+                        pat,
+                        break_scope,
+                        Some(variable_source_info.scope),
+                        variable_source_info.span,
+                        true,
+                    )
+                })
+            }
             _ => {
                 let temp_scope = temp_scope_override.unwrap_or_else(|| this.local_scope());
                 let mutability = Mutability::Mut;
-                let place =
-                    unpack!(block = this.as_temp(block, Some(temp_scope), expr_id, mutability));
+                let place = unpack!(
+                    block = this.in_coverage_condition_scope(|this| {
+                        this.as_temp(block, Some(temp_scope), expr_id, mutability)
+                    })
+                );
                 let operand = Operand::Move(Place::from(place));
 
                 let then_block = this.cfg.start_new_block();
@@ -148,6 +169,9 @@ pub(crate) fn then_else_break(
 
                 let source_info = this.source_info(expr_span);
                 this.cfg.terminate(block, source_info, term);
+                // If branch coverage is enabled, inject markers for the outcomes
+                // of the condition.
+                this.visit_coverage_branch_condition(expr_id, then_block, else_block);
                 this.break_for_else(else_block, break_scope, source_info);
 
                 then_block.unit()
@@ -387,6 +411,7 @@ fn lower_match_arms(
         outer_source_info: SourceInfo,
         fake_borrow_temps: Vec<(Place<'tcx>, Local)>,
     ) -> BlockAnd<()> {
+        let mut coverage_arms = vec![];
         let arm_end_blocks: Vec<_> = arm_candidates
             .into_iter()
             .map(|(arm, candidate)| {
@@ -436,10 +461,12 @@ fn lower_match_arms(
                         this.source_scope = source_scope;
                     }
 
+                    this.visit_coverage_match_arm(&mut coverage_arms, arm.pattern.span, arm_block);
                     this.expr_into_dest(destination, arm_block, arm.body)
                 })
             })
             .collect();
+        self.visit_coverage_match(outer_source_info.span, coverage_arms);
 
         // all the arm blocks will rejoin here
         let end_block = self.cfg.start_new_block();
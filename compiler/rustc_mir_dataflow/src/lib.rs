@@ -23,8 +23,9 @@
 pub use self::framework::{
     fmt, lattice, visit_results, Analysis, AnalysisDomain, Direction, GenKill, GenKillAnalysis,
     JoinSemiLattice, MaybeReachable, Results, ResultsCursor, ResultsVisitable, ResultsVisitor,
+    SwitchIntEdgeEffects, SwitchIntTarget,
 };
-use self::framework::{Backward, SwitchIntEdgeEffects};
+use self::framework::Backward;
 use self::move_paths::MoveData;
 
 pub mod debuginfo;
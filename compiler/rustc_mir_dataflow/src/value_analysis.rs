@@ -244,6 +244,7 @@ fn super_constant(
     fn handle_terminator<'mir>(
         &self,
         terminator: &'mir Terminator<'tcx>,
+        _location: Location,
         state: &mut State<Self::Value>,
     ) -> TerminatorEdges<'mir, 'tcx> {
         self.super_terminator(terminator, state)
@@ -319,6 +320,26 @@ fn super_switch_int<'mir>(
         TerminatorEdges::SwitchInt { discr, targets }
     }
 
+    /// Applies the effects specific to each outgoing edge of the `SwitchInt` terminating `block`,
+    /// e.g. to refine the values of places using the value of `discr` on this edge.
+    fn handle_switch_int_edge_effects(
+        &self,
+        block: BasicBlock,
+        discr: &Operand<'tcx>,
+        apply_edge_effects: &mut impl SwitchIntEdgeEffects<State<Self::Value>>,
+    ) {
+        self.super_switch_int_edge_effects(block, discr, apply_edge_effects)
+    }
+
+    fn super_switch_int_edge_effects(
+        &self,
+        _block: BasicBlock,
+        _discr: &Operand<'tcx>,
+        _apply_edge_effects: &mut impl SwitchIntEdgeEffects<State<Self::Value>>,
+    ) {
+        // All the edges get the state at the end of the block.
+    }
+
     fn wrap(self) -> ValueAnalysisWrapper<Self>
     where
         Self: Sized,
@@ -368,10 +389,10 @@ fn apply_terminator_effect<'mir>(
         &mut self,
         state: &mut Self::Domain,
         terminator: &'mir Terminator<'tcx>,
-        _location: Location,
+        location: Location,
     ) -> TerminatorEdges<'mir, 'tcx> {
         if state.is_reachable() {
-            self.0.handle_terminator(terminator, state)
+            self.0.handle_terminator(terminator, location, state)
         } else {
             TerminatorEdges::None
         }
@@ -390,10 +411,11 @@ fn apply_call_return_effect(
 
     fn apply_switch_int_edge_effects(
         &mut self,
-        _block: BasicBlock,
-        _discr: &Operand<'tcx>,
-        _apply_edge_effects: &mut impl SwitchIntEdgeEffects<Self::Domain>,
+        block: BasicBlock,
+        discr: &Operand<'tcx>,
+        apply_edge_effects: &mut impl SwitchIntEdgeEffects<Self::Domain>,
     ) {
+        self.0.handle_switch_int_edge_effects(block, discr, apply_edge_effects)
     }
 }
 
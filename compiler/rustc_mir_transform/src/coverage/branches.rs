@@ -0,0 +1,173 @@
+use rustc_index::IndexVec;
+use rustc_middle::mir::coverage::{
+    BlockMarkerId, BranchInfo, BranchSpan, ConditionInfo, CoverageKind, MCDCBranchSpan,
+    MCDCDecisionSpan, MatchArmSpan, MatchSpan,
+};
+use rustc_middle::mir::{self, BasicBlock, StatementKind};
+use rustc_span::Span;
+
+use super::graph::{BasicCoverageBlock, CoverageGraph};
+use super::ExtractedHirInfo;
+
+/// A branch recorded during MIR building, mapped to the BCBs its outcomes begin at.
+#[derive(Debug)]
+pub(super) struct BcbBranchPair {
+    pub(super) span: Span,
+    pub(super) true_bcb: BasicCoverageBlock,
+    pub(super) false_bcb: BasicCoverageBlock,
+}
+
+/// An arm of a `match`, mapped to the BCB its code begins at.
+#[derive(Debug)]
+pub(super) struct BcbMatchArm {
+    pub(super) span: Span,
+    pub(super) bcb: BasicCoverageBlock,
+}
+
+/// A condition of an MC/DC decision. The blocks its outcomes begin at are kept
+/// too, because the condition bitmap is updated in them.
+#[derive(Debug)]
+pub(super) struct BcbMCDCBranch {
+    pub(super) span: Span,
+    pub(super) condition_info: ConditionInfo,
+    pub(super) true_bcb: BasicCoverageBlock,
+    pub(super) false_bcb: BasicCoverageBlock,
+    pub(super) true_bb: BasicBlock,
+    pub(super) false_bb: BasicBlock,
+}
+
+/// A decision made of several conditions, for MC/DC.
+#[derive(Debug)]
+pub(super) struct BcbMCDCDecision {
+    pub(super) span: Span,
+    pub(super) conditions: Vec<BcbMCDCBranch>,
+    /// The blocks where the outcome of the decision is known, in which the test
+    /// vector bitmap is updated.
+    pub(super) end_bbs: Vec<BasicBlock>,
+}
+
+/// The branches of a function that branch coverage and MC/DC report, extracted
+/// from the [`BranchInfo`] recorded during MIR building.
+#[derive(Debug, Default)]
+pub(super) struct ExtractedBranches {
+    pub(super) branch_pairs: Vec<BcbBranchPair>,
+    pub(super) matches: Vec<Vec<BcbMatchArm>>,
+    pub(super) mcdc_decisions: Vec<BcbMCDCDecision>,
+}
+
+impl ExtractedBranches {
+    pub(super) fn extract(
+        mir_body: &mir::Body<'_>,
+        hir_info: &ExtractedHirInfo,
+        basic_coverage_blocks: &CoverageGraph,
+    ) -> Self {
+        let Some(branch_info) = mir_body.coverage_branch_info.as_deref() else {
+            return Self::default();
+        };
+        let BranchInfo { num_block_markers, branch_spans, match_spans, mcdc_decision_spans } =
+            branch_info;
+
+        // Find the blocks that block markers were injected into. Markers can
+        // disappear if their block was removed as unreachable.
+        let mut block_markers =
+            IndexVec::<BlockMarkerId, Option<BasicBlock>>::from_elem_n(None, *num_block_markers);
+        for (bb, data) in mir_body.basic_blocks.iter_enumerated() {
+            for statement in &data.statements {
+                if let StatementKind::Coverage(box mir::Coverage {
+                    kind: CoverageKind::BlockMarker { id },
+                }) = statement.kind
+                {
+                    block_markers[id] = Some(bb);
+                }
+            }
+        }
+
+        let body_span = hir_info.body_span;
+        // Branches are reported on the code the user wrote, so spans that come
+        // from an expansion are replaced by the call site inside the body.
+        let unexpand = |span: Span| span.find_ancestor_inside(body_span);
+        let marker_bb = |marker: BlockMarkerId| block_markers[marker];
+        let marker_bcb =
+            |marker: BlockMarkerId| basic_coverage_blocks.bcb_from_bb(block_markers[marker]?);
+
+        let branch_pair = |span: Span, true_marker, false_marker| {
+            Some(BcbBranchPair {
+                span: unexpand(span)?,
+                true_bcb: marker_bcb(true_marker)?,
+                false_bcb: marker_bcb(false_marker)?,
+            })
+        };
+
+        let mut branch_pairs = branch_spans
+            .iter()
+            .filter_map(|&BranchSpan { span, true_marker, false_marker }| {
+                branch_pair(span, true_marker, false_marker)
+            })
+            .collect::<Vec<_>>();
+
+        // A `match` is only reported if all of its arms can be, because the
+        // counts of the later arms make up the false outcome of each arm.
+        let matches = match_spans
+            .iter()
+            .filter_map(|MatchSpan { arms, .. }| {
+                arms.iter()
+                    .map(|&MatchArmSpan { span, marker }| {
+                        Some(BcbMatchArm { span: unexpand(span)?, bcb: marker_bcb(marker)? })
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect();
+
+        let mut mcdc_decisions = vec![];
+        for MCDCDecisionSpan { span, conditions, end_markers } in mcdc_decision_spans {
+            let mcdc_branch =
+                |&MCDCBranchSpan { span, condition_info, true_marker, false_marker }| {
+                    Some(BcbMCDCBranch {
+                        span: unexpand(span)?,
+                        condition_info,
+                        true_bcb: marker_bcb(true_marker)?,
+                        false_bcb: marker_bcb(false_marker)?,
+                        true_bb: marker_bb(true_marker)?,
+                        false_bb: marker_bb(false_marker)?,
+                    })
+                };
+            let decision: Option<_> = try {
+                BcbMCDCDecision {
+                    span: unexpand(*span)?,
+                    conditions: conditions.iter().map(mcdc_branch).collect::<Option<_>>()?,
+                    end_bbs: end_markers
+                        .iter()
+                        .map(|&marker| marker_bb(marker))
+                        .collect::<Option<_>>()?,
+                }
+            };
+            match decision {
+                Some(decision) => mcdc_decisions.push(decision),
+                // The test vectors of a decision can't be recorded if some of
+                // its conditions are missing, so they are reported as plain
+                // branches instead.
+                None => branch_pairs.extend(conditions.iter().filter_map(
+                    |&MCDCBranchSpan { span, true_marker, false_marker, .. }| {
+                        branch_pair(span, true_marker, false_marker)
+                    },
+                )),
+            }
+        }
+
+        Self { branch_pairs, matches, mcdc_decisions }
+    }
+
+    /// The BCBs whose counters are needed to report the branches.
+    pub(super) fn bcbs_with_branch_counters(
+        &self,
+    ) -> impl Iterator<Item = BasicCoverageBlock> + '_ {
+        let pairs = self.branch_pairs.iter().flat_map(|pair| [pair.true_bcb, pair.false_bcb]);
+        let arms = self.matches.iter().flatten().map(|arm| arm.bcb);
+        let conditions = self
+            .mcdc_decisions
+            .iter()
+            .flat_map(|decision| &decision.conditions)
+            .flat_map(|condition| [condition.true_bcb, condition.false_bcb]);
+        pairs.chain(arms).chain(conditions)
+    }
+}
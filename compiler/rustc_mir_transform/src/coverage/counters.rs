@@ -100,6 +100,12 @@ fn make_sum_expression(&mut self, lhs: Option<BcbCounter>, rhs: BcbCounter) -> B
         self.make_expression(lhs, Op::Add, rhs)
     }
 
+    /// Makes an expression that adds up two counters, e.g. to count the
+    /// executions of several arms of a `match`.
+    pub(super) fn make_sum(&mut self, lhs: BcbCounter, rhs: BcbCounter) -> BcbCounter {
+        self.make_expression(lhs, Op::Add, rhs)
+    }
+
     /// Counter IDs start from one and go up.
     fn next_counter(&mut self) -> CounterId {
         let next = self.next_counter_id;
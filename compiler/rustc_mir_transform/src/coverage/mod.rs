@@ -1,5 +1,6 @@
 pub mod query;
 
+mod branches;
 mod counters;
 mod graph;
 mod spans;
@@ -7,12 +8,15 @@
 #[cfg(test)]
 mod tests;
 
+use self::branches::ExtractedBranches;
 use self::counters::{BcbCounter, CoverageCounters};
-use self::graph::CoverageGraph;
+use self::graph::{BasicCoverageBlock, CoverageGraph};
 use self::spans::CoverageSpans;
 
 use crate::MirPass;
 
+use rustc_data_structures::graph::WithNumNodes;
+use rustc_index::bit_set::BitSet;
 use rustc_middle::hir;
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
 use rustc_middle::mir::coverage::*;
@@ -97,20 +101,33 @@ fn inject_counters(&'a mut self) {
             return;
         };
 
+        // Find the branches recorded during MIR building, if branch coverage is enabled.
+        let branches =
+            ExtractedBranches::extract(self.mir_body, &self.hir_info, &self.basic_coverage_blocks);
+
         ////////////////////////////////////////////////////
         // Create an optimized mix of `Counter`s and `Expression`s for the `CoverageGraph`. Ensure
-        // every coverage span has a `Counter` or `Expression` assigned to its `BasicCoverageBlock`
-        // and all `Expression` dependencies (operands) are also generated, for any other
-        // `BasicCoverageBlock`s not already associated with a coverage span.
-        let bcb_has_coverage_spans = |bcb| coverage_spans.bcb_has_coverage_spans(bcb);
-        self.coverage_counters
-            .make_bcb_counters(&self.basic_coverage_blocks, bcb_has_coverage_spans);
+        // every coverage span and branch outcome has a `Counter` or `Expression` assigned to its
+        // `BasicCoverageBlock` and all `Expression` dependencies (operands) are also generated,
+        // for any other `BasicCoverageBlock`s not already associated with a coverage span.
+        let mut bcbs_with_branch_counters =
+            BitSet::new_empty(self.basic_coverage_blocks.num_nodes());
+        for bcb in branches.bcbs_with_branch_counters() {
+            bcbs_with_branch_counters.insert(bcb);
+        }
+        let bcb_needs_counter = |bcb| {
+            coverage_spans.bcb_has_coverage_spans(bcb) || bcbs_with_branch_counters.contains(bcb)
+        };
+        self.coverage_counters.make_bcb_counters(&self.basic_coverage_blocks, bcb_needs_counter);
 
-        let mappings = self.create_mappings_and_inject_coverage_statements(&coverage_spans);
+        let mut mappings = self.create_mappings_and_inject_coverage_statements(&coverage_spans);
+        let mcdc_bitmap_bytes =
+            self.create_branch_mappings_and_inject_mcdc_statements(&branches, &mut mappings);
 
         self.mir_body.function_coverage_info = Some(Box::new(FunctionCoverageInfo {
             function_source_hash: self.hir_info.function_source_hash,
             num_counters: self.coverage_counters.num_counters(),
+            mcdc_bitmap_bytes,
             expressions: self.coverage_counters.take_expressions(),
             mappings,
         }));
@@ -125,11 +142,7 @@ fn create_mappings_and_inject_coverage_statements(
     ) -> Vec<Mapping> {
         let source_map = self.tcx.sess.source_map();
         let body_span = self.hir_info.body_span;
-
-        let source_file = source_map.lookup_source_file(body_span.lo());
-        use rustc_session::RemapFileNameExt;
-        let file_name =
-            Symbol::intern(&source_file.name.for_codegen(self.tcx.sess).to_string_lossy());
+        let file_name = self.file_name();
 
         let mut mappings = Vec::new();
 
@@ -144,7 +157,7 @@ fn create_mappings_and_inject_coverage_statements(
                 let term = counter_kind.as_term();
                 mappings.extend(spans.iter().map(|&span| {
                     let code_region = make_code_region(source_map, file_name, span, body_span);
-                    Mapping { code_region, term }
+                    Mapping { kind: MappingKind::Code(term), code_region }
                 }));
             }
 
@@ -196,6 +209,113 @@ fn create_mappings_and_inject_coverage_statements(
         mappings
     }
 
+    /// Creates the mappings of the branches recorded during MIR building, and
+    /// injects the statements that update the MC/DC bitmaps. Returns the number
+    /// of bytes of the test vector bitmaps of the function.
+    fn create_branch_mappings_and_inject_mcdc_statements(
+        &mut self,
+        branches: &ExtractedBranches,
+        mappings: &mut Vec<Mapping>,
+    ) -> u32 {
+        let source_map = self.tcx.sess.source_map();
+        let body_span = self.hir_info.body_span;
+        let file_name = self.file_name();
+        let code_region = |span| make_code_region(source_map, file_name, span, body_span);
+        let coverage_counters = &mut self.coverage_counters;
+        let term = |counters: &CoverageCounters, bcb: BasicCoverageBlock| {
+            counters.bcb_counter(bcb).expect("branch BCBs have counters").as_term()
+        };
+
+        for pair in &branches.branch_pairs {
+            let true_term = term(coverage_counters, pair.true_bcb);
+            let false_term = term(coverage_counters, pair.false_bcb);
+            mappings.push(Mapping {
+                kind: MappingKind::Branch { true_term, false_term },
+                code_region: code_region(pair.span),
+            });
+        }
+
+        // Each arm of a `match` is reported as a branch whose true outcome is
+        // the arm being taken, and whose false outcome is one of the later arms
+        // being taken. The last arm is always taken if it is reached, so it
+        // isn't reported.
+        for arms in &branches.matches {
+            let mut later_arms = None;
+            for (arm, next_arm) in arms.iter().zip(arms.iter().skip(1)).rev() {
+                let next_counter =
+                    coverage_counters.bcb_counter(next_arm.bcb).expect("arm BCBs have counters");
+                let false_counter = match later_arms {
+                    Some(later_arms) => coverage_counters.make_sum(next_counter, later_arms),
+                    None => next_counter,
+                };
+                later_arms = Some(false_counter);
+                mappings.push(Mapping {
+                    kind: MappingKind::Branch {
+                        true_term: term(coverage_counters, arm.bcb),
+                        false_term: false_counter.as_term(),
+                    },
+                    code_region: code_region(arm.span),
+                });
+            }
+        }
+
+        let mut mcdc_bitmap_bytes = 0;
+        for decision in &branches.mcdc_decisions {
+            let conditions_num = decision.conditions.len();
+            // Each possible test vector of the decision has a bit in the bitmap.
+            let bitmap_idx = mcdc_bitmap_bytes;
+            mcdc_bitmap_bytes += (1_u32 << conditions_num).div_ceil(8);
+            mappings.push(Mapping {
+                kind: MappingKind::MCDCDecision(DecisionInfo {
+                    bitmap_idx,
+                    conditions_num: conditions_num as u16,
+                }),
+                code_region: code_region(decision.span),
+            });
+            for &end_bb in &decision.end_bbs {
+                inject_statement(
+                    self.mir_body,
+                    CoverageKind::TestVectorBitmapUpdate { bitmap_idx },
+                    end_bb,
+                );
+            }
+
+            for condition in &decision.conditions {
+                let id = condition.condition_info.condition_id;
+                mappings.push(Mapping {
+                    kind: MappingKind::MCDCBranch {
+                        true_term: term(coverage_counters, condition.true_bcb),
+                        false_term: term(coverage_counters, condition.false_bcb),
+                        mcdc_params: condition.condition_info,
+                    },
+                    code_region: code_region(condition.span),
+                });
+                // Injected after the test vector bitmap updates, so that the
+                // condition bitmap is updated first in end blocks.
+                inject_statement(
+                    self.mir_body,
+                    CoverageKind::CondBitmapUpdate { id, value: true },
+                    condition.true_bb,
+                );
+                inject_statement(
+                    self.mir_body,
+                    CoverageKind::CondBitmapUpdate { id, value: false },
+                    condition.false_bb,
+                );
+            }
+        }
+
+        mcdc_bitmap_bytes
+    }
+
+    /// The name of the source file of the function, as it appears in the coverage map.
+    fn file_name(&self) -> Symbol {
+        let source_map = self.tcx.sess.source_map();
+        let source_file = source_map.lookup_source_file(self.hir_info.body_span.lo());
+        use rustc_session::RemapFileNameExt;
+        Symbol::intern(&source_file.name.for_codegen(self.tcx.sess).to_string_lossy())
+    }
+
     fn make_mir_coverage_kind(&self, counter_kind: &BcbCounter) -> CoverageKind {
         match *counter_kind {
             BcbCounter::Counter { id } => CoverageKind::CounterIncrement { id },
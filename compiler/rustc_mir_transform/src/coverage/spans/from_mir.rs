@@ -111,7 +111,12 @@ fn filtered_statement_span(statement: &Statement<'_>) -> Option<Span> {
         // Ignore `ConstEvalCounter`s
         | StatementKind::ConstEvalCounter
         // Ignore `Nop`s
-        | StatementKind::Nop => None,
+        | StatementKind::Nop
+        // Block markers are only used to find the blocks of branches, and
+        // their spans are the spans of whole conditions.
+        | StatementKind::Coverage(box mir::Coverage {
+            kind: CoverageKind::BlockMarker { .. }
+        }) => None,
 
         // FIXME(#78546): MIR InstrumentCoverage - Can the source_info.span for `FakeRead`
         // statements be more consistent?
@@ -149,7 +154,10 @@ fn filtered_statement_span(statement: &Statement<'_>) -> Option<Span> {
 
         StatementKind::Coverage(box mir::Coverage {
             // These coverage statements should not exist prior to coverage instrumentation.
-            kind: CoverageKind::CounterIncrement { .. } | CoverageKind::ExpressionUsed { .. }
+            kind: CoverageKind::CounterIncrement { .. }
+                | CoverageKind::ExpressionUsed { .. }
+                | CoverageKind::CondBitmapUpdate { .. }
+                | CoverageKind::TestVectorBitmapUpdate { .. }
         }) => bug!("Unexpected coverage statement found during coverage instrumentation: {statement:?}"),
     }
 }
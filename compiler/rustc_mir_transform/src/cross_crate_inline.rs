@@ -1,5 +1,6 @@
 use crate::inline;
 use crate::pass_manager as pm;
+use crate::specialize_const_args;
 use rustc_attr::InlineAttr;
 use rustc_hir::def::DefKind;
 use rustc_hir::def_id::LocalDefId;
@@ -70,10 +71,13 @@ fn cross_crate_inlinable(tcx: TyCtxt<'_>, def_id: LocalDefId) -> bool {
     let mut checker =
         CostChecker { tcx, callee_body: mir, calls: 0, statements: 0, landing_pads: 0, resumes: 0 };
     checker.visit_body(mir);
-    checker.calls == 0
+    let inlinable = checker.calls == 0
         && checker.resumes == 0
         && checker.landing_pads == 0
-        && checker.statements <= threshold
+        && checker.statements <= threshold;
+    // Calls from other crates can only be specialized for constant arguments if the MIR of the
+    // callee is available to them.
+    inlinable || specialize_const_args::may_be_specialized(tcx, def_id, mir)
 }
 
 struct CostChecker<'b, 'tcx> {
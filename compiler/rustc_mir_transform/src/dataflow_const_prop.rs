@@ -1,23 +1,29 @@
-//! A constant propagation optimization pass based on dataflow analysis.
+//! Optimization passes based on dataflow analyses of the values of places.
 //!
-//! Currently, this pass only propagates scalar values.
+//! `DataflowConstProp` propagates constants. Currently, it only propagates scalar values.
+//! `RemoveRedundantChecks` tracks the intervals of integers with the same framework, to remove
+//! the assertions that cannot fail.
 
 use rustc_const_eval::interpret::{ImmTy, Immediate, InterpCx, OpTy, PlaceTy, Projectable};
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def::DefKind;
+use rustc_index::IndexVec;
 use rustc_middle::mir::interpret::{AllocId, ConstAllocation, InterpResult, Scalar};
 use rustc_middle::mir::visit::{MutVisitor, PlaceContext, Visitor};
 use rustc_middle::mir::*;
 use rustc_middle::query::TyCtxtAt;
-use rustc_middle::ty::layout::{LayoutOf, TyAndLayout};
+use rustc_middle::ty::adjustment::PointerCoercion;
+use rustc_middle::ty::layout::{IntegerExt, LayoutOf, TyAndLayout};
 use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_mir_dataflow::lattice::{FlatSet, HasBottom, HasTop, JoinSemiLattice};
 use rustc_mir_dataflow::value_analysis::{
     Map, PlaceIndex, State, TrackElem, ValueAnalysis, ValueAnalysisWrapper, ValueOrPlace,
 };
-use rustc_mir_dataflow::{lattice::FlatSet, Analysis, Results, ResultsVisitor};
+use rustc_mir_dataflow::{Analysis, Results, ResultsVisitor, SwitchIntEdgeEffects};
 use rustc_span::def_id::DefId;
 use rustc_span::DUMMY_SP;
-use rustc_target::abi::{Abi, FieldIdx, Size, VariantIdx, FIRST_VARIANT};
+use rustc_target::abi::{Abi, FieldIdx, Integer, Size, VariantIdx, FIRST_VARIANT};
+use smallvec::SmallVec;
 
 use crate::const_prop::throw_machine_stop_str;
 
@@ -67,6 +73,63 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
     }
 }
 
+/// Removes the bounds and overflow checks that cannot fail, using a range analysis.
+///
+/// The analysis tracks an interval of possible values for each integer place, like
+/// `DataflowConstProp` tracks a constant value. The intervals are refined when branching on a
+/// comparison or on the value of an integer, and after an assertion, in which case we know that
+/// the asserted condition holds. For instance, in
+///
+/// ```ignore (syntax-highlighting-only)
+/// if i < 8 {
+///     array[i] // with `array: [u8; 16]`
+/// }
+/// ```
+///
+/// the analysis deduces that `i` is in `0..=7` in the `if` block, so the bounds check of
+/// `array[i]` always succeeds. Likewise, after the bounds check of `slice[3]`, we know that the
+/// length of `slice` is at least 4, so the bounds check of a following `slice[2]` can be removed.
+///
+/// The comparisons are only used to refine places that are not modified between the comparison
+/// and the branch. In MIR, these are in the same block, so we only look for comparisons in the
+/// block of the branch.
+///
+/// Intervals form a lattice of infinite height, so joining two intervals widens the bounds that
+/// grow to the next power of two. This ensures that the analysis terminates.
+pub struct RemoveRedundantChecks;
+
+impl<'tcx> MirPass<'tcx> for RemoveRedundantChecks {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        // Enabled with `-O`, as removing the checks in MIR leaves less code for LLVM to optimize.
+        // Below `mir-opt-level=4`, the block and place limits of `DataflowConstProp` bound its cost.
+        sess.mir_opt_level() >= 2
+    }
+
+    #[instrument(skip_all level = "debug")]
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        debug!(def_id = ?body.source.def_id());
+        if tcx.sess.mir_opt_level() < 4 && body.basic_blocks.len() > BLOCK_LIMIT {
+            debug!("aborted range analysis due too many basic blocks");
+            return;
+        }
+
+        let place_limit = if tcx.sess.mir_opt_level() < 4 { Some(PLACE_LIMIT) } else { None };
+        let map = Map::new(tcx, body, place_limit);
+
+        let analysis = RangeAnalysis::new(tcx, body, map);
+        let mut results = debug_span!("analyze")
+            .in_scope(|| analysis.wrap().into_engine(tcx, body).iterate_to_fixpoint());
+
+        let mut collector = RedundantCheckCollector { removable: Vec::new() };
+        debug_span!("collect").in_scope(|| results.visit_reachable_with(body, &mut collector));
+
+        for (block, target) in collector.removable {
+            debug!(?block, "removing redundant assertion");
+            body.basic_blocks_mut()[block].terminator_mut().kind = TerminatorKind::Goto { target };
+        }
+    }
+}
+
 struct ConstAnalysis<'a, 'tcx> {
     map: Map,
     tcx: TyCtxt<'tcx>,
@@ -522,6 +585,692 @@ fn wrap_immediate(&self, imm: Immediate) -> FlatSet<Scalar> {
     }
 }
 
+/// A set of integers `lo..=hi`.
+///
+/// The bounds are mathematical integers: signed values are sign-extended. 128-bit integers are not
+/// tracked, so all the values fit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Interval {
+    /// No value, i.e. an uninitialized place or a place that cannot be reached.
+    Bottom,
+    Range(i128, i128),
+}
+
+impl HasBottom for Interval {
+    const BOTTOM: Self = Interval::Bottom;
+}
+
+impl HasTop for Interval {
+    const TOP: Self = Interval::Range(i128::MIN, i128::MAX);
+}
+
+impl JoinSemiLattice for Interval {
+    fn join(&mut self, other: &Self) -> bool {
+        let result = match (*self, *other) {
+            (_, Interval::Bottom) => return false,
+            (Interval::Bottom, other) => other,
+            (Interval::Range(lo, hi), Interval::Range(other_lo, other_hi)) => {
+                // Widen the bounds that grow, so an interval can only grow a bounded number of
+                // times.
+                let lo = if other_lo < lo { widen_down(other_lo) } else { lo };
+                let hi = if other_hi > hi { widen_up(other_hi) } else { hi };
+                Interval::Range(lo, hi)
+            }
+        };
+        if result == *self {
+            return false;
+        }
+        *self = result;
+        true
+    }
+}
+
+/// The smallest `2^k - 1` or `-2^k` that is greater or equal to `x`.
+fn widen_up(x: i128) -> i128 {
+    if x >= 0 {
+        ((x as u128 + 1).next_power_of_two() - 1) as i128
+    } else {
+        let magnitude = x.unsigned_abs();
+        ((1u128 << (127 - magnitude.leading_zeros())) as i128).wrapping_neg()
+    }
+}
+
+/// The greatest `2^k - 1` or `-2^k` that is less or equal to `x`.
+fn widen_down(x: i128) -> i128 {
+    if x >= 0 {
+        let next = x as u128 + 1;
+        ((1u128 << (127 - next.leading_zeros())) - 1) as i128
+    } else {
+        // `-2^127` wraps to itself.
+        (x.unsigned_abs().next_power_of_two() as i128).wrapping_neg()
+    }
+}
+
+impl Interval {
+    fn singleton(value: i128) -> Interval {
+        Interval::Range(value, value)
+    }
+
+    fn new(lo: i128, hi: i128) -> Interval {
+        if lo <= hi {
+            Interval::Range(lo, hi)
+        } else {
+            Interval::Bottom
+        }
+    }
+
+    fn as_singleton(self) -> Option<i128> {
+        match self {
+            Interval::Range(lo, hi) if lo == hi => Some(lo),
+            _ => None,
+        }
+    }
+
+    fn intersect(self, other: Interval) -> Interval {
+        match (self, other) {
+            (Interval::Range(lo, hi), Interval::Range(other_lo, other_hi)) => {
+                Interval::new(lo.max(other_lo), hi.min(other_hi))
+            }
+            _ => Interval::Bottom,
+        }
+    }
+
+    fn contains(self, other: Interval) -> bool {
+        match (self, other) {
+            (_, Interval::Bottom) => true,
+            (Interval::Bottom, _) => false,
+            (Interval::Range(lo, hi), Interval::Range(other_lo, other_hi)) => {
+                lo <= other_lo && other_hi <= hi
+            }
+        }
+    }
+}
+
+const FALSE: Interval = Interval::Range(0, 0);
+const TRUE: Interval = Interval::Range(1, 1);
+const BOOL: Interval = Interval::Range(0, 1);
+
+fn bool_interval(value: Option<bool>) -> Interval {
+    match value {
+        Some(value) => Interval::singleton(value.into()),
+        None => BOOL,
+    }
+}
+
+/// The tracked places that hold the value of an operand at the end of a block.
+#[derive(Debug)]
+struct Term<'tcx> {
+    operand: Operand<'tcx>,
+    places: SmallVec<[PlaceIndex; 2]>,
+}
+
+/// What we know from the value of the operand of the `SwitchInt` or `Assert` terminating a block.
+#[derive(Debug)]
+struct Condition<'tcx> {
+    discr: Term<'tcx>,
+    /// The comparison that computed the operand, if it is computed in the block.
+    comparison: Option<(BinOp, Term<'tcx>, Term<'tcx>)>,
+}
+
+struct RangeAnalysis<'a, 'tcx> {
+    map: Map,
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    conditions: IndexVec<BasicBlock, Option<Condition<'tcx>>>,
+}
+
+impl<'tcx> ValueAnalysis<'tcx> for RangeAnalysis<'_, 'tcx> {
+    type Value = Interval;
+
+    const NAME: &'static str = "RangeAnalysis";
+
+    fn map(&self) -> &Map {
+        &self.map
+    }
+
+    fn handle_assign(
+        &self,
+        target: Place<'tcx>,
+        rvalue: &Rvalue<'tcx>,
+        state: &mut State<Interval>,
+    ) {
+        match rvalue {
+            Rvalue::CheckedBinaryOp(op, box (left, right)) => {
+                // Flood everything now, so we can use `insert_value_idx` directly later.
+                state.flood(target.as_ref(), self.map());
+
+                let Some(target) = self.map().find(target.as_ref()) else { return };
+                let (value, overflow) = self.binary_op(*op, left, right, state);
+                if let Some(value_target) = self.map().apply(target, TrackElem::Field(0_u32.into()))
+                {
+                    state.insert_value_idx(value_target, value, self.map());
+                }
+                if let Some(overflow_target) =
+                    self.map().apply(target, TrackElem::Field(1_u32.into()))
+                {
+                    state.insert_value_idx(overflow_target, overflow, self.map());
+                }
+            }
+            Rvalue::Cast(CastKind::PointerCoercion(PointerCoercion::Unsize), operand, _) => {
+                let pointer = self.handle_operand(operand, state);
+                state.assign(target.as_ref(), pointer, self.map());
+
+                if let Some(target_len) = self.map().find_len(target.as_ref())
+                    && let Some(operand_ty) = operand.ty(self.body, self.tcx).builtin_deref(true)
+                    && let ty::Array(_, len) = operand_ty.ty.kind()
+                    && let Some(len) = len.try_eval_target_usize(self.tcx, self.param_env)
+                {
+                    state.insert_value_idx(target_len, Interval::singleton(len.into()), self.map());
+                }
+            }
+            _ => self.super_assign(target, rvalue, state),
+        }
+    }
+
+    fn handle_rvalue(
+        &self,
+        rvalue: &Rvalue<'tcx>,
+        state: &mut State<Interval>,
+    ) -> ValueOrPlace<Interval> {
+        let value = match rvalue {
+            Rvalue::Len(place) => {
+                let usize_range = self.type_range(self.tcx.types.usize).unwrap();
+                let place_ty = place.ty(self.body, self.tcx).ty;
+                if let ty::Array(_, len) = place_ty.kind() {
+                    len.try_eval_target_usize(self.tcx, self.param_env)
+                        .map_or(usize_range, |len| Interval::singleton(len.into()))
+                } else if let [ProjectionElem::Deref] = place.projection[..] {
+                    state.get_len(place.local.into(), self.map()).intersect(usize_range)
+                } else {
+                    usize_range
+                }
+            }
+            Rvalue::BinaryOp(op, box (left, right)) => self.binary_op(*op, left, right, state).0,
+            Rvalue::UnaryOp(op, operand) => {
+                let ty = operand.ty(self.body, self.tcx);
+                let Some(range) = self.type_range(ty) else { return ValueOrPlace::TOP };
+                match (op, self.eval_operand(operand, state)) {
+                    (_, Interval::Bottom) => Interval::Bottom,
+                    (UnOp::Not, value) if ty.is_bool() => match value.as_singleton() {
+                        Some(value) => Interval::singleton(1 - value),
+                        None => BOOL,
+                    },
+                    (UnOp::Neg, Interval::Range(lo, hi)) if ty.is_signed() => {
+                        let negated = Interval::new(-hi, -lo);
+                        if range.contains(negated) {
+                            negated
+                        } else {
+                            range
+                        }
+                    }
+                    _ => range,
+                }
+            }
+            Rvalue::Cast(CastKind::IntToInt, operand, ty) => {
+                let Some(range) = self.type_range(*ty) else { return ValueOrPlace::TOP };
+                let value = self.eval_operand(operand, state);
+                if range.contains(value) {
+                    value
+                } else {
+                    range
+                }
+            }
+            _ => return self.super_rvalue(rvalue, state),
+        };
+        ValueOrPlace::Value(value)
+    }
+
+    fn handle_constant(
+        &self,
+        constant: &ConstOperand<'tcx>,
+        _state: &mut State<Interval>,
+    ) -> Interval {
+        self.eval_constant(constant)
+    }
+
+    fn handle_terminator<'mir>(
+        &self,
+        terminator: &'mir Terminator<'tcx>,
+        location: Location,
+        state: &mut State<Interval>,
+    ) -> TerminatorEdges<'mir, 'tcx> {
+        if let TerminatorKind::Assert { expected, .. } = terminator.kind
+            && let Some(condition) = &self.conditions[location.block]
+        {
+            // Execution only continues to the target if the assertion holds. The unwind edge gets
+            // this state too, but we never remove the assertions of cleanup blocks.
+            self.refine(&condition.discr, Interval::singleton(expected.into()), state);
+            self.refine_condition(condition, Some(expected), state);
+        }
+        self.super_terminator(terminator, state)
+    }
+
+    fn handle_switch_int_edge_effects(
+        &self,
+        block: BasicBlock,
+        _discr: &Operand<'tcx>,
+        apply_edge_effects: &mut impl SwitchIntEdgeEffects<State<Interval>>,
+    ) {
+        let Some(condition) = &self.conditions[block] else { return };
+        let TerminatorKind::SwitchInt { ref targets, .. } = self.body[block].terminator().kind
+        else {
+            return;
+        };
+        let discr_ty = condition.discr.operand.ty(self.body, self.tcx);
+        let Some(range) = self.type_range(discr_ty) else { return };
+
+        apply_edge_effects.apply(|state, target| {
+            let truth = match target.value {
+                Some(value) => {
+                    let value = self.bits_to_int(value, discr_ty);
+                    self.refine(&condition.discr, Interval::singleton(value), state);
+                    Some(value != 0)
+                }
+                None => {
+                    // Trim the bounds of the discriminant that go to other edges.
+                    let mut value = self.term_value(&condition.discr, state).intersect(range);
+                    while let Interval::Range(lo, hi) = value
+                        && targets.iter().any(|(v, _)| self.bits_to_int(v, discr_ty) == lo)
+                    {
+                        value = Interval::new(lo + 1, hi);
+                    }
+                    while let Interval::Range(lo, hi) = value
+                        && targets.iter().any(|(v, _)| self.bits_to_int(v, discr_ty) == hi)
+                    {
+                        value = Interval::new(lo, hi - 1);
+                    }
+                    self.refine(&condition.discr, value, state);
+                    value.as_singleton().map(|value| value != 0)
+                }
+            };
+            self.refine_condition(condition, truth, state);
+        });
+    }
+}
+
+impl<'a, 'tcx> RangeAnalysis<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, body: &'a Body<'tcx>, map: Map) -> Self {
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+        let conditions =
+            body.basic_blocks.indices().map(|bb| block_condition(body, &map, bb)).collect();
+        RangeAnalysis { map, tcx, body, param_env, conditions }
+    }
+
+    /// The values of a type whose values we track.
+    fn type_range(&self, ty: Ty<'tcx>) -> Option<Interval> {
+        let size = match *ty.kind() {
+            ty::Bool => return Some(BOOL),
+            ty::Int(int_ty) => Integer::from_int_ty(&self.tcx, int_ty).size(),
+            ty::Uint(uint_ty) => Integer::from_uint_ty(&self.tcx, uint_ty).size(),
+            _ => return None,
+        };
+        if size.bits() >= 128 {
+            None
+        } else if ty.is_signed() {
+            Some(Interval::Range(size.signed_int_min(), size.signed_int_max()))
+        } else {
+            Some(Interval::Range(0, size.unsigned_int_max() as i128))
+        }
+    }
+
+    /// Converts the bits of a value of type `ty` to the integer they represent.
+    fn bits_to_int(&self, bits: u128, ty: Ty<'tcx>) -> i128 {
+        match *ty.kind() {
+            ty::Int(int_ty) => {
+                Integer::from_int_ty(&self.tcx, int_ty).size().sign_extend(bits) as i128
+            }
+            _ => bits as i128,
+        }
+    }
+
+    fn eval_constant(&self, constant: &ConstOperand<'tcx>) -> Interval {
+        let ty = constant.const_.ty();
+        if self.type_range(ty).is_none() {
+            return Interval::TOP;
+        }
+        constant
+            .const_
+            .try_eval_bits(self.tcx, self.param_env)
+            .map_or(Interval::TOP, |bits| Interval::singleton(self.bits_to_int(bits, ty)))
+    }
+
+    fn eval_operand(&self, operand: &Operand<'tcx>, state: &State<Interval>) -> Interval {
+        let ty = operand.ty(self.body, self.tcx);
+        let Some(range) = self.type_range(ty) else { return Interval::TOP };
+        let value = match operand {
+            Operand::Constant(box constant) => self.eval_constant(constant),
+            Operand::Copy(place) | Operand::Move(place) => self
+                .map
+                .find(place.as_ref())
+                .map_or(Interval::TOP, |place| state.get_idx(place, &self.map)),
+        };
+        value.intersect(range)
+    }
+
+    /// Returns the value of a binary operation, and whether it overflows.
+    fn binary_op(
+        &self,
+        op: BinOp,
+        left: &Operand<'tcx>,
+        right: &Operand<'tcx>,
+        state: &State<Interval>,
+    ) -> (Interval, Interval) {
+        let ty = left.ty(self.body, self.tcx);
+        let Some(range) = self.type_range(ty) else {
+            let value = if op.ty(self.tcx, ty, right.ty(self.body, self.tcx)).is_bool() {
+                BOOL
+            } else {
+                Interval::TOP
+            };
+            return (value, BOOL);
+        };
+        let (Interval::Range(a_lo, a_hi), Interval::Range(b_lo, b_hi)) =
+            (self.eval_operand(left, state), self.eval_operand(right, state))
+        else {
+            return (Interval::Bottom, Interval::Bottom);
+        };
+
+        // The result of the operation on mathematical integers, if we know its bounds.
+        let exact = match op {
+            BinOp::Add | BinOp::AddUnchecked => a_lo
+                .checked_add(b_lo)
+                .zip(a_hi.checked_add(b_hi))
+                .map(|(lo, hi)| Interval::Range(lo, hi)),
+            BinOp::Sub | BinOp::SubUnchecked => a_lo
+                .checked_sub(b_hi)
+                .zip(a_hi.checked_sub(b_lo))
+                .map(|(lo, hi)| Interval::Range(lo, hi)),
+            BinOp::Mul | BinOp::MulUnchecked => {
+                let products = [
+                    a_lo.checked_mul(b_lo),
+                    a_lo.checked_mul(b_hi),
+                    a_hi.checked_mul(b_lo),
+                    a_hi.checked_mul(b_hi),
+                ];
+                products.into_iter().collect::<Option<SmallVec<[i128; 4]>>>().map(|products| {
+                    Interval::Range(
+                        *products.iter().min().unwrap(),
+                        *products.iter().max().unwrap(),
+                    )
+                })
+            }
+            BinOp::Div if a_lo >= 0 && b_lo > 0 => Some(Interval::Range(a_lo / b_hi, a_hi / b_lo)),
+            BinOp::Rem if a_lo >= 0 && b_lo > 0 => {
+                if a_hi < b_lo {
+                    Some(Interval::Range(a_lo, a_hi))
+                } else {
+                    Some(Interval::Range(0, a_hi.min(b_hi - 1)))
+                }
+            }
+            BinOp::BitAnd if a_lo >= 0 || b_lo >= 0 => {
+                // The result is non-negative and less than each non-negative operand.
+                let hi = match (a_lo >= 0, b_lo >= 0) {
+                    (true, true) => a_hi.min(b_hi),
+                    (true, false) => a_hi,
+                    (false, _) => b_hi,
+                };
+                Some(Interval::Range(0, hi))
+            }
+            BinOp::BitOr if a_lo >= 0 && b_lo >= 0 => {
+                Some(Interval::Range(a_lo.max(b_lo), widen_up(a_hi.max(b_hi))))
+            }
+            BinOp::Shr | BinOp::ShrUnchecked
+                if a_lo >= 0
+                    && b_lo >= 0
+                    && b_hi < i128::from(ty.primitive_size(self.tcx).bits()) =>
+            {
+                Some(Interval::Range(a_lo >> b_hi, a_hi >> b_lo))
+            }
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                let value = compare(op, (a_lo, a_hi), (b_lo, b_hi));
+                return (bool_interval(value), FALSE);
+            }
+            _ => None,
+        };
+
+        let overflow = match op {
+            BinOp::Shl | BinOp::Shr => {
+                let bits = i128::from(ty.primitive_size(self.tcx).bits());
+                if b_lo >= 0 && b_hi < bits {
+                    FALSE
+                } else if b_hi < 0 || b_lo >= bits {
+                    TRUE
+                } else {
+                    BOOL
+                }
+            }
+            _ => match exact {
+                Some(exact) if range.contains(exact) => FALSE,
+                Some(exact) if exact.intersect(range) == Interval::Bottom => TRUE,
+                _ => BOOL,
+            },
+        };
+        let value = match exact {
+            Some(exact) if range.contains(exact) => exact,
+            _ => range,
+        };
+        (value, overflow)
+    }
+
+    /// The value of an operand, using the places that hold it.
+    fn term_value(&self, term: &Term<'tcx>, state: &State<Interval>) -> Interval {
+        let mut value = self.eval_operand(&term.operand, state);
+        for &place in &term.places {
+            value = value.intersect(state.get_idx(place, &self.map));
+        }
+        value
+    }
+
+    /// Restricts the values of the places holding the operand to `value`.
+    fn refine(&self, term: &Term<'tcx>, value: Interval, state: &mut State<Interval>) {
+        for &place in &term.places {
+            let refined = state.get_idx(place, &self.map).intersect(value);
+            state.insert_value_idx(place, refined, &self.map);
+        }
+    }
+
+    /// Refines the operands of the comparison computing the condition, knowing its truth value.
+    fn refine_condition(
+        &self,
+        condition: &Condition<'tcx>,
+        truth: Option<bool>,
+        state: &mut State<Interval>,
+    ) {
+        let (Some(truth), Some((op, left, right))) = (truth, &condition.comparison) else { return };
+        let (Interval::Range(a_lo, a_hi), Interval::Range(b_lo, b_hi)) =
+            (self.term_value(left, state), self.term_value(right, state))
+        else {
+            return;
+        };
+
+        // Normalize to `left op right`.
+        let op = if truth {
+            *op
+        } else {
+            match *op {
+                BinOp::Lt => BinOp::Ge,
+                BinOp::Le => BinOp::Gt,
+                BinOp::Gt => BinOp::Le,
+                BinOp::Ge => BinOp::Lt,
+                BinOp::Eq => BinOp::Ne,
+                BinOp::Ne => BinOp::Eq,
+                _ => bug!("{op:?} is not a comparison"),
+            }
+        };
+        let (left_value, right_value) = match op {
+            BinOp::Lt => (
+                Interval::new(a_lo, a_hi.min(b_hi.saturating_sub(1))),
+                Interval::new(b_lo.max(a_lo.saturating_add(1)), b_hi),
+            ),
+            BinOp::Le => (Interval::new(a_lo, a_hi.min(b_hi)), Interval::new(b_lo.max(a_lo), b_hi)),
+            BinOp::Gt => (
+                Interval::new(a_lo.max(b_lo.saturating_add(1)), a_hi),
+                Interval::new(b_lo, b_hi.min(a_hi.saturating_sub(1))),
+            ),
+            BinOp::Ge => (Interval::new(a_lo.max(b_lo), a_hi), Interval::new(b_lo, b_hi.min(a_hi))),
+            BinOp::Eq => {
+                let value = Interval::new(a_lo.max(b_lo), a_hi.min(b_hi));
+                (value, value)
+            }
+            BinOp::Ne => (exclude(a_lo, a_hi, b_lo, b_hi), exclude(b_lo, b_hi, a_lo, a_hi)),
+            _ => bug!("{op:?} is not a comparison"),
+        };
+        self.refine(left, left_value, state);
+        self.refine(right, right_value, state);
+    }
+}
+
+/// Removes `other` from `lo..=hi` if it is a single value at one of its ends.
+fn exclude(lo: i128, hi: i128, other_lo: i128, other_hi: i128) -> Interval {
+    if other_lo != other_hi {
+        Interval::new(lo, hi)
+    } else if other_lo == lo {
+        Interval::new(lo.saturating_add(1), hi)
+    } else if other_lo == hi {
+        Interval::new(lo, hi.saturating_sub(1))
+    } else {
+        Interval::new(lo, hi)
+    }
+}
+
+/// Compares all the values of two non-empty intervals, if they all give the same result.
+fn compare(op: BinOp, (a_lo, a_hi): (i128, i128), (b_lo, b_hi): (i128, i128)) -> Option<bool> {
+    let lt = if a_hi < b_lo {
+        Some(true)
+    } else if a_lo >= b_hi {
+        Some(false)
+    } else {
+        None
+    };
+    let gt = if a_lo > b_hi {
+        Some(true)
+    } else if a_hi <= b_lo {
+        Some(false)
+    } else {
+        None
+    };
+    let eq = if a_lo == a_hi && b_lo == b_hi && a_lo == b_lo {
+        Some(true)
+    } else if a_hi < b_lo || b_hi < a_lo {
+        Some(false)
+    } else {
+        None
+    };
+    match op {
+        BinOp::Lt => lt,
+        BinOp::Ge => lt.map(|lt| !lt),
+        BinOp::Gt => gt,
+        BinOp::Le => gt.map(|gt| !gt),
+        BinOp::Eq => eq,
+        BinOp::Ne => eq.map(|eq| !eq),
+        _ => bug!("{op:?} is not a comparison"),
+    }
+}
+
+/// Finds which places hold the operand of the terminator of `block`, and the comparison it is the
+/// result of.
+fn block_condition<'tcx>(
+    body: &Body<'tcx>,
+    map: &Map,
+    block: BasicBlock,
+) -> Option<Condition<'tcx>> {
+    let data = &body.basic_blocks[block];
+    let (TerminatorKind::SwitchInt { discr: operand, .. }
+    | TerminatorKind::Assert { cond: operand, .. }) = &data.terminator().kind
+    else {
+        return None;
+    };
+    let statements = &data.statements[..];
+    let discr = term(map, statements, statements.len(), operand);
+
+    let comparison = operand
+        .place()
+        .and_then(|place| place.as_local())
+        .and_then(|local| last_assignment(statements, local))
+        .and_then(|(index, rvalue)| match rvalue {
+            Rvalue::BinaryOp(
+                op @ (BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge),
+                box (left, right),
+            ) => Some((
+                *op,
+                term(map, statements, index + 1, left),
+                term(map, statements, index + 1, right),
+            )),
+            _ => None,
+        });
+
+    Some(Condition { discr, comparison })
+}
+
+/// Finds the tracked places that hold, at the end of the block, the value `operand` has when it
+/// is read before the statement at `index`.
+fn term<'tcx>(
+    map: &Map,
+    statements: &[Statement<'tcx>],
+    index: usize,
+    operand: &Operand<'tcx>,
+) -> Term<'tcx> {
+    let mut places = SmallVec::new();
+    if let Some(place) = operand.place()
+        && !is_modified(&statements[index..], place.local)
+        && let Some(place_index) = map.find(place.as_ref())
+    {
+        places.push(place_index);
+
+        // Look through the assignment of a temporary in the same block.
+        if let Some(local) = place.as_local()
+            && let Some((def_index, rvalue)) = last_assignment(&statements[..index], local)
+        {
+            let after_def = &statements[def_index + 1..];
+            match rvalue {
+                Rvalue::Use(Operand::Copy(source) | Operand::Move(source))
+                | Rvalue::CopyForDeref(source)
+                    if !is_modified(after_def, source.local) =>
+                {
+                    places.extend(map.find(source.as_ref()));
+                }
+                Rvalue::Len(source)
+                    if matches!(source.projection[..], [ProjectionElem::Deref])
+                        && !is_modified(after_def, source.local) =>
+                {
+                    places.extend(map.find_len(source.local.into()));
+                }
+                _ => {}
+            }
+        }
+    }
+    Term { operand: operand.clone(), places }
+}
+
+/// The last statement assigning to `local`, if there is no other write to it after it.
+fn last_assignment<'a, 'tcx>(
+    statements: &'a [Statement<'tcx>],
+    local: Local,
+) -> Option<(usize, &'a Rvalue<'tcx>)> {
+    let index =
+        statements.iter().rposition(|stmt| is_modified(std::slice::from_ref(stmt), local))?;
+    match &statements[index].kind {
+        StatementKind::Assign(box (place, rvalue)) if place.as_local() == Some(local) => {
+            Some((index, rvalue))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if a statement may change the value of a place based on `local`.
+///
+/// The tracked places cannot be borrowed, so they can only be changed through their local.
+fn is_modified(statements: &[Statement<'_>], local: Local) -> bool {
+    statements.iter().any(|stmt| match &stmt.kind {
+        StatementKind::Assign(box (place, _))
+        | StatementKind::SetDiscriminant { box place, .. }
+        | StatementKind::Deinit(box place) => place.local == local,
+        StatementKind::StorageLive(l) | StatementKind::StorageDead(l) => *l == local,
+        _ => false,
+    })
+}
+
 pub(crate) struct Patch<'tcx> {
     tcx: TyCtxt<'tcx>,
 
@@ -774,6 +1523,40 @@ fn visit_terminator_before_primary_effect(
     }
 }
 
+struct RedundantCheckCollector {
+    /// The blocks whose assertion always holds, with the target of the assertion.
+    removable: Vec<(BasicBlock, BasicBlock)>,
+}
+
+impl<'mir, 'tcx>
+    ResultsVisitor<'mir, 'tcx, Results<'tcx, ValueAnalysisWrapper<RangeAnalysis<'_, 'tcx>>>>
+    for RedundantCheckCollector
+{
+    type FlowState = State<Interval>;
+
+    fn visit_terminator_before_primary_effect(
+        &mut self,
+        results: &mut Results<'tcx, ValueAnalysisWrapper<RangeAnalysis<'_, 'tcx>>>,
+        state: &Self::FlowState,
+        terminator: &'mir Terminator<'tcx>,
+        location: Location,
+    ) {
+        let analysis = &results.analysis.0;
+        if let TerminatorKind::Assert { ref cond, expected, ref msg, target, .. } = terminator.kind
+            && matches!(
+                **msg,
+                AssertKind::BoundsCheck { .. }
+                    | AssertKind::Overflow(..)
+                    | AssertKind::OverflowNeg(..)
+            )
+            && !analysis.body[location.block].is_cleanup
+            && analysis.eval_operand(cond, state) == Interval::singleton(expected.into())
+        {
+            self.removable.push((location.block, target));
+        }
+    }
+}
+
 impl<'tcx> MutVisitor<'tcx> for Patch<'tcx> {
     fn tcx(&self) -> TyCtxt<'tcx> {
         self.tcx
@@ -1028,9 +1028,10 @@ fn visit_statement(&mut self, stmt: &mut Statement<'tcx>, location: Location) {
     }
 }
 
-struct StorageRemover<'tcx> {
-    tcx: TyCtxt<'tcx>,
-    reused_locals: BitSet<Local>,
+/// Turns the moves from the given locals into copies, and removes their storage statements.
+pub(crate) struct StorageRemover<'tcx> {
+    pub(crate) tcx: TyCtxt<'tcx>,
+    pub(crate) reused_locals: BitSet<Local>,
 }
 
 impl<'tcx> MutVisitor<'tcx> for StorageRemover<'tcx> {
@@ -0,0 +1,470 @@
+//! Heap-to-stack promotion of boxes.
+//!
+//! A `Box::new(value)` whose box is only used to access its contents and to drop them is replaced
+//! with a stack local holding the value:
+//!
+//! ```text
+//! _2 = Box::<u32>::new(move _3) -> bb1        _6 = move _3; goto -> bb1
+//! _5 = copy ((_2.0: Unique<u32>).0 ...)  =>   (nop)
+//! _4 = (*_5)                                  _4 = _6
+//! drop(_2) -> bb2                             goto -> bb2
+//! ```
+//!
+//! After `ElaborateBoxDerefs`, the contents of a box `_b` are accessed through a raw pointer temp
+//! `_t = copy (((_b.0: Unique<T>).0: NonNull<T>).0: *const T)`, or directly through
+//! `(*(((_b.0).0).0))` in the drops elaborated for the contents. The box is promoted when:
+//! - it is the destination of a single `Box::new` call, and `T` has a known layout that is small
+//!   enough for the stack;
+//! - it is only used to create such pointer temps, to access its contents through them, and to be
+//!   dropped, either by a `Drop` terminator or by a call to `<Box<T> as Drop>::drop(&mut _b)`;
+//! - the pointer temps are assigned once, not borrowed, and only used through a deref;
+//! - the contents are never borrowed, so no pointer to them outlives the accesses we rewrite.
+//!
+//! The accesses to the contents then become accesses to the stack local, and the drop of the box
+//! becomes a drop of the stack local, which doesn't free anything.
+//!
+//! This has to run before inlining, which would replace the `Box::new` calls with the allocation.
+//! The promoted boxes are reported in the `HeapToStack-promoted` MIR dump.
+
+use rustc_hir::def_id::DefId;
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+use rustc_middle::mir::visit::{
+    MutVisitor, MutatingUseContext, NonMutatingUseContext, NonUseContext, PlaceContext, Visitor,
+};
+use rustc_middle::mir::*;
+use rustc_middle::mir::{dump_mir, PassWhere};
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_mir_dataflow::impls::borrowed_locals;
+use rustc_span::sym;
+
+pub struct HeapToStack;
+
+/// Boxes whose contents are larger than this stay on the heap, so we don't overflow the stack.
+const MAX_PROMOTED_SIZE: u64 = 4096;
+
+impl<'tcx> MirPass<'tcx> for HeapToStack {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        sess.mir_opt_level() >= 2
+    }
+
+    #[instrument(level = "trace", skip(self, tcx, body))]
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        debug!(def_id = ?body.source.def_id());
+        let Some(box_new) = tcx.get_diagnostic_item(sym::box_new) else { return };
+        let Some(drop_trait) = tcx.lang_items().drop_trait() else { return };
+        let drop_fn = tcx.associated_item_def_ids(drop_trait)[0];
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+
+        let mut candidates = find_candidates(tcx, param_env, body, box_new);
+        if candidates.iter().all(Option::is_none) {
+            return;
+        }
+
+        // The locals derived from each box: the pointer temps and the `&mut` references passed to
+        // the destructor of the box.
+        let mut derived = IndexVec::from_elem(None, &body.local_decls);
+        let borrowed = borrowed_locals(body);
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            for (statement_index, stmt) in data.statements.iter().enumerate() {
+                let StatementKind::Assign(box (place, ref rvalue)) = stmt.kind else { continue };
+                let (box_local, kind) = match *rvalue {
+                    Rvalue::Use(Operand::Copy(source)) if is_box_pointer(source.projection) => {
+                        (source.local, Derived::Pointer)
+                    }
+                    Rvalue::Ref(_, BorrowKind::Mut { .. }, source)
+                        if source.projection.is_empty() =>
+                    {
+                        (source.local, Derived::DestructorRef)
+                    }
+                    _ => continue,
+                };
+                if candidates[box_local].is_none() {
+                    continue;
+                }
+                let location = Location { block, statement_index };
+                match place.as_local() {
+                    Some(local) if derived[local].is_none() && !borrowed.contains(local) => {
+                        derived[local] = Some(DerivedLocal { box_local, kind, location });
+                    }
+                    _ => candidates[box_local] = None,
+                }
+            }
+        }
+
+        let mut escapes = EscapeVisitor {
+            body,
+            drop_fn,
+            candidates: &candidates,
+            derived: &derived,
+            escaped: BitSet::new_empty(body.local_decls.len()),
+        };
+        escapes.visit_body(body);
+        let escaped = escapes.escaped;
+
+        let mut promoted = Vec::new();
+        let mut stack_locals = IndexVec::from_elem(None, &body.local_decls);
+        for (box_local, candidate) in candidates.iter_enumerated() {
+            let Some(candidate) = candidate else { continue };
+            if escaped.contains(box_local) {
+                continue;
+            }
+            let span = body.local_decls[box_local].source_info.span;
+            let stack_local = body.local_decls.push(LocalDecl::new(candidate.pointee, span));
+            stack_locals[box_local] = Some(stack_local);
+            promoted.push((box_local, stack_local, *candidate));
+        }
+        if promoted.is_empty() {
+            return;
+        }
+        debug!(?promoted);
+
+        let basic_blocks = body.basic_blocks.as_mut();
+        for &(_, stack_local, candidate) in &promoted {
+            // The allocation becomes an assignment of the value to the stack local.
+            let data = &mut basic_blocks[candidate.call];
+            let terminator = data.terminator_mut();
+            let TerminatorKind::Call { ref args, target: Some(target), .. } = terminator.kind
+            else {
+                bug!("expected a call to `Box::new`, found {:?}", terminator.kind);
+            };
+            let value = args[0].clone();
+            let source_info = terminator.source_info;
+            terminator.kind = TerminatorKind::Goto { target };
+            data.statements.push(Statement {
+                source_info,
+                kind: StatementKind::Assign(Box::new((
+                    Place::from(stack_local),
+                    Rvalue::Use(value),
+                ))),
+            });
+        }
+        for data in basic_blocks.iter_mut() {
+            let terminator = data.terminator_mut();
+            match terminator.kind {
+                TerminatorKind::Drop { place, target, unwind, replace }
+                    if place.projection.is_empty()
+                        && let Some(stack_local) = stack_locals[place.local] =>
+                {
+                    // Dropping the box frees it after dropping its contents, which now only need
+                    // to be dropped in place.
+                    terminator.kind = if body.local_decls[stack_local].ty.needs_drop(tcx, param_env)
+                    {
+                        TerminatorKind::Drop {
+                            place: Place::from(stack_local),
+                            target,
+                            unwind,
+                            replace,
+                        }
+                    } else {
+                        TerminatorKind::Goto { target }
+                    };
+                }
+                // The destructor of the box only frees it, the contents are dropped separately.
+                TerminatorKind::Call { ref args, target: Some(target), .. }
+                    if let [Operand::Move(arg)] = args[..]
+                        && let Some(local) = arg.as_local()
+                        && let Some(derived) = derived[local]
+                        && derived.kind == Derived::DestructorRef
+                        && stack_locals[derived.box_local].is_some() =>
+                {
+                    terminator.kind = TerminatorKind::Goto { target };
+                }
+                _ => {}
+            }
+        }
+
+        // Only the debuginfo of the contents can be kept, the box itself doesn't exist anymore.
+        body.var_debug_info.retain(|info| match info.value {
+            VarDebugInfoContents::Place(place) => {
+                if let Some(derived) = derived[place.local]
+                    && stack_locals[derived.box_local].is_some()
+                {
+                    place.projection.first() == Some(&ProjectionElem::Deref)
+                } else if stack_locals[place.local].is_some() {
+                    box_contents_projection(place.projection).is_some()
+                } else {
+                    true
+                }
+            }
+            VarDebugInfoContents::Const(_) => true,
+        });
+
+        Promoter { tcx, derived: &derived, stack_locals: &stack_locals }
+            .visit_body_preserves_cfg(body);
+
+        dump_mir(tcx, false, "HeapToStack-promoted", &0, body, |pass_where, w| {
+            if let PassWhere::BeforeCFG = pass_where {
+                for &(box_local, stack_local, candidate) in &promoted {
+                    writeln!(
+                        w,
+                        "// promoted {box_local:?} (`Box::new` in {:?}) to stack local {stack_local:?}: {}",
+                        candidate.call, candidate.pointee,
+                    )?;
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Candidate<'tcx> {
+    /// The block whose terminator is the call to `Box::new`.
+    call: BasicBlock,
+    pointee: Ty<'tcx>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Derived {
+    /// `_t = copy (((_b.0: Unique<T>).0: NonNull<T>).0: *const T)`
+    Pointer,
+    /// `_r = &mut _b`, passed to `<Box<T> as Drop>::drop`.
+    DestructorRef,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct DerivedLocal {
+    box_local: Local,
+    kind: Derived,
+    /// The only assignment of the local.
+    location: Location,
+}
+
+/// Finds the boxes that are the destination of a single `Box::new` call and whose contents could
+/// live on the stack.
+fn find_candidates<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    body: &Body<'tcx>,
+    box_new: DefId,
+) -> IndexVec<Local, Option<Candidate<'tcx>>> {
+    let mut candidates = IndexVec::from_elem(None, &body.local_decls);
+    let mut assigned = BitSet::new_empty(body.local_decls.len());
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        if data.is_cleanup {
+            continue;
+        }
+        let TerminatorKind::Call { ref func, destination, target: Some(_), .. } =
+            data.terminator().kind
+        else {
+            continue;
+        };
+        let Some((def_id, args)) = func.const_fn_def() else { continue };
+        let Some(local) = destination.as_local() else { continue };
+        if def_id != box_new || body.local_kind(local) != LocalKind::Temp {
+            continue;
+        }
+        if !assigned.insert(local) {
+            // `Box::new` is called several times for this box, so several values would need to
+            // live in the stack local at the same time.
+            candidates[local] = None;
+            continue;
+        }
+
+        let pointee = args.type_at(0);
+        let Ok(layout) = tcx.layout_of(param_env.and(pointee)) else { continue };
+        // Boxes of zero-sized types don't allocate anyway.
+        if layout.is_unsized() || layout.is_zst() || layout.size.bytes() > MAX_PROMOTED_SIZE {
+            continue;
+        }
+        candidates[local] = Some(Candidate { call: block, pointee });
+    }
+    candidates
+}
+
+/// Returns true for the projection from a box to the raw pointer inside it, `((_b.0).0).0`.
+fn is_box_pointer(projection: &[PlaceElem<'_>]) -> bool {
+    matches!(
+        projection,
+        [
+            ProjectionElem::Field(f0, _),
+            ProjectionElem::Field(f1, _),
+            ProjectionElem::Field(f2, _),
+        ] if f0.as_u32() == 0 && f1.as_u32() == 0 && f2.as_u32() == 0
+    )
+}
+
+/// Returns the projection from the contents of a box if the projection derefs the pointer inside
+/// the box.
+fn box_contents_projection<'a, 'tcx>(
+    projection: &'a [PlaceElem<'tcx>],
+) -> Option<&'a [PlaceElem<'tcx>]> {
+    match projection {
+        [_, _, _, ProjectionElem::Deref, rest @ ..] if is_box_pointer(&projection[..3]) => {
+            Some(rest)
+        }
+        _ => None,
+    }
+}
+
+/// Marks the candidate boxes that are used for anything else than accessing their contents and
+/// dropping them.
+struct EscapeVisitor<'a, 'tcx> {
+    body: &'a Body<'tcx>,
+    drop_fn: DefId,
+    candidates: &'a IndexVec<Local, Option<Candidate<'tcx>>>,
+    derived: &'a IndexVec<Local, Option<DerivedLocal>>,
+    escaped: BitSet<Local>,
+}
+
+impl<'tcx> EscapeVisitor<'_, 'tcx> {
+    /// Returns true if the terminator at `location` calls `<Box<T> as Drop>::drop(move _r)`.
+    fn is_destructor_call(&self, location: Location, reference: Local) -> bool {
+        let data = &self.body.basic_blocks[location.block];
+        if location.statement_index != data.statements.len() {
+            return false;
+        }
+        let TerminatorKind::Call { ref func, ref args, target: Some(_), .. } =
+            data.terminator().kind
+        else {
+            return false;
+        };
+        func.const_fn_def()
+            .is_some_and(|(def_id, fn_args)| def_id == self.drop_fn && fn_args.type_at(0).is_box())
+            && matches!(args[..], [Operand::Move(arg)] if arg.as_local() == Some(reference))
+    }
+
+    /// Returns true if the statement at `location` is the assignment of a derived local.
+    fn defines_derived_local(&self, location: Location) -> bool {
+        let Some(stmt) =
+            self.body.basic_blocks[location.block].statements.get(location.statement_index)
+        else {
+            return false;
+        };
+        let StatementKind::Assign(box (place, _)) = stmt.kind else { return false };
+        place
+            .as_local()
+            .and_then(|local| self.derived[local])
+            .is_some_and(|derived| derived.location == location)
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for EscapeVisitor<'_, 'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        let is_borrow = matches!(
+            context,
+            PlaceContext::NonMutatingUse(
+                NonMutatingUseContext::SharedBorrow
+                    | NonMutatingUseContext::FakeBorrow
+                    | NonMutatingUseContext::AddressOf
+            ) | PlaceContext::MutatingUse(
+                MutatingUseContext::Borrow | MutatingUseContext::AddressOf
+            )
+        );
+
+        if let Some(derived) = self.derived[place.local] {
+            let allowed = match context {
+                PlaceContext::NonUse(_) => true,
+                _ if place.projection.first() == Some(&ProjectionElem::Deref) => {
+                    derived.kind == Derived::Pointer && !is_borrow
+                }
+                PlaceContext::MutatingUse(MutatingUseContext::Store) => {
+                    place.projection.is_empty() && location == derived.location
+                }
+                PlaceContext::NonMutatingUse(NonMutatingUseContext::Move) => {
+                    derived.kind == Derived::DestructorRef
+                        && place.projection.is_empty()
+                        && self.is_destructor_call(location, place.local)
+                }
+                _ => false,
+            };
+            if !allowed {
+                debug!(?place, ?context, ?location, "escapes");
+                self.escaped.insert(derived.box_local);
+            }
+        } else if let Some(candidate) = self.candidates[place.local] {
+            let allowed = match context {
+                PlaceContext::NonUse(_) => true,
+                _ if box_contents_projection(place.projection).is_some() => !is_borrow,
+                PlaceContext::MutatingUse(MutatingUseContext::Call) => {
+                    location == self.body.terminator_loc(candidate.call)
+                }
+                PlaceContext::MutatingUse(MutatingUseContext::Drop) => place.projection.is_empty(),
+                // The definitions of the derived locals.
+                PlaceContext::NonMutatingUse(NonMutatingUseContext::Copy)
+                | PlaceContext::MutatingUse(MutatingUseContext::Borrow) => {
+                    self.defines_derived_local(location)
+                }
+                _ => false,
+            };
+            if !allowed {
+                debug!(?place, ?context, ?location, "escapes");
+                self.escaped.insert(place.local);
+            }
+        }
+        // The locals used to index places are integers, not boxes or pointers, so we don't need
+        // to visit the projection.
+    }
+}
+
+/// Replaces the accesses to the contents of the promoted boxes with the stack locals, and removes
+/// the derived locals.
+struct Promoter<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    derived: &'a IndexVec<Local, Option<DerivedLocal>>,
+    stack_locals: &'a IndexVec<Local, Option<Local>>,
+}
+
+impl<'tcx> Promoter<'_, 'tcx> {
+    // The stack locals are pushed after the maps are built, so they aren't in them.
+    fn stack_local(&self, local: Local) -> Option<Local> {
+        self.stack_locals.get(local).copied().flatten()
+    }
+
+    fn stack_local_of_derived(&self, local: Local) -> Option<Local> {
+        self.derived.get(local).copied().flatten().and_then(|d| self.stack_local(d.box_local))
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for Promoter<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_statement(&mut self, stmt: &mut Statement<'tcx>, location: Location) {
+        match stmt.kind {
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local)
+                if self.stack_local_of_derived(local).is_some() =>
+            {
+                stmt.make_nop();
+            }
+            StatementKind::Assign(box (place, _))
+                if place.projection.is_empty()
+                    && self.stack_local_of_derived(place.local).is_some() =>
+            {
+                stmt.make_nop();
+            }
+            _ => self.super_statement(stmt, location),
+        }
+    }
+
+    fn visit_local(&mut self, local: &mut Local, context: PlaceContext, _: Location) {
+        // The stack local lives as long as the box.
+        if let PlaceContext::NonUse(NonUseContext::StorageLive | NonUseContext::StorageDead) =
+            context
+            && let Some(stack_local) = self.stack_local(*local)
+        {
+            *local = stack_local;
+        }
+    }
+
+    fn visit_place(&mut self, place: &mut Place<'tcx>, context: PlaceContext, location: Location) {
+        let projection = place.projection;
+        let contents = if let Some(stack_local) = self.stack_local_of_derived(place.local)
+            && let [ProjectionElem::Deref, ref rest @ ..] = projection[..]
+        {
+            Some((stack_local, rest))
+        } else if let Some(stack_local) = self.stack_local(place.local)
+            && let Some(rest) = box_contents_projection(projection)
+        {
+            Some((stack_local, rest))
+        } else {
+            None
+        };
+        if let Some((stack_local, rest)) = contents {
+            *place = Place { local: stack_local, projection: self.tcx.mk_place_elems(rest) };
+        }
+        self.super_place(place, context, location);
+    }
+}
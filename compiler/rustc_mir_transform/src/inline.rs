@@ -309,6 +309,8 @@ fn check_mir_is_available(
             InstanceDef::Intrinsic(_) | InstanceDef::Virtual(..) => {
                 return Err("instance without MIR (intrinsic / virtual)");
             }
+            // These are only called through function pointers, so they are never resolved here.
+            InstanceDef::ConstArgShim { .. } => return Err("const argument shim"),
             // This cannot result in an immediate cycle since the callee MIR is a shim, which does
             // not get any optimizations run on it. Any subsequent inlining may cause cycles, but we
             // do not need to catch this here, we can wait until the inliner decides to continue
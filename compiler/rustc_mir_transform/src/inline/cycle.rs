@@ -92,6 +92,8 @@ fn process<'tcx>(
 
                 // This shim does not call any other functions, thus there can be no recursion.
                 InstanceDef::FnPtrAddrShim(..) => continue,
+                // These are only called through function pointers, which aren't followed here.
+                InstanceDef::ConstArgShim { .. } => continue,
                 InstanceDef::DropGlue(..) => {
                     // FIXME: A not fully substituted drop shim can cause ICEs if one attempts to
                     // have its MIR built. Likely oli-obk just screwed up the `ParamEnv`s, so this
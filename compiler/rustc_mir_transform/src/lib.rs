@@ -82,10 +82,12 @@
 mod ffi_unwind_calls;
 mod function_item_references;
 mod gvn;
+mod heap_to_stack;
 pub mod inline;
 mod instsimplify;
 mod jump_threading;
 mod large_enums;
+mod licm;
 mod lint;
 mod lower_intrinsics;
 mod lower_slice_len;
@@ -110,6 +112,7 @@
 pub mod simplify;
 mod simplify_branches;
 mod simplify_comparison_integral;
+mod specialize_const_args;
 mod sroa;
 mod uninhabited_enum_branching;
 mod unreachable_prop;
@@ -566,6 +569,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
         &[
             &check_alignment::CheckAlignment,
             &lower_slice_len::LowerSliceLenCalls, // has to be done before inlining, otherwise actual call will be almost always inlined. Also simple, so can just do first
+            &licm::LoopInvariantCodeMotion,
+            &heap_to_stack::HeapToStack, // has to run before inlining, which would inline `Box::new`
             &inline::Inline,
             // Substitutions during inlining may introduce switch on enums with uninhabited branches.
             &uninhabited_enum_branching::UninhabitedEnumBranching,
@@ -592,6 +597,8 @@ fn o1<T>(x: T) -> WithMinOptLevel<T> {
             &gvn::GVN,
             &simplify::SimplifyLocals::AfterGVN,
             &dataflow_const_prop::DataflowConstProp,
+            &dataflow_const_prop::RemoveRedundantChecks,
+            &specialize_const_args::SpecializeConstArgs,
             &const_debuginfo::ConstDebugInfo,
             &o1(simplify_branches::SimplifyConstCondition::AfterConstProp),
             &jump_threading::JumpThreading,
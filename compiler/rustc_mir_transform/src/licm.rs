@@ -0,0 +1,269 @@
+//! Loop-invariant code motion.
+//!
+//! This pass moves the computations whose result is the same at each iteration of a loop out of
+//! the loop, into a block that jumps to the loop header, the preheader:
+//!
+//! ```text
+//!            bb0: _2 = Len((*_1))
+//! bb1: ...   bb1: ...
+//! _2 = Len((*_1))        =>       (nop)
+//! _3 = Lt(_4, _2)        _3 = Lt(_4, _2)
+//! ```
+//!
+//! We hoist an assignment `_l = rvalue` from a loop when:
+//! - `_l` is an SSA local, so its value only depends on `rvalue`;
+//! - `rvalue` is a pure computation that only reads constants and SSA locals that are assigned
+//!   outside of the loop or hoisted, or reads the length or the discriminant of such a local;
+//! - evaluating `rvalue` cannot be UB whatever the values of its operands, as the assignment may
+//!   be in a block that doesn't execute at each iteration, or at all.
+//!
+//! The loops are processed from the innermost to the outermost, so a computation hoisted out of
+//! an inner loop can then be hoisted out of the outer loop.
+//!
+//! As the hoisted locals are now assigned outside of the loop, we remove their storage statements,
+//! like GVN does for the locals it reuses.
+
+use rustc_data_structures::graph::loops::{LoopIdx, LoopNest};
+use rustc_index::bit_set::BitSet;
+use rustc_index::IndexVec;
+use rustc_middle::mir::visit::MutVisitor;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, TyCtxt};
+
+use crate::gvn::StorageRemover;
+use crate::ssa::SsaLocals;
+
+pub struct LoopInvariantCodeMotion;
+
+impl<'tcx> MirPass<'tcx> for LoopInvariantCodeMotion {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        sess.mir_opt_level() >= 2
+    }
+
+    #[instrument(level = "trace", skip(self, tcx, body))]
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        debug!(def_id = ?body.source.def_id());
+        // Clone the loop nest and the dominators as we need them while mutating the body.
+        let loop_nest = body.basic_blocks.loop_nest().clone();
+        if loop_nest.loops().is_empty() {
+            return;
+        }
+        let dominators = body.basic_blocks.dominators().clone();
+        let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+        let ssa = SsaLocals::new(body);
+
+        // The blocks of each loop, with the preheaders created for the loops nested in it.
+        let mut loop_blocks: IndexVec<LoopIdx, Vec<BasicBlock>> =
+            loop_nest.loops().iter().map(|l| l.nodes.clone()).collect();
+        let mut hoisted = BitSet::new_empty(body.local_decls.len());
+        let mut reused = BitSet::new_empty(body.local_decls.len());
+
+        // A loop comes before the loops nested in it, so this visits the innermost loops first.
+        for loop_idx in loop_nest.loops().indices().rev() {
+            let header = loop_nest[loop_idx].header;
+            if header == START_BLOCK || body.basic_blocks[header].is_cleanup {
+                continue;
+            }
+
+            let header_start = Location { block: header, statement_index: 0 };
+            let mut invariant = BitSet::new_empty(body.local_decls.len());
+            let mut candidates = Vec::new();
+            for &bb in &loop_blocks[loop_idx] {
+                let data = &body.basic_blocks[bb];
+                if data.is_cleanup {
+                    continue;
+                }
+                for (statement_index, stmt) in data.statements.iter().enumerate() {
+                    let StatementKind::Assign(box (place, ref rvalue)) = stmt.kind else {
+                        continue;
+                    };
+                    let Some(local) = place.as_local() else { continue };
+                    if !ssa.is_ssa(local) {
+                        continue;
+                    }
+                    let is_invariant = |l: Local| {
+                        invariant.contains(l)
+                            || ssa.assignment_dominates(&dominators, l, header_start)
+                    };
+                    if is_hoistable(tcx, param_env, body, &ssa, rvalue, is_invariant) {
+                        invariant.insert(local);
+                        candidates.push(Location { block: bb, statement_index });
+                    }
+                }
+            }
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let preheader = preheader(body, &loop_nest, &mut loop_blocks, loop_idx);
+            debug!(?loop_idx, ?header, ?preheader, ?candidates);
+            let basic_blocks = body.basic_blocks.as_mut_preserves_cfg();
+            for location in candidates {
+                // Leave a `Nop` behind, so the locations of the following statements don't change.
+                let stmt = &mut basic_blocks[location.block].statements[location.statement_index];
+                let nop = Statement { source_info: stmt.source_info, kind: StatementKind::Nop };
+                let stmt = std::mem::replace(stmt, nop);
+                if let StatementKind::Assign(box (place, ref rvalue)) = stmt.kind {
+                    hoisted.insert(place.local);
+                    mark_moved_locals(rvalue, &mut reused);
+                }
+                basic_blocks[preheader].statements.push(stmt);
+            }
+        }
+
+        // The hoisted locals are now live across the loop, and the locals the hoisted statements
+        // move from may be read at each iteration.
+        reused.union(&hoisted);
+        StorageRemover { tcx, reused_locals: reused }.visit_body_preserves_cfg(body);
+    }
+}
+
+/// Returns true if the rvalue gives the same result at each iteration of the loop when
+/// `is_invariant` returns true for the locals it reads, and can be evaluated speculatively.
+fn is_hoistable<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    body: &Body<'tcx>,
+    ssa: &SsaLocals,
+    rvalue: &Rvalue<'tcx>,
+    is_invariant: impl Fn(Local) -> bool,
+) -> bool {
+    let operand_is_invariant = |operand: &Operand<'tcx>| match operand {
+        Operand::Constant(_) => true,
+        Operand::Copy(place) | Operand::Move(place) => place.as_local().is_some_and(&is_invariant),
+    };
+
+    match *rvalue {
+        Rvalue::BinaryOp(op, box (ref lhs, ref rhs))
+        | Rvalue::CheckedBinaryOp(op, box (ref lhs, ref rhs)) => {
+            // Division and remainder are UB for a zero divisor, and the unchecked operations and
+            // `Offset` for some operands.
+            matches!(
+                op,
+                BinOp::Add
+                    | BinOp::Sub
+                    | BinOp::Mul
+                    | BinOp::BitXor
+                    | BinOp::BitAnd
+                    | BinOp::BitOr
+                    | BinOp::Shl
+                    | BinOp::Shr
+                    | BinOp::Eq
+                    | BinOp::Lt
+                    | BinOp::Le
+                    | BinOp::Ne
+                    | BinOp::Ge
+                    | BinOp::Gt
+            ) && operand_is_invariant(lhs)
+                && operand_is_invariant(rhs)
+        }
+        Rvalue::UnaryOp(_, ref operand) => operand_is_invariant(operand),
+        Rvalue::Cast(kind, ref operand, _) => {
+            // Transmutes may create invalid values, and exposing an address has side effects.
+            matches!(
+                kind,
+                CastKind::IntToInt
+                    | CastKind::FloatToInt
+                    | CastKind::FloatToFloat
+                    | CastKind::IntToFloat
+                    | CastKind::PtrToPtr
+                    | CastKind::FnPtrToPtr
+            ) && operand_is_invariant(operand)
+        }
+        // The length is either a constant or the metadata of a reference.
+        Rvalue::Len(place) => match place.as_ref() {
+            PlaceRef { local, projection: [] } => is_invariant(local),
+            PlaceRef { local, projection: [ProjectionElem::Deref] } => {
+                body.local_decls[local].ty.is_ref() && is_invariant(local)
+            }
+            _ => false,
+        },
+        Rvalue::Discriminant(place) => match place.as_ref() {
+            PlaceRef { local, projection: [] } => is_invariant(local),
+            // A shared reference passed as argument points to the same value for the whole call,
+            // and it cannot change if it has no interior mutability.
+            PlaceRef { local, projection: [ProjectionElem::Deref] } => {
+                let ty::Ref(_, pointee, Mutability::Not) = *body.local_decls[local].ty.kind()
+                else {
+                    return false;
+                };
+                body.local_kind(local) == LocalKind::Arg
+                    && ssa.is_ssa(local)
+                    && pointee.is_freeze(tcx, param_env)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns a block that jumps to the header of the loop and that all the edges entering the loop
+/// go through, creating it if needed.
+fn preheader<'tcx>(
+    body: &mut Body<'tcx>,
+    loop_nest: &LoopNest<BasicBlock>,
+    loop_blocks: &mut IndexVec<LoopIdx, Vec<BasicBlock>>,
+    loop_idx: LoopIdx,
+) -> BasicBlock {
+    let header = loop_nest[loop_idx].header;
+    let mut entries: Vec<BasicBlock> = body.basic_blocks.predecessors()[header]
+        .iter()
+        .copied()
+        .filter(|&pred| !loop_blocks[loop_idx].contains(&pred))
+        .collect();
+    entries.sort_unstable();
+    entries.dedup();
+
+    // The only block entering the loop dominates the header if it only jumps to it.
+    if let [entry] = entries[..]
+        && let entry_data = &body.basic_blocks[entry]
+        && !entry_data.is_cleanup
+        && let TerminatorKind::Goto { .. } = entry_data.terminator().kind
+    {
+        return entry;
+    }
+
+    let source_info = body.basic_blocks[entries[0]].terminator().source_info;
+    let preheader = body.basic_blocks_mut().push(BasicBlockData::new(Some(Terminator {
+        source_info,
+        kind: TerminatorKind::Goto { target: header },
+    })));
+    for entry in entries {
+        for target in body.basic_blocks_mut()[entry].terminator_mut().successors_mut() {
+            if *target == header {
+                *target = preheader;
+            }
+        }
+    }
+
+    // The preheader is part of the loops containing this one, so computations hoisted to it can
+    // be hoisted further.
+    let mut parent = loop_nest[loop_idx].parent;
+    while let Some(parent_idx) = parent {
+        let blocks = &mut loop_blocks[parent_idx];
+        let header_index = blocks.iter().position(|&bb| bb == header).unwrap();
+        blocks.insert(header_index, preheader);
+        parent = loop_nest[parent_idx].parent;
+    }
+    preheader
+}
+
+/// Record the locals the rvalue moves from, as moving from them in the preheader would make them
+/// unusable in the loop.
+fn mark_moved_locals(rvalue: &Rvalue<'_>, moved: &mut BitSet<Local>) {
+    let mut mark = |operand: &Operand<'_>| {
+        if let Operand::Move(place) = operand
+            && let Some(local) = place.as_local()
+        {
+            moved.insert(local);
+        }
+    };
+    match rvalue {
+        Rvalue::BinaryOp(_, box (lhs, rhs)) | Rvalue::CheckedBinaryOp(_, box (lhs, rhs)) => {
+            mark(lhs);
+            mark(rhs);
+        }
+        Rvalue::UnaryOp(_, operand) | Rvalue::Cast(_, operand, _) => mark(operand),
+        _ => {}
+    }
+}
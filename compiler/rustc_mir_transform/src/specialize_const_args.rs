@@ -0,0 +1,362 @@
+//! Specialization of functions for the constant arguments they are called with.
+//!
+//! Calls often pass constants, like flags or lengths, to functions that are too large to be
+//! inlined. This pass redirects such a call to a clone of the callee in which these arguments are
+//! replaced by the constants and which is optimized again, an `InstanceDef::ConstArgShim`:
+//!
+//! ```text
+//! _0 = f(_1, const true)    =>    _0 = const {f - shim(const args)}(_1, const true)
+//! ```
+//!
+//! The clone keeps the signature of the callee, so it is called through a function pointer to
+//! it. It is only used if it is smaller than the callee, which has to be smaller than a threshold
+//! itself. The clones are built by `mir_shims` and instantiated in each codegen unit that uses
+//! them, so the MIR of the callee must be usable by the caller, as for inlining: we only clone
+//! functions that are `cross_crate_inlinable`, which accepts the functions that switch on their
+//! arguments when `-Zmir-specialize-const-args` is passed.
+
+use rustc_hir::def_id::LocalDefId;
+use rustc_index::{Idx, IndexVec};
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::mir::interpret::{Pointer, Scalar};
+use rustc_middle::mir::visit::{MutVisitor, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, Instance, InstanceDef, ParamEnv, Ty, TyCtxt};
+use rustc_session::config::OptLevel;
+use rustc_target::spec::abi::Abi;
+
+use crate::cost_checker::CostChecker;
+use crate::ssa::SsaLocals;
+use crate::{
+    dataflow_const_prop, dead_store_elimination, gvn, jump_threading, pass_manager as pm, simplify,
+    simplify_branches,
+};
+
+/// The fixed arguments are recorded in a `u32` bit mask.
+const MAX_FIXED_ARGS: usize = u32::BITS as usize;
+
+const DEFAULT_THRESHOLD: usize = 250;
+
+pub struct SpecializeConstArgs;
+
+impl<'tcx> MirPass<'tcx> for SpecializeConstArgs {
+    fn is_enabled(&self, sess: &rustc_session::Session) -> bool {
+        if let Some(enabled) = sess.opts.unstable_opts.mir_specialize_const_args {
+            return enabled;
+        }
+        sess.mir_opt_level() >= 3
+    }
+
+    #[instrument(level = "trace", skip(self, tcx, body))]
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        let def_id = body.source.def_id().expect_local();
+        debug!(?def_id);
+        // Like the inliner, only look at fn bodies, and not at coroutines as their `optimized_mir`
+        // is used for layout computation, which could create query cycles.
+        if !tcx.hir().body_owner_kind(def_id).is_fn_or_closure()
+            || body.source.promoted.is_some()
+            || body.coroutine.is_some()
+        {
+            return;
+        }
+        let param_env = tcx.param_env_reveal_all_normalized(def_id);
+
+        let mut specialized = Vec::new();
+        for (bb, data) in body.basic_blocks.iter_enumerated() {
+            if data.is_cleanup {
+                continue;
+            }
+            if let Some(func) = specialize_call(tcx, param_env, def_id, body, data.terminator()) {
+                specialized.push((bb, func));
+            }
+        }
+
+        let basic_blocks = body.basic_blocks.as_mut_preserves_cfg();
+        for (bb, specialized_func) in specialized {
+            if let TerminatorKind::Call { ref mut func, .. } =
+                basic_blocks[bb].terminator_mut().kind
+            {
+                *func = specialized_func;
+            }
+        }
+    }
+}
+
+/// If the terminator is a call passing constants to a function that gets smaller once specialized
+/// for them, returns the pointer to the specialized clone to call instead.
+fn specialize_call<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    caller: LocalDefId,
+    body: &Body<'tcx>,
+    terminator: &Terminator<'tcx>,
+) -> Option<Operand<'tcx>> {
+    let TerminatorKind::Call { ref func, ref args, .. } = terminator.kind else { return None };
+    if !args.iter().take(MAX_FIXED_ARGS).any(|arg| arg.constant().is_some()) {
+        return None;
+    }
+    let ty::FnDef(def_id, generic_args) = *func.ty(body, tcx).kind() else { return None };
+    // To resolve an instance its args have to be fully normalized.
+    let generic_args = tcx.try_normalize_erasing_regions(param_env, generic_args).ok()?;
+    let callee = Instance::resolve(tcx, param_env, def_id, generic_args).ok().flatten()?;
+    let InstanceDef::Item(callee_def_id) = callee.def else { return None };
+    if callee_def_id == caller.to_def_id()
+        || tcx.is_constructor(callee_def_id)
+        || callee.args.non_erasable_generics(tcx, callee_def_id).next().is_some()
+    {
+        return None;
+    }
+
+    // The clone is called through a function pointer, so it must have the calling convention of a
+    // function pointer of the same type.
+    let fn_sig = tcx.fn_sig(callee_def_id).instantiate(tcx, callee.args);
+    if fn_sig.abi() != Abi::Rust || fn_sig.c_variadic() || callee.def.requires_caller_location(tcx)
+    {
+        return None;
+    }
+    let codegen_fn_attrs = tcx.codegen_fn_attrs(callee_def_id);
+    if codegen_fn_attrs.contains_extern_indicator()
+        || codegen_fn_attrs.flags.contains(CodegenFnAttrFlags::NAKED)
+    {
+        return None;
+    }
+
+    if !callee_mir_is_available(tcx, caller, callee) {
+        return None;
+    }
+    // The clone is instantiated where it is used, and may reference anything the callee does.
+    if !tcx.cross_crate_inlinable(callee_def_id) {
+        return None;
+    }
+    let callee_body = tcx.instance_mir(callee.def);
+    // The clone would duplicate the coverage counters of the callee.
+    if callee_body.function_coverage_info.is_some() {
+        return None;
+    }
+
+    // Only fix the arguments that are never modified, so their constants can be propagated.
+    let ssa = SsaLocals::new(callee_body);
+    let mut fixed_args = 0;
+    let mut values = Vec::new();
+    for (index, arg) in args.iter().enumerate().take(MAX_FIXED_ARGS) {
+        if let Some(constant) = arg.constant()
+            && ssa.is_ssa(Local::new(index + 1))
+            && let Some(value) = type_level_const(tcx, param_env, constant)
+        {
+            fixed_args |= 1 << index;
+            values.push(value);
+        }
+    }
+    if fixed_args == 0 {
+        return None;
+    }
+
+    let callee_param_env = tcx.param_env_reveal_all_normalized(callee_def_id);
+    let cost = body_cost(tcx, callee_param_env, callee_body);
+    if cost > threshold(tcx) {
+        debug!(?callee, cost, "callee too large");
+        return None;
+    }
+
+    if !tcx.consider_optimizing(|| {
+        format!("SpecializeConstArgs {callee:?} for {values:?} in {:?}", body.source)
+    }) {
+        return None;
+    }
+
+    let shim = InstanceDef::ConstArgShim {
+        def_id: callee_def_id,
+        fixed_args,
+        values: tcx.mk_const_list(&values),
+    };
+    let specialized_cost = body_cost(tcx, callee_param_env, tcx.instance_mir(shim));
+    debug!(?shim, cost, specialized_cost);
+    if specialized_cost >= cost {
+        return None;
+    }
+
+    let fn_ptr_ty = tcx.erase_regions(Ty::new_fn_ptr(tcx, fn_sig));
+    let alloc_id = tcx.reserve_and_set_fn_alloc(Instance { def: shim, args: callee.args });
+    let ptr = Scalar::from_pointer(Pointer::from(alloc_id), &tcx);
+    Some(Operand::Constant(Box::new(ConstOperand {
+        span: terminator.source_info.span,
+        user_ty: None,
+        const_: Const::from_scalar(tcx, ptr, fn_ptr_ty),
+    })))
+}
+
+/// Whether the MIR of the callee can be used without creating a query cycle, as checked by the
+/// inliner.
+fn callee_mir_is_available<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    caller: LocalDefId,
+    callee: Instance<'tcx>,
+) -> bool {
+    let callee_def_id = callee.def_id();
+    if !tcx.is_mir_available(callee_def_id) {
+        return false;
+    }
+    if !callee_def_id.is_local() {
+        // The MIR from other crates is already optimized.
+        return true;
+    }
+    // Only the function with the lower `DefPathHash` can use the MIR of the other one without
+    // checking that the other one cannot reach it.
+    tcx.def_path_hash(caller.to_def_id()).local_hash()
+        < tcx.def_path_hash(callee_def_id).local_hash()
+        || !tcx.mir_callgraph_reachable((callee, caller))
+}
+
+/// The constant as a type-level constant, if it has a type whose values are leaves of value trees.
+fn type_level_const<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    constant: &ConstOperand<'tcx>,
+) -> Option<ty::Const<'tcx>> {
+    let ty = constant.ty();
+    if !matches!(ty.kind(), ty::Bool | ty::Char | ty::Int(_) | ty::Uint(_)) {
+        return None;
+    }
+    let scalar = constant.const_.try_eval_scalar_int(tcx, param_env)?;
+    Some(ty::Const::new_value(tcx, ty::ValTree::from_scalar_int(scalar), ty))
+}
+
+fn threshold(tcx: TyCtxt<'_>) -> usize {
+    tcx.sess.opts.unstable_opts.mir_specialize_const_args_threshold.unwrap_or(DEFAULT_THRESHOLD)
+}
+
+fn body_cost<'tcx>(tcx: TyCtxt<'tcx>, param_env: ParamEnv<'tcx>, body: &Body<'tcx>) -> usize {
+    let mut checker = CostChecker::new(tcx, param_env, None, body);
+    checker.visit_body(body);
+    checker.cost()
+}
+
+/// Returns true if calls from other crates may want to specialize the function for constant
+/// arguments, which requires its MIR to be available to them: the function is small enough and it
+/// switches on one of its arguments.
+///
+/// Making the MIR available makes the function instantiated in every crate that uses it, like an
+/// `#[inline]` function, so this is only done when the pass is explicitly enabled with
+/// `-Zmir-specialize-const-args` in an optimized build, and not just by `-Zmir-opt-level`.
+pub(crate) fn may_be_specialized<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def_id: LocalDefId,
+    body: &Body<'tcx>,
+) -> bool {
+    if tcx.sess.opts.unstable_opts.mir_specialize_const_args != Some(true)
+        || tcx.sess.opts.optimize == OptLevel::No
+        || !switches_on_argument(body)
+    {
+        return false;
+    }
+    let param_env = tcx.param_env_reveal_all_normalized(def_id);
+    body_cost(tcx, param_env, body) <= threshold(tcx)
+}
+
+/// Returns true if the discriminant of a `SwitchInt` is an argument, or is computed from an
+/// argument in the same block.
+fn switches_on_argument(body: &Body<'_>) -> bool {
+    let is_arg = |operand: &Operand<'_>| {
+        operand
+            .place()
+            .and_then(|place| place.as_local())
+            .is_some_and(|local| body.local_kind(local) == LocalKind::Arg)
+    };
+
+    body.basic_blocks.iter().any(|data| {
+        let TerminatorKind::SwitchInt { ref discr, .. } = data.terminator().kind else {
+            return false;
+        };
+        if is_arg(discr) {
+            return true;
+        }
+        let Some(discr) = discr.place().and_then(|place| place.as_local()) else { return false };
+        data.statements.iter().any(|stmt| {
+            let StatementKind::Assign(box (place, ref rvalue)) = stmt.kind else { return false };
+            place.as_local() == Some(discr)
+                && match rvalue {
+                    Rvalue::BinaryOp(_, box (lhs, rhs)) => is_arg(lhs) || is_arg(rhs),
+                    Rvalue::UnaryOp(_, operand) | Rvalue::Cast(_, operand, _) => is_arg(operand),
+                    _ => false,
+                }
+        })
+    })
+}
+
+/// Builds the body of a `ConstArgShim`: the optimized MIR of the function, in which the fixed
+/// arguments are replaced by their constants, optimized again.
+pub(crate) fn build_const_arg_shim<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: InstanceDef<'tcx>,
+) -> Body<'tcx> {
+    let InstanceDef::ConstArgShim { def_id, fixed_args, values } = instance else {
+        bug!("{instance:?} is not a const argument shim")
+    };
+    let mut body = tcx.optimized_mir(def_id).clone();
+    body.source = MirSource::from_instance(instance);
+
+    // The fixed arguments are never modified, so we replace them by new locals that are assigned
+    // their constants on entry. The arguments themselves become unused.
+    let source_info = SourceInfo::outermost(body.span);
+    let mut replacements = IndexVec::from_elem_n(None, body.local_decls.len() + values.len());
+    let mut assignments = Vec::with_capacity(values.len());
+    let arg_locals = (0..MAX_FIXED_ARGS)
+        .filter(|&index| fixed_args & (1 << index) != 0)
+        .map(|index| Local::new(index + 1));
+    for (arg, value) in arg_locals.zip(values) {
+        let LocalDecl { ty, source_info: arg_source_info, .. } = body.local_decls[arg];
+        let local = body.local_decls.push(LocalDecl::new(ty, arg_source_info.span));
+        replacements[arg] = Some(local);
+        let constant = ConstOperand {
+            span: body.span,
+            user_ty: None,
+            const_: Const::from_ty_const(value, tcx),
+        };
+        assignments.push(Statement {
+            source_info,
+            kind: StatementKind::Assign(Box::new((
+                local.into(),
+                Rvalue::Use(Operand::Constant(Box::new(constant))),
+            ))),
+        });
+    }
+    LocalReplacer { tcx, replacements }.visit_body_preserves_cfg(&mut body);
+    body.basic_blocks.as_mut_preserves_cfg()[START_BLOCK].statements.splice(0..0, assignments);
+
+    pm::run_passes(
+        tcx,
+        &mut body,
+        &[
+            &gvn::GVN,
+            &simplify::SimplifyLocals::AfterGVN,
+            &dataflow_const_prop::DataflowConstProp,
+            &dataflow_const_prop::RemoveRedundantChecks,
+            &simplify_branches::SimplifyConstCondition::AfterConstProp,
+            &jump_threading::JumpThreading,
+            &dead_store_elimination::DeadStoreElimination,
+            &simplify_branches::SimplifyConstCondition::Final,
+            &simplify::SimplifyCfg::Final,
+            &simplify::SimplifyLocals::Final,
+        ],
+        None,
+    );
+    debug!("build_const_arg_shim({:?}) = {:?}", instance, body);
+    body
+}
+
+struct LocalReplacer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    replacements: IndexVec<Local, Option<Local>>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for LocalReplacer<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_local(&mut self, local: &mut Local, _: PlaceContext, _: Location) {
+        if let Some(replacement) = self.replacements[*local] {
+            *local = replacement;
+        }
+    }
+}
@@ -1,6 +1,9 @@
 monomorphize_consider_type_length_limit =
     consider adding a `#![type_length_limit="{$type_length}"]` attribute to your crate
 
+monomorphize_couldnt_dump_mono_bloat =
+    unexpected error occurred while dumping monomorphization bloat: {$error}
+
 monomorphize_couldnt_dump_mono_stats =
     unexpected error occurred while dumping monomorphization stats: {$error}
 
@@ -0,0 +1,171 @@
+//! The call graph of the functions codegened in the current crate, from which
+//! `-Z stack-report` computes the worst-case stack usage of the entry points.
+//!
+//! The calls are read from the MIR of the mono items, so a function that LLVM
+//! inlines into its callers is still a node of the graph: the report then
+//! counts the frames of both functions, which overestimates the stack usage
+//! but never underestimates it.
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::lang_items::LangItem;
+use rustc_middle::mir::mono::{CallGraph, CallGraphNode, Callee, MonoItem};
+use rustc_middle::mir::{AssertKind, TerminatorKind, UnwindAction};
+use rustc_middle::query::Providers;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, InstanceDef, TyCtxt};
+use rustc_span::def_id::DefId;
+
+fn stack_call_graph(tcx: TyCtxt<'_>, (): ()) -> CallGraph {
+    let (_, codegen_units) = tcx.collect_and_partition_mono_items(());
+    // Functions with a local copy in each codegen unit using them appear once.
+    let instances: FxHashSet<Instance<'_>> = codegen_units
+        .iter()
+        .flat_map(|cgu| cgu.items().keys())
+        .filter_map(|item| match *item {
+            MonoItem::Fn(instance) => Some(instance),
+            MonoItem::Static(..) | MonoItem::GlobalAsm(..) => None,
+        })
+        .collect();
+
+    let symbol_name = |instance| tcx.symbol_name(instance).name.to_string();
+    let mut graph = CallGraph::default();
+    for &instance in &instances {
+        let callees = callees(tcx, instance, &instances);
+        let name = with_no_trimmed_paths!(instance.to_string());
+        graph.functions.insert(symbol_name(instance), CallGraphNode { name, callees });
+
+        if let InstanceDef::Item(def_id) = instance.def {
+            if tcx.def_kind(def_id).has_codegen_attrs()
+                && tcx.codegen_fn_attrs(def_id).contains_extern_indicator()
+            {
+                graph.entry_points.push(symbol_name(instance));
+            }
+        }
+    }
+    if let Some((entry_def_id, _)) = tcx.entry_fn(()) {
+        let entry = Instance::mono(tcx, entry_def_id);
+        if instances.contains(&entry) {
+            graph.entry_points.push(symbol_name(entry));
+        }
+    }
+    graph.entry_points.sort_unstable();
+    graph.entry_points.dedup();
+    graph
+}
+
+/// Returns the calls made by the terminators of the MIR of `instance`.
+fn callees<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    instances: &FxHashSet<Instance<'tcx>>,
+) -> Vec<Callee> {
+    let body = tcx.instance_mir(instance.def);
+    let monomorphize = |ty| {
+        instance.instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            ty::ParamEnv::reveal_all(),
+            ty::EarlyBinder::bind(ty),
+        )
+    };
+    let lang_item_call = |lang_item| {
+        let def_id = tcx.require_lang_item(lang_item, None);
+        Callee::Direct(tcx.symbol_name(Instance::mono(tcx, def_id)).name.to_string())
+    };
+
+    let mut callees = vec![];
+    for data in body.basic_blocks.iter() {
+        let terminator = data.terminator();
+        match terminator.kind {
+            TerminatorKind::Call { ref func, .. } => {
+                let callee_ty = monomorphize(func.ty(body, tcx));
+                match *callee_ty.kind() {
+                    ty::FnDef(def_id, args) => {
+                        let param_env = ty::ParamEnv::reveal_all();
+                        let callee = Instance::expect_resolve(tcx, param_env, def_id, args);
+                        callees.extend(instance_call(tcx, callee, instances));
+                    }
+                    _ => callees.push(Callee::FnPtr),
+                }
+            }
+            TerminatorKind::Drop { ref place, .. } => {
+                let ty = monomorphize(place.ty(body, tcx).ty);
+                let callee = Instance::resolve_drop_in_place(tcx, ty);
+                callees.extend(instance_call(tcx, callee, instances));
+            }
+            TerminatorKind::Assert { ref msg, .. } => {
+                callees.push(lang_item_call(match **msg {
+                    AssertKind::BoundsCheck { .. } => LangItem::PanicBoundsCheck,
+                    AssertKind::MisalignedPointerDereference { .. } => {
+                        LangItem::PanicMisalignedPointerDereference
+                    }
+                    _ => LangItem::Panic,
+                }));
+            }
+            TerminatorKind::UnwindTerminate(reason) => {
+                callees.push(lang_item_call(reason.lang_item()));
+            }
+            _ => {}
+        }
+        if let Some(UnwindAction::Terminate(reason)) = terminator.unwind() {
+            callees.push(lang_item_call(reason.lang_item()));
+        }
+    }
+    callees
+}
+
+/// Returns the call to `callee` made by a `Call` or `Drop` terminator, if it
+/// calls a function.
+fn instance_call<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    callee: Instance<'tcx>,
+    instances: &FxHashSet<Instance<'tcx>>,
+) -> Option<Callee> {
+    match callee.def {
+        // Intrinsics are expanded inline, and so is the drop of types without drop glue.
+        InstanceDef::Intrinsic(_) | InstanceDef::DropGlue(_, None) => None,
+        InstanceDef::Virtual(trait_method, _) => Some(virtual_call(tcx, trait_method, instances)),
+        // Dropping a trait object calls the drop glue of its type through its vtable.
+        InstanceDef::DropGlue(_, Some(ty)) if ty.is_trait() => {
+            let mut candidates: Vec<_> = instances
+                .iter()
+                .filter(|instance| matches!(instance.def, InstanceDef::DropGlue(_, Some(_))))
+                .map(|&instance| tcx.symbol_name(instance).name.to_string())
+                .collect();
+            candidates.sort_unstable();
+            // With shared generics, the drop glue can come from another crate.
+            Some(Callee::Virtual { candidates, complete: !tcx.sess.opts.share_generics() })
+        }
+        _ => Some(Callee::Direct(tcx.symbol_name(callee).name.to_string())),
+    }
+}
+
+/// Returns the call through a vtable to `trait_method`: its candidates are
+/// the implementations of the method codegened in the current crate, and the
+/// default implementation if it is.
+fn virtual_call<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    trait_method: DefId,
+    instances: &FxHashSet<Instance<'tcx>>,
+) -> Callee {
+    let implements = |def_id: DefId| {
+        def_id == trait_method
+            || tcx.opt_associated_item(def_id).and_then(|item| item.trait_item_def_id)
+                == Some(trait_method)
+    };
+    let mut candidates: Vec<_> = instances
+        .iter()
+        .filter(|instance| match instance.def {
+            InstanceDef::Item(def_id) | InstanceDef::VTableShim(def_id) => implements(def_id),
+            _ => false,
+        })
+        .map(|&instance| tcx.symbol_name(instance).name.to_string())
+        .collect();
+    candidates.sort_unstable();
+    let trait_def_id = tcx.trait_of_item(trait_method).unwrap();
+    let complete = tcx.all_impls(trait_def_id).all(|impl_def_id| impl_def_id.is_local());
+    Callee::Virtual { candidates, complete }
+}
+
+pub(crate) fn provide(providers: &mut Providers) {
+    providers.stack_call_graph = stack_call_graph;
+}
@@ -123,6 +123,13 @@
 //! pointers to these functions even if they never get called anywhere. This can
 //! be seen as a special case of taking a function reference.
 //!
+//! #### Mentioning functions
+//! A function item can also appear in MIR as a value of its zero-sized type
+//! without being called or turned into a function pointer, e.g. when it is
+//! passed to a generic function that calls it. Such a use only *mentions* the
+//! function: it is recorded in the usage map for tools that inspect the graph,
+//! but doesn't cause the function to be collected.
+//!
 //!
 //! Interaction with Cross-Crate Inlining
 //! -------------------------------------
@@ -164,14 +171,14 @@
 //! this is not implemented however: a mono item will be produced
 //! regardless of whether it is actually needed or not.
 
-use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::{par_for_each_in, MTLock, MTLockRef};
 use rustc_hir as hir;
 use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{DefId, DefIdMap, LocalDefId};
 use rustc_hir::lang_items::LangItem;
 use rustc_middle::mir::interpret::{AllocId, ErrorHandled, GlobalAlloc, Scalar};
-use rustc_middle::mir::mono::{InstantiationMode, MonoItem};
+use rustc_middle::mir::mono::{MonoItem, UsageKind, UsageMap};
 use rustc_middle::mir::visit::Visitor as MirVisitor;
 use rustc_middle::mir::{self, Location};
 use rustc_middle::query::TyCtxtAt;
@@ -204,54 +211,7 @@ pub enum MonoItemCollectionMode {
     Lazy,
 }
 
-pub struct UsageMap<'tcx> {
-    // Maps every mono item to the mono items used by it.
-    used_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
-
-    // Maps every mono item to the mono items that use it.
-    user_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
-}
-
-type MonoItems<'tcx> = Vec<Spanned<MonoItem<'tcx>>>;
-
-impl<'tcx> UsageMap<'tcx> {
-    fn new() -> UsageMap<'tcx> {
-        UsageMap { used_map: FxHashMap::default(), user_map: FxHashMap::default() }
-    }
-
-    fn record_used<'a>(
-        &mut self,
-        user_item: MonoItem<'tcx>,
-        used_items: &'a [Spanned<MonoItem<'tcx>>],
-    ) where
-        'tcx: 'a,
-    {
-        let used_items: Vec<_> = used_items.iter().map(|item| item.node).collect();
-        for &used_item in used_items.iter() {
-            self.user_map.entry(used_item).or_default().push(user_item);
-        }
-
-        assert!(self.used_map.insert(user_item, used_items).is_none());
-    }
-
-    pub fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
-        self.user_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
-    }
-
-    /// Internally iterate over all inlined items used by `item`.
-    pub fn for_each_inlined_used_item<F>(&self, tcx: TyCtxt<'tcx>, item: MonoItem<'tcx>, mut f: F)
-    where
-        F: FnMut(MonoItem<'tcx>),
-    {
-        let used_items = self.used_map.get(&item).unwrap();
-        for used_item in used_items.iter() {
-            let is_inlined = used_item.instantiation_mode(tcx) == InstantiationMode::LocalCopy;
-            if is_inlined {
-                f(*used_item);
-            }
-        }
-    }
-}
+type MonoItems<'tcx> = Vec<(Spanned<MonoItem<'tcx>>, UsageKind)>;
 
 #[instrument(skip(tcx, mode), level = "debug")]
 pub fn collect_crate_mono_items(
@@ -266,7 +226,8 @@ pub fn collect_crate_mono_items(
     debug!("building mono item graph, beginning at roots");
 
     let mut visited = MTLock::new(FxHashSet::default());
-    let mut usage_map = MTLock::new(UsageMap::new());
+    let record_collected_by = tcx.sess.opts.unstable_opts.dump_mono_bloat.enabled();
+    let mut usage_map = MTLock::new(UsageMap::new(record_collected_by));
     let recursion_limit = tcx.recursion_limit();
 
     {
@@ -279,6 +240,7 @@ pub fn collect_crate_mono_items(
                 collect_items_rec(
                     tcx,
                     dummy_spanned(root),
+                    None,
                     visited,
                     &mut recursion_depths,
                     recursion_limit,
@@ -335,6 +297,7 @@ fn collect_roots(tcx: TyCtxt<'_>, mode: MonoItemCollectionMode) -> Vec<MonoItem<
 fn collect_items_rec<'tcx>(
     tcx: TyCtxt<'tcx>,
     starting_item: Spanned<MonoItem<'tcx>>,
+    user: Option<MonoItem<'tcx>>,
     visited: MTLockRef<'_, FxHashSet<MonoItem<'tcx>>>,
     recursion_depths: &mut DefIdMap<usize>,
     recursion_limit: Limit,
@@ -344,6 +307,11 @@ fn collect_items_rec<'tcx>(
         // We've been here already, no need to search again.
         return;
     }
+    if let Some(user) = user {
+        usage_map
+            .lock_mut()
+            .record_collected_by(starting_item.node, respan(starting_item.span, user));
+    }
 
     let mut used_items = Vec::new();
     let recursion_depth_reset;
@@ -387,17 +355,20 @@ fn collect_items_rec<'tcx>(
 
             if let Ok(alloc) = tcx.eval_static_initializer(def_id) {
                 for &prov in alloc.inner().provenance().ptrs().values() {
-                    collect_alloc(tcx, prov.alloc_id(), &mut used_items);
+                    collect_alloc(tcx, prov.alloc_id(), UsageKind::FnPointer, &mut used_items);
                 }
             }
 
             if tcx.needs_thread_local_shim(def_id) {
-                used_items.push(respan(
-                    starting_item.span,
-                    MonoItem::Fn(Instance {
-                        def: InstanceDef::ThreadLocalShim(def_id),
-                        args: GenericArgs::empty(),
-                    }),
+                used_items.push((
+                    respan(
+                        starting_item.span,
+                        MonoItem::Fn(Instance {
+                            def: InstanceDef::ThreadLocalShim(def_id),
+                            args: GenericArgs::empty(),
+                        }),
+                    ),
+                    UsageKind::Static,
                 ));
             }
         }
@@ -440,7 +411,10 @@ fn collect_items_rec<'tcx>(
                             let instance = Instance::mono(tcx, *def_id);
                             if should_codegen_locally(tcx, &instance) {
                                 trace!("collecting static {:?}", def_id);
-                                used_items.push(dummy_spanned(MonoItem::Static(*def_id)));
+                                used_items.push((
+                                    dummy_spanned(MonoItem::Static(*def_id)),
+                                    UsageKind::Static,
+                                ));
                             }
                         }
                         hir::InlineAsmOperand::In { .. }
@@ -471,8 +445,19 @@ fn collect_items_rec<'tcx>(
     }
     usage_map.lock_mut().record_used(starting_item.node, &used_items);
 
-    for used_item in used_items {
-        collect_items_rec(tcx, used_item, visited, recursion_depths, recursion_limit, usage_map);
+    for (used_item, kind) in used_items {
+        if kind == UsageKind::Mentioned {
+            continue;
+        }
+        collect_items_rec(
+            tcx,
+            used_item,
+            Some(starting_item.node),
+            visited,
+            recursion_depths,
+            recursion_limit,
+            usage_map,
+        );
     }
 
     if let Some((def_id, depth)) = recursion_depth_reset {
@@ -760,7 +745,10 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
                         )
                         .expect("failed to normalize and resolve closure during codegen");
                         if should_codegen_locally(self.tcx, &instance) {
-                            self.output.push(create_fn_mono_item(self.tcx, instance, span));
+                            self.output.push((
+                                create_fn_mono_item(self.tcx, instance, span),
+                                UsageKind::FnPointer,
+                            ));
                         }
                     }
                     _ => bug!(),
@@ -771,7 +759,7 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
                 let instance = Instance::mono(self.tcx, def_id);
                 if should_codegen_locally(self.tcx, &instance) {
                     trace!("collecting thread-local static {:?}", def_id);
-                    self.output.push(respan(span, MonoItem::Static(def_id)));
+                    self.output.push((respan(span, MonoItem::Static(def_id)), UsageKind::Static));
                 }
             }
             _ => { /* not interesting */ }
@@ -798,6 +786,23 @@ fn visit_constant(&mut self, constant: &mir::ConstOperand<'tcx>, location: Locat
         };
         collect_const_value(self.tcx, val, self.output);
         MirVisitor::visit_ty(self, const_.ty(), TyContext::Location(location));
+
+        // A function item is mentioned by the value of its type, which doesn't require it to be
+        // collected unless it is also called or turned into a function pointer. Mentions are
+        // only recorded, so whether the function would be codegened locally isn't checked.
+        if !self.tcx.sess.opts.unstable_opts.record_mono_item_mentions {
+            return;
+        }
+        if let ty::FnDef(def_id, args) = *const_.ty().kind() {
+            let Ok(Some(instance)) = Instance::resolve(self.tcx, param_env, def_id, args) else {
+                return;
+            };
+            if !matches!(instance.def, InstanceDef::Virtual(..) | InstanceDef::Intrinsic(_)) {
+                let span = self.body.source_info(location).span;
+                self.output
+                    .push((create_fn_mono_item(self.tcx, instance, span), UsageKind::Mentioned));
+            }
+        }
     }
 
     fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Location) {
@@ -808,7 +813,7 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
         let push_mono_lang_item = |this: &mut Self, lang_item: LangItem| {
             let instance = Instance::mono(tcx, tcx.require_lang_item(lang_item, Some(source)));
             if should_codegen_locally(tcx, &instance) {
-                this.output.push(create_fn_mono_item(tcx, instance, source));
+                this.output.push((create_fn_mono_item(tcx, instance, source), UsageKind::Call));
             }
         };
 
@@ -835,7 +840,10 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
                             let instance = Instance::mono(self.tcx, def_id);
                             if should_codegen_locally(self.tcx, &instance) {
                                 trace!("collecting asm sym static {:?}", def_id);
-                                self.output.push(respan(source, MonoItem::Static(def_id)));
+                                self.output.push((
+                                    respan(source, MonoItem::Static(def_id)),
+                                    UsageKind::Static,
+                                ));
                             }
                         }
                         _ => {}
@@ -889,7 +897,7 @@ fn visit_drop_use<'tcx>(
     output: &mut MonoItems<'tcx>,
 ) {
     let instance = Instance::resolve_drop_in_place(tcx, ty);
-    visit_instance_use(tcx, instance, is_direct_call, source, output);
+    visit_instance_use(tcx, instance, is_direct_call, UsageKind::DropGlue, source, output);
 }
 
 fn visit_fn_use<'tcx>(
@@ -908,7 +916,8 @@ fn visit_fn_use<'tcx>(
                 _ => bug!("failed to resolve instance for {ty}"),
             }
         };
-        visit_instance_use(tcx, instance, is_direct_call, source, output);
+        let kind = if is_direct_call { UsageKind::Call } else { UsageKind::FnPointer };
+        visit_instance_use(tcx, instance, is_direct_call, kind, source, output);
     }
 }
 
@@ -916,6 +925,7 @@ fn visit_instance_use<'tcx>(
     tcx: TyCtxt<'tcx>,
     instance: ty::Instance<'tcx>,
     is_direct_call: bool,
+    kind: UsageKind,
     source: Span,
     output: &mut MonoItems<'tcx>,
 ) {
@@ -934,7 +944,7 @@ fn visit_instance_use<'tcx>(
             let def_id = tcx.lang_items().get(LangItem::PanicNounwind).unwrap();
             let panic_instance = Instance::mono(tcx, def_id);
             if should_codegen_locally(tcx, &panic_instance) {
-                output.push(create_fn_mono_item(tcx, panic_instance, source));
+                output.push((create_fn_mono_item(tcx, panic_instance, source), UsageKind::Call));
             }
         }
     }
@@ -951,7 +961,7 @@ fn visit_instance_use<'tcx>(
         ty::InstanceDef::DropGlue(_, None) => {
             // Don't need to emit noop drop glue if we are calling directly.
             if !is_direct_call {
-                output.push(create_fn_mono_item(tcx, instance, source));
+                output.push((create_fn_mono_item(tcx, instance, source), kind));
             }
         }
         ty::InstanceDef::DropGlue(_, Some(_))
@@ -961,8 +971,9 @@ fn visit_instance_use<'tcx>(
         | ty::InstanceDef::Item(..)
         | ty::InstanceDef::FnPtrShim(..)
         | ty::InstanceDef::CloneShim(..)
-        | ty::InstanceDef::FnPtrAddrShim(..) => {
-            output.push(create_fn_mono_item(tcx, instance, source));
+        | ty::InstanceDef::FnPtrAddrShim(..)
+        | ty::InstanceDef::ConstArgShim { .. } => {
+            output.push((create_fn_mono_item(tcx, instance, source), kind));
         }
     }
 }
@@ -1159,12 +1170,13 @@ fn create_mono_items_for_vtable_methods<'tcx>(
                         Some(*instance).filter(|instance| should_codegen_locally(tcx, instance))
                     }
                 })
-                .map(|item| create_fn_mono_item(tcx, item, source));
+                .map(|item| (create_fn_mono_item(tcx, item, source), UsageKind::Vtable));
             output.extend(methods);
         }
 
         // Also add the destructor.
-        visit_drop_use(tcx, impl_ty, false, source, output);
+        let drop_instance = Instance::resolve_drop_in_place(tcx, impl_ty);
+        visit_instance_use(tcx, drop_instance, false, UsageKind::Vtable, source, output);
     }
 }
 
@@ -1175,7 +1187,7 @@ fn create_mono_items_for_vtable_methods<'tcx>(
 struct RootCollector<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
     mode: MonoItemCollectionMode,
-    output: &'a mut MonoItems<'tcx>,
+    output: &'a mut Vec<Spanned<MonoItem<'tcx>>>,
     entry_fn: Option<(DefId, EntryFnType)>,
 }
 
@@ -1189,7 +1201,7 @@ fn process_item(&mut self, id: hir::ItemId) {
                     debug!("RootCollector: ADT drop-glue for `{id:?}`",);
 
                     let ty = self.tcx.type_of(id.owner_id.to_def_id()).no_bound_vars().unwrap();
-                    visit_drop_use(self.tcx, ty, true, DUMMY_SP, self.output);
+                    self.push_used(|tcx, output| visit_drop_use(tcx, ty, true, DUMMY_SP, output));
                 }
             }
             DefKind::GlobalAsm => {
@@ -1210,7 +1222,7 @@ fn process_item(&mut self, id: hir::ItemId) {
 
                 // but even just declaring them must collect the items they refer to
                 if let Ok(val) = self.tcx.const_eval_poly(id.owner_id.to_def_id()) {
-                    collect_const_value(self.tcx, val, self.output);
+                    self.push_used(|tcx, output| collect_const_value(tcx, val, output));
                 }
             }
             DefKind::Impl { .. } => {
@@ -1225,6 +1237,13 @@ fn process_item(&mut self, id: hir::ItemId) {
         }
     }
 
+    /// Pushes the items that `collect` finds as roots.
+    fn push_used(&mut self, collect: impl FnOnce(TyCtxt<'v>, &mut MonoItems<'v>)) {
+        let mut used_items = MonoItems::new();
+        collect(self.tcx, &mut used_items);
+        self.output.extend(used_items.into_iter().map(|(item, _)| item));
+    }
+
     fn process_impl_item(&mut self, id: hir::ImplItemId) {
         if matches!(self.tcx.def_kind(id.owner_id), DefKind::AssocFn) {
             self.push_if_root(id.owner_id.def_id);
@@ -1299,7 +1318,7 @@ fn push_extra_entry_roots(&mut self) {
 fn create_mono_items_for_default_impls<'tcx>(
     tcx: TyCtxt<'tcx>,
     item: hir::ItemId,
-    output: &mut MonoItems<'tcx>,
+    output: &mut Vec<Spanned<MonoItem<'tcx>>>,
 ) {
     let polarity = tcx.impl_polarity(item.owner_id);
     if matches!(polarity, ty::ImplPolarity::Negative) {
@@ -1369,34 +1388,40 @@ fn create_mono_items_for_default_impls<'tcx>(
     }
 }
 
-/// Scans the CTFE alloc in order to find function calls, closures, and drop-glue.
-fn collect_alloc<'tcx>(tcx: TyCtxt<'tcx>, alloc_id: AllocId, output: &mut MonoItems<'tcx>) {
+/// Scans the CTFE alloc in order to find function calls, closures, and drop-glue. The functions
+/// it points to are used with `fn_kind`, unless they are in a vtable.
+fn collect_alloc<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    alloc_id: AllocId,
+    fn_kind: UsageKind,
+    output: &mut MonoItems<'tcx>,
+) {
     match tcx.global_alloc(alloc_id) {
         GlobalAlloc::Static(def_id) => {
             assert!(!tcx.is_thread_local_static(def_id));
             let instance = Instance::mono(tcx, def_id);
             if should_codegen_locally(tcx, &instance) {
                 trace!("collecting static {:?}", def_id);
-                output.push(dummy_spanned(MonoItem::Static(def_id)));
+                output.push((dummy_spanned(MonoItem::Static(def_id)), UsageKind::Static));
             }
         }
         GlobalAlloc::Memory(alloc) => {
             trace!("collecting {:?} with {:#?}", alloc_id, alloc);
             for &prov in alloc.inner().provenance().ptrs().values() {
                 rustc_data_structures::stack::ensure_sufficient_stack(|| {
-                    collect_alloc(tcx, prov.alloc_id(), output);
+                    collect_alloc(tcx, prov.alloc_id(), fn_kind, output);
                 });
             }
         }
         GlobalAlloc::Function(fn_instance) => {
             if should_codegen_locally(tcx, &fn_instance) {
                 trace!("collecting {:?} with {:#?}", alloc_id, fn_instance);
-                output.push(create_fn_mono_item(tcx, fn_instance, DUMMY_SP));
+                output.push((create_fn_mono_item(tcx, fn_instance, DUMMY_SP), fn_kind));
             }
         }
         GlobalAlloc::VTable(ty, trait_ref) => {
             let alloc_id = tcx.vtable_allocation((ty, trait_ref));
-            collect_alloc(tcx, alloc_id, output)
+            collect_alloc(tcx, alloc_id, UsageKind::Vtable, output)
         }
     }
 }
@@ -1449,6 +1474,20 @@ fn collect_used_items<'tcx>(
         skip_move_check_fns: None,
     }
     .visit_body(body);
+
+    // The resolver selecting the clone of a `#[target_clones]` function to call checks the CPU
+    // features with a lang item.
+    if let ty::InstanceDef::Item(def_id) = instance.def {
+        if tcx.def_kind(def_id).has_codegen_attrs()
+            && !tcx.codegen_fn_attrs(def_id).target_clones.is_empty()
+        {
+            if let Some(supported_fn) = tcx.lang_items().target_clone_supported_fn() {
+                let supported_fn = Instance::mono(tcx, supported_fn);
+                let span = tcx.def_span(def_id);
+                visit_instance_use(tcx, supported_fn, true, UsageKind::Call, span, output);
+            }
+        }
+    }
 }
 
 #[instrument(skip(tcx, output), level = "debug")]
@@ -1459,12 +1498,14 @@ fn collect_const_value<'tcx>(
 ) {
     match value {
         mir::ConstValue::Scalar(Scalar::Ptr(ptr, _size)) => {
-            collect_alloc(tcx, ptr.provenance.alloc_id(), output)
+            collect_alloc(tcx, ptr.provenance.alloc_id(), UsageKind::FnPointer, output)
+        }
+        mir::ConstValue::Indirect { alloc_id, .. } => {
+            collect_alloc(tcx, alloc_id, UsageKind::FnPointer, output)
         }
-        mir::ConstValue::Indirect { alloc_id, .. } => collect_alloc(tcx, alloc_id, output),
         mir::ConstValue::Slice { data, meta: _ } => {
             for &prov in data.inner().provenance().ptrs().values() {
-                collect_alloc(tcx, prov.alloc_id(), output);
+                collect_alloc(tcx, prov.alloc_id(), UsageKind::FnPointer, output);
             }
         }
         _ => {}
@@ -80,6 +80,12 @@ pub struct SymbolAlreadyDefined {
     pub symbol: String,
 }
 
+#[derive(Diagnostic)]
+#[diag(monomorphize_couldnt_dump_mono_bloat)]
+pub struct CouldntDumpMonoBloat {
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(monomorphize_couldnt_dump_mono_stats)]
 pub struct CouldntDumpMonoStats {
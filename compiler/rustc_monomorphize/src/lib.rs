@@ -16,6 +16,7 @@
 use rustc_middle::ty::adjustment::CustomCoerceUnsized;
 use rustc_middle::ty::{self, Ty};
 
+mod call_graph;
 mod collector;
 mod errors;
 mod partitioning;
@@ -48,6 +49,7 @@ fn custom_coerce_unsize_info<'tcx>(
 }
 
 pub fn provide(providers: &mut Providers) {
+    call_graph::provide(providers);
     partitioning::provide(providers);
     polymorphize::provide(providers);
 }
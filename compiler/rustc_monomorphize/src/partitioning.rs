@@ -107,7 +107,7 @@
 use rustc_middle::middle::exported_symbols::{SymbolExportInfo, SymbolExportLevel};
 use rustc_middle::mir::mono::{
     CodegenUnit, CodegenUnitNameBuilder, InstantiationMode, Linkage, MonoItem, MonoItemData,
-    Visibility,
+    UsageMap, Visibility,
 };
 use rustc_middle::query::Providers;
 use rustc_middle::ty::print::{characteristic_def_id_of_type, with_no_trimmed_paths};
@@ -116,9 +116,10 @@
 use rustc_session::CodegenUnits;
 use rustc_span::symbol::Symbol;
 
-use crate::collector::UsageMap;
 use crate::collector::{self, MonoItemCollectionMode};
-use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode};
+use crate::errors::{
+    CouldntDumpMonoBloat, CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode,
+};
 
 struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -625,7 +626,8 @@ fn characteristic_def_id_of_mono_item<'tcx>(
                 | ty::InstanceDef::Virtual(..)
                 | ty::InstanceDef::CloneShim(..)
                 | ty::InstanceDef::ThreadLocalShim(..)
-                | ty::InstanceDef::FnPtrAddrShim(..) => return None,
+                | ty::InstanceDef::FnPtrAddrShim(..)
+                | ty::InstanceDef::ConstArgShim { .. } => return None,
             };
 
             // If this is a method, we want to put it into the same module as
@@ -785,7 +787,8 @@ fn mono_item_visibility<'tcx>(
         | InstanceDef::ClosureOnceShim { .. }
         | InstanceDef::DropGlue(..)
         | InstanceDef::CloneShim(..)
-        | InstanceDef::FnPtrAddrShim(..) => return Visibility::Hidden,
+        | InstanceDef::FnPtrAddrShim(..)
+        | InstanceDef::ConstArgShim { .. } => return Visibility::Hidden,
     };
 
     // The `start_fn` lang item is actually a monomorphized instance of a
@@ -1084,8 +1087,9 @@ fn assert_symbols_are_distinct<'a, 'tcx, I>(tcx: TyCtxt<'tcx>, mono_items: I)
     }
 }
 
-fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[CodegenUnit<'_>]) {
-    let collection_mode = match tcx.sess.opts.unstable_opts.print_mono_items {
+/// The collection mode to use for the local crate, which `-Z print-mono-items` may override.
+fn collection_mode(tcx: TyCtxt<'_>) -> MonoItemCollectionMode {
+    match tcx.sess.opts.unstable_opts.print_mono_items {
         Some(ref s) => {
             let mode = s.to_lowercase();
             let mode = mode.trim();
@@ -1106,16 +1110,22 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
                 MonoItemCollectionMode::Lazy
             }
         }
-    };
+    }
+}
 
-    let (items, usage_map) = collector::collect_crate_mono_items(tcx, collection_mode);
+fn collect_mono_items(tcx: TyCtxt<'_>, (): ()) -> (FxHashSet<MonoItem<'_>>, UsageMap<'_>) {
+    collector::collect_crate_mono_items(tcx, collection_mode(tcx))
+}
+
+fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[CodegenUnit<'_>]) {
+    let (items, usage_map) = tcx.collect_mono_items(());
 
     tcx.dcx().abort_if_errors();
 
     let (codegen_units, _) = tcx.sess.time("partition_and_assert_distinct_symbols", || {
         sync::join(
             || {
-                let mut codegen_units = partition(tcx, items.iter().copied(), &usage_map);
+                let mut codegen_units = partition(tcx, items.iter().copied(), usage_map);
                 codegen_units[0].make_primary();
                 &*tcx.arena.alloc_from_iter(codegen_units)
             },
@@ -1152,6 +1162,14 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
         }
     }
 
+    // Output the instantiations of generic items and why they were collected
+    if let SwitchWithOptPath::Enabled(ref path) = tcx.sess.opts.unstable_opts.dump_mono_bloat {
+        if let Err(err) = dump_mono_bloat(tcx, items, usage_map, path, tcx.crate_name(LOCAL_CRATE))
+        {
+            tcx.dcx().emit_fatal(CouldntDumpMonoBloat { error: err.to_string() });
+        }
+    }
+
     if tcx.sess.opts.unstable_opts.print_mono_items.is_some() {
         let mut item_to_cgus: FxHashMap<_, Vec<_>> = Default::default();
 
@@ -1284,7 +1302,147 @@ struct MonoItem {
     Ok(())
 }
 
+/// The number of instantiations per generic item whose chain of uses is output
+/// by `-Z dump-mono-bloat`, from the largest.
+const MONO_BLOAT_CHAINS: usize = 3;
+
+/// Outputs the instantiations of every generic item and their estimated size,
+/// with the chains of uses through which the largest ones were collected, to a
+/// file in the given output directory.
+fn dump_mono_bloat<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    usage_map: &UsageMap<'tcx>,
+    output_directory: &Option<PathBuf>,
+    crate_name: Symbol,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    let format = tcx.sess.opts.unstable_opts.dump_mono_stats_format;
+    let ext = format.extension();
+    let filename = format!("{crate_name}.mono_bloat.{ext}");
+    let output_path = output_directory.join(&filename);
+    let file = File::create(&output_path)?;
+    let mut file = BufWriter::new(file);
+
+    // Gather the instantiations of generic items grouped by def_id
+    let mut items_per_def_id: FxHashMap<_, Vec<_>> = Default::default();
+    for &mono_item in items {
+        if mono_item.is_user_defined() && mono_item.is_generic_fn(tcx) {
+            items_per_def_id.entry(mono_item.def_id()).or_default().push(mono_item);
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct GenericItem {
+        name: String,
+        instantiation_count: usize,
+        total_estimate: usize,
+        largest_instantiations: Vec<Instantiation>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Instantiation {
+        name: String,
+        size_estimate: usize,
+        /// The mono items through which the instantiation was collected,
+        /// starting with a root.
+        chain: Vec<ChainItem>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct ChainItem {
+        name: String,
+        /// Where the previous item of the chain uses this one.
+        used_at: Option<String>,
+    }
+
+    let source_map = tcx.sess.source_map();
+    let collection_chain = |mono_item| {
+        let chain = usage_map.collection_chain(mono_item).unwrap_or_default();
+        chain
+            .into_iter()
+            .map(|link| ChainItem {
+                name: with_no_trimmed_paths!(link.node.to_string()),
+                used_at: (!link.span.is_dummy())
+                    .then(|| source_map.span_to_embeddable_string(link.span)),
+            })
+            .collect()
+    };
+
+    // Output items sorted by total instantiated size, from heaviest to lightest
+    let mut stats: Vec<_> = items_per_def_id
+        .into_iter()
+        .map(|(def_id, instances)| {
+            let name = with_no_trimmed_paths!(tcx.def_path_str(def_id));
+            let instantiation_count = instances.len();
+            let mut instantiations: Vec<_> = instances
+                .into_iter()
+                .map(|item| {
+                    let name = with_no_trimmed_paths!(item.to_string());
+                    (item, name, item.size_estimate(tcx))
+                })
+                .collect();
+            let total_estimate = instantiations.iter().map(|&(_, _, size)| size).sum();
+            // Sort by name among instantiations of the same size, to be deterministic
+            instantiations.sort_unstable_by(|(_, a_name, a_size), (_, b_name, b_size)| {
+                b_size.cmp(a_size).then_with(|| a_name.cmp(b_name))
+            });
+            let largest_instantiations = instantiations
+                .into_iter()
+                .take(MONO_BLOAT_CHAINS)
+                .map(|(item, name, size_estimate)| Instantiation {
+                    name,
+                    size_estimate,
+                    chain: collection_chain(item),
+                })
+                .collect();
+            GenericItem { name, instantiation_count, total_estimate, largest_instantiations }
+        })
+        .collect();
+    stats.sort_unstable_by(|a, b| {
+        b.total_estimate.cmp(&a.total_estimate).then_with(|| a.name.cmp(&b.name))
+    });
+
+    if !stats.is_empty() {
+        match format {
+            DumpMonoStatsFormat::Json => serde_json::to_writer(file, &stats)?,
+            DumpMonoStatsFormat::Markdown => {
+                writeln!(file, "| Item | Instantiation count | Total Estimated Cost |")?;
+                writeln!(file, "| --- | ---: | ---: |")?;
+                for GenericItem { name, instantiation_count, total_estimate, .. } in &stats {
+                    writeln!(file, "| `{name}` | {instantiation_count} | {total_estimate} |")?;
+                }
+
+                for GenericItem { name, largest_instantiations, .. } in &stats {
+                    writeln!(file, "\n## `{name}`\n")?;
+                    for Instantiation { name, size_estimate, chain } in largest_instantiations {
+                        writeln!(file, "- `{name}` (estimated cost {size_estimate}), through:")?;
+                        for (i, ChainItem { name, used_at }) in chain.iter().enumerate() {
+                            let index = i + 1;
+                            match used_at {
+                                Some(used_at) => {
+                                    writeln!(file, "  {index}. `{name}`, used at {used_at}")?
+                                }
+                                None => writeln!(file, "  {index}. `{name}`")?,
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub fn provide(providers: &mut Providers) {
+    providers.collect_mono_items = collect_mono_items;
     providers.collect_and_partition_mono_items = collect_and_partition_mono_items;
 
     providers.is_codegened_item = |tcx, def_id| {
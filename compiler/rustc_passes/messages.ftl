@@ -109,6 +109,9 @@ passes_continue_labeled_block =
     .label = labeled blocks cannot be `continue`'d
     .block_label = labeled block the `continue` points to
 
+passes_couldnt_write_api_usage =
+    unexpected error occurred while writing the API usage of the crate: {$error}
+
 passes_coverage_fn_defn =
     `#[coverage]` may only be applied to function definitions
 
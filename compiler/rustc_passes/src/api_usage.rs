@@ -0,0 +1,185 @@
+//! `-Z api-usage`: outputs the public items of the crate and the items of other
+//! crates it uses, identified by their `DefPathHash`.
+//!
+//! The `dead_code` lint only sees one crate, so it cannot report the public items
+//! of a library that no crate of a workspace uses. Building each crate of the
+//! workspace with this flag and combining the files with the `unused-pub-api`
+//! tool reports them instead. Since the `DefPathHash` of an item is the same in
+//! every crate, the uses recorded by a crate can be matched against the exports
+//! recorded by the crate defining the item.
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir as hir;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{DefId, LocalDefId, CRATE_DEF_ID, LOCAL_CRATE};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_middle::hir::nested_filter;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::CrateType;
+
+use crate::errors::CouldntWriteApiUsage;
+
+#[derive(serde::Serialize)]
+struct ApiUsage {
+    crate_name: String,
+    /// The public items of the crate that other crates can use, empty unless
+    /// the crate is a Rust library.
+    exports: Vec<Item>,
+    /// The items of other crates used by the crate.
+    uses: Vec<Item>,
+}
+
+#[derive(serde::Serialize)]
+struct Item {
+    def_path_hash: String,
+    path: String,
+    kind: &'static str,
+    crate_name: String,
+}
+
+/// Writes the public items of the crate and the items of other crates it uses
+/// to `{crate_name}.api_usage.json` in the given output directory.
+pub fn write(tcx: TyCtxt<'_>, output_directory: &Option<PathBuf>) {
+    if let Err(err) = try_write(tcx, output_directory) {
+        tcx.dcx().emit_fatal(CouldntWriteApiUsage { error: err.to_string() });
+    }
+}
+
+fn try_write(
+    tcx: TyCtxt<'_>,
+    output_directory: &Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let is_library =
+        tcx.crate_types().iter().any(|ty| matches!(ty, CrateType::Rlib | CrateType::Dylib));
+    let exports = if is_library { exported_items(tcx) } else { vec![] };
+
+    let mut collector = UseCollector { tcx, uses: Default::default() };
+    tcx.hir().visit_all_item_likes_in_crate(&mut collector);
+    for def_id in tcx.hir().body_owners() {
+        if tcx.is_typeck_child(def_id.to_def_id()) {
+            continue;
+        }
+        // Method calls and associated items named through a type, such as
+        // `Vec::new`, are resolved by type checking.
+        for (_, res) in tcx.typeck(def_id).type_dependent_defs().items_in_stable_order() {
+            if let Ok((_, def_id)) = *res {
+                collector.record(def_id);
+            }
+        }
+    }
+
+    let item = |def_id: DefId| Item {
+        def_path_hash: tcx.def_path_hash(def_id).0.to_hex(),
+        path: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+        kind: tcx.def_descr(def_id),
+        crate_name: tcx.crate_name(def_id.krate).to_string(),
+    };
+    let mut exports: Vec<_> = exports.into_iter().map(|def_id| item(def_id.to_def_id())).collect();
+    // We will soon sort, so the initial order does not matter.
+    #[allow(rustc::potential_query_instability)]
+    let mut uses: Vec<_> = collector.uses.into_iter().map(item).collect();
+    // Sort by hash among items with the same path, to be deterministic
+    for items in [&mut exports, &mut uses] {
+        items.sort_unstable_by(|a, b| {
+            a.path.cmp(&b.path).then_with(|| a.def_path_hash.cmp(&b.def_path_hash))
+        });
+    }
+
+    let usage = ApiUsage { crate_name: crate_name.to_string(), exports, uses };
+    let output_path = output_directory.join(format!("{crate_name}.api_usage.json"));
+    let file = BufWriter::new(File::create(output_path)?);
+    serde_json::to_writer(file, &usage)?;
+    Ok(())
+}
+
+/// Returns the items other crates can name through the public modules of the
+/// crate, as encoded in its metadata: the module children reachable from the
+/// crate root, and the public associated items of their inherent impls.
+///
+/// Macros are left out, since their uses are expanded away before name
+/// resolution records them, and so are trait items and enum variants, which
+/// are used through their trait or enum.
+fn exported_items(tcx: TyCtxt<'_>) -> Vec<LocalDefId> {
+    let mut exports = vec![];
+    let mut seen = FxHashSet::default();
+    let mut modules = vec![CRATE_DEF_ID];
+    while let Some(module) = modules.pop() {
+        for child in tcx.module_children_local(module) {
+            if !child.vis.is_public() {
+                continue;
+            }
+            // Reexports of items of other crates are uses, recorded by the use
+            // collector, rather than exports.
+            let Res::Def(kind, def_id) = child.res else { continue };
+            let Some(def_id) = def_id.as_local() else { continue };
+            if !seen.insert(def_id) {
+                continue;
+            }
+            match kind {
+                DefKind::Mod => modules.push(def_id),
+                DefKind::Macro(_) | DefKind::Ctor(..) => {}
+                DefKind::Struct | DefKind::Enum | DefKind::Union | DefKind::ForeignTy => {
+                    exports.push(def_id);
+                    for &impl_def_id in tcx.inherent_impls(def_id.to_def_id()) {
+                        exports.extend(
+                            tcx.associated_item_def_ids(impl_def_id)
+                                .iter()
+                                .filter(|&&item| tcx.visibility(item).is_public())
+                                .filter_map(|item| item.as_local())
+                                .filter(|&item| seen.insert(item)),
+                        );
+                    }
+                }
+                _ => exports.push(def_id),
+            }
+        }
+    }
+    exports
+}
+
+/// Collects the items of other crates named by the paths of the crate.
+struct UseCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    uses: FxHashSet<DefId>,
+}
+
+impl<'tcx> UseCollector<'tcx> {
+    fn record(&mut self, mut def_id: DefId) {
+        if def_id.is_local() {
+            return;
+        }
+        // Constructors and variants are used through their struct or enum.
+        while matches!(self.tcx.def_kind(def_id), DefKind::Ctor(..) | DefKind::Variant) {
+            def_id = self.tcx.parent(def_id);
+        }
+        self.uses.insert(def_id);
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for UseCollector<'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_path(&mut self, path: &hir::Path<'tcx>, _: hir::HirId) {
+        if let Res::Def(_, def_id) = path.res {
+            self.record(def_id);
+        }
+        intravisit::walk_path(self, path);
+    }
+}
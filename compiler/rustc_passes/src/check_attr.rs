@@ -123,6 +123,9 @@ fn check_attributes(
                 sym::non_exhaustive => self.check_non_exhaustive(hir_id, attr, span, target),
                 sym::marker => self.check_marker(hir_id, attr, span, target),
                 sym::target_feature => self.check_target_feature(hir_id, attr, span, target, attrs),
+                sym::target_clones => {
+                    self.check_applied_to_fn_or_method(hir_id, attr, span, target)
+                }
                 sym::thread_local => self.check_thread_local(attr, span, target),
                 sym::track_caller => {
                     self.check_track_caller(hir_id, attr.span, attrs, span, target)
@@ -609,6 +609,12 @@ pub struct DebugVisualizerInvalid {
     pub span: Span,
 }
 
+#[derive(Diagnostic)]
+#[diag(passes_couldnt_write_api_usage)]
+pub struct CouldntWriteApiUsage {
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(passes_debug_visualizer_unreadable)]
 pub struct DebugVisualizerUnreadable<'a> {
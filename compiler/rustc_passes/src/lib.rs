@@ -26,6 +26,7 @@
 use rustc_middle::query::Providers;
 
 pub mod abi_test;
+pub mod api_usage;
 mod check_attr;
 mod check_const;
 pub mod dead;
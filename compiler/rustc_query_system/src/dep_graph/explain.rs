@@ -0,0 +1,95 @@
+//! Recording why nodes of the previous dep-graph could not be reused, for
+//! `-Z incremental-explain`.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lock;
+
+use super::{DepNode, SerializedDepNodeIndex};
+
+/// Remembers, for each node of the previous dep-graph that could not be
+/// marked green, the dependency that was found to be red.
+pub struct InvalidationRecorder {
+    /// The tracked command-line options that changed since the previous
+    /// session. If there are any, the previous dep-graph was discarded.
+    changed_options: Vec<String>,
+    red_deps: Lock<FxHashMap<SerializedDepNodeIndex, SerializedDepNodeIndex>>,
+}
+
+impl InvalidationRecorder {
+    pub fn new(changed_options: Vec<String>) -> InvalidationRecorder {
+        InvalidationRecorder { changed_options, red_deps: Default::default() }
+    }
+
+    pub(super) fn changed_options(&self) -> &[String] {
+        &self.changed_options
+    }
+
+    pub(super) fn record(&self, node: SerializedDepNodeIndex, red_dep: SerializedDepNodeIndex) {
+        // Several threads may try to mark the same node concurrently, the
+        // first one to give up wins.
+        self.red_deps.lock().entry(node).or_insert(red_dep);
+    }
+
+    /// Returns the nodes that were recorded, sorted by index.
+    pub(super) fn nodes(&self) -> Vec<SerializedDepNodeIndex> {
+        let mut nodes: Vec<_> = self.red_deps.lock().keys().copied().collect();
+        nodes.sort_unstable_by_key(|node| node.as_u32());
+        nodes
+    }
+
+    /// Follows the recorded red dependencies from `node` back to the node
+    /// that started the invalidation.
+    pub(super) fn chain(&self, node: SerializedDepNodeIndex) -> Vec<SerializedDepNodeIndex> {
+        let red_deps = self.red_deps.lock();
+        let mut chain = vec![];
+        let mut current = node;
+        while let Some(&red_dep) = red_deps.get(&current) {
+            chain.push(red_dep);
+            current = red_dep;
+        }
+        chain
+    }
+}
+
+/// Why work of the previous session could not be reused.
+#[derive(Debug)]
+pub struct Invalidations {
+    /// The tracked command-line options that changed since the previous
+    /// session, invalidating everything.
+    pub changed_options: Vec<String>,
+    /// The nodes that were re-executed because one of their dependencies
+    /// changed, sorted by their index in the previous dep-graph.
+    pub nodes: Vec<Invalidation>,
+}
+
+/// A node of the previous session that had to be re-executed, and why.
+#[derive(Debug)]
+pub struct Invalidation {
+    /// The re-executed node.
+    pub node: DepNode,
+    /// Whether re-executing the node produced a different result than in the
+    /// previous session, invalidating the nodes depending on it in turn.
+    pub changed: bool,
+    /// The dependencies through which the invalidation reached `node`,
+    /// starting with the red dependency of `node` and ending with the root.
+    pub chain: Vec<DepNode>,
+    pub root: InvalidationRoot,
+}
+
+impl Invalidation {
+    /// The node that started the invalidation.
+    pub fn root_node(&self) -> &DepNode {
+        self.chain.last().unwrap_or(&self.node)
+    }
+}
+
+/// How the node that started an invalidation differs from the previous session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InvalidationRoot {
+    /// The node was re-executed and produced a different result, e.g. an
+    /// `eval_always` query reading the sources.
+    Changed,
+    /// The node could not be re-executed, usually because the item it refers
+    /// to does not exist anymore.
+    Unrecoverable,
+}
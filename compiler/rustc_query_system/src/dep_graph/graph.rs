@@ -15,6 +15,7 @@
 use std::marker::PhantomData;
 use std::sync::atomic::Ordering::Relaxed;
 
+use super::explain::{Invalidation, InvalidationRecorder, InvalidationRoot, Invalidations};
 use super::query::DepGraphQuery;
 use super::serialized::{GraphEncoder, SerializedDepGraph, SerializedDepNodeIndex};
 use super::{DepContext, DepKind, DepNode, Deps, HasDepContext, WorkProductId};
@@ -100,6 +101,10 @@ pub(crate) struct DepGraphData<D: Deps> {
     /// a particular query result was decoded from disk
     /// (not just marked green)
     debug_loaded_from_disk: Lock<FxHashSet<DepNode>>,
+
+    /// Records why previous nodes could not be marked green, when
+    /// `-Z incremental-explain` is enabled.
+    invalidations: Option<InvalidationRecorder>,
 }
 
 pub fn hash_result<R>(hcx: &mut StableHashingContext<'_>, result: &R) -> Fingerprint
@@ -119,6 +124,7 @@ pub fn new(
         encoder: FileEncoder,
         record_graph: bool,
         record_stats: bool,
+        invalidations: Option<InvalidationRecorder>,
     ) -> DepGraph<D> {
         let prev_graph_node_count = prev_graph.node_count();
 
@@ -174,6 +180,7 @@ pub fn new(
                 previous: prev_graph,
                 colors,
                 debug_loaded_from_disk: Default::default(),
+                invalidations,
             })),
             virtual_dep_node_index: Lrc::new(AtomicU32::new(0)),
         }
@@ -861,7 +868,13 @@ fn try_mark_previous_green<Qcx: QueryContext<Deps = D>>(
         let prev_deps = self.previous.edge_targets_from(prev_dep_node_index);
 
         for dep_dep_node_index in prev_deps {
-            self.try_mark_parent_green(qcx, dep_dep_node_index, dep_node, Some(&frame))?;
+            if self.try_mark_parent_green(qcx, dep_dep_node_index, dep_node, Some(&frame)).is_none()
+            {
+                if let Some(invalidations) = &self.invalidations {
+                    invalidations.record(prev_dep_node_index, dep_dep_node_index);
+                }
+                return None;
+            }
         }
 
         // If we got here without hitting a `return` that means that all
@@ -982,6 +995,40 @@ pub fn print_incremental_info(&self) {
         }
     }
 
+    /// Returns why work of the previous session was redone, if the dep-graph
+    /// was created with an `InvalidationRecorder` (`-Z incremental-explain`).
+    pub fn invalidations(&self) -> Option<Invalidations> {
+        let data = self.data.as_ref()?;
+        let recorder = data.invalidations.as_ref()?;
+
+        let nodes = recorder
+            .nodes()
+            .into_iter()
+            .filter_map(|prev_index| {
+                // Nodes without a color were not re-executed, e.g. because
+                // nothing needed them after all. They may still show up in
+                // the chain of other nodes.
+                let changed = match data.colors.get(prev_index)? {
+                    DepNodeColor::Red => true,
+                    DepNodeColor::Green(_) => false,
+                };
+                let chain = recorder.chain(prev_index);
+                let root_index = *chain.last().unwrap_or(&prev_index);
+                let root = match data.colors.get(root_index) {
+                    Some(_) => InvalidationRoot::Changed,
+                    None => InvalidationRoot::Unrecoverable,
+                };
+                Some(Invalidation {
+                    node: data.previous.index_to_node(prev_index),
+                    changed,
+                    chain: chain.into_iter().map(|i| data.previous.index_to_node(i)).collect(),
+                    root,
+                })
+            })
+            .collect();
+        Some(Invalidations { changed_options: recorder.changed_options().to_vec(), nodes })
+    }
+
     pub fn finish_encoding(&self, profiler: &SelfProfilerRef) -> FileEncodeResult {
         if let Some(data) = &self.data {
             data.current.encoder.steal().finish(profiler)
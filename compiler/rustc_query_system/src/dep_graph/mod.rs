@@ -1,11 +1,13 @@
 pub mod debug;
 pub mod dep_node;
 mod edges;
+mod explain;
 mod graph;
 mod query;
 mod serialized;
 
 pub use dep_node::{DepKind, DepKindStruct, DepNode, DepNodeParams, WorkProductId};
+pub use explain::{Invalidation, InvalidationRecorder, InvalidationRoot, Invalidations};
 pub(crate) use graph::DepGraphData;
 pub use graph::{hash_result, DepGraph, DepNodeIndex, TaskDepsRef, WorkProduct, WorkProductMap};
 pub use query::DepGraphQuery;
@@ -12,6 +12,7 @@
 use rustc_span::source_map::SourceMap;
 use rustc_span::symbol::Symbol;
 use rustc_span::{BytePos, CachingSourceMapView, SourceFile, Span, SpanData, DUMMY_SP};
+use std::path::{Path, PathBuf};
 
 /// This is the context state available during incr. comp. hashing. It contains
 /// enough information to transform `DefId`s and `HirId`s into stable `DefPath`s (i.e.,
@@ -23,6 +24,8 @@ pub struct StableHashingContext<'a> {
     // The value of `-Z incremental-ignore-spans`.
     // This field should only be used by `unstable_opts_incremental_ignore_span`
     incremental_ignore_spans: bool,
+    // The value of `-Z incremental-relocatable`.
+    incremental_relocatable: bool,
     pub(super) body_resolver: BodyResolver<'a>,
     // Very often, we are hashing something that does not need the
     // `CachingSourceMapView`, so we initialize it lazily.
@@ -53,6 +56,7 @@ pub fn new(sess: &'a Session, untracked: &'a Untracked) -> Self {
             body_resolver: BodyResolver::Forbidden,
             untracked,
             incremental_ignore_spans: sess.opts.unstable_opts.incremental_ignore_spans,
+            incremental_relocatable: sess.opts.unstable_opts.incremental_relocatable,
             caching_source_map: None,
             raw_source_map: sess.source_map(),
             hashing_controls: HashingControls { hash_spans: hash_spans_initial },
@@ -163,4 +167,12 @@ fn hashing_controls(&self) -> HashingControls {
     }
 }
 
-impl<'a> rustc_session::HashStableContext for StableHashingContext<'a> {}
+impl<'a> rustc_session::HashStableContext for StableHashingContext<'a> {
+    fn path_for_hashing(&self, path: &Path) -> PathBuf {
+        if self.incremental_relocatable {
+            self.raw_source_map.path_mapping().map_prefix(path).0.into_owned()
+        } else {
+            path.to_path_buf()
+        }
+    }
+}
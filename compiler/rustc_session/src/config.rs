@@ -3,13 +3,14 @@
 
 pub use crate::options::*;
 
+use crate::config::lint_config::LintConfig;
 use crate::errors::FileWriteFail;
 use crate::search_paths::SearchPath;
 use crate::utils::{CanonicalizedPath, NativeLib, NativeLibKind};
 use crate::{lint, HashStableContext};
 use crate::{EarlyDiagCtxt, Session};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap, FxIndexSet};
-use rustc_data_structures::stable_hasher::{StableOrd, ToStableHashKey};
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher, StableOrd, ToStableHashKey};
 use rustc_errors::emitter::HumanReadableErrorType;
 use rustc_errors::{ColorConfig, DiagCtxtFlags, DiagnosticArgValue, IntoDiagnosticArg};
 use rustc_feature::UnstableFeatures;
@@ -34,6 +35,7 @@
 use std::str::{self, FromStr};
 use std::sync::LazyLock;
 
+pub mod lint_config;
 pub mod sigpipe;
 
 /// The different settings that the `-C strip` flag can have.
@@ -166,6 +168,10 @@ pub enum InstrumentCoverage {
     /// Additionally, instrument branches and output branch coverage.
     /// `-Zunstable-options -C instrument-coverage=branch`
     Branch,
+    /// Additionally, instrument boolean decisions for modified condition/decision
+    /// coverage, on top of branch coverage.
+    /// `-Zunstable-options -C instrument-coverage=mcdc`
+    Mcdc,
     /// `-Zunstable-options -C instrument-coverage=except-unused-generics`
     ExceptUnusedGenerics,
     /// `-Zunstable-options -C instrument-coverage=except-unused-functions`
@@ -540,6 +546,11 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF 2.1.0 log, for code-scanning services.
+    Sarif {
+        /// Render the log in a human readable way (with indents and newlines).
+        pretty: bool,
+    },
 }
 
 impl Default for ErrorOutputType {
@@ -752,6 +763,7 @@ pub enum PrintKind {
     LinkArgs,
     SplitDebuginfo,
     DeploymentTarget,
+    SizeAttribution,
 }
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -890,7 +902,7 @@ pub fn overwrite(&self, content: &str, sess: &Session) {
     }
 }
 
-#[derive(Clone, Hash, Debug, HashStable_Generic, Encodable, Decodable)]
+#[derive(Clone, Hash, Debug, Encodable, Decodable)]
 pub struct OutputFilenames {
     pub out_directory: PathBuf,
     /// Crate name. Never contains '-'.
@@ -902,6 +914,32 @@ pub struct OutputFilenames {
     pub outputs: OutputTypes,
 }
 
+impl<CTX: HashStableContext> HashStable<CTX> for OutputFilenames {
+    fn hash_stable(&self, hcx: &mut CTX, hasher: &mut StableHasher) {
+        let OutputFilenames {
+            out_directory,
+            crate_stem,
+            filestem,
+            single_output_file,
+            temps_directory,
+            outputs,
+        } = self;
+        // The output directories are hashed as remapped, so that the cache of
+        // `-Z incremental-relocatable` can be used with other output locations.
+        hcx.path_for_hashing(out_directory).hash_stable(hcx, hasher);
+        crate_stem.hash_stable(hcx, hasher);
+        filestem.hash_stable(hcx, hasher);
+        match single_output_file {
+            Some(OutFileName::Real(path)) => {
+                Some(OutFileName::Real(hcx.path_for_hashing(path))).hash_stable(hcx, hasher)
+            }
+            _ => single_output_file.hash_stable(hcx, hasher),
+        }
+        temps_directory.as_deref().map(|path| hcx.path_for_hashing(path)).hash_stable(hcx, hasher);
+        outputs.hash_stable(hcx, hasher);
+    }
+}
+
 pub const RLINK_EXT: &str = "rlink";
 pub const RUST_CGU_EXT: &str = "rcgu";
 pub const DWARF_OBJECT_EXT: &str = "dwo";
@@ -1083,6 +1121,7 @@ fn default() -> Options {
             debuginfo_compression: DebugInfoCompression::None,
             lint_opts: Vec::new(),
             lint_cap: None,
+            lint_config: None,
             describe_lints: false,
             output_types: OutputTypes(BTreeMap::new()),
             search_paths: vec![],
@@ -1165,6 +1204,7 @@ pub fn dcx_flags(&self, can_emit_warnings: bool) -> DiagCtxtFlags {
             macro_backtrace: self.macro_backtrace,
             deduplicate_diagnostics: self.deduplicate_diagnostics,
             track_diagnostics: self.track_diagnostics,
+            collect_fixes: self.fix,
         }
     }
 }
@@ -1989,14 +2029,16 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif { pretty: false },
+            Some("pretty-sarif") => ErrorOutputType::Sarif { pretty: true },
 
             Some(arg) => {
                 early_dcx.abort_if_error_and_set_error_format(ErrorOutputType::HumanReadable(
                     HumanReadableErrorType::Default(color),
                 ));
                 early_dcx.early_fatal(format!(
-                    "argument for `--error-format` must be `human`, `json` or \
-                     `short` (instead was `{arg}`)"
+                    "argument for `--error-format` must be `human`, `json`, \
+                     `short` or `sarif` (instead was `{arg}`)"
                 ))
             }
         }
@@ -2069,6 +2111,14 @@ fn check_error_format_stability(
             });
             early_dcx.early_fatal("`--error-format=human-annotate-rs` is unstable");
         }
+        if let ErrorOutputType::Sarif { pretty } = error_format {
+            early_dcx.abort_if_error_and_set_error_format(ErrorOutputType::Json {
+                pretty: false,
+                json_rendered,
+            });
+            let format = if pretty { "pretty-sarif" } else { "sarif" };
+            early_dcx.early_fatal(format!("`--error-format={format}` is unstable"));
+        }
     }
 }
 
@@ -2179,6 +2229,7 @@ fn collect_print_requests(
         ("link-args", PrintKind::LinkArgs),
         ("native-static-libs", PrintKind::NativeStaticLibs),
         ("relocation-models", PrintKind::RelocationModels),
+        ("size-attribution", PrintKind::SizeAttribution),
         ("split-debuginfo", PrintKind::SplitDebuginfo),
         ("stack-protector-strategies", PrintKind::StackProtectorStrategies),
         ("sysroot", PrintKind::Sysroot),
@@ -2221,6 +2272,16 @@ fn collect_print_requests(
                     );
                 }
             }
+            Some((_, PrintKind::SizeAttribution)) => {
+                if unstable_opts.unstable_options {
+                    PrintKind::SizeAttribution
+                } else {
+                    early_dcx.early_fatal(
+                        "the `-Z unstable-options` flag must also be passed to \
+                         enable the size-attribution print option",
+                    );
+                }
+            }
             Some(&(_, print_kind)) => print_kind,
             None => {
                 let prints =
@@ -2745,12 +2806,13 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
         InstrumentCoverage::All | InstrumentCoverage::Off => {}
         // Unstable values:
         InstrumentCoverage::Branch
+        | InstrumentCoverage::Mcdc
         | InstrumentCoverage::ExceptUnusedFunctions
         | InstrumentCoverage::ExceptUnusedGenerics => {
             if !unstable_opts.unstable_options {
                 early_dcx.early_fatal(
-                    "`-C instrument-coverage=branch` and `-C instrument-coverage=except-*` \
-                    require `-Z unstable-options`",
+                    "`-C instrument-coverage=branch`, `-C instrument-coverage=mcdc` and \
+                    `-C instrument-coverage=except-*` require `-Z unstable-options`",
                 );
             }
         }
@@ -2877,6 +2939,15 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
 
     let logical_env = parse_logical_env(early_dcx, matches);
 
+    let lint_config = unstable_opts.lint_config.as_deref().map(|path| {
+        LintConfig::load(path).unwrap_or_else(|e| {
+            early_dcx.early_fatal(format!(
+                "failed to load lint configuration file `{}`: {e}",
+                path.display()
+            ))
+        })
+    });
+
     // Try to find a directory containing the Rust `src`, for more details see
     // the doc comment on the `real_rust_source_base_dir` field.
     let tmp_buf;
@@ -2927,6 +2998,7 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
         debuginfo_compression,
         lint_opts,
         lint_cap,
+        lint_config,
         describe_lints,
         output_types,
         search_paths,
@@ -3233,6 +3305,7 @@ pub(crate) mod dep_tracking {
         SourceFileHashAlgorithm, SplitDwarfKind, SwitchWithOptPath, SymbolManglingVersion,
         TrimmedDefPaths, WasiExecModel,
     };
+    use crate::config::lint_config::LintConfig;
     use crate::lint;
     use crate::utils::NativeLib;
     use rustc_data_structures::fx::FxIndexMap;
@@ -3246,7 +3319,7 @@ pub(crate) mod dep_tracking {
         RelroLevel, SanitizerSet, SplitDebuginfo, StackProtector, TargetTriple, TlsModel,
     };
     use std::collections::BTreeMap;
-    use std::hash::{DefaultHasher, Hash};
+    use std::hash::{DefaultHasher, Hash, Hasher};
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
 
@@ -3338,6 +3411,7 @@ fn hash(
         Polonius,
         InliningThreshold,
         FunctionReturn,
+        LintConfig,
     );
 
     impl<T1, T2> DepTrackingHash for (T1, T2)
@@ -3441,6 +3515,22 @@ pub(crate) fn stable_hash(
             sub_hash.hash(hasher, error_format, for_crate_hash);
         }
     }
+
+    /// Hashes each entry of `sub_hashes` on its own, under the option name
+    /// as it is spelled on the command line.
+    pub(crate) fn individual_hashes(
+        sub_hashes: BTreeMap<&'static str, &dyn DepTrackingHash>,
+        prefix: &str,
+        error_format: ErrorOutputType,
+        for_crate_hash: bool,
+        hashes: &mut BTreeMap<String, u64>,
+    ) {
+        for (key, sub_hash) in sub_hashes {
+            let mut hasher = DefaultHasher::new();
+            sub_hash.hash(&mut hasher, error_format, for_crate_hash);
+            hashes.insert(format!("{prefix}{}", key.replace('_', "-")), hasher.finish());
+        }
+    }
 }
 
 /// Default behavior to use in out-of-memory situations.
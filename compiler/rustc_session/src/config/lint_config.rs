@@ -0,0 +1,457 @@
+//! The lint configuration file (`-Z lint-config`).
+//!
+//! The file uses the same layout as the `[lints]` table of a Cargo manifest,
+//! with one table per tool:
+//!
+//! ```toml
+//! [lints.rust]
+//! unsafe_code = "forbid"
+//! unused = { level = "deny", priority = -1 }
+//!
+//! [lints.clippy]
+//! pedantic = "warn"
+//! ```
+//!
+//! Levels can also be overridden for the modules loaded from some paths,
+//! which are relative to the directory containing the configuration file:
+//!
+//! ```toml
+//! [[overrides]]
+//! paths = ["src/generated"]
+//!
+//! [overrides.lints.rust]
+//! missing_docs = "allow"
+//! ```
+//!
+//! Only the subset of TOML needed to express this is supported.
+
+use crate::lint::Level;
+use rustc_fs_util::try_canonicalize;
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A lint level set in the configuration file.
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct LintConfigEntry {
+    /// The name of the lint or lint group, including its tool (e.g.
+    /// `clippy::pedantic`), with dashes replaced by underscores.
+    pub name: String,
+    pub level: Level,
+    /// Entries are applied by ascending priority, so that a lint can be
+    /// configured differently from a group containing it.
+    pub priority: i32,
+    /// The line of the file defining this entry, starting at 1.
+    pub line: u32,
+}
+
+/// Lint levels applying to the modules loaded from some paths only.
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct LintConfigOverride {
+    /// The canonicalized files and directories the override applies to.
+    pub paths: Vec<PathBuf>,
+    pub lints: Vec<LintConfigEntry>,
+    /// The line of the `[[overrides]]` header, starting at 1.
+    pub line: u32,
+}
+
+/// The contents of a lint configuration file.
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct LintConfig {
+    /// The path of the file, as given on the command line.
+    pub path: PathBuf,
+    /// The levels applying to the whole crate, in the order of the file.
+    pub lints: Vec<LintConfigEntry>,
+    pub overrides: Vec<LintConfigOverride>,
+}
+
+#[derive(Debug)]
+pub enum LintConfigError {
+    Io(io::Error),
+    Syntax { line: u32, msg: String },
+}
+
+impl fmt::Display for LintConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintConfigError::Io(err) => write!(f, "{err}"),
+            LintConfigError::Syntax { line, msg } => write!(f, "line {line}: {msg}"),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn load(path: &Path) -> Result<LintConfig, LintConfigError> {
+        let src = std::fs::read_to_string(path).map_err(LintConfigError::Io)?;
+        LintConfig::parse(path, &src)
+    }
+
+    pub fn parse(path: &Path, src: &str) -> Result<LintConfig, LintConfigError> {
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let mut config = LintConfig { path: path.to_owned(), lints: vec![], overrides: vec![] };
+        let mut parser = Parser { src, pos: 0, line: 1 };
+        let mut table = Table::Root;
+
+        loop {
+            parser.skip_trivia();
+            if parser.at_end() {
+                break;
+            }
+            let line = parser.line;
+            if parser.eat("[[") {
+                let header = parser.header_keys("]]")?;
+                if header != ["overrides"] {
+                    return Err(parser.error_at(line, "expected `[[overrides]]`"));
+                }
+                config.overrides.push(LintConfigOverride { paths: vec![], lints: vec![], line });
+                table = Table::Override;
+            } else if parser.eat("[") {
+                let header = parser.header_keys("]")?;
+                table = match &header.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+                    ["lints", tool] => Table::Lints { tool: tool_prefix(tool), in_override: false },
+                    ["overrides", "lints", tool] => {
+                        if config.overrides.is_empty() {
+                            return Err(parser.error_at(
+                                line,
+                                "`[overrides.lints.*]` must follow an `[[overrides]]` table",
+                            ));
+                        }
+                        Table::Lints { tool: tool_prefix(tool), in_override: true }
+                    }
+                    _ => {
+                        return Err(parser.error_at(
+                            line,
+                            "expected `[lints.<tool>]`, `[[overrides]]` or \
+                             `[overrides.lints.<tool>]`",
+                        ));
+                    }
+                };
+            } else {
+                let key = parser.key()?;
+                parser.skip_whitespace();
+                parser.expect("=")?;
+                parser.skip_whitespace();
+                let value = parser.value()?;
+                parser.end_of_line()?;
+
+                match &table {
+                    Table::Root => {
+                        return Err(parser.error_at(line, "keys must be inside of a table"));
+                    }
+                    Table::Override => {
+                        if key != "paths" {
+                            return Err(parser.error_at(line, format!("unknown key `{key}`")));
+                        }
+                        let Value::Array(paths) = value else {
+                            return Err(parser.error_at(line, "`paths` must be an array"));
+                        };
+                        let over = config.overrides.last_mut().unwrap();
+                        for path in paths {
+                            let Value::String(path) = path else {
+                                return Err(parser.error_at(line, "`paths` must contain strings"));
+                            };
+                            let path = base_dir.join(path);
+                            over.paths.push(try_canonicalize(&path).unwrap_or(path));
+                        }
+                    }
+                    Table::Lints { tool, in_override } => {
+                        let name = format!("{tool}{}", key.replace('-', "_"));
+                        let (level, priority) = lint_level(&parser, line, value)?;
+                        let lints = if *in_override {
+                            &mut config.overrides.last_mut().unwrap().lints
+                        } else {
+                            &mut config.lints
+                        };
+                        if lints.iter().any(|entry| entry.name == name) {
+                            return Err(parser.error_at(line, format!("duplicate lint `{name}`")));
+                        }
+                        lints.push(LintConfigEntry { name, level, priority, line });
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the overrides applying to the module loaded from `file`.
+    pub fn overrides_for(&self, file: &Path) -> impl Iterator<Item = &LintConfigOverride> {
+        let file = try_canonicalize(file).unwrap_or_else(|_| file.to_owned());
+        self.overrides.iter().filter(move |over| over.paths.iter().any(|p| file.starts_with(p)))
+    }
+}
+
+enum Table {
+    Root,
+    Override,
+    /// `tool` is the prefix added to the lint names, e.g. `clippy::`.
+    Lints {
+        tool: String,
+        in_override: bool,
+    },
+}
+
+fn tool_prefix(tool: &str) -> String {
+    if tool == "rust" {
+        String::new()
+    } else {
+        format!("{tool}::")
+    }
+}
+
+fn lint_level(
+    parser: &Parser<'_>,
+    line: u32,
+    value: Value,
+) -> Result<(Level, i32), LintConfigError> {
+    let parse_level = |level: &str| match level {
+        "force-warn" => Ok(Level::ForceWarn(None)),
+        "expect" => Err(parser.error_at(line, "`expect` can only be used in attributes")),
+        _ => Level::from_str(level).ok_or_else(|| {
+            parser.error_at(
+                line,
+                format!(
+                    "unknown lint level `{level}`, expected one of `allow`, `warn`, \
+                     `force-warn`, `deny` or `forbid`"
+                ),
+            )
+        }),
+    };
+
+    match value {
+        Value::String(level) => Ok((parse_level(&level)?, 0)),
+        Value::Table(fields) => {
+            let mut level = None;
+            let mut priority = 0;
+            for (key, value) in fields {
+                match (key.as_str(), value) {
+                    ("level", Value::String(s)) => level = Some(parse_level(&s)?),
+                    ("priority", Value::Integer(p)) => {
+                        priority = i32::try_from(p)
+                            .map_err(|_| parser.error_at(line, "priority out of range"))?;
+                    }
+                    ("level", _) => return Err(parser.error_at(line, "`level` must be a string")),
+                    ("priority", _) => {
+                        return Err(parser.error_at(line, "`priority` must be an integer"));
+                    }
+                    (key, _) => return Err(parser.error_at(line, format!("unknown key `{key}`"))),
+                }
+            }
+            let level = level.ok_or_else(|| parser.error_at(line, "missing `level`"))?;
+            Ok((level, priority))
+        }
+        _ => Err(parser.error_at(line, "expected a lint level or an inline table")),
+    }
+}
+
+enum Value {
+    String(String),
+    Integer(i64),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    /// The current line, starting at 1.
+    line: u32,
+}
+
+impl Parser<'_> {
+    fn error_at(&self, line: u32, msg: impl Into<String>) -> LintConfigError {
+        LintConfigError::Syntax { line, msg: msg.into() }
+    }
+
+    fn error(&self, msg: impl Into<String>) -> LintConfigError {
+        self.error_at(self.line, msg)
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.src.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            // `s` never contains newlines.
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), LintConfigError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{s}`")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t') = self.peek() {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while self.peek().is_some_and(|c| c != '\n') {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            if !self.eat("\r\n") && !self.eat("\n") {
+                break;
+            }
+            self.line += 1;
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), LintConfigError> {
+        self.skip_whitespace();
+        self.skip_comment();
+        if self.at_end() || self.rest().starts_with('\n') || self.rest().starts_with("\r\n") {
+            Ok(())
+        } else {
+            Err(self.error("expected a newline"))
+        }
+    }
+
+    /// Parses the dotted keys of a table header up to `close`.
+    fn header_keys(&mut self, close: &str) -> Result<Vec<String>, LintConfigError> {
+        let mut keys = vec![];
+        loop {
+            self.skip_whitespace();
+            keys.push(self.key()?);
+            self.skip_whitespace();
+            if self.eat(close) {
+                self.end_of_line()?;
+                return Ok(keys);
+            }
+            self.expect(".")?;
+        }
+    }
+
+    /// Parses a bare or quoted key.
+    fn key(&mut self) -> Result<String, LintConfigError> {
+        match self.peek() {
+            Some('"' | '\'') => self.string(),
+            _ => {
+                let len = self
+                    .rest()
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+                    .unwrap_or(self.rest().len());
+                if len == 0 {
+                    return Err(self.error("expected a key"));
+                }
+                let key = self.rest()[..len].to_owned();
+                self.pos += len;
+                Ok(key)
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, LintConfigError> {
+        match self.peek() {
+            Some('"' | '\'') => Ok(Value::String(self.string()?)),
+            Some('[') => {
+                self.bump();
+                let mut values = vec![];
+                loop {
+                    self.skip_trivia();
+                    if self.eat("]") {
+                        return Ok(Value::Array(values));
+                    }
+                    values.push(self.value()?);
+                    self.skip_trivia();
+                    if !self.eat(",") {
+                        self.skip_trivia();
+                        self.expect("]")?;
+                        return Ok(Value::Array(values));
+                    }
+                }
+            }
+            Some('{') => {
+                self.bump();
+                let mut fields = vec![];
+                self.skip_whitespace();
+                if self.eat("}") {
+                    return Ok(Value::Table(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.key()?;
+                    self.skip_whitespace();
+                    self.expect("=")?;
+                    self.skip_whitespace();
+                    fields.push((key, self.value()?));
+                    self.skip_whitespace();
+                    if self.eat("}") {
+                        return Ok(Value::Table(fields));
+                    }
+                    self.expect(",")?;
+                }
+            }
+            Some('+' | '-' | '0'..='9') => {
+                let len = self
+                    .rest()
+                    .char_indices()
+                    .find(|&(i, c)| {
+                        !(c.is_ascii_digit() || c == '_' || (i == 0 && "+-".contains(c)))
+                    })
+                    .map_or(self.rest().len(), |(i, _)| i);
+                let digits = self.rest()[..len].replace('_', "");
+                let value = digits.parse().map_err(|_| self.error("invalid integer"))?;
+                self.pos += len;
+                Ok(Value::Integer(value))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// Parses a basic (`"..."`) or literal (`'...'`) single-line string.
+    fn string(&mut self) -> Result<String, LintConfigError> {
+        let quote = self.bump().unwrap();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => return Ok(s),
+                Some('\\') if quote == '"' => match self.bump() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    _ => return Err(self.error("unsupported escape sequence")),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+}
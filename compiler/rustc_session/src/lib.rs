@@ -37,6 +37,8 @@
 
 pub use getopts;
 
+use std::path::{Path, PathBuf};
+
 mod version;
 pub use version::RustcVersion;
 
@@ -45,4 +47,8 @@
 /// Requirements for a `StableHashingContext` to be used in this crate.
 /// This is a hack to allow using the `HashStable_Generic` derive macro
 /// instead of implementing everything in `rustc_middle`.
-pub trait HashStableContext: rustc_ast::HashStableContext + rustc_hir::HashStableContext {}
+pub trait HashStableContext: rustc_ast::HashStableContext + rustc_hir::HashStableContext {
+    /// Returns the path to hash in place of `path`, which is remapped with
+    /// `--remap-path-prefix` under `-Z incremental-relocatable`.
+    fn path_for_hashing(&self, path: &Path) -> PathBuf;
+}
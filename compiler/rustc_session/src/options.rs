@@ -1,4 +1,5 @@
 use crate::config::*;
+use crate::config::lint_config::LintConfig;
 
 use crate::search_paths::SearchPath;
 use crate::utils::NativeLib;
@@ -61,6 +62,15 @@ macro_rules! hash_substruct {
     };
 }
 
+macro_rules! substruct_hashes {
+    ($opt_expr:expr, $error_format:expr, $hashes:expr, [UNTRACKED]) => {{}};
+    ($opt_expr:expr, $error_format:expr, $hashes:expr, [TRACKED]) => {{}};
+    ($opt_expr:expr, $error_format:expr, $hashes:expr, [TRACKED_NO_CRATE_HASH]) => {{}};
+    ($opt_expr:expr, $error_format:expr, $hashes:expr, [SUBSTRUCT]) => {{
+        $opt_expr.dep_tracking_hashes($error_format, $hashes)
+    }};
+}
+
 macro_rules! top_level_options {
     ( $( #[$top_level_attr:meta] )* pub struct Options { $(
         $( #[$attr:meta] )*
@@ -100,6 +110,35 @@ pub fn dep_tracking_hash(&self, for_crate_hash: bool) -> u64 {
                 })*
                 hasher.finish()
             }
+
+            /// Returns the hash of every tracked option, keyed by the option name
+            /// (prefixed with `-C ` or `-Z ` for codegen and unstable options), so
+            /// that a change of `dep_tracking_hash` can be traced back to the
+            /// options that caused it.
+            pub fn dep_tracking_hashes(&self) -> BTreeMap<String, u64> {
+                let for_crate_hash = false;
+                let mut sub_hashes = BTreeMap::new();
+                $({
+                    hash_opt!($opt,
+                                &self.$opt,
+                                &mut sub_hashes,
+                                for_crate_hash,
+                                [$dep_tracking_marker]);
+                })*
+                let mut hashes = BTreeMap::new();
+                dep_tracking::individual_hashes(sub_hashes,
+                                                "",
+                                                self.error_format,
+                                                for_crate_hash,
+                                                &mut hashes);
+                $({
+                    substruct_hashes!(&self.$opt,
+                        self.error_format,
+                        &mut hashes,
+                        [$dep_tracking_marker]);
+                })*
+                hashes
+            }
         }
     );
 }
@@ -143,6 +182,8 @@ pub struct Options {
         debuginfo_compression: DebugInfoCompression [TRACKED],
         lint_opts: Vec<(String, lint::Level)> [TRACKED_NO_CRATE_HASH],
         lint_cap: Option<lint::Level> [TRACKED_NO_CRATE_HASH],
+        /// The lint configuration file loaded with `-Z lint-config`.
+        lint_config: Option<LintConfig> [TRACKED_NO_CRATE_HASH],
         describe_lints: bool [UNTRACKED],
         output_types: OutputTypes [TRACKED],
         search_paths: Vec<SearchPath> [UNTRACKED],
@@ -280,6 +321,27 @@ fn dep_tracking_hash(&self, for_crate_hash: bool, error_format: ErrorOutputType)
                                         );
             hasher.finish()
         }
+
+        fn dep_tracking_hashes(
+            &self,
+            error_format: ErrorOutputType,
+            hashes: &mut BTreeMap<String, u64>,
+        ) {
+            let for_crate_hash = false;
+            let mut sub_hashes = BTreeMap::new();
+            $({
+                hash_opt!($opt,
+                            &self.$opt,
+                            &mut sub_hashes,
+                            for_crate_hash,
+                            [$dep_tracking_marker]);
+            })*
+            dep_tracking::individual_hashes(sub_hashes,
+                                            concat!("-", $prefix, " "),
+                                            error_format,
+                                            for_crate_hash,
+                                            hashes);
+        }
     }
 
     pub const $stat: OptionDescrs<$struct_name> =
@@ -394,7 +456,7 @@ mod desc {
     pub const parse_mir_spanview: &str = "`statement` (default), `terminator`, or `block`";
     pub const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
     pub const parse_instrument_coverage: &str =
-        "`all` (default), `branch`, `except-unused-generics`, `except-unused-functions`, or `off`";
+        "`all` (default), `branch`, `mcdc`, `except-unused-generics`, `except-unused-functions`, or `off`";
     pub const parse_instrument_xray: &str = "either a boolean (`yes`, `no`, `on`, `off`, etc), or a comma separated list of settings: `always` or `never` (mutually exclusive), `ignore-loops`, `instruction-threshold=N`, `skip-entry`, `skip-exit`";
     pub const parse_unpretty: &str = "`string` or `string=string`";
     pub const parse_treat_err_as_bug: &str = "either no value or a non-negative number";
@@ -939,6 +1001,7 @@ pub(crate) fn parse_instrument_coverage(
         *slot = match v {
             "all" => InstrumentCoverage::All,
             "branch" => InstrumentCoverage::Branch,
+            "mcdc" => InstrumentCoverage::Mcdc,
             "except-unused-generics" | "except_unused_generics" => {
                 InstrumentCoverage::ExceptUnusedGenerics
             }
@@ -1539,6 +1602,10 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
         "only allow the listed language features to be enabled in code (comma separated)"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata (default: no)"),
+    api_usage: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "output the public items of the crate and the items of other crates it uses, \
+        for finding public items no crate of a workspace uses"),
     asm_comments: bool = (false, parse_bool, [TRACKED],
         "generate comments into the assembly (may change behavior) (default: no)"),
     assert_incr_state: Option<String> = (None, parse_opt_string, [UNTRACKED],
@@ -1606,6 +1673,10 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
         all `statement`s (including terminators), only `terminator` spans, or \
         computed `block` spans (one span encompassing a block's terminator and \
         all statements)."),
+    dump_mono_bloat: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "output the instantiations of every generic item, with their estimated size and \
+        the chains of uses that caused them to be monomorphized"),
     dump_mono_stats: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
         parse_switch_with_opt_path, [UNTRACKED],
         "output statistics about monomorphization collection"),
@@ -1629,6 +1700,11 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
     fewer_names: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "reduce memory use by retaining fewer names within compilation artifacts (LLVM-IR) \
         (default: no)"),
+    fix: bool = (false, parse_bool, [UNTRACKED],
+        "apply machine-applicable suggestions to the source files (default: no)"),
+    fix_passes: usize = (1, parse_number, [UNTRACKED],
+        "with `-Z fix`, recompile and apply suggestions again until no more apply, at most this \
+        many times (default: 1)"),
     flatten_format_args: bool = (true, parse_bool, [TRACKED],
         "flatten nested format_args!() and literals into a simplified format_args!() call \
         (default: yes)"),
@@ -1657,11 +1733,19 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
         "display unnamed regions as `'<id>`, using a non-ident unique id (default: no)"),
     ignore_directory_in_diagnostics_source_blocks: Vec<String> = (Vec::new(), parse_string_push, [UNTRACKED],
         "do not display the source code block in diagnostics for files in the directory"),
+    incremental_explain: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "explain why queries were re-executed and codegen units re-codegened by \
+        incremental compilation, writing a JSON report to the given directory \
+        (default: no)"),
     incremental_ignore_spans: bool = (false, parse_bool, [TRACKED],
         "ignore spans during ICH computation -- used for testing (default: no)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
         "print high-level information about incremental reuse (or the lack thereof) \
         (default: no)"),
+    incremental_relocatable: bool = (false, parse_bool, [UNTRACKED],
+        "make the incremental compilation cache independent of where the sources and \
+        outputs are located, according to `--remap-path-prefix` (default: no)"),
     incremental_verify_ich: bool = (false, parse_bool, [UNTRACKED],
         "verify incr. comp. hashes of green query instances (default: no)"),
     inline_in_all_cgus: Option<bool> = (None, parse_opt_bool, [TRACKED],
@@ -1696,6 +1780,9 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
         "link native libraries in the linker invocation (default: yes)"),
     link_only: bool = (false, parse_bool, [TRACKED],
         "link the `.rlink` file generated by `-Z no-link` (default: no)"),
+    // The contents of the file are tracked through `Options::lint_config`.
+    lint_config: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "load lint levels from a TOML file, applied before the `-A`/`-W`/`-D`/`-F` flags"),
     lint_mir: bool = (false, parse_bool, [UNTRACKED],
         "lint MIR before and after each transformation"),
     llvm_module_flag: Vec<(String, u32, String)> = (Vec::new(), parse_llvm_module_flag, [TRACKED],
@@ -1737,6 +1824,10 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
     #[rustc_lint_opt_deny_field_access("use `Session::mir_opt_level` instead of this field")]
     mir_opt_level: Option<usize> = (None, parse_opt_number, [TRACKED],
         "MIR optimization level (0-4; default: 1 in non optimized builds and 2 in optimized builds)"),
+    mir_specialize_const_args: Option<bool> = (None, parse_opt_bool, [TRACKED],
+        "clone functions for the constant arguments they are called with (default: no)"),
+    mir_specialize_const_args_threshold: Option<usize> = (None, parse_opt_number, [TRACKED],
+        "the MIR size threshold of the functions cloned for constant arguments (default: 250)"),
     move_size_limit: Option<usize> = (None, parse_opt_number, [TRACKED],
         "the size at which the `large_assignments` lint starts to be emitted"),
     mutable_noalias: bool = (true, parse_bool, [TRACKED],
@@ -1830,6 +1921,9 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
         "enable queries of the dependency graph for regression testing (default: no)"),
     randomize_layout: bool = (false, parse_bool, [TRACKED],
         "randomize the layout of types (default: no)"),
+    record_mono_item_mentions: bool = (false, parse_bool, [UNTRACKED],
+        "record the function items that mono items only mention, for the mono item graph of \
+        `rustc_smir` (default: no)"),
     relax_elf_relocations: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "whether ELF relocations can be relaxed"),
     relro_level: Option<RelroLevel> = (None, parse_relro_level, [TRACKED],
@@ -1900,6 +1994,10 @@ pub(crate) fn parse_function_return(slot: &mut FunctionReturn, v: Option<&str>)
         "enable LTO unit splitting (default: no)"),
     src_hash_algorithm: Option<SourceFileHashAlgorithm> = (None, parse_src_file_hash, [TRACKED],
         "hash algorithm of source files in debug info (`md5`, `sha1`, or `sha256`)"),
+    stack_report: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [TRACKED],
+        "output the worst-case stack usage of each entry point, computed from the call graph \
+        and the stack sizes of the functions (implies `-Z emit-stack-sizes`)"),
     #[rustc_lint_opt_deny_field_access("use `Session::stack_protector` instead of this field")]
     stack_protector: StackProtector = (StackProtector::None, parse_stack_protector, [TRACKED],
         "control stack smash protection strategy (`rustc --print stack-protector-strategies` for details)"),
@@ -21,6 +21,7 @@
 use rustc_errors::emitter::{DynEmitter, EmitterWriter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{
     error_code, fallback_fluent_bundle, DiagCtxt, DiagnosticBuilder, DiagnosticId,
     DiagnosticMessage, ErrorGuaranteed, FatalAbort, FluentBundle, IntoDiagnostic,
@@ -176,6 +177,10 @@ pub struct Session {
     /// Should be set if any lints are registered in `lint_store`.
     pub registered_lints: bool,
 
+    /// The MIR transformation a tool installed with `rustc_smir`. This only ever stores a
+    /// `rustc_smir::rustc_internal::MirTransform` but we don't want a dependency on that type here.
+    pub mir_transform: Lock<Option<Box<dyn Any + DynSync + DynSend>>>,
+
     /// Cap lint level specified by a driver specifically.
     pub driver_lint_caps: FxHashMap<lint::LintId, lint::Level>,
 
@@ -389,7 +394,14 @@ pub fn instrument_coverage(&self) -> bool {
     }
 
     pub fn instrument_coverage_branch(&self) -> bool {
-        self.opts.cg.instrument_coverage() == InstrumentCoverage::Branch
+        matches!(
+            self.opts.cg.instrument_coverage(),
+            InstrumentCoverage::Branch | InstrumentCoverage::Mcdc
+        )
+    }
+
+    pub fn instrument_coverage_mcdc(&self) -> bool {
+        self.opts.cg.instrument_coverage() == InstrumentCoverage::Mcdc
     }
 
     pub fn instrument_coverage_except_unused_generics(&self) -> bool {
@@ -1042,6 +1054,13 @@ fn default_emitter(
                 sopts.unstable_opts.ignore_directory_in_diagnostics_source_blocks.clone(),
             ),
         ),
+        config::ErrorOutputType::Sarif { pretty } => Box::new(SarifEmitter::stderr(
+            Some(registry),
+            source_map,
+            bundle,
+            fallback_bundle,
+            pretty,
+        )),
     }
 }
 
@@ -1190,6 +1209,7 @@ pub fn build_session(
         jobserver: jobserver::client(),
         lint_store: None,
         registered_lints: false,
+        mir_transform: Lock::new(None),
         driver_lint_caps,
         ctfe_backtrace,
         miri_unleashed_features: Lock::new(Default::default()),
@@ -1513,6 +1533,9 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
             false,
             TerminalUrl::No,
         )),
+        config::ErrorOutputType::Sarif { pretty } => {
+            Box::new(SarifEmitter::early(pretty, fallback_bundle))
+        }
     };
     emitter
 }
@@ -0,0 +1,33 @@
+//! Module that computes the facts of the borrow checker for the bodies of the local crate.
+//!
+//! The facts are computed on demand by borrow-checking a body again, which is only possible until
+//! the body is optimized. Tools that need the facts ask `run!` to give them control before the
+//! bodies are optimized with `RunOptions::borrowck_facts`.
+
+use rustc_borrowck::consumers::{self, BodyWithBorrowckFacts, ConsumerOptions};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::def_id::LocalDefId;
+use stable_mir::Error;
+
+/// Borrow-check the body of `def` again, and return it with its facts.
+pub(crate) fn body_with_facts<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def: LocalDefId,
+) -> Result<BodyWithBorrowckFacts<'tcx>, Error> {
+    if tcx.hir().maybe_body_owned_by(def).is_none() {
+        return Err(Error::new(format!("Item `{}` has no body", tcx.def_path_str(def))));
+    }
+    let (body, _) = tcx.mir_promoted(def);
+    // The body is stolen once it is optimized, e.g. for constants that are evaluated during type
+    // checking.
+    if body.is_stolen() {
+        return Err(Error::new(format!(
+            "The body of `{}` was already optimized",
+            tcx.def_path_str(def)
+        )));
+    }
+    if body.borrow().should_skip() {
+        return Err(Error::new(format!("Item `{}` is not borrow-checked", tcx.def_path_str(def))));
+    }
+    Ok(consumers::get_body_with_borrowck_facts(tcx, def, ConsumerOptions::RegionInferenceContext))
+}
@@ -5,12 +5,17 @@
 
 // Prefer importing stable_mir over internal rustc constructs to make this file more readable.
 use crate::rustc_smir::Tables;
+use rustc_middle::mir as rustc_mir;
 use rustc_middle::ty::{self as rustc_ty, Ty as InternalTy};
 use rustc_span::Symbol;
 use stable_mir::abi::Layout;
 use stable_mir::mir::alloc::AllocId;
 use stable_mir::mir::mono::{Instance, MonoItem, StaticDef};
-use stable_mir::mir::{Mutability, Safety};
+use stable_mir::mir::{
+    AggregateKind, AssertMessage, BinOp, BorrowKind, CastKind, Constant, CoroutineDesugaring,
+    CoroutineKind, CoroutineSource, MutBorrowKind, Mutability, NonDivergingIntrinsic, NullOp,
+    Operand, Place, PointerCoercion, ProjectionElem, RetagKind, Rvalue, Safety, UnOp,
+};
 use stable_mir::ty::{
     Abi, AdtDef, Binder, BoundRegionKind, BoundTyKind, BoundVariableKind, ClosureKind, Const,
     DynKind, ExistentialPredicate, ExistentialProjection, ExistentialTraitRef, FloatTy, FnSig,
@@ -467,6 +472,377 @@ fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
     }
 }
 
+impl<'tcx> RustcInternal<'tcx> for Place {
+    type T = rustc_mir::Place<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        rustc_mir::Place {
+            local: rustc_mir::Local::from_usize(self.local),
+            projection: tables.tcx.mk_place_elems(&self.projection.internal(tables)),
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for ProjectionElem {
+    type T = rustc_mir::PlaceElem<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            ProjectionElem::Deref => rustc_mir::ProjectionElem::Deref,
+            ProjectionElem::Field(idx, ty) => rustc_mir::ProjectionElem::Field(
+                rustc_target::abi::FieldIdx::from_usize(*idx),
+                ty.internal(tables),
+            ),
+            ProjectionElem::Index(local) => {
+                rustc_mir::ProjectionElem::Index(rustc_mir::Local::from_usize(*local))
+            }
+            ProjectionElem::ConstantIndex { offset, min_length, from_end } => {
+                rustc_mir::ProjectionElem::ConstantIndex {
+                    offset: *offset,
+                    min_length: *min_length,
+                    from_end: *from_end,
+                }
+            }
+            ProjectionElem::Subslice { from, to, from_end } => {
+                rustc_mir::ProjectionElem::Subslice { from: *from, to: *to, from_end: *from_end }
+            }
+            // The name of the variant is only used to print MIR.
+            ProjectionElem::Downcast(idx) => {
+                rustc_mir::ProjectionElem::Downcast(None, idx.internal(tables))
+            }
+            ProjectionElem::OpaqueCast(ty) => {
+                rustc_mir::ProjectionElem::OpaqueCast(ty.internal(tables))
+            }
+            ProjectionElem::Subtype(ty) => rustc_mir::ProjectionElem::Subtype(ty.internal(tables)),
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for Operand {
+    type T = rustc_mir::Operand<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            Operand::Copy(place) => rustc_mir::Operand::Copy(place.internal(tables)),
+            Operand::Move(place) => rustc_mir::Operand::Move(place.internal(tables)),
+            Operand::Constant(constant) => {
+                rustc_mir::Operand::Constant(Box::new(constant.internal(tables)))
+            }
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for Constant {
+    type T = rustc_mir::ConstOperand<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        rustc_mir::ConstOperand {
+            span: self.span.internal(tables),
+            user_ty: self.user_ty.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+            const_: self.literal.internal(tables),
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for Rvalue {
+    type T = rustc_mir::Rvalue<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            Rvalue::AddressOf(mutability, place) => {
+                rustc_mir::Rvalue::AddressOf(mutability.internal(tables), place.internal(tables))
+            }
+            Rvalue::Aggregate(kind, operands) => rustc_mir::Rvalue::Aggregate(
+                Box::new(kind.internal(tables)),
+                operands.iter().map(|operand| operand.internal(tables)).collect(),
+            ),
+            Rvalue::BinaryOp(bin_op, lhs, rhs) => rustc_mir::Rvalue::BinaryOp(
+                bin_op.internal(tables),
+                Box::new((lhs.internal(tables), rhs.internal(tables))),
+            ),
+            Rvalue::Cast(cast_kind, operand, ty) => rustc_mir::Rvalue::Cast(
+                cast_kind.internal(tables),
+                operand.internal(tables),
+                ty.internal(tables),
+            ),
+            Rvalue::CheckedBinaryOp(bin_op, lhs, rhs) => rustc_mir::Rvalue::CheckedBinaryOp(
+                bin_op.internal(tables),
+                Box::new((lhs.internal(tables), rhs.internal(tables))),
+            ),
+            Rvalue::CopyForDeref(place) => rustc_mir::Rvalue::CopyForDeref(place.internal(tables)),
+            Rvalue::Discriminant(place) => rustc_mir::Rvalue::Discriminant(place.internal(tables)),
+            Rvalue::Len(place) => rustc_mir::Rvalue::Len(place.internal(tables)),
+            Rvalue::Ref(region, kind, place) => rustc_mir::Rvalue::Ref(
+                region.internal(tables),
+                kind.internal(tables),
+                place.internal(tables),
+            ),
+            Rvalue::Repeat(operand, count) => {
+                rustc_mir::Rvalue::Repeat(operand.internal(tables), ty_const(count, tables))
+            }
+            Rvalue::ShallowInitBox(operand, ty) => {
+                rustc_mir::Rvalue::ShallowInitBox(operand.internal(tables), ty.internal(tables))
+            }
+            Rvalue::ThreadLocalRef(item) => {
+                rustc_mir::Rvalue::ThreadLocalRef(item.internal(tables))
+            }
+            Rvalue::NullaryOp(null_op, ty) => {
+                rustc_mir::Rvalue::NullaryOp(null_op.internal(tables), ty.internal(tables))
+            }
+            Rvalue::UnaryOp(un_op, operand) => {
+                rustc_mir::Rvalue::UnaryOp(un_op.internal(tables), operand.internal(tables))
+            }
+            Rvalue::Use(operand) => rustc_mir::Rvalue::Use(operand.internal(tables)),
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for AggregateKind {
+    type T = rustc_mir::AggregateKind<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            AggregateKind::Array(ty) => rustc_mir::AggregateKind::Array(ty.internal(tables)),
+            AggregateKind::Tuple => rustc_mir::AggregateKind::Tuple,
+            AggregateKind::Adt(def, var_idx, args, user_ty_index, field_idx) => {
+                rustc_mir::AggregateKind::Adt(
+                    def.0.internal(tables),
+                    var_idx.internal(tables),
+                    args.internal(tables),
+                    user_ty_index.map(rustc_ty::UserTypeAnnotationIndex::from_usize),
+                    field_idx.map(rustc_target::abi::FieldIdx::from_usize),
+                )
+            }
+            AggregateKind::Closure(def, args) => {
+                rustc_mir::AggregateKind::Closure(def.0.internal(tables), args.internal(tables))
+            }
+            AggregateKind::Coroutine(def, args, _) => {
+                rustc_mir::AggregateKind::Coroutine(def.0.internal(tables), args.internal(tables))
+            }
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for BorrowKind {
+    type T = rustc_mir::BorrowKind;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            BorrowKind::Shared => rustc_mir::BorrowKind::Shared,
+            BorrowKind::Fake => rustc_mir::BorrowKind::Fake,
+            BorrowKind::Mut { kind } => rustc_mir::BorrowKind::Mut { kind: kind.internal(tables) },
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for MutBorrowKind {
+    type T = rustc_mir::MutBorrowKind;
+
+    fn internal(&self, _tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            MutBorrowKind::Default => rustc_mir::MutBorrowKind::Default,
+            MutBorrowKind::TwoPhaseBorrow => rustc_mir::MutBorrowKind::TwoPhaseBorrow,
+            MutBorrowKind::ClosureCapture => rustc_mir::MutBorrowKind::ClosureCapture,
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for NullOp {
+    type T = rustc_mir::NullOp<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            NullOp::SizeOf => rustc_mir::NullOp::SizeOf,
+            NullOp::AlignOf => rustc_mir::NullOp::AlignOf,
+            NullOp::OffsetOf(indices) => rustc_mir::NullOp::OffsetOf(
+                tables.tcx.mk_offset_of_from_iter(indices.iter().map(|(variant, field)| {
+                    (variant.internal(tables), rustc_target::abi::FieldIdx::from_usize(*field))
+                })),
+            ),
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for CastKind {
+    type T = rustc_mir::CastKind;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            CastKind::PointerExposeAddress => rustc_mir::CastKind::PointerExposeAddress,
+            CastKind::PointerFromExposedAddress => rustc_mir::CastKind::PointerFromExposedAddress,
+            CastKind::PointerCoercion(coercion) => {
+                rustc_mir::CastKind::PointerCoercion(coercion.internal(tables))
+            }
+            CastKind::DynStar => rustc_mir::CastKind::DynStar,
+            CastKind::IntToInt => rustc_mir::CastKind::IntToInt,
+            CastKind::FloatToInt => rustc_mir::CastKind::FloatToInt,
+            CastKind::FloatToFloat => rustc_mir::CastKind::FloatToFloat,
+            CastKind::IntToFloat => rustc_mir::CastKind::IntToFloat,
+            CastKind::PtrToPtr => rustc_mir::CastKind::PtrToPtr,
+            CastKind::FnPtrToPtr => rustc_mir::CastKind::FnPtrToPtr,
+            CastKind::Transmute => rustc_mir::CastKind::Transmute,
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for PointerCoercion {
+    type T = rustc_ty::adjustment::PointerCoercion;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use rustc_ty::adjustment::PointerCoercion as InternalCoercion;
+        match self {
+            PointerCoercion::ReifyFnPointer => InternalCoercion::ReifyFnPointer,
+            PointerCoercion::UnsafeFnPointer => InternalCoercion::UnsafeFnPointer,
+            PointerCoercion::ClosureFnPointer(safety) => {
+                InternalCoercion::ClosureFnPointer(safety.internal(tables))
+            }
+            PointerCoercion::MutToConstPointer => InternalCoercion::MutToConstPointer,
+            PointerCoercion::ArrayToPointer => InternalCoercion::ArrayToPointer,
+            PointerCoercion::Unsize => InternalCoercion::Unsize,
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for BinOp {
+    type T = rustc_mir::BinOp;
+
+    fn internal(&self, _tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            BinOp::Add => rustc_mir::BinOp::Add,
+            BinOp::AddUnchecked => rustc_mir::BinOp::AddUnchecked,
+            BinOp::Sub => rustc_mir::BinOp::Sub,
+            BinOp::SubUnchecked => rustc_mir::BinOp::SubUnchecked,
+            BinOp::Mul => rustc_mir::BinOp::Mul,
+            BinOp::MulUnchecked => rustc_mir::BinOp::MulUnchecked,
+            BinOp::Div => rustc_mir::BinOp::Div,
+            BinOp::Rem => rustc_mir::BinOp::Rem,
+            BinOp::BitXor => rustc_mir::BinOp::BitXor,
+            BinOp::BitAnd => rustc_mir::BinOp::BitAnd,
+            BinOp::BitOr => rustc_mir::BinOp::BitOr,
+            BinOp::Shl => rustc_mir::BinOp::Shl,
+            BinOp::ShlUnchecked => rustc_mir::BinOp::ShlUnchecked,
+            BinOp::Shr => rustc_mir::BinOp::Shr,
+            BinOp::ShrUnchecked => rustc_mir::BinOp::ShrUnchecked,
+            BinOp::Eq => rustc_mir::BinOp::Eq,
+            BinOp::Lt => rustc_mir::BinOp::Lt,
+            BinOp::Le => rustc_mir::BinOp::Le,
+            BinOp::Ne => rustc_mir::BinOp::Ne,
+            BinOp::Ge => rustc_mir::BinOp::Ge,
+            BinOp::Gt => rustc_mir::BinOp::Gt,
+            BinOp::Offset => rustc_mir::BinOp::Offset,
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for UnOp {
+    type T = rustc_mir::UnOp;
+
+    fn internal(&self, _tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            UnOp::Not => rustc_mir::UnOp::Not,
+            UnOp::Neg => rustc_mir::UnOp::Neg,
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for RetagKind {
+    type T = rustc_mir::RetagKind;
+
+    fn internal(&self, _tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            RetagKind::FnEntry => rustc_mir::RetagKind::FnEntry,
+            RetagKind::TwoPhase => rustc_mir::RetagKind::TwoPhase,
+            RetagKind::Raw => rustc_mir::RetagKind::Raw,
+            RetagKind::Default => rustc_mir::RetagKind::Default,
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for NonDivergingIntrinsic {
+    type T = rustc_mir::NonDivergingIntrinsic<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            NonDivergingIntrinsic::Assume(operand) => {
+                rustc_mir::NonDivergingIntrinsic::Assume(operand.internal(tables))
+            }
+            NonDivergingIntrinsic::CopyNonOverlapping(copy) => {
+                rustc_mir::NonDivergingIntrinsic::CopyNonOverlapping(
+                    rustc_mir::CopyNonOverlapping {
+                        src: copy.src.internal(tables),
+                        dst: copy.dst.internal(tables),
+                        count: copy.count.internal(tables),
+                    },
+                )
+            }
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for AssertMessage {
+    type T = rustc_mir::AssertMessage<'tcx>;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use rustc_mir::AssertKind;
+        match self {
+            AssertMessage::BoundsCheck { len, index } => {
+                AssertKind::BoundsCheck { len: len.internal(tables), index: index.internal(tables) }
+            }
+            AssertMessage::Overflow(bin_op, lhs, rhs) => AssertKind::Overflow(
+                bin_op.internal(tables),
+                lhs.internal(tables),
+                rhs.internal(tables),
+            ),
+            AssertMessage::OverflowNeg(operand) => {
+                AssertKind::OverflowNeg(operand.internal(tables))
+            }
+            AssertMessage::DivisionByZero(operand) => {
+                AssertKind::DivisionByZero(operand.internal(tables))
+            }
+            AssertMessage::RemainderByZero(operand) => {
+                AssertKind::RemainderByZero(operand.internal(tables))
+            }
+            AssertMessage::ResumedAfterReturn(coroutine) => {
+                AssertKind::ResumedAfterReturn(coroutine.internal(tables))
+            }
+            AssertMessage::ResumedAfterPanic(coroutine) => {
+                AssertKind::ResumedAfterPanic(coroutine.internal(tables))
+            }
+            AssertMessage::MisalignedPointerDereference { required, found } => {
+                AssertKind::MisalignedPointerDereference {
+                    required: required.internal(tables),
+                    found: found.internal(tables),
+                }
+            }
+        }
+    }
+}
+
+impl<'tcx> RustcInternal<'tcx> for CoroutineKind {
+    type T = rustc_hir::CoroutineKind;
+
+    fn internal(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            CoroutineKind::Desugared(desugaring, source) => {
+                let desugaring = match desugaring {
+                    CoroutineDesugaring::Async => rustc_hir::CoroutineDesugaring::Async,
+                    CoroutineDesugaring::Gen => rustc_hir::CoroutineDesugaring::Gen,
+                    CoroutineDesugaring::AsyncGen => rustc_hir::CoroutineDesugaring::AsyncGen,
+                };
+                let source = match source {
+                    CoroutineSource::Block => rustc_hir::CoroutineSource::Block,
+                    CoroutineSource::Closure => rustc_hir::CoroutineSource::Closure,
+                    CoroutineSource::Fn => rustc_hir::CoroutineSource::Fn,
+                };
+                rustc_hir::CoroutineKind::Desugared(desugaring, source)
+            }
+            CoroutineKind::Coroutine(movability) => {
+                rustc_hir::CoroutineKind::Coroutine(movability.internal(tables))
+            }
+        }
+    }
+}
+
 impl<'tcx, T> RustcInternal<'tcx> for &T
 where
     T: RustcInternal<'tcx>,
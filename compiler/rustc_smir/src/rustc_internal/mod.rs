@@ -5,8 +5,9 @@
 
 use crate::rustc_smir::{context::TablesWrapper, Stable, Tables};
 use rustc_data_structures::fx;
-use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
 use rustc_middle::mir::interpret::AllocId;
+use rustc_middle::query::Providers;
 use rustc_middle::ty;
 use rustc_middle::ty::TyCtxt;
 use rustc_span::def_id::{CrateNum, DefId};
@@ -21,8 +22,35 @@
 use std::hash::Hash;
 use std::ops::Index;
 
+mod borrowck;
 mod internal;
 pub mod pretty;
+mod transform;
+
+pub(crate) use borrowck::body_with_facts;
+pub use transform::{install_mir_transform, MirTransform};
+
+/// Override the queries that apply the MIR transformation installed with [install_mir_transform].
+/// This is meant to be called from `Config::override_queries`.
+pub fn override_queries(providers: &mut Providers) {
+    transform::override_queries(providers);
+}
+
+/// Options of the compilation driven by the `run!` macro.
+#[derive(Clone, Copy, Default)]
+pub struct RunOptions {
+    /// A transformation to apply to the body of every function of the local crate, before it is
+    /// optimized. Const functions and coroutines are not transformed.
+    pub mir_transform: Option<MirTransform>,
+    /// Give control to the callback before the bodies of the local crate are optimized, so that
+    /// the facts of the borrow checker can be retrieved, e.g. with `CrateItem::borrowck_results`.
+    /// The crate is borrow-checked, but the rest of the analysis only runs after the callback.
+    pub borrowck_facts: bool,
+    /// Record the functions that the mono items only mention, which `mono_item_graph` reports
+    /// as uses of kind `UsageKind::Mentioned`. This resolves the type of every constant that is a
+    /// function item while collecting the mono items, so it isn't done otherwise.
+    pub mono_item_mentions: bool,
+}
 
 pub fn stable<'tcx, S: Stable<'tcx>>(item: S) -> S::T {
     with_tables(|tables| item.stable(tables))
@@ -186,16 +214,25 @@ pub fn run<F, T>(tcx: TyCtxt<'_>, f: F) -> Result<T, Error>
         instances: IndexMap::default(),
         constants: IndexMap::default(),
         layouts: IndexMap::default(),
+        borrowck_facts: FxHashMap::default(),
+        mono_item_graph: None,
     }));
     stable_mir::compiler_interface::run(&tables, || init(&tables, f))
 }
 
+/// Runs the compiler with the given arguments, and calls the callback with the `TyCtxt` of the
+/// compilation once it is analyzed, or once it is borrow-checked if the facts of the borrow
+/// checker are asked for. The compilation can be configured with a `rustc_internal::RunOptions`,
+/// given before the callback.
 #[macro_export]
 macro_rules! run {
     ($args:expr, $callback:expr) => {
         run!($args, tcx, $callback)
     };
-    ($args:expr, $tcx:ident, $callback:expr) => {{
+    ($args:expr, $tcx:ident, $callback:expr) => {
+        run!($args, rustc_internal::RunOptions::default(), $tcx, $callback)
+    };
+    ($args:expr, $options:expr, $tcx:ident, $callback:expr) => {{
         use rustc_driver::{Callbacks, Compilation, RunCompiler};
         use rustc_interface::{interface, Queries};
         use stable_mir::CompilerError;
@@ -207,6 +244,7 @@ pub struct StableMir<B = (), C = ()>
             C: Send,
         {
             args: Vec<String>,
+            options: rustc_internal::RunOptions,
             callback: fn(TyCtxt<'_>) -> ControlFlow<B, C>,
             result: Option<ControlFlow<B, C>>,
         }
@@ -217,8 +255,25 @@ impl<B, C> StableMir<B, C>
             C: Send,
         {
             /// Creates a new `StableMir` instance, with given test_function and arguments.
-            pub fn new(args: Vec<String>, callback: fn(TyCtxt<'_>) -> ControlFlow<B, C>) -> Self {
-                StableMir { args, callback, result: None }
+            pub fn new(
+                args: Vec<String>,
+                options: rustc_internal::RunOptions,
+                callback: fn(TyCtxt<'_>) -> ControlFlow<B, C>,
+            ) -> Self {
+                StableMir { args, options, callback, result: None }
+            }
+
+            /// Calls the callback, and tells whether to continue the compilation.
+            fn call(&mut self, tcx: TyCtxt<'_>) -> Compilation {
+                rustc_internal::run(tcx, || {
+                    self.result = Some((self.callback)(tcx));
+                })
+                .unwrap();
+                if self.result.as_ref().is_some_and(|val| val.is_continue()) {
+                    Compilation::Continue
+                } else {
+                    Compilation::Stop
+                }
             }
 
             /// Runs the compiler against given target and tests it with `test_function`
@@ -243,28 +298,65 @@ impl<B, C> Callbacks for StableMir<B, C>
             B: Send,
             C: Send,
         {
-            /// Called after analysis. Return value instructs the compiler whether to
-            /// continue the compilation afterwards (defaults to `Compilation::Continue`)
-            fn after_analysis<'tcx>(
+            /// Overrides the queries that apply the MIR transformation of the options, if any,
+            /// and records the mentions of mono items if the options ask for them.
+            fn config(&mut self, config: &mut interface::Config) {
+                if self.options.mir_transform.is_some() {
+                    config.override_queries =
+                        Some(|_, providers| rustc_internal::override_queries(providers));
+                }
+                if self.options.mono_item_mentions {
+                    config.opts.unstable_opts.record_mono_item_mentions = true;
+                }
+            }
+
+            /// Installs the MIR transformation of the options for the session of the compilation.
+            fn after_crate_root_parsing<'tcx>(
+                &mut self,
+                compiler: &interface::Compiler,
+                _queries: &'tcx Queries<'tcx>,
+            ) -> Compilation {
+                if let Some(transform) = self.options.mir_transform {
+                    rustc_internal::install_mir_transform(&compiler.sess, transform);
+                }
+                Compilation::Continue
+            }
+
+            /// Borrow-checks the crate and calls the callback if the options ask for the facts
+            /// of the borrow checker, which are only available until the bodies are optimized.
+            fn after_expansion<'tcx>(
                 &mut self,
                 _compiler: &interface::Compiler,
                 queries: &'tcx Queries<'tcx>,
             ) -> Compilation {
+                if !self.options.borrowck_facts {
+                    return Compilation::Continue;
+                }
                 queries.global_ctxt().unwrap().enter(|tcx| {
-                    rustc_internal::run(tcx, || {
-                        self.result = Some((self.callback)(tcx));
-                    })
-                    .unwrap();
-                    if self.result.as_ref().is_some_and(|val| val.is_continue()) {
-                        Compilation::Continue
-                    } else {
-                        Compilation::Stop
+                    tcx.hir().par_body_owners(|def_id| tcx.ensure().mir_borrowck(def_id));
+                    if tcx.dcx().has_errors().is_some() {
+                        return Compilation::Stop;
                     }
+                    self.call(tcx)
                 })
             }
+
+            /// Called after analysis. Return value instructs the compiler whether to
+            /// continue the compilation afterwards (defaults to `Compilation::Continue`)
+            fn after_analysis<'tcx>(
+                &mut self,
+                _compiler: &interface::Compiler,
+                queries: &'tcx Queries<'tcx>,
+            ) -> Compilation {
+                if self.result.is_some() {
+                    // The callback was already called after expansion.
+                    return Compilation::Continue;
+                }
+                queries.global_ctxt().unwrap().enter(|tcx| self.call(tcx))
+            }
         }
 
-        StableMir::new($args, |$tcx| $callback).run()
+        StableMir::new($args, $options, |$tcx| $callback).run()
     }};
 }
 
@@ -0,0 +1,421 @@
+//! Module that applies the MIR transformation given by a tool to the bodies of the local crate.
+//!
+//! The body of every function is converted to Stable MIR right before it is optimized, given to
+//! the transformation, and converted back to replace the internal body. Stable MIR doesn't have
+//! source scopes for statements, so the scope of a statement is the one of the original statement
+//! with the same span, if any. Constructs that Stable MIR only describes opaquely, i.e. coverage
+//! statements and inline assembly, are restored from the original body.
+
+use super::{run, with_tables, RustcInternal, TLV};
+use crate::rustc_smir::{Stable, Tables};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::steal::Steal;
+use rustc_index::IndexVec;
+use rustc_middle::mir;
+use rustc_middle::query::Providers;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::Session;
+use rustc_span::def_id::LocalDefId;
+use rustc_span::Symbol;
+use stable_mir::mir::{
+    BasicBlock, BasicBlockIdx, Body, Statement, StatementKind, Terminator, TerminatorKind,
+    UnwindAction, VarDebugInfo, VarDebugInfoContents,
+};
+use stable_mir::ty::Span;
+use stable_mir::{CrateItem, Error};
+use std::any::Any;
+use std::sync::OnceLock;
+
+/// A transformation of the body of a function of the local crate.
+pub type MirTransform = fn(CrateItem, &mut Body);
+
+type MirProvider = for<'tcx> fn(TyCtxt<'tcx>, LocalDefId) -> &'tcx Steal<mir::Body<'tcx>>;
+
+static DEFAULT_PROVIDER: OnceLock<MirProvider> = OnceLock::new();
+
+/// Apply `transform` to the body of every function of the local crate compiled by `sess`, before
+/// it is optimized. Const functions and coroutines are not transformed.
+///
+/// The queries must also be overridden with `rustc_internal::override_queries`. The `run!` macro
+/// does both when it is given a transformation in its `RunOptions`.
+pub fn install_mir_transform(sess: &Session, transform: MirTransform) {
+    *sess.mir_transform.lock() = Some(Box::new(transform));
+}
+
+/// The transformation installed for `sess`, if any.
+///
+/// Query providers can't capture the options of the compilation they belong to, so the provider
+/// looks up the transformation stored in the session of its `TyCtxt`.
+fn mir_transform(sess: &Session) -> Option<MirTransform> {
+    let transform = sess.mir_transform.lock();
+    let transform: &dyn Any = &**transform.as_ref()?;
+    transform.downcast_ref().copied()
+}
+
+/// Install the provider that applies the transformation of the session, if any.
+pub(super) fn override_queries(providers: &mut Providers) {
+    let _ = DEFAULT_PROVIDER.set(providers.mir_drops_elaborated_and_const_checked);
+    providers.mir_drops_elaborated_and_const_checked = mir_drops_elaborated_and_const_checked;
+}
+
+fn mir_drops_elaborated_and_const_checked(
+    tcx: TyCtxt<'_>,
+    def: LocalDefId,
+) -> &Steal<mir::Body<'_>> {
+    let default = DEFAULT_PROVIDER.get().unwrap();
+    let body = default(tcx, def);
+    let Some(transform) = mir_transform(tcx.sess) else {
+        return body;
+    };
+    if !tcx.def_kind(def).is_fn_like()
+        || tcx.hir().body_const_context(def).is_some()
+        || tcx.is_coroutine(def.to_def_id())
+        || body.borrow().tainted_by_errors.is_some()
+    {
+        return body;
+    }
+
+    let mut body = body.steal();
+    let mut transform = || transform_body(tcx, def, &mut body, transform);
+    // The body may be computed while the tool is already using Stable MIR, e.g. if it asks for
+    // the body of a function.
+    if TLV.is_set() {
+        transform()
+    } else {
+        run(tcx, transform).unwrap()
+    }
+    tcx.alloc_steal_mir(body)
+}
+
+/// Apply `transform` to `body`. If the transformed body is invalid, an error is reported and the
+/// original body is kept.
+fn transform_body<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def: LocalDefId,
+    body: &mut mir::Body<'tcx>,
+    transform: MirTransform,
+) {
+    let (item, mut stable_body) =
+        with_tables(|tables| (tables.crate_item(def.to_def_id()), body.stable(tables)));
+    transform(item, &mut stable_body);
+    // The original body is only modified once the whole transformed body was converted.
+    if let Err(err) =
+        with_tables(|tables| BodyConverter::new(body, tables).convert(&stable_body, body))
+    {
+        tcx.dcx().span_err(
+            body.span,
+            format!("invalid MIR transformation of `{}`: {err}", tcx.def_path_str(def)),
+        );
+    }
+}
+
+/// Converts a transformed body back to the internal body it originates from.
+struct BodyConverter<'a, 'tcx> {
+    tables: &'a mut Tables<'tcx>,
+    /// The scopes of the spans of the original statements and terminators.
+    scopes: FxHashMap<rustc_span::Span, mir::SourceScope>,
+    /// The coverage statements of the original body.
+    coverage: Vec<(StatementKind, mir::StatementKind<'tcx>)>,
+    /// The inline assembly terminators of the original body.
+    inline_asm: Vec<(TerminatorKind, mir::TerminatorKind<'tcx>)>,
+}
+
+impl<'a, 'tcx> BodyConverter<'a, 'tcx> {
+    fn new(original: &mir::Body<'tcx>, tables: &'a mut Tables<'tcx>) -> Self {
+        let mut scopes = FxHashMap::default();
+        let mut coverage = vec![];
+        let mut inline_asm = vec![];
+        for block in original.basic_blocks.iter() {
+            for statement in &block.statements {
+                scopes.entry(statement.source_info.span).or_insert(statement.source_info.scope);
+                if let mir::StatementKind::Coverage(..) = statement.kind {
+                    coverage.push((statement.kind.stable(tables), statement.kind.clone()));
+                }
+            }
+            let terminator = block.terminator();
+            scopes.entry(terminator.source_info.span).or_insert(terminator.source_info.scope);
+            if let mir::TerminatorKind::InlineAsm { .. } = terminator.kind {
+                inline_asm.push((terminator.kind.stable(tables), terminator.kind.clone()));
+            }
+        }
+        BodyConverter { tables, scopes, coverage, inline_asm }
+    }
+
+    fn convert(mut self, body: &Body, original: &mut mir::Body<'tcx>) -> Result<(), Error> {
+        if body.locals().len() < original.local_decls.len() {
+            return Err(Error::from("Locals of the original body were removed"));
+        }
+        if body.arg_locals().len() != original.arg_count {
+            return Err(Error::from("The number of arguments changed"));
+        }
+        for (idx, block) in body.blocks.iter().enumerate() {
+            if let Some(target) = block
+                .terminator
+                .successors()
+                .into_iter()
+                .find(|&target| target >= body.blocks.len())
+            {
+                return Err(Error::new(format!("bb{idx} jumps to non-existent bb{target}")));
+            }
+        }
+
+        let is_cleanup = cleanup_blocks(body);
+        let blocks = body
+            .blocks
+            .iter()
+            .zip(is_cleanup)
+            .map(|(block, is_cleanup)| self.basic_block(block, is_cleanup))
+            .collect::<Result<IndexVec<mir::BasicBlock, _>, _>>()?;
+        let var_debug_info = body
+            .var_debug_info
+            .iter()
+            .map(|info| self.var_debug_info(info))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        *original.basic_blocks_mut() = blocks;
+        for decl in &body.locals()[original.local_decls.len()..] {
+            let mut local_decl =
+                mir::LocalDecl::new(decl.ty.internal(self.tables), decl.span.internal(self.tables));
+            local_decl.mutability = decl.mutability.internal(self.tables);
+            original.local_decls.push(local_decl);
+        }
+        original.var_debug_info = var_debug_info;
+        Ok(())
+    }
+
+    fn basic_block(
+        &mut self,
+        block: &BasicBlock,
+        is_cleanup: bool,
+    ) -> Result<mir::BasicBlockData<'tcx>, Error> {
+        let statements = block
+            .statements
+            .iter()
+            .map(|statement| self.statement(statement))
+            .collect::<Result<Vec<_>, _>>()?;
+        let terminator = self.terminator(&block.terminator, is_cleanup)?;
+        let mut data = mir::BasicBlockData::new(Some(terminator));
+        data.statements = statements;
+        data.is_cleanup = is_cleanup;
+        Ok(data)
+    }
+
+    fn source_info(&mut self, span: Span) -> mir::SourceInfo {
+        let span = span.internal(self.tables);
+        let scope = self.scopes.get(&span).copied().unwrap_or(mir::OUTERMOST_SOURCE_SCOPE);
+        mir::SourceInfo { span, scope }
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<mir::Statement<'tcx>, Error> {
+        let source_info = self.source_info(statement.span);
+        let tables = &mut *self.tables;
+        let kind = match &statement.kind {
+            StatementKind::Assign(place, rvalue) => mir::StatementKind::Assign(Box::new((
+                place.internal(tables),
+                rvalue.internal(tables),
+            ))),
+            StatementKind::SetDiscriminant { place, variant_index } => {
+                mir::StatementKind::SetDiscriminant {
+                    place: Box::new(place.internal(tables)),
+                    variant_index: variant_index.internal(tables),
+                }
+            }
+            StatementKind::Deinit(place) => {
+                mir::StatementKind::Deinit(Box::new(place.internal(tables)))
+            }
+            StatementKind::StorageLive(local) => {
+                mir::StatementKind::StorageLive(mir::Local::from_usize(*local))
+            }
+            StatementKind::StorageDead(local) => {
+                mir::StatementKind::StorageDead(mir::Local::from_usize(*local))
+            }
+            StatementKind::Retag(kind, place) => {
+                mir::StatementKind::Retag(kind.internal(tables), Box::new(place.internal(tables)))
+            }
+            StatementKind::PlaceMention(place) => {
+                mir::StatementKind::PlaceMention(Box::new(place.internal(tables)))
+            }
+            StatementKind::Intrinsic(intrinsic) => {
+                mir::StatementKind::Intrinsic(Box::new(intrinsic.internal(tables)))
+            }
+            StatementKind::ConstEvalCounter => mir::StatementKind::ConstEvalCounter,
+            StatementKind::Nop => mir::StatementKind::Nop,
+            StatementKind::Coverage(_) => self
+                .coverage
+                .iter()
+                .find(|(stable, _)| *stable == statement.kind)
+                .map(|(_, kind)| kind.clone())
+                .ok_or_else(|| Error::from("Coverage statements can't be created"))?,
+            StatementKind::FakeRead(..) | StatementKind::AscribeUserType { .. } => {
+                return Err(Error::new(format!(
+                    "`{:?}` is not allowed after borrow checking",
+                    statement.kind
+                )));
+            }
+        };
+        Ok(mir::Statement { source_info, kind })
+    }
+
+    fn terminator(
+        &mut self,
+        terminator: &Terminator,
+        is_cleanup: bool,
+    ) -> Result<mir::Terminator<'tcx>, Error> {
+        let source_info = self.source_info(terminator.span);
+        // Stable MIR doesn't record why unwinding terminates, which depends on whether the
+        // function is already unwinding.
+        let reason = if is_cleanup {
+            mir::UnwindTerminateReason::InCleanup
+        } else {
+            mir::UnwindTerminateReason::Abi
+        };
+        let unwind = |unwind: &UnwindAction| match unwind {
+            UnwindAction::Continue => mir::UnwindAction::Continue,
+            UnwindAction::Unreachable => mir::UnwindAction::Unreachable,
+            UnwindAction::Terminate => mir::UnwindAction::Terminate(reason),
+            UnwindAction::Cleanup(target) => mir::UnwindAction::Cleanup(block(*target)),
+        };
+        let tables = &mut *self.tables;
+        let kind = match &terminator.kind {
+            TerminatorKind::Goto { target } => mir::TerminatorKind::Goto { target: block(*target) },
+            TerminatorKind::SwitchInt { discr, targets } => mir::TerminatorKind::SwitchInt {
+                discr: discr.internal(tables),
+                targets: mir::SwitchTargets::new(
+                    targets.branches().map(|(value, target)| (value, block(target))),
+                    block(targets.otherwise()),
+                ),
+            },
+            TerminatorKind::Resume => mir::TerminatorKind::UnwindResume,
+            TerminatorKind::Abort => mir::TerminatorKind::UnwindTerminate(reason),
+            TerminatorKind::Return => mir::TerminatorKind::Return,
+            TerminatorKind::Unreachable => mir::TerminatorKind::Unreachable,
+            TerminatorKind::Drop { place, target, unwind: drop_unwind } => {
+                mir::TerminatorKind::Drop {
+                    place: place.internal(tables),
+                    target: block(*target),
+                    unwind: unwind(drop_unwind),
+                    replace: false,
+                }
+            }
+            TerminatorKind::Call { func, args, destination, target, unwind: call_unwind } => {
+                mir::TerminatorKind::Call {
+                    func: func.internal(tables),
+                    args: args.internal(tables),
+                    destination: destination.internal(tables),
+                    target: target.map(block),
+                    unwind: unwind(call_unwind),
+                    call_source: mir::CallSource::Normal,
+                    fn_span: source_info.span,
+                }
+            }
+            TerminatorKind::Assert { cond, expected, msg, target, unwind: assert_unwind } => {
+                mir::TerminatorKind::Assert {
+                    cond: cond.internal(tables),
+                    expected: *expected,
+                    msg: Box::new(msg.internal(tables)),
+                    target: block(*target),
+                    unwind: unwind(assert_unwind),
+                }
+            }
+            TerminatorKind::InlineAsm { destination, unwind: asm_unwind, .. } => {
+                let Some((_, original)) = self
+                    .inline_asm
+                    .iter()
+                    .find(|(stable, _)| same_inline_asm(stable, &terminator.kind))
+                else {
+                    return Err(Error::from("Inline assembly can't be created"));
+                };
+                let mut kind = original.clone();
+                let mir::TerminatorKind::InlineAsm { destination: dest, unwind: unw, .. } =
+                    &mut kind
+                else {
+                    unreachable!()
+                };
+                *dest = destination.map(block);
+                *unw = unwind(asm_unwind);
+                kind
+            }
+        };
+        Ok(mir::Terminator { source_info, kind })
+    }
+
+    fn var_debug_info(&mut self, info: &VarDebugInfo) -> Result<mir::VarDebugInfo<'tcx>, Error> {
+        let tables = &mut *self.tables;
+        let composite = info.composite.as_ref().map(|composite| {
+            Box::new(mir::VarDebugInfoFragment {
+                ty: composite.ty.internal(tables),
+                projection: composite.projection.internal(tables),
+            })
+        });
+        let value = match &info.value {
+            VarDebugInfoContents::Place(place) => {
+                mir::VarDebugInfoContents::Place(place.internal(tables))
+            }
+            VarDebugInfoContents::Const(constant) => {
+                mir::VarDebugInfoContents::Const(mir::ConstOperand {
+                    span: constant.span.internal(tables),
+                    user_ty: constant
+                        .user_ty
+                        .map(rustc_middle::ty::UserTypeAnnotationIndex::from_usize),
+                    const_: constant.const_.internal(tables),
+                })
+            }
+        };
+        Ok(mir::VarDebugInfo {
+            name: Symbol::intern(&info.name),
+            source_info: mir::SourceInfo {
+                span: info.source_info.span.internal(tables),
+                scope: mir::SourceScope::from_u32(info.source_info.scope),
+            },
+            composite,
+            value,
+            argument_index: info.argument_index,
+        })
+    }
+}
+
+fn block(idx: BasicBlockIdx) -> mir::BasicBlock {
+    mir::BasicBlock::from_usize(idx)
+}
+
+/// Stable MIR doesn't record which blocks are cleanup blocks, i.e. the blocks only reached while
+/// unwinding. Those are the blocks reachable from an unwind edge.
+fn cleanup_blocks(body: &Body) -> Vec<bool> {
+    let mut is_cleanup = vec![false; body.blocks.len()];
+    let mut worklist: Vec<BasicBlockIdx> = body
+        .blocks
+        .iter()
+        .filter_map(|block| match block.terminator.kind.unwind() {
+            Some(UnwindAction::Cleanup(target)) => Some(*target),
+            _ => None,
+        })
+        .collect();
+    while let Some(idx) = worklist.pop() {
+        if !std::mem::replace(&mut is_cleanup[idx], true) {
+            worklist.extend(body.blocks[idx].terminator.successors());
+        }
+    }
+    is_cleanup
+}
+
+/// Whether two inline assembly terminators only differ by their successors.
+fn same_inline_asm(a: &TerminatorKind, b: &TerminatorKind) -> bool {
+    match (a, b) {
+        (
+            TerminatorKind::InlineAsm { template, operands, options, line_spans, .. },
+            TerminatorKind::InlineAsm {
+                template: b_template,
+                operands: b_operands,
+                options: b_options,
+                line_spans: b_line_spans,
+                ..
+            },
+        ) => {
+            template == b_template
+                && operands == b_operands
+                && options == b_options
+                && line_spans == b_line_spans
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,191 @@
+//! Run the dataflow analyses exposed by Stable MIR on the bodies the borrow checker analyzed, and
+//! convert their results.
+
+use rustc_borrowck::borrow_set::TwoPhaseActivation;
+use rustc_borrowck::consumers::{BodyWithBorrowckFacts, Borrows};
+use rustc_index::bit_set::ChunkedBitSet;
+use rustc_middle::mir;
+use rustc_middle::mir::traversal;
+use rustc_mir_dataflow::impls::{
+    MaybeBorrowedLocals, MaybeInitializedPlaces, MaybeLiveLocals, MaybeStorageLive,
+    MaybeUninitializedPlaces,
+};
+use rustc_mir_dataflow::move_paths::{MoveData, MovePathIndex};
+use rustc_mir_dataflow::storage::always_storage_live_locals;
+use rustc_mir_dataflow::{Analysis, Direction, MaybeReachable, MoveDataParamEnv, Results};
+use stable_mir::mir::analysis::{
+    BodyLocation, BorrowckResults, DataflowResults, Loan, LocalAnalysis, OutlivesConstraint,
+    PlaceAnalysis,
+};
+use stable_mir::mir::{Local, Place};
+use stable_mir::Error;
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use crate::rustc_internal;
+use crate::rustc_smir::{Stable, Tables};
+
+/// Retrieve the borrow-checked body of `item` with its facts. The body is borrow-checked again the
+/// first time its facts are requested, which must happen before it is optimized.
+pub(crate) fn body_with_facts<'tcx>(
+    tables: &mut Tables<'tcx>,
+    item: stable_mir::DefId,
+) -> Result<Rc<BodyWithBorrowckFacts<'tcx>>, Error> {
+    let def_id = tables[item];
+    let Some(def) = def_id.as_local() else {
+        return Err(Error::new(format!(
+            "Item `{}` is not in the local crate",
+            tables.tcx.def_path_str(def_id)
+        )));
+    };
+    if let Some(body_with_facts) = tables.borrowck_facts.get(&def) {
+        return Ok(body_with_facts.clone());
+    }
+    let body_with_facts = Rc::new(rustc_internal::body_with_facts(tables.tcx, def)?);
+    tables.borrowck_facts.insert(def, body_with_facts.clone());
+    Ok(body_with_facts)
+}
+
+pub(crate) fn place_dataflow<'tcx>(
+    tables: &mut Tables<'tcx>,
+    body: &mir::Body<'tcx>,
+    analysis: PlaceAnalysis,
+) -> DataflowResults<Place> {
+    let tcx = tables.tcx;
+    let param_env = tcx.param_env(body.source.def_id());
+    let move_data = MoveData::gather_moves(body, tcx, param_env, |_| true);
+    let mdpe = MoveDataParamEnv { move_data, param_env };
+    let mut places = |paths: &ChunkedBitSet<MovePathIndex>| {
+        paths.iter().map(|path| mdpe.move_data.move_paths[path].place.stable(tables)).collect()
+    };
+    match analysis {
+        PlaceAnalysis::MaybeInitialized => {
+            let results = MaybeInitializedPlaces::new(tcx, body, &mdpe)
+                .into_engine(tcx, body)
+                .iterate_to_fixpoint();
+            collect_states(body, results, |state| match state {
+                MaybeReachable::Reachable(paths) => places(paths),
+                MaybeReachable::Unreachable => vec![],
+            })
+        }
+        PlaceAnalysis::MaybeUninitialized => {
+            let results = MaybeUninitializedPlaces::new(tcx, body, &mdpe)
+                .into_engine(tcx, body)
+                .iterate_to_fixpoint();
+            collect_states(body, results, places)
+        }
+    }
+}
+
+pub(crate) fn local_dataflow<'tcx>(
+    tables: &mut Tables<'tcx>,
+    body: &mir::Body<'tcx>,
+    analysis: LocalAnalysis,
+) -> DataflowResults<Local> {
+    let tcx = tables.tcx;
+    match analysis {
+        LocalAnalysis::MaybeLive => {
+            let results = MaybeLiveLocals.into_engine(tcx, body).iterate_to_fixpoint();
+            collect_states(body, results, |locals| locals.iter().map(|l| l.as_usize()).collect())
+        }
+        LocalAnalysis::MaybeStorageLive => {
+            let always_live_locals = always_storage_live_locals(body);
+            let results = MaybeStorageLive::new(Cow::Owned(always_live_locals))
+                .into_engine(tcx, body)
+                .iterate_to_fixpoint();
+            collect_states(body, results, |locals| locals.iter().map(|l| l.as_usize()).collect())
+        }
+        LocalAnalysis::MaybeBorrowed => {
+            let results = MaybeBorrowedLocals.into_engine(tcx, body).iterate_to_fixpoint();
+            collect_states(body, results, |locals| locals.iter().map(|l| l.as_usize()).collect())
+        }
+    }
+}
+
+pub(crate) fn borrowck_results<'tcx>(
+    tables: &mut Tables<'tcx>,
+    body_with_facts: &BodyWithBorrowckFacts<'tcx>,
+) -> BorrowckResults {
+    let tcx = tables.tcx;
+    let body = &body_with_facts.body;
+    let borrow_set = &body_with_facts.borrow_set;
+    let regioncx = &body_with_facts.region_inference_context;
+    let loans = borrow_set
+        .location_map
+        .values()
+        .map(|loan| Loan {
+            borrowed_place: loan.borrowed_place.stable(tables),
+            assigned_place: loan.assigned_place.stable(tables),
+            kind: loan.kind.stable(tables),
+            region: loan.region.as_u32(),
+            reserve_location: body_location(loan.reserve_location),
+            activation_location: match loan.activation_location {
+                TwoPhaseActivation::ActivatedAt(location) => Some(body_location(location)),
+                TwoPhaseActivation::NotTwoPhase | TwoPhaseActivation::NotActivated => None,
+            },
+        })
+        .collect();
+    let outlives_constraints = regioncx
+        .outlives_constraints()
+        .map(|constraint| OutlivesConstraint {
+            longer: constraint.sup.as_u32(),
+            shorter: constraint.sub.as_u32(),
+            span: constraint.span.stable(tables),
+        })
+        .collect();
+    let results =
+        Borrows::new(tcx, body, regioncx, borrow_set).into_engine(tcx, body).iterate_to_fixpoint();
+    let loans_in_scope =
+        collect_states(body, results, |loans| loans.iter().map(|l| l.as_usize()).collect());
+    BorrowckResults { loans, outlives_constraints, loans_in_scope }
+}
+
+/// Collect the states of an analysis before and after each location of `body`, in the order of
+/// execution.
+fn collect_states<'tcx, A, T>(
+    body: &mir::Body<'tcx>,
+    results: Results<'tcx, A>,
+    mut elements: impl FnMut(&A::Domain) -> Vec<T>,
+) -> DataflowResults<T>
+where
+    A: Analysis<'tcx>,
+{
+    let reachable = traversal::reachable_as_bitset(body);
+    let mut cursor = results.into_results_cursor(body);
+    let mut before = Vec::with_capacity(body.basic_blocks.len());
+    let mut after = Vec::with_capacity(body.basic_blocks.len());
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        let len = data.statements.len() + 1;
+        let mut block_before: Vec<Vec<T>> = (0..len).map(|_| vec![]).collect();
+        let mut block_after: Vec<Vec<T>> = (0..len).map(|_| vec![]).collect();
+        if reachable.contains(block) {
+            // The cursor is only efficient when it moves in the direction of the analysis.
+            let mut indices: Vec<usize> = (0..len).collect();
+            if A::Direction::IS_BACKWARD {
+                indices.reverse();
+            }
+            for statement_index in indices {
+                let location = mir::Location { block, statement_index };
+                cursor.seek_before_primary_effect(location);
+                let before_effect = elements(cursor.get());
+                cursor.seek_after_primary_effect(location);
+                let after_effect = elements(cursor.get());
+                // A backward analysis applies the effect of a location to the state after it.
+                let (state_before, state_after) = if A::Direction::IS_FORWARD {
+                    (before_effect, after_effect)
+                } else {
+                    (after_effect, before_effect)
+                };
+                block_before[statement_index] = state_before;
+                block_after[statement_index] = state_after;
+            }
+        }
+        before.push(block_before);
+        after.push(block_after);
+    }
+    DataflowResults::new(before, after)
+}
+
+fn body_location(location: mir::Location) -> BodyLocation {
+    BodyLocation { block: location.block.as_usize(), statement_index: location.statement_index }
+}
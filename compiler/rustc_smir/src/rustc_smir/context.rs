@@ -6,6 +6,7 @@
 #![allow(rustc::usage_of_qualified_ty)]
 
 use rustc_abi::HasDataLayout;
+use rustc_data_structures::fx::FxHashMap;
 use rustc_middle::ty;
 use rustc_middle::ty::layout::{
     FnAbiOf, FnAbiOfHelpers, HasParamEnv, HasTyCtxt, LayoutOf, LayoutOfHelpers,
@@ -14,12 +15,15 @@
 use rustc_middle::ty::{
     GenericPredicates, Instance, List, ParamEnv, ScalarInt, TyCtxt, TypeVisitableExt, ValTree,
 };
+use rustc_resolve::rustdoc::{add_doc_fragment, attrs_to_doc_fragments};
 use rustc_span::def_id::LOCAL_CRATE;
 use stable_mir::abi::{FnAbi, Layout, LayoutShape};
 use stable_mir::compiler_interface::Context;
+use stable_mir::crate_def::{Attribute, Visibility};
 use stable_mir::mir::alloc::GlobalAlloc;
-use stable_mir::mir::mono::{InstanceDef, StaticDef};
-use stable_mir::mir::Body;
+use stable_mir::mir::analysis::{BorrowckResults, DataflowResults, LocalAnalysis, PlaceAnalysis};
+use stable_mir::mir::mono::{InstanceDef, MonoItemGraph, MonoItemUse, StaticDef, UsageKind};
+use stable_mir::mir::{Body, Local, Place};
 use stable_mir::target::{MachineInfo, MachineSize};
 use stable_mir::ty::{
     AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, Const, FieldDef, FnDef, GenericArgs,
@@ -30,7 +34,9 @@
 
 use crate::rustc_internal::{internal, RustcInternal};
 use crate::rustc_smir::builder::BodyBuilder;
-use crate::rustc_smir::{alloc, new_item_kind, smir_crate, Stable, Tables};
+use crate::rustc_smir::{
+    alloc, analysis, has_visibility, new_item_kind, smir_crate, Stable, Tables,
+};
 
 impl<'tcx> Context for TablesWrapper<'tcx> {
     fn target_info(&self) -> MachineInfo {
@@ -54,10 +60,62 @@ fn all_local_items(&self) -> stable_mir::CrateItems {
         tables.tcx.mir_keys(()).iter().map(|item| tables.crate_item(item.to_def_id())).collect()
     }
 
-    fn mir_body(&self, item: stable_mir::DefId) -> stable_mir::mir::Body {
+    fn mono_item_graph(&self) -> MonoItemGraph {
         let mut tables = self.0.borrow_mut();
-        let def_id = tables[item];
-        tables.tcx.instance_mir(rustc_middle::ty::InstanceDef::Item(def_id)).stable(&mut tables)
+        if let Some(graph) = &tables.mono_item_graph {
+            return graph.clone();
+        }
+        let tcx = tables.tcx;
+        // The mono items collected for code generation, which are only collected once.
+        let (items, usage_map) = tcx.collect_mono_items(());
+        let mut items: Vec<_> = items.iter().copied().collect();
+        items.sort_by_cached_key(|item| item.symbol_name(tcx).name);
+        let indices: FxHashMap<_, _> =
+            items.iter().enumerate().map(|(index, item)| (*item, index)).collect();
+        let uses = items
+            .iter()
+            .map(|item| {
+                let mut uses: Vec<_> = usage_map
+                    .get_used_items(*item)
+                    .iter()
+                    .filter_map(|(used, kind)| {
+                        Some(MonoItemUse {
+                            item: *indices.get(used)?,
+                            kind: kind.stable(&mut *tables),
+                        })
+                    })
+                    .collect();
+                uses.sort_unstable();
+                uses.dedup();
+                // Only keep the mentions of items that are not otherwise used.
+                let mut mentioned: Vec<_> = usage_map
+                    .get_mentioned_items(*item)
+                    .iter()
+                    .filter_map(|mentioned| indices.get(mentioned).copied())
+                    .filter(|&index| uses.binary_search_by_key(&index, |used| used.item).is_err())
+                    .map(|index| MonoItemUse { item: index, kind: UsageKind::Mentioned })
+                    .collect();
+                uses.append(&mut mentioned);
+                uses.sort_unstable();
+                uses.dedup();
+                uses
+            })
+            .collect();
+        let items = items.iter().map(|item| item.stable(&mut *tables)).collect();
+        let graph = MonoItemGraph::new(items, uses);
+        tables.mono_item_graph = Some(graph.clone());
+        graph
+    }
+
+    fn mir_body(&self, item: stable_mir::DefId) -> stable_mir::mir::Body {
+        let (tcx, def_id) = {
+            let tables = self.0.borrow();
+            (tables.tcx, tables[item])
+        };
+        // Don't borrow the tables while building the body, since that may run a MIR
+        // transformation registered by the user, which accesses the tables.
+        let body = tcx.instance_mir(rustc_middle::ty::InstanceDef::Item(def_id));
+        body.stable(&mut *self.0.borrow_mut())
     }
 
     fn has_body(&self, def: DefId) -> bool {
@@ -66,6 +124,38 @@ fn has_body(&self, def: DefId) -> bool {
         tables.tcx.is_mir_available(def_id)
     }
 
+    fn analysis_body(&self, item: DefId) -> Result<Body, Error> {
+        let mut tables = self.0.borrow_mut();
+        let body_with_facts = analysis::body_with_facts(&mut tables, item)?;
+        Ok(body_with_facts.body.stable(&mut *tables))
+    }
+
+    fn place_dataflow(
+        &self,
+        item: DefId,
+        analysis: PlaceAnalysis,
+    ) -> Result<DataflowResults<Place>, Error> {
+        let mut tables = self.0.borrow_mut();
+        let body_with_facts = analysis::body_with_facts(&mut tables, item)?;
+        Ok(analysis::place_dataflow(&mut tables, &body_with_facts.body, analysis))
+    }
+
+    fn local_dataflow(
+        &self,
+        item: DefId,
+        analysis: LocalAnalysis,
+    ) -> Result<DataflowResults<Local>, Error> {
+        let mut tables = self.0.borrow_mut();
+        let body_with_facts = analysis::body_with_facts(&mut tables, item)?;
+        Ok(analysis::local_dataflow(&mut tables, &body_with_facts.body, analysis))
+    }
+
+    fn borrowck_results(&self, item: DefId) -> Result<BorrowckResults, Error> {
+        let mut tables = self.0.borrow_mut();
+        let body_with_facts = analysis::body_with_facts(&mut tables, item)?;
+        Ok(analysis::borrowck_results(&mut tables, &body_with_facts))
+    }
+
     fn all_trait_decls(&self) -> stable_mir::TraitDecls {
         let mut tables = self.0.borrow_mut();
         tables
@@ -201,6 +291,36 @@ fn get_lines(&self, span: &Span) -> LineInfo {
         LineInfo { start_line: lines.1, start_col: lines.2, end_line: lines.3, end_col: lines.4 }
     }
 
+    fn def_attrs(&self, def_id: stable_mir::DefId) -> Vec<Attribute> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let attrs = tcx.get_attrs_unchecked(tables[def_id]);
+        attrs.iter().map(|attr| attr.stable(&mut *tables)).collect()
+    }
+
+    fn def_visibility(&self, def_id: stable_mir::DefId) -> Option<Visibility> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let def_id = tables[def_id];
+        has_visibility(tcx.def_kind(def_id)).then(|| tcx.visibility(def_id).stable(&mut *tables))
+    }
+
+    fn def_docs(&self, def_id: stable_mir::DefId) -> Option<String> {
+        let tables = self.0.borrow();
+        let attrs = tables.tcx.get_attrs_unchecked(tables[def_id]);
+        let (fragments, _) = attrs_to_doc_fragments(attrs.iter().map(|attr| (attr, None)), true);
+        if fragments.is_empty() {
+            return None;
+        }
+        let mut docs = String::new();
+        for fragment in &fragments {
+            add_doc_fragment(&mut docs, fragment);
+        }
+        // Like rustdoc, remove the newline that ends the last fragment.
+        docs.pop();
+        Some(docs)
+    }
+
     fn item_kind(&self, item: CrateItem) -> ItemKind {
         let tables = self.0.borrow();
         new_item_kind(tables.tcx.def_kind(tables[item.0]))
@@ -281,6 +401,30 @@ fn usize_to_const(&self, val: u64) -> Result<Const, Error> {
             .stable(&mut *tables))
     }
 
+    fn try_new_const_zst(&self, ty: stable_mir::ty::Ty) -> Result<Const, Error> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let ty_internal = ty.internal(&mut *tables);
+        let size = tcx
+            .layout_of(ParamEnv::reveal_all().and(ty_internal))
+            .map_err(|err| {
+                Error::new(format!(
+                    "Cannot create a zero-sized constant for type `{ty_internal}`: {err}"
+                ))
+            })?
+            .size;
+        if size.bytes() != 0 {
+            return Err(Error::new(format!(
+                "Cannot create a zero-sized constant for type `{ty_internal}`: \
+                 Type `{ty_internal}` has {} bytes",
+                size.bytes()
+            )));
+        }
+
+        Ok(rustc_middle::mir::Const::Val(rustc_middle::mir::ConstValue::ZeroSized, ty_internal)
+            .stable(&mut *tables))
+    }
+
     fn new_rigid_ty(&self, kind: RigidTy) -> stable_mir::ty::Ty {
         let mut tables = self.0.borrow_mut();
         let internal_kind = kind.internal(&mut *tables);
@@ -327,11 +471,17 @@ fn rigid_ty_discriminant_ty(&self, ty: &RigidTy) -> stable_mir::ty::Ty {
     }
 
     fn instance_body(&self, def: InstanceDef) -> Option<Body> {
-        let mut tables = self.0.borrow_mut();
-        let instance = tables.instances[def];
-        tables
-            .has_body(instance)
-            .then(|| BodyBuilder::new(tables.tcx, instance).build(&mut *tables))
+        let (tcx, instance, has_body) = {
+            let tables = self.0.borrow();
+            let instance = tables.instances[def];
+            (tables.tcx, instance, tables.has_body(instance))
+        };
+        if !has_body {
+            return None;
+        }
+        // See `mir_body` for why the body is computed before borrowing the tables.
+        tcx.instance_mir(instance.def);
+        Some(BodyBuilder::new(tcx, instance).build(&mut *self.0.borrow_mut()))
     }
 
     fn instance_ty(&self, def: InstanceDef) -> stable_mir::ty::Ty {
@@ -744,3 +744,20 @@ fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
         }
     }
 }
+
+impl<'tcx> Stable<'tcx> for mir::mono::UsageKind {
+    type T = stable_mir::mir::mono::UsageKind;
+
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use rustc_middle::mir::mono::UsageKind;
+        use stable_mir::mir::mono::UsageKind as StableUsageKind;
+        match self {
+            UsageKind::Call => StableUsageKind::Call,
+            UsageKind::DropGlue => StableUsageKind::DropGlue,
+            UsageKind::Vtable => StableUsageKind::Vtable,
+            UsageKind::FnPointer => StableUsageKind::FnPointer,
+            UsageKind::Static => StableUsageKind::Static,
+            UsageKind::Mentioned => StableUsageKind::Mentioned,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Conversion of internal Rust compiler items to stable ones.
 
+use rustc_ast_pretty::pprust;
+use rustc_span::sym;
 use rustc_target::abi::FieldIdx;
 
 use crate::rustc_smir::{Stable, Tables};
@@ -83,3 +85,40 @@ fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
         tables.create_span(*self)
     }
 }
+
+impl<'tcx> Stable<'tcx> for rustc_ast::Attribute {
+    type T = stable_mir::crate_def::Attribute;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use rustc_ast::{AttrArgs, AttrArgsEq, AttrKind, AttrStyle};
+        use stable_mir::crate_def::{AttrArgs as StableAttrArgs, Attribute};
+        let (path, args) = match &self.kind {
+            AttrKind::Normal(normal) => {
+                let item = &normal.item;
+                let path = item.path.segments.iter().map(|seg| seg.ident.name.to_string());
+                let args = match &item.args {
+                    AttrArgs::Empty => StableAttrArgs::Empty,
+                    AttrArgs::Delimited(args) => {
+                        StableAttrArgs::Delimited(pprust::tts_to_string(&args.tokens))
+                    }
+                    AttrArgs::Eq(_, AttrArgsEq::Ast(expr)) => {
+                        StableAttrArgs::Eq(pprust::expr_to_string(expr))
+                    }
+                    AttrArgs::Eq(_, AttrArgsEq::Hir(lit)) => {
+                        StableAttrArgs::Eq(lit.as_token_lit().to_string())
+                    }
+                };
+                (path.collect(), args)
+            }
+            AttrKind::DocComment(_, comment) => {
+                (vec![sym::doc.to_string()], StableAttrArgs::Eq(format!("{:?}", comment.as_str())))
+            }
+        };
+        Attribute {
+            path,
+            args,
+            is_inner: matches!(self.style, AttrStyle::Inner),
+            span: self.span.stable(tables),
+        }
+    }
+}
@@ -775,12 +775,27 @@ fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
                     },
                 })
             }
+            ty::ReVar(vid) => RegionKind::ReVar(vid.as_u32()),
             ty::ReErased => RegionKind::ReErased,
             _ => unreachable!("{self:?}"),
         }
     }
 }
 
+impl<'tcx> Stable<'tcx> for ty::Visibility<rustc_span::def_id::DefId> {
+    type T = stable_mir::crate_def::Visibility;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::crate_def::Visibility;
+        match *self {
+            ty::Visibility::Public => Visibility::Public,
+            ty::Visibility::Restricted(module) => {
+                Visibility::Restricted(tables.create_def_id(module))
+            }
+        }
+    }
+}
+
 impl<'tcx> Stable<'tcx> for ty::Instance<'tcx> {
     type T = stable_mir::mir::mono::Instance;
 
@@ -799,7 +814,8 @@ fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
             | ty::InstanceDef::ThreadLocalShim(..)
             | ty::InstanceDef::DropGlue(..)
             | ty::InstanceDef::CloneShim(..)
-            | ty::InstanceDef::FnPtrShim(..) => stable_mir::mir::mono::InstanceKind::Shim,
+            | ty::InstanceDef::FnPtrShim(..)
+            | ty::InstanceDef::ConstArgShim { .. } => stable_mir::mir::mono::InstanceKind::Shim,
         };
         stable_mir::mir::mono::Instance { def, kind }
     }
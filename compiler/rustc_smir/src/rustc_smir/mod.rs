@@ -7,21 +7,25 @@
 //!
 //! For now, we are developing everything inside `rustc`, thus, we keep this module private.
 
+use rustc_borrowck::consumers::BodyWithBorrowckFacts;
+use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def::DefKind;
 use rustc_middle::mir;
 use rustc_middle::mir::interpret::AllocId;
 use rustc_middle::ty::{self, Instance, Ty, TyCtxt};
-use rustc_span::def_id::{CrateNum, DefId, LOCAL_CRATE};
+use rustc_span::def_id::{CrateNum, DefId, LocalDefId, LOCAL_CRATE};
 use stable_mir::abi::Layout;
-use stable_mir::mir::mono::InstanceDef;
+use stable_mir::mir::mono::{InstanceDef, MonoItemGraph};
 use stable_mir::ty::{ConstId, Span};
 use stable_mir::{CtorKind, ItemKind};
 use std::ops::RangeInclusive;
+use std::rc::Rc;
 use tracing::debug;
 
 use crate::rustc_internal::IndexMap;
 
 mod alloc;
+mod analysis;
 mod builder;
 pub(crate) mod context;
 mod convert;
@@ -35,6 +39,10 @@ pub struct Tables<'tcx> {
     pub(crate) instances: IndexMap<ty::Instance<'tcx>, InstanceDef>,
     pub(crate) constants: IndexMap<mir::Const<'tcx>, ConstId>,
     pub(crate) layouts: IndexMap<rustc_target::abi::Layout<'tcx>, Layout>,
+    /// The bodies borrow-checked again to retrieve their facts, see [analysis::body_with_facts].
+    pub(crate) borrowck_facts: FxHashMap<LocalDefId, Rc<BodyWithBorrowckFacts<'tcx>>>,
+    /// The mono item graph of the local crate, once it is collected.
+    pub(crate) mono_item_graph: Option<MonoItemGraph>,
 }
 
 impl<'tcx> Tables<'tcx> {
@@ -101,6 +109,42 @@ pub(crate) fn new_item_kind(kind: DefKind) -> ItemKind {
     }
 }
 
+/// Whether definitions of the given kind have a visibility of their own.
+pub(crate) fn has_visibility(kind: DefKind) -> bool {
+    match kind {
+        DefKind::Mod
+        | DefKind::Struct
+        | DefKind::Union
+        | DefKind::Enum
+        | DefKind::Variant
+        | DefKind::Trait
+        | DefKind::TyAlias
+        | DefKind::ForeignTy
+        | DefKind::TraitAlias
+        | DefKind::AssocTy
+        | DefKind::Fn
+        | DefKind::Const
+        | DefKind::Static(_)
+        | DefKind::Ctor(..)
+        | DefKind::AssocFn
+        | DefKind::AssocConst
+        | DefKind::Macro(_)
+        | DefKind::Field => true,
+        DefKind::Use
+        | DefKind::ForeignMod
+        | DefKind::TyParam
+        | DefKind::ConstParam
+        | DefKind::LifetimeParam
+        | DefKind::AnonConst
+        | DefKind::InlineConst
+        | DefKind::OpaqueTy
+        | DefKind::GlobalAsm
+        | DefKind::Impl { .. }
+        | DefKind::Closure
+        | DefKind::ExternCrate => false,
+    }
+}
+
 /// Trait used to convert between an internal MIR type to a Stable MIR type.
 pub trait Stable<'tcx> {
     /// The stable representation of the type implementing Stable.
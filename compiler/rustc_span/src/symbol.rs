@@ -1622,6 +1622,8 @@
         target,
         target_abi,
         target_arch,
+        target_clone_supported,
+        target_clones,
         target_endian,
         target_env,
         target_family,
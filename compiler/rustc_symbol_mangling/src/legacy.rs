@@ -76,6 +76,10 @@ pub(super) fn mangle<'tcx>(
         let _ = printer.write_str("{{reify-shim}}");
     }
 
+    if let ty::InstanceDef::ConstArgShim { .. } = instance.def {
+        let _ = printer.write_str("{{const-arg-shim}}");
+    }
+
     printer.path.finish(hash)
 }
 
@@ -132,6 +136,12 @@ fn get_symbol_hash<'tcx>(
             // Especially, `VTableShim`s and `ReifyShim`s may overlap with their original
             // instances without this.
             discriminant(&instance.def).hash_stable(hcx, &mut hasher);
+
+            // The clones of a function for different constant arguments must not collide either.
+            if let ty::InstanceDef::ConstArgShim { fixed_args, values, .. } = instance.def {
+                fixed_args.hash_stable(hcx, &mut hasher);
+                values.hash_stable(hcx, &mut hasher);
+            }
         });
 
         // 64 bits should be enough to avoid collisions.
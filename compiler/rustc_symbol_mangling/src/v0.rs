@@ -1,6 +1,7 @@
 use rustc_data_structures::base_n;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::intern::Interned;
+use rustc_data_structures::stable_hasher::{Hash64, HashStable, StableHasher};
 use rustc_hir as hir;
 use rustc_hir::def::CtorKind;
 use rustc_hir::def_id::{CrateNum, DefId};
@@ -45,12 +46,29 @@ pub(super) fn mangle<'tcx>(
         ty::InstanceDef::ThreadLocalShim(_) => Some("tls"),
         ty::InstanceDef::VTableShim(_) => Some("vtable"),
         ty::InstanceDef::ReifyShim(_) => Some("reify"),
+        ty::InstanceDef::ConstArgShim { .. } => Some("const_args"),
 
         _ => None,
     };
 
+    // The clones of a function for different constant arguments are told apart by a hash of the
+    // constants.
+    let shim_disambiguator = match instance.def {
+        ty::InstanceDef::ConstArgShim { fixed_args, values, .. } => {
+            let hash: Hash64 = tcx.with_stable_hashing_context(|mut hcx| {
+                let mut hasher = StableHasher::new();
+                fixed_args.hash_stable(&mut hcx, &mut hasher);
+                values.hash_stable(&mut hcx, &mut hasher);
+                hasher.finish()
+            });
+            hash.as_u64()
+        }
+        _ => 0,
+    };
+
     if let Some(shim_kind) = shim_kind {
-        cx.path_append_ns(|cx| cx.print_def_path(def_id, args), 'S', 0, shim_kind).unwrap()
+        cx.path_append_ns(|cx| cx.print_def_path(def_id, args), 'S', shim_disambiguator, shim_kind)
+            .unwrap()
     } else {
         cx.print_def_path(def_id, args).unwrap()
     };
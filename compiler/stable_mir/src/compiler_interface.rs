@@ -6,9 +6,11 @@
 use std::cell::Cell;
 
 use crate::abi::{FnAbi, Layout, LayoutShape};
+use crate::crate_def::{Attribute, Visibility};
 use crate::mir::alloc::{AllocId, GlobalAlloc};
-use crate::mir::mono::{Instance, InstanceDef, StaticDef};
-use crate::mir::Body;
+use crate::mir::analysis::{BorrowckResults, DataflowResults, LocalAnalysis, PlaceAnalysis};
+use crate::mir::mono::{Instance, InstanceDef, MonoItemGraph, StaticDef};
+use crate::mir::{Body, Local, Place};
 use crate::target::MachineInfo;
 use crate::ty::{
     AdtDef, AdtKind, Allocation, ClosureDef, ClosureKind, Const, FieldDef, FnDef, GenericArgs,
@@ -26,11 +28,29 @@ pub trait Context {
     fn entry_fn(&self) -> Option<CrateItem>;
     /// Retrieve all items of the local crate that have a MIR associated with them.
     fn all_local_items(&self) -> CrateItems;
+    /// Collect the mono items of the local crate and the items each of them uses.
+    fn mono_item_graph(&self) -> MonoItemGraph;
     /// Retrieve the body of a function.
     /// This function will panic if the body is not available.
     fn mir_body(&self, item: DefId) -> mir::Body;
     /// Check whether the body of a function is available.
     fn has_body(&self, item: DefId) -> bool;
+    /// Retrieve the body of a function as it is borrow-checked.
+    fn analysis_body(&self, item: DefId) -> Result<Body, Error>;
+    /// Run a dataflow analysis of places on the body returned by `analysis_body`.
+    fn place_dataflow(
+        &self,
+        item: DefId,
+        analysis: PlaceAnalysis,
+    ) -> Result<DataflowResults<Place>, Error>;
+    /// Run a dataflow analysis of locals on the body returned by `analysis_body`.
+    fn local_dataflow(
+        &self,
+        item: DefId,
+        analysis: LocalAnalysis,
+    ) -> Result<DataflowResults<Local>, Error>;
+    /// Retrieve the output of the borrow checker for the body returned by `analysis_body`.
+    fn borrowck_results(&self, item: DefId) -> Result<BorrowckResults, Error>;
     fn all_trait_decls(&self) -> TraitDecls;
     fn trait_decl(&self, trait_def: &TraitDef) -> TraitDecl;
     fn all_trait_impls(&self) -> ImplTraitDecls;
@@ -58,6 +78,15 @@ pub trait Context {
     /// Return lines corresponding to this `Span`
     fn get_lines(&self, span: &Span) -> LineInfo;
 
+    /// Returns the attributes of the given definition.
+    fn def_attrs(&self, def_id: DefId) -> Vec<Attribute>;
+
+    /// Returns the visibility of the given definition, if it has one.
+    fn def_visibility(&self, def_id: DefId) -> Option<Visibility>;
+
+    /// Returns the documentation of the given definition, if any.
+    fn def_docs(&self, def_id: DefId) -> Option<String>;
+
     /// Returns the `kind` of given `DefId`
     fn item_kind(&self, item: CrateItem) -> ItemKind;
 
@@ -95,6 +124,9 @@ pub trait Context {
     /// Create a target usize constant for the given value.
     fn usize_to_const(&self, val: u64) -> Result<Const, Error>;
 
+    /// Create a zero-sized constant of the given type.
+    fn try_new_const_zst(&self, ty: Ty) -> Result<Const, Error>;
+
     /// Create a new type from the given kind.
     fn new_rigid_ty(&self, kind: RigidTy) -> Ty;
 
@@ -3,9 +3,10 @@
 
 use crate::ty::Span;
 use crate::{with, Crate, Symbol};
+use serde::{Deserialize, Serialize};
 
 /// A unique identification number for each item accessible for the current compilation unit.
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DefId(pub(crate) usize);
 
 /// A trait for retrieving information about a particular definition.
@@ -50,6 +51,77 @@ fn span(&self) -> Span {
         let def_id = self.def_id();
         with(|cx| cx.span_of_an_item(def_id))
     }
+
+    /// Return the attributes of this definition, in the order they are written in. Doc comments
+    /// are included as `doc` attributes.
+    ///
+    /// Note that the attributes of definitions from other crates only include the ones kept in
+    /// the crate metadata.
+    fn attrs(&self) -> Vec<Attribute> {
+        let def_id = self.def_id();
+        with(|cx| cx.def_attrs(def_id))
+    }
+
+    /// Return the attributes of this definition with the given path, e.g. `["kani", "proof"]` for
+    /// `#[kani::proof]`.
+    fn attrs_by_path(&self, path: &[&str]) -> Vec<Attribute> {
+        let mut attrs = self.attrs();
+        attrs.retain(|attr| attr.path == path);
+        attrs
+    }
+
+    /// Return the visibility of this definition, or `None` for definitions that have no
+    /// visibility of their own, e.g. closures, impls and generic parameters.
+    fn visibility(&self) -> Option<Visibility> {
+        let def_id = self.def_id();
+        with(|cx| cx.def_visibility(def_id))
+    }
+
+    /// Return the documentation of this definition, i.e. its doc comments and `doc` attributes
+    /// joined into one string the way rustdoc does, or `None` if it has none.
+    fn docs(&self) -> Option<String> {
+        let def_id = self.def_id();
+        with(|cx| cx.def_docs(def_id))
+    }
+}
+
+/// An attribute of a definition, e.g. `#[inline]`, `#[kani::proof]` or a doc comment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attribute {
+    /// The path of the attribute, e.g. `["kani", "proof"]` for `#[kani::proof]`. Doc comments
+    /// have the path `["doc"]`.
+    pub path: Vec<Symbol>,
+    pub args: AttrArgs,
+    /// Whether this is an inner attribute, e.g. `#![allow(unused)]` or `//! Docs`.
+    pub is_inner: bool,
+    pub span: Span,
+}
+
+/// The arguments of an attribute, as written in the source.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttrArgs {
+    /// No arguments: `#[attr]`.
+    Empty,
+    /// The tokens between the delimiters of `#[attr(...)]`, `#[attr[...]]` or `#[attr{...}]`.
+    Delimited(String),
+    /// The value of `#[attr = value]`. For a doc comment, this is its text as a string literal.
+    Eq(String),
+}
+
+/// The visibility of a definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Visibility {
+    /// Visible everywhere, e.g. `pub`.
+    Public,
+    /// Visible only in the given module and its descendants, e.g. `pub(crate)`, or a private
+    /// definition of that module.
+    Restricted(DefId),
+}
+
+impl Visibility {
+    pub fn is_public(&self) -> bool {
+        matches!(self, Visibility::Public)
+    }
 }
 
 macro_rules! crate_def {
@@ -57,7 +129,7 @@ macro_rules! crate_def {
       $vis:vis $name:ident $(;)?
     ) => {
         $(#[$attr])*
-        #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+        #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, serde::Serialize, serde::Deserialize)]
         $vis struct $name(pub DefId);
 
         impl CrateDef for $name {
@@ -20,6 +20,7 @@
 #[macro_use]
 extern crate scoped_tls;
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Debug;
 use std::io;
@@ -28,6 +29,7 @@
 pub use crate::crate_def::CrateDef;
 pub use crate::crate_def::DefId;
 pub use crate::error::*;
+use crate::mir::mono::MonoItemGraph;
 use crate::mir::pretty::function_name;
 use crate::mir::Body;
 use crate::mir::Mutability;
@@ -40,6 +42,7 @@
 #[macro_use]
 pub mod error;
 pub mod mir;
+pub mod serialize;
 pub mod target;
 pub mod ty;
 pub mod visitor;
@@ -79,14 +82,14 @@ fn to_index(&self) -> usize {
 pub type ImplTraitDecls = Vec<ImplDef>;
 
 /// Holds information about a crate.
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Crate {
     pub id: CrateNum,
     pub name: Symbol,
     pub is_local: bool,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum ItemKind {
     Fn,
     Static,
@@ -94,7 +97,7 @@ pub enum ItemKind {
     Ctor(CtorKind),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum CtorKind {
     Const,
     Fn,
@@ -165,6 +168,12 @@ pub fn all_local_items() -> CrateItems {
     with(|cx| cx.all_local_items())
 }
 
+/// Collect the mono items of the local crate and the items each of them uses, the way the
+/// compiler does before generating code. The graph is only collected once per compilation.
+pub fn mono_item_graph() -> MonoItemGraph {
+    with(|cx| cx.mono_item_graph())
+}
+
 pub fn all_trait_decls() -> TraitDecls {
     with(|cx| cx.all_trait_decls())
 }
@@ -182,7 +191,7 @@ pub fn trait_impl(trait_impl: &ImplDef) -> ImplTrait {
 }
 
 /// A type that provides internal information but that can still be used for debug purpose.
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Opaque(String);
 
 impl std::fmt::Display for Opaque {
@@ -1,7 +1,9 @@
 pub mod alloc;
+pub mod analysis;
 mod body;
 pub mod mono;
 pub mod pretty;
+pub mod transform;
 pub mod visit;
 
 pub use body::*;
@@ -0,0 +1,138 @@
+//! Results of the dataflow analyses and of the borrow checker of the compiler.
+//!
+//! The analyses run on the body of a function as it is borrow-checked, which is returned by
+//! [analysis_body]. This body is built before the function is optimized, so its locals and
+//! locations differ from the ones of [CrateItem::body]. Its regions are the ones inferred by the
+//! borrow checker, i.e. `RegionKind::ReVar`.
+//!
+//! The results are only available for the items of the local crate, until their body is
+//! optimized. The `run!` macro gives control to the tool before the bodies are optimized if its
+//! `RunOptions` ask for the facts of the borrow checker. The bodies of constants may still be
+//! optimized earlier, if they are evaluated during type checking.
+
+use crate::mir::{BasicBlockIdx, Body, BorrowKind, Local, Place};
+use crate::ty::{RegionVid, Span};
+use crate::{with, CrateItem, Error};
+
+/// Retrieve the body of an item as it is borrow-checked, which the results of this module refer
+/// to.
+pub fn analysis_body(item: CrateItem) -> Result<Body, Error> {
+    with(|cx| cx.analysis_body(item.0))
+}
+
+/// Run a dataflow analysis computing a set of places at each location of [analysis_body].
+pub fn place_dataflow(
+    item: CrateItem,
+    analysis: PlaceAnalysis,
+) -> Result<DataflowResults<Place>, Error> {
+    with(|cx| cx.place_dataflow(item.0, analysis))
+}
+
+/// Run a dataflow analysis computing a set of locals at each location of [analysis_body].
+pub fn local_dataflow(
+    item: CrateItem,
+    analysis: LocalAnalysis,
+) -> Result<DataflowResults<Local>, Error> {
+    with(|cx| cx.local_dataflow(item.0, analysis))
+}
+
+/// Retrieve the loans and region constraints computed by the borrow checker for
+/// [analysis_body].
+pub fn borrowck_results(item: CrateItem) -> Result<BorrowckResults, Error> {
+    with(|cx| cx.borrowck_results(item.0))
+}
+
+/// A statement or terminator of a body: the statement at `statement_index` in `block`, or the
+/// terminator of `block` if `statement_index` is the number of statements of the block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyLocation {
+    pub block: BasicBlockIdx,
+    pub statement_index: usize,
+}
+
+/// Dataflow analyses whose state is a set of places. The places are the ones the compiler tracks
+/// moves of, i.e. locals and their fields, but not what is behind references.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaceAnalysis {
+    /// The places that may be initialized.
+    MaybeInitialized,
+    /// The places that may be uninitialized.
+    MaybeUninitialized,
+}
+
+/// Dataflow analyses whose state is a set of locals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocalAnalysis {
+    /// The locals that may be used later.
+    MaybeLive,
+    /// The locals whose storage may be live, i.e. which are not marked dead.
+    MaybeStorageLive,
+    /// The locals that may be borrowed, or whose address may have been taken.
+    MaybeBorrowed,
+}
+
+/// The state of a dataflow analysis before and after each statement and terminator of a body,
+/// in the order of execution regardless of the direction of the analysis.
+///
+/// The states of unreachable blocks are empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataflowResults<T> {
+    /// The states before each location, indexed by block and statement.
+    before: Vec<Vec<Vec<T>>>,
+    /// The states after each location, indexed by block and statement.
+    after: Vec<Vec<Vec<T>>>,
+}
+
+impl<T> DataflowResults<T> {
+    /// Create the results of an analysis from its states, indexed by block and statement.
+    pub fn new(before: Vec<Vec<Vec<T>>>, after: Vec<Vec<Vec<T>>>) -> Self {
+        DataflowResults { before, after }
+    }
+
+    /// The state before the statement or terminator at `location` is executed.
+    pub fn before(&self, location: BodyLocation) -> &[T] {
+        &self.before[location.block][location.statement_index]
+    }
+
+    /// The state after the statement or terminator at `location` is executed. For a terminator,
+    /// this is the state that flows to all of its successors.
+    pub fn after(&self, location: BodyLocation) -> &[T] {
+        &self.after[location.block][location.statement_index]
+    }
+}
+
+/// The index of a loan in [BorrowckResults::loans].
+pub type LoanIdx = usize;
+
+/// A borrow in a body, which the borrow checker tracks as a loan of the borrowed place.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Loan {
+    /// The place that is borrowed.
+    pub borrowed_place: Place,
+    /// The place the reference is assigned to.
+    pub assigned_place: Place,
+    pub kind: BorrowKind,
+    /// The region of the reference.
+    pub region: RegionVid,
+    /// The location of the borrow.
+    pub reserve_location: BodyLocation,
+    /// The location where a two-phase borrow is first used, if it is one and it is used.
+    pub activation_location: Option<BodyLocation>,
+}
+
+/// A constraint that `longer` outlives `shorter`, which the borrow checker inferred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutlivesConstraint {
+    pub longer: RegionVid,
+    pub shorter: RegionVid,
+    pub span: Span,
+}
+
+/// The output of the borrow checker for a body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BorrowckResults {
+    pub loans: Vec<Loan>,
+    pub outlives_constraints: Vec<OutlivesConstraint>,
+    /// The loans that are in scope at each location, i.e. which may still be used.
+    pub loans_in_scope: DataflowResults<LoanIdx>,
+}
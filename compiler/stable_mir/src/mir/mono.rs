@@ -3,16 +3,18 @@
 use crate::mir::Body;
 use crate::ty::{Allocation, ClosureDef, ClosureKind, FnDef, GenericArgs, IndexedVal, Ty};
 use crate::{with, CrateItem, DefId, Error, ItemKind, Opaque, Symbol};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MonoItem {
     Fn(Instance),
     Static(StaticDef),
     GlobalAsm(Opaque),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Instance {
     /// The type of instance.
     pub kind: InstanceKind,
@@ -21,7 +23,7 @@ pub struct Instance {
     pub def: InstanceDef,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum InstanceKind {
     /// A user defined item.
     Item,
@@ -209,7 +211,112 @@ fn from(value: StaticDef) -> Self {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The mono items of the local crate, i.e. the items it generates code for, and the items used
+/// by each of them, as collected by the compiler.
+///
+/// The uses of an item include the functions it calls, the drop glue it runs, the items of the
+/// vtables it creates, the functions it turns into function pointers, and the statics it refers
+/// to, as well as the functions it only mentions, see [UsageKind]. Items that the local crate
+/// doesn't generate code for, e.g. non-generic functions of other crates, are not part of the
+/// graph.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "MonoItemEdges", from = "MonoItemEdges")]
+pub struct MonoItemGraph {
+    items: Vec<MonoItem>,
+    uses: Vec<Vec<MonoItemUse>>,
+    /// The index of each item.
+    indices: HashMap<MonoItem, usize>,
+    /// The indices of the items that use each item, in increasing order.
+    users: Vec<Vec<usize>>,
+}
+
+/// A use of an item of a [MonoItemGraph] by another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MonoItemUse {
+    /// The index of the used item.
+    pub item: usize,
+    /// How the item is used.
+    pub kind: UsageKind,
+}
+
+/// The kind of use of a mono item by another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum UsageKind {
+    /// The item is called, e.g. a function or a lang item to panic with.
+    Call,
+    /// The item is the drop glue of a value that is dropped.
+    DropGlue,
+    /// The item is in a vtable that is created, i.e. a method or the drop glue of the type.
+    Vtable,
+    /// The item is turned into a function pointer.
+    FnPointer,
+    /// The item is a static that is referred to, or the shim to access a thread local static.
+    Static,
+    /// The item is only mentioned as a function item value, e.g. when it is passed to a generic
+    /// function. Such an item is only part of the graph if another item uses it. Mentions are
+    /// only recorded if the compilation is run with the `mono_item_mentions` option.
+    Mentioned,
+}
+
+/// The serialized form of a [MonoItemGraph], without the indices derived from its edges.
+#[derive(Serialize, Deserialize)]
+struct MonoItemEdges {
+    items: Vec<MonoItem>,
+    uses: Vec<Vec<MonoItemUse>>,
+}
+
+impl From<MonoItemGraph> for MonoItemEdges {
+    fn from(graph: MonoItemGraph) -> Self {
+        MonoItemEdges { items: graph.items, uses: graph.uses }
+    }
+}
+
+impl From<MonoItemEdges> for MonoItemGraph {
+    fn from(edges: MonoItemEdges) -> Self {
+        MonoItemGraph::new(edges.items, edges.uses)
+    }
+}
+
+impl MonoItemGraph {
+    /// Create a graph from its items and the uses of each of them, which must be sorted.
+    pub fn new(items: Vec<MonoItem>, uses: Vec<Vec<MonoItemUse>>) -> MonoItemGraph {
+        assert_eq!(items.len(), uses.len());
+        let indices = items.iter().enumerate().map(|(index, item)| (item.clone(), index)).collect();
+        let mut users = vec![vec![]; items.len()];
+        for (user, uses) in uses.iter().enumerate() {
+            for used in uses {
+                // An item may be used in several ways by the same user.
+                if users[used.item].last() != Some(&user) {
+                    users[used.item].push(user);
+                }
+            }
+        }
+        MonoItemGraph { items, uses, indices, users }
+    }
+
+    /// The items of the graph, ordered by their symbol names.
+    pub fn items(&self) -> &[MonoItem] {
+        &self.items
+    }
+
+    /// The index of `item` in [Self::items], if it is part of the graph.
+    pub fn index_of(&self, item: &MonoItem) -> Option<usize> {
+        self.indices.get(item).copied()
+    }
+
+    /// The uses of other items by the item at `index`, in increasing order of the used items.
+    /// An item may be used in several ways, but it is only mentioned if it isn't otherwise used.
+    pub fn uses(&self, index: usize) -> &[MonoItemUse] {
+        &self.uses[index]
+    }
+
+    /// The indices of the items that use the item at `index` in any way, in increasing order.
+    pub fn users(&self, index: usize) -> &[usize] {
+        &self.users[index]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct InstanceDef(usize);
 
 impl CrateDef for InstanceDef {
@@ -0,0 +1,99 @@
+//! Modification of function bodies, e.g. to instrument them.
+//!
+//! Tools give a transformation in the `RunOptions` of the `run!` macro, which is then given the
+//! body of every function of the local crate before it is optimized. The transformation can add
+//! locals, basic blocks and statements with a [BodyBuilder], and the modified body is then
+//! compiled instead of the original one. If the modified body is invalid, an error is reported
+//! and the original body is compiled.
+
+use crate::mir::{
+    BasicBlock, BasicBlockIdx, Body, Local, LocalDecl, Mutability, Operand, Place, Statement,
+    Terminator, TerminatorKind, UnwindAction,
+};
+use crate::ty::{Span, Ty};
+use std::mem;
+
+/// Adds locals, basic blocks and statements to a function body.
+///
+/// Locals and basic blocks can only be added, so that the ones of the original body keep their
+/// index.
+pub struct BodyBuilder<'a> {
+    body: &'a mut Body,
+}
+
+impl<'a> BodyBuilder<'a> {
+    pub fn new(body: &'a mut Body) -> Self {
+        BodyBuilder { body }
+    }
+
+    /// The body being modified.
+    pub fn body(&self) -> &Body {
+        self.body
+    }
+
+    /// Declare a new local of the given type.
+    pub fn new_local(&mut self, ty: Ty, span: Span, mutability: Mutability) -> Local {
+        self.body.locals.push(LocalDecl { ty, span, mutability });
+        self.body.locals.len() - 1
+    }
+
+    /// Add a new basic block, which is unreachable until a terminator jumps to it.
+    pub fn new_block(
+        &mut self,
+        statements: Vec<Statement>,
+        terminator: Terminator,
+    ) -> BasicBlockIdx {
+        self.body.blocks.push(BasicBlock { statements, terminator });
+        self.body.blocks.len() - 1
+    }
+
+    /// Insert a statement in `block` before the statement at `index`, or before the terminator
+    /// if `index` is the number of statements of the block.
+    pub fn insert_statement(&mut self, block: BasicBlockIdx, index: usize, statement: Statement) {
+        self.body.blocks[block].statements.insert(index, statement);
+    }
+
+    /// Replace the terminator of `block`, and return the previous one.
+    pub fn set_terminator(&mut self, block: BasicBlockIdx, terminator: Terminator) -> Terminator {
+        mem::replace(&mut self.body.blocks[block].terminator, terminator)
+    }
+
+    /// Split `block` before the statement at `index`. The statements from `index` on and the
+    /// terminator are moved to a new block, which `block` jumps to instead.
+    ///
+    /// Returns the new block.
+    pub fn split_block(&mut self, block: BasicBlockIdx, index: usize) -> BasicBlockIdx {
+        let next = self.body.blocks.len();
+        let data = &mut self.body.blocks[block];
+        let statements = data.statements.split_off(index);
+        let goto =
+            Terminator { kind: TerminatorKind::Goto { target: next }, span: data.terminator.span };
+        let terminator = mem::replace(&mut data.terminator, goto);
+        self.new_block(statements, terminator)
+    }
+
+    /// Insert a call of `func` in `block` before the statement at `index`, by splitting the block
+    /// like [Self::split_block] does. The call has the span of the statement, or of the
+    /// terminator if `index` is the number of statements of the block.
+    ///
+    /// Note that `unwind` must be `UnwindAction::Terminate` or `UnwindAction::Unreachable` if
+    /// `block` is a cleanup block, i.e. if it is only reached while unwinding.
+    ///
+    /// Returns the block the call returns to.
+    pub fn insert_call(
+        &mut self,
+        block: BasicBlockIdx,
+        index: usize,
+        func: Operand,
+        args: Vec<Operand>,
+        destination: Place,
+        unwind: UnwindAction,
+    ) -> BasicBlockIdx {
+        let data = &self.body.blocks[block];
+        let span = data.statements.get(index).map_or(data.terminator.span, |stmt| stmt.span);
+        let next = self.split_block(block, index);
+        let kind = TerminatorKind::Call { func, args, destination, target: Some(next), unwind };
+        self.set_terminator(block, Terminator { kind, span });
+        next
+    }
+}
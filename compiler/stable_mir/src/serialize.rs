@@ -0,0 +1,260 @@
+//! Serialization of the Stable MIR data model, to analyze it or to compare it without running the
+//! compiler.
+//!
+//! A [MirDump] collects bodies, types, instances and allocations while the compiler runs, and can
+//! be written as JSON or in a compact binary format. Types, spans, definitions and instances are
+//! interned by the compiler and only identified by an index in the data model. A dump thus keeps
+//! what they refer to in side tables, which [MirDump::ty_kind], [MirDump::span],
+//! [MirDump::def_name] and [MirDump::instance] give access to once the dump is loaded.
+//!
+//! Note that the methods of the data model that query the compiler, e.g. `Ty::kind` or the
+//! `Debug` implementation of `Ty`, cannot be used on loaded values.
+
+use crate::crate_def::CrateDef;
+use crate::mir::mono::Instance;
+use crate::mir::visit::{Location, MirVisitor};
+use crate::mir::Body;
+use crate::ty::{Allocation, Const, GenericArgs, IndexedVal, LineInfo, RigidTy, Span, Ty, TyKind};
+use crate::visitor::{Visitable, Visitor};
+use crate::{with, DefId, Error, Filename, Symbol};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+
+/// The version of the format of [MirDump]. It changes whenever the data model does, and dumps of
+/// another version are rejected when loaded.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Values of the Stable MIR data model and the side tables needed to interpret them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MirDump {
+    /// Always the first field, so that the version can be checked before the rest is decoded.
+    format_version: u32,
+    bodies: Vec<(Symbol, Body)>,
+    types: Vec<Ty>,
+    instances: Vec<Instance>,
+    allocations: Vec<(Symbol, Allocation)>,
+    ty_kinds: BTreeMap<usize, TyKind>,
+    spans: BTreeMap<usize, SpanInfo>,
+    def_names: BTreeMap<usize, Symbol>,
+    instance_infos: BTreeMap<usize, InstanceInfo>,
+}
+
+/// The location of a [Span] in the source code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanInfo {
+    pub filename: Filename,
+    pub lines: LineInfo,
+}
+
+/// The information about an [Instance] that is otherwise retrieved from the compiler.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceInfo {
+    pub name: Symbol,
+    pub mangled_name: Symbol,
+    pub def: DefId,
+    pub ty: Ty,
+}
+
+/// The part of a dump that is decoded first, to check its version.
+#[derive(Deserialize)]
+struct Header {
+    format_version: u32,
+}
+
+impl MirDump {
+    pub fn new() -> MirDump {
+        MirDump {
+            format_version: FORMAT_VERSION,
+            bodies: vec![],
+            types: vec![],
+            instances: vec![],
+            allocations: vec![],
+            ty_kinds: BTreeMap::new(),
+            spans: BTreeMap::new(),
+            def_names: BTreeMap::new(),
+            instance_infos: BTreeMap::new(),
+        }
+    }
+
+    /// Add the body of a function, e.g. the one of a crate item or of an instance, under `name`.
+    pub fn add_body(&mut self, name: Symbol, body: Body) {
+        let mut side_tables = SideTables { dump: self };
+        side_tables.visit_body(&body);
+        for local in body.locals() {
+            side_tables.visit_span(&local.span);
+        }
+        self.bodies.push((name, body));
+    }
+
+    pub fn add_ty(&mut self, ty: Ty) {
+        let _ = Visitor::visit_ty(&mut SideTables { dump: self }, &ty);
+        self.types.push(ty);
+    }
+
+    pub fn add_instance(&mut self, instance: Instance) {
+        SideTables { dump: self }.add_instance(&instance);
+        self.instances.push(instance);
+    }
+
+    /// Add an allocation, e.g. the one of a static, under `name`.
+    ///
+    /// Note that the allocations its pointers point to are not part of the dump.
+    pub fn add_allocation(&mut self, name: Symbol, allocation: Allocation) {
+        self.allocations.push((name, allocation));
+    }
+
+    /// The bodies of the dump, in the order they were added.
+    pub fn bodies(&self) -> &[(Symbol, Body)] {
+        &self.bodies
+    }
+
+    /// The types of the dump, in the order they were added.
+    pub fn types(&self) -> &[Ty] {
+        &self.types
+    }
+
+    /// The instances of the dump, in the order they were added.
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// The allocations of the dump, in the order they were added.
+    pub fn allocations(&self) -> &[(Symbol, Allocation)] {
+        &self.allocations
+    }
+
+    /// The kind of a type that appears in the dump.
+    pub fn ty_kind(&self, ty: Ty) -> Option<&TyKind> {
+        self.ty_kinds.get(&ty.to_index())
+    }
+
+    /// The location of a span that appears in the bodies of the dump.
+    pub fn span(&self, span: Span) -> Option<&SpanInfo> {
+        self.spans.get(&span.to_index())
+    }
+
+    /// The name of a definition that a type or an instance of the dump refers to.
+    pub fn def_name(&self, def: DefId) -> Option<&str> {
+        self.def_names.get(&def.to_index()).map(|name| name.as_str())
+    }
+
+    /// The information about an instance of the dump.
+    pub fn instance(&self, instance: &Instance) -> Option<&InstanceInfo> {
+        self.instance_infos.get(&instance.def.to_index())
+    }
+
+    /// Write the dump as JSON, formatted to be diffed.
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(|err| error!("Failed to write dump: {err}"))
+    }
+
+    /// Load a dump written by [MirDump::to_json].
+    pub fn from_json(json: &str) -> Result<MirDump, Error> {
+        let header: Header =
+            serde_json::from_str(json).map_err(|err| error!("Failed to read dump: {err}"))?;
+        check_version(header.format_version)?;
+        serde_json::from_str(json).map_err(|err| error!("Failed to read dump: {err}"))
+    }
+
+    /// Write the dump in a compact binary format.
+    pub fn to_binary(&self) -> Result<Vec<u8>, Error> {
+        bincode::serialize(self).map_err(|err| error!("Failed to write dump: {err}"))
+    }
+
+    /// Load a dump written by [MirDump::to_binary].
+    pub fn from_binary(bytes: &[u8]) -> Result<MirDump, Error> {
+        let header: Header =
+            bincode::deserialize(bytes).map_err(|err| error!("Failed to read dump: {err}"))?;
+        check_version(header.format_version)?;
+        bincode::deserialize(bytes).map_err(|err| error!("Failed to read dump: {err}"))
+    }
+}
+
+impl Default for MirDump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_version(format_version: u32) -> Result<(), Error> {
+    if format_version == FORMAT_VERSION {
+        Ok(())
+    } else {
+        Err(error!(
+            "Unsupported dump format version `{format_version}`, expected `{FORMAT_VERSION}`"
+        ))
+    }
+}
+
+/// Fill the side tables of a dump with the interned values that a value of the dump refers to,
+/// using the compiler.
+struct SideTables<'a> {
+    dump: &'a mut MirDump,
+}
+
+impl SideTables<'_> {
+    fn add_instance(&mut self, instance: &Instance) {
+        let def = instance.def.def_id();
+        let ty = instance.ty();
+        let info =
+            InstanceInfo { name: instance.name(), mangled_name: instance.mangled_name(), def, ty };
+        self.dump.instance_infos.insert(instance.def.to_index(), info);
+        self.add_def(def);
+        let _ = Visitor::visit_ty(self, &ty);
+    }
+
+    fn add_def(&mut self, def: DefId) {
+        self.dump
+            .def_names
+            .entry(def.to_index())
+            .or_insert_with(|| with(|cx| cx.def_name(def, false)));
+    }
+}
+
+impl Visitor for SideTables<'_> {
+    type Break = ();
+
+    fn visit_ty(&mut self, ty: &Ty) -> ControlFlow<()> {
+        if self.dump.ty_kinds.contains_key(&ty.to_index()) {
+            return ControlFlow::Continue(());
+        }
+        let kind = ty.kind();
+        let def = match &kind {
+            TyKind::RigidTy(RigidTy::Adt(def, _)) => Some(def.def_id()),
+            TyKind::RigidTy(RigidTy::Foreign(def)) => Some(def.def_id()),
+            TyKind::RigidTy(RigidTy::FnDef(def, _)) => Some(def.def_id()),
+            TyKind::RigidTy(RigidTy::Closure(def, _)) => Some(def.def_id()),
+            TyKind::RigidTy(RigidTy::Coroutine(def, ..)) => Some(def.def_id()),
+            TyKind::RigidTy(RigidTy::CoroutineWitness(def, _)) => Some(def.def_id()),
+            TyKind::Alias(_, alias) => Some(alias.def_id.def_id()),
+            TyKind::RigidTy(_) | TyKind::Param(_) | TyKind::Bound(..) => None,
+        };
+        if let Some(def) = def {
+            self.add_def(def);
+        }
+        self.dump.ty_kinds.insert(ty.to_index(), kind);
+        ty.super_visit(self)
+    }
+}
+
+impl MirVisitor for SideTables<'_> {
+    fn visit_span(&mut self, span: &Span) {
+        self.dump
+            .spans
+            .entry(span.to_index())
+            .or_insert_with(|| SpanInfo { filename: span.get_filename(), lines: span.get_lines() });
+    }
+
+    fn visit_ty(&mut self, ty: &Ty, _location: Location) {
+        let _ = Visitor::visit_ty(self, ty);
+    }
+
+    fn visit_const(&mut self, constant: &Const, _location: Location) {
+        let _ = constant.visit(self);
+    }
+
+    fn visit_args(&mut self, args: &GenericArgs, _location: Location) {
+        let _ = args.visit(self);
+    }
+}
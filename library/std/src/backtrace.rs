@@ -93,9 +93,10 @@
 use crate::ffi::c_void;
 use crate::fmt;
 use crate::panic::UnwindSafe;
+use crate::path::PathBuf;
 use crate::sync::atomic::{AtomicUsize, Ordering::Relaxed};
 use crate::sync::LazyLock;
-use crate::sys_common::backtrace::{lock, output_filename, set_image_base};
+use crate::sys_common::backtrace::{filename_to_path, lock, output_filename, set_image_base};
 
 /// A captured OS thread stack backtrace.
 ///
@@ -159,11 +160,14 @@ enum RawFrame {
     Fake,
 }
 
-struct BacktraceSymbol {
+/// A symbol that an instruction pointer of a [`BacktraceFrame`] resolved to.
+#[unstable(feature = "backtrace_frames", issue = "79676")]
+pub struct BacktraceSymbol {
     name: Option<Vec<u8>>,
     filename: Option<BytesOrWide>,
     lineno: Option<u32>,
     colno: Option<u32>,
+    inlined: bool,
 }
 
 enum BytesOrWide {
@@ -207,6 +211,7 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+#[unstable(feature = "backtrace_frames", issue = "79676")]
 impl fmt::Debug for BacktraceSymbol {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         // FIXME: improve formatting: https://github.com/rust-lang/rust/issues/65280
@@ -237,16 +242,22 @@ impl fmt::Debug for BytesOrWide {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         output_filename(
             fmt,
-            match self {
-                BytesOrWide::Bytes(w) => BytesOrWideString::Bytes(w),
-                BytesOrWide::Wide(w) => BytesOrWideString::Wide(w),
-            },
+            self.as_bytes_or_wide_string(),
             backtrace_rs::PrintFmt::Short,
             crate::env::current_dir().as_ref().ok(),
         )
     }
 }
 
+impl BytesOrWide {
+    fn as_bytes_or_wide_string(&self) -> BytesOrWideString<'_> {
+        match self {
+            BytesOrWide::Bytes(w) => BytesOrWideString::Bytes(w),
+            BytesOrWide::Wide(w) => BytesOrWideString::Wide(w),
+        }
+    }
+}
+
 impl Backtrace {
     /// Returns whether backtrace captures are enabled through environment
     /// variables.
@@ -371,11 +382,128 @@ pub fn status(&self) -> BacktraceStatus {
 
 impl<'a> Backtrace {
     /// Returns an iterator over the backtrace frames.
+    ///
+    /// Capturing a backtrace doesn't resolve the symbols of its frames, so the
+    /// first call of this function resolves them, unless the backtrace was
+    /// already printed or [`Backtrace::resolve`] was called.
     #[must_use]
     #[unstable(feature = "backtrace_frames", issue = "79676")]
     pub fn frames(&'a self) -> &'a [BacktraceFrame] {
         if let Inner::Captured(c) = &self.inner { &c.frames } else { &[] }
     }
+
+    /// Resolves the symbols of the frames of this backtrace, if they aren't
+    /// resolved yet.
+    ///
+    /// Symbols are otherwise resolved the first time the backtrace is printed
+    /// or its frames are accessed. As resolving symbols can be much more
+    /// expensive than capturing the backtrace, this allows doing it at a
+    /// convenient point, e.g. on a thread reporting the backtrace rather than
+    /// on the thread that captured it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(backtrace_frames)]
+    /// use std::backtrace::Backtrace;
+    ///
+    /// let backtrace = Backtrace::force_capture();
+    /// std::thread::spawn(move || {
+    ///     backtrace.resolve();
+    ///     for frame in backtrace.frames() {
+    ///         for symbol in frame.symbols() {
+    ///             if let (Some(name), Some(line)) = (symbol.name(), symbol.lineno()) {
+    ///                 println!("{name} at line {line}");
+    ///             }
+    ///         }
+    ///     }
+    /// })
+    /// .join()
+    /// .unwrap();
+    /// ```
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn resolve(&self) {
+        if let Inner::Captured(c) = &self.inner {
+            LazyLock::force(c);
+        }
+    }
+}
+
+impl BacktraceFrame {
+    /// Returns the instruction pointer of this frame.
+    ///
+    /// This is normally the address of the next instruction to execute in the
+    /// frame, i.e. the return address for all frames but the innermost one.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn ip(&self) -> *mut c_void {
+        self.frame.ip()
+    }
+
+    /// Returns the base address of the module (executable or shared library)
+    /// containing the instruction pointer of this frame.
+    ///
+    /// Together with [`BacktraceFrame::ip`] this allows resolving the frame
+    /// offline, e.g. against separate debug information. Returns `None` if the
+    /// base address isn't known on this platform.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn module_base_address(&self) -> Option<*mut c_void> {
+        self.frame.module_base_address()
+    }
+
+    /// Returns the symbols the instruction pointer of this frame resolved to.
+    ///
+    /// There is more than one symbol if functions were inlined at the
+    /// instruction pointer, in which case the innermost inlined function comes
+    /// first. The slice is empty if no symbol could be resolved.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn symbols(&self) -> &[BacktraceSymbol] {
+        &self.symbols
+    }
+}
+
+impl BacktraceSymbol {
+    /// Returns the demangled name of this symbol, without the hash that
+    /// mangled Rust symbol names end with.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn name(&self) -> Option<String> {
+        self.name.as_ref().map(|b| format!("{:#}", backtrace_rs::SymbolName::new(b)))
+    }
+
+    /// Returns the path of the file containing the source of this symbol, as
+    /// recorded in the debug information.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn filename(&self) -> Option<PathBuf> {
+        self.filename.as_ref().map(|b| filename_to_path(b.as_bytes_or_wide_string()).into_owned())
+    }
+
+    /// Returns the line number in [`BacktraceSymbol::filename`] that this
+    /// symbol is at.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn lineno(&self) -> Option<u32> {
+        self.lineno
+    }
+
+    /// Returns the column number in [`BacktraceSymbol::filename`] that this
+    /// symbol is at.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn colno(&self) -> Option<u32> {
+        self.colno
+    }
+
+    /// Returns whether this symbol belongs to a function that was inlined into
+    /// the function of the next symbol of the frame.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn is_inlined(&self) -> bool {
+        self.inlined
+    }
 }
 
 #[stable(feature = "backtrace", since = "1.65.0")]
@@ -413,10 +541,7 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
                     f.frame().print_raw_with_column(
                         frame.frame.ip(),
                         symbol.name.as_ref().map(|b| backtrace_rs::SymbolName::new(b)),
-                        symbol.filename.as_ref().map(|b| match b {
-                            BytesOrWide::Bytes(w) => BytesOrWideString::Bytes(w),
-                            BytesOrWide::Wide(w) => BytesOrWideString::Wide(w),
-                        }),
+                        symbol.filename.as_ref().map(BytesOrWide::as_bytes_or_wide_string),
                         symbol.lineno,
                         symbol.colno,
                     )?;
@@ -453,9 +578,15 @@ fn lazy_resolve(mut capture: Capture) -> LazyResolve {
                         }),
                         lineno: symbol.lineno(),
                         colno: symbol.colno(),
+                        inlined: true,
                     });
                 });
             }
+            // Inlined functions are reported before the function they were
+            // inlined into, which is therefore the only one not inlined.
+            if let Some(symbol) = symbols.last_mut() {
+                symbol.inlined = false;
+            }
         }
 
         capture
@@ -470,4 +601,12 @@ fn ip(&self) -> *mut c_void {
             RawFrame::Fake => crate::ptr::invalid_mut(1),
         }
     }
+
+    fn module_base_address(&self) -> Option<*mut c_void> {
+        match self {
+            RawFrame::Actual(frame) => frame.module_base_address(),
+            #[cfg(test)]
+            RawFrame::Fake => None,
+        }
+    }
 }
@@ -10,6 +10,7 @@ fn generate_fake_frames() -> Vec<BacktraceFrame> {
                 filename: Some(BytesOrWide::Bytes(b"rust/backtrace.rs".to_vec())),
                 lineno: Some(100),
                 colno: None,
+                inlined: false,
             }],
         },
         BacktraceFrame {
@@ -19,6 +20,7 @@ fn generate_fake_frames() -> Vec<BacktraceFrame> {
                 filename: None,
                 lineno: None,
                 colno: None,
+                inlined: false,
             }],
         },
         BacktraceFrame {
@@ -29,12 +31,14 @@ fn generate_fake_frames() -> Vec<BacktraceFrame> {
                     filename: Some(BytesOrWide::Bytes(b"rust/rt.rs".to_vec())),
                     lineno: Some(300),
                     colno: Some(5),
+                    inlined: true,
                 },
                 BacktraceSymbol {
                     name: Some(b"std::rt::lang_start".to_vec()),
                     filename: Some(BytesOrWide::Bytes(b"rust/rt.rs".to_vec())),
                     lineno: Some(400),
                     colno: None,
+                    inlined: false,
                 },
             ],
         },
@@ -93,6 +97,47 @@ fn test_frames() {
     assert!(iter.all(|(f, e)| format!("{f:#?}") == *e));
 }
 
+#[test]
+fn test_frame_accessors() {
+    let backtrace = Backtrace {
+        inner: Inner::Captured(LazyLock::preinit(Capture {
+            actual_start: 1,
+            frames: generate_fake_frames(),
+        })),
+    };
+    backtrace.resolve();
+
+    let frames = backtrace.frames();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].ip(), crate::ptr::invalid_mut(1));
+    assert_eq!(frames[0].module_base_address(), None);
+
+    let symbols = frames[1].symbols();
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].name().as_deref(), Some("__rust_maybe_catch_panic"));
+    assert_eq!(symbols[0].filename(), None);
+    assert_eq!(symbols[0].lineno(), None);
+    assert_eq!(symbols[0].colno(), None);
+    assert!(!symbols[0].is_inlined());
+
+    let symbols = frames[2].symbols();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0].name().as_deref(), Some("std::rt::lang_start_internal"));
+    assert_eq!(symbols[0].filename(), Some(PathBuf::from("rust/rt.rs")));
+    assert_eq!(symbols[0].lineno(), Some(300));
+    assert_eq!(symbols[0].colno(), Some(5));
+    assert!(symbols[0].is_inlined());
+    assert_eq!(symbols[1].name().as_deref(), Some("std::rt::lang_start"));
+    assert!(!symbols[1].is_inlined());
+}
+
+#[test]
+fn test_disabled_frames() {
+    let backtrace = Backtrace::disabled();
+    backtrace.resolve();
+    assert!(backtrace.frames().is_empty());
+}
+
 #[test]
 fn backtrace_unwind_safe() {
     fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
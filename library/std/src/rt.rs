@@ -154,6 +154,16 @@ fn lang_start_internal(
     ret_code
 }
 
+/// Returns whether the CPU supports all the comma-separated target features, for the resolver
+/// of a `#[target_clones]` function.
+#[cfg(not(any(test, doctest)))]
+#[lang = "target_clone_supported"]
+fn target_clone_supported(features: &str) -> bool {
+    features.split(',').all(|feature| {
+        std_detect::detect::features().any(|(name, enabled)| name == feature && enabled)
+    })
+}
+
 #[cfg(not(any(test, doctest)))]
 #[lang = "start"]
 fn lang_start<T: crate::process::Termination + 'static>(
@@ -153,6 +153,11 @@
 use crate::sync::mpmc;
 use crate::time::{Duration, Instant};
 
+#[unstable(feature = "mpsc_select", issue = "none")]
+pub use self::select::{Select, SelectTimeoutError, SelectedOperation, TrySelectError};
+
+mod select;
+
 /// The receiving half of Rust's [`channel`] (or [`sync_channel`]) type.
 /// This half can only be owned by one thread.
 ///
@@ -0,0 +1,423 @@
+//! Waiting on several channel operations at once.
+
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod tests;
+
+use super::{Receiver, RecvError, SendError, Sender, SyncSender};
+use crate::error;
+use crate::fmt;
+use crate::marker::PhantomData;
+use crate::mem;
+use crate::sync::mpmc::{self, SelectHandle, Timeout, Token};
+use crate::thread;
+use crate::time::{Duration, Instant};
+
+/// Waits on a set of channel operations until one of them can proceed.
+///
+/// Operations are added with [`recv`], [`send`] and [`sync_send`], each of which returns the
+/// index of the operation. Selection then blocks until one of the operations is ready, which
+/// for a receive means that a message is available or the channel is disconnected, and for a
+/// send that the message can be sent or the channel is disconnected. When several operations
+/// are ready, one of them is picked at random.
+///
+/// Selection returns a [`SelectedOperation`], which must be completed by calling its method
+/// matching the operation with the same [`Receiver`], [`Sender`] or [`SyncSender`] that was
+/// added to the `Select`.
+///
+/// Blocked threads are woken up by the channels, so selection does not busy-poll.
+///
+/// [`recv`]: Select::recv
+/// [`send`]: Select::send
+/// [`sync_send`]: Select::sync_send
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpsc_select)]
+/// use std::sync::mpsc::{channel, sync_channel, Select};
+/// use std::thread;
+///
+/// let (tx1, rx1) = channel();
+/// let (tx2, rx2) = sync_channel(0);
+///
+/// thread::spawn(move || tx1.send(1).unwrap());
+/// thread::spawn(move || tx2.send(2).unwrap());
+///
+/// let mut sel = Select::new();
+/// let oper1 = sel.recv(&rx1);
+/// let oper2 = sel.recv(&rx2);
+///
+/// // Both operations will eventually be ready, and either may be selected.
+/// let oper = sel.select();
+/// match oper.index() {
+///     i if i == oper1 => assert_eq!(oper.recv(&rx1), Ok(1)),
+///     i if i == oper2 => assert_eq!(oper.recv(&rx2), Ok(2)),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[unstable(feature = "mpsc_select", issue = "none")]
+pub struct Select<'a> {
+    /// The operations, with their index and the address of their channel half.
+    handles: Vec<(&'a dyn SelectHandle, usize, *const u8)>,
+
+    /// The index of the next added operation.
+    next_index: usize,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of channel operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::Select;
+    /// use std::time::Duration;
+    ///
+    /// let mut sel = Select::new();
+    ///
+    /// // The list of operations is empty, so selection times out.
+    /// assert!(sel.select_timeout(Duration::from_millis(10)).is_err());
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn new() -> Select<'a> {
+        Select { handles: Vec::with_capacity(4), next_index: 0 }
+    }
+
+    /// Adds a receive operation and returns its index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    ///
+    /// let (tx, rx) = channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.recv(&rx);
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
+        self.add(&r.inner, &r.inner as *const mpmc::Receiver<T> as *const u8)
+    }
+
+    /// Adds a send operation on a [`Sender`] and returns its index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    ///
+    /// let (tx, rx) = channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.send(&tx);
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
+        self.add(&s.inner, &s.inner as *const mpmc::Sender<T> as *const u8)
+    }
+
+    /// Adds a send operation on a [`SyncSender`] and returns its index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{sync_channel, Select};
+    ///
+    /// let (tx, rx) = sync_channel::<i32>(1);
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.sync_send(&tx);
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn sync_send<T>(&mut self, s: &'a SyncSender<T>) -> usize {
+        self.add(&s.inner, &s.inner as *const mpmc::Sender<T> as *const u8)
+    }
+
+    fn add(&mut self, handle: &'a dyn SelectHandle, ptr: *const u8) -> usize {
+        let index = self.next_index;
+        self.handles.push((handle, index, ptr));
+        self.next_index += 1;
+        index
+    }
+
+    /// Removes a previously added operation.
+    ///
+    /// This is useful when an operation is selected because its channel got disconnected and
+    /// the remaining operations should be selected from afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is invalid or the operation has already been removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    ///
+    /// let (tx1, rx1) = channel::<i32>();
+    /// let (_tx2, rx2) = channel::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&rx1);
+    /// let oper2 = sel.recv(&rx2);
+    ///
+    /// // Disconnect the first channel, whose receive operation is then ready.
+    /// drop(tx1);
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert!(oper.recv(&rx1).is_err());
+    /// sel.remove(oper1);
+    ///
+    /// // Only the second operation remains, which is not ready.
+    /// assert!(sel.try_select().is_err());
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn remove(&mut self, index: usize) {
+        assert!(index < self.next_index, "index out of bounds; {index} >= {}", self.next_index);
+
+        let i = self
+            .handles
+            .iter()
+            .position(|&(_, i, _)| i == index)
+            .expect("no operation with this index");
+
+        self.handles.swap_remove(i);
+    }
+
+    /// Attempts to select one of the operations without blocking.
+    ///
+    /// If an operation is ready, it is selected and returned. If several operations are ready,
+    /// one of them is picked at random. Otherwise an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{channel, Select};
+    ///
+    /// let (tx, rx) = channel();
+    ///
+    /// let mut sel = Select::new();
+    /// let index = sel.recv(&rx);
+    /// assert!(sel.try_select().is_err());
+    ///
+    /// tx.send(1).unwrap();
+    /// let oper = sel.try_select().unwrap();
+    /// assert_eq!(oper.index(), index);
+    /// assert_eq!(oper.recv(&rx), Ok(1));
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
+        self.run(Timeout::Now).ok_or(TrySelectError)
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it.
+    ///
+    /// If several operations are ready, one of them is picked at random. If there are no
+    /// operations, this blocks forever.
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        self.run(Timeout::Never).unwrap()
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready and selects it.
+    ///
+    /// If several operations are ready, one of them is picked at random. If no operation
+    /// becomes ready in time, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpsc_select)]
+    /// use std::sync::mpsc::{sync_channel, Select};
+    /// use std::time::Duration;
+    ///
+    /// let (tx, rx) = sync_channel::<i32>(0);
+    ///
+    /// // No receiver is waiting on the zero-capacity channel, so the send is not ready.
+    /// let mut sel = Select::new();
+    /// sel.sync_send(&tx);
+    /// assert!(sel.select_timeout(Duration::from_millis(10)).is_err());
+    /// ```
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.select_deadline(deadline),
+            // So far in the future that it's practically the same as waiting indefinitely.
+            None => Ok(self.select()),
+        }
+    }
+
+    /// Blocks until a deadline until one of the operations becomes ready and selects it.
+    ///
+    /// If several operations are ready, one of them is picked at random. If no operation
+    /// becomes ready before the deadline, an error is returned.
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn select_deadline(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        self.run(Timeout::At(deadline)).ok_or(SelectTimeoutError)
+    }
+
+    fn run(&mut self, timeout: Timeout) -> Option<SelectedOperation<'a>> {
+        let (token, index, ptr) = mpmc::run_select(&mut self.handles, timeout)?;
+        Some(SelectedOperation { token, index, ptr, _marker: PhantomData })
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl Default for Select<'_> {
+    fn default() -> Self {
+        Select::new()
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Select { .. }")
+    }
+}
+
+/// An operation selected by a [`Select`], which must be completed by calling the method
+/// matching the operation.
+///
+/// # Panics
+///
+/// Dropping a `SelectedOperation` without completing it panics, since the other side of the
+/// channel may already be waiting for the operation to complete.
+#[must_use = "a selected operation must be completed by calling `recv`, `send` or `sync_send`"]
+#[unstable(feature = "mpsc_select", issue = "none")]
+pub struct SelectedOperation<'a> {
+    /// The token prepared for completing the operation.
+    token: Token,
+
+    /// The index of the operation.
+    index: usize,
+
+    /// The address of the channel half of the operation.
+    ptr: *const u8,
+
+    /// Borrows the channel halves of the `Select` the operation was selected by.
+    _marker: PhantomData<&'a ()>,
+}
+
+impl SelectedOperation<'_> {
+    /// Returns the index of the selected operation.
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// Returns an error if the channel is empty and disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r` is not the receiver of the selected operation.
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn recv<T>(mut self, r: &Receiver<T>) -> Result<T, RecvError> {
+        assert!(
+            &r.inner as *const mpmc::Receiver<T> as *const u8 == self.ptr,
+            "passed a receiver that wasn't selected",
+        );
+        let res = unsafe { r.inner.read(&mut self.token) };
+        mem::forget(self);
+        res.map_err(|_| RecvError)
+    }
+
+    /// Completes the send operation on a [`Sender`].
+    ///
+    /// Returns an error containing the message if the channel is disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `s` is not the sender of the selected operation.
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn send<T>(mut self, s: &Sender<T>, msg: T) -> Result<(), SendError<T>> {
+        assert!(
+            &s.inner as *const mpmc::Sender<T> as *const u8 == self.ptr,
+            "passed a sender that wasn't selected",
+        );
+        let res = unsafe { s.inner.write(&mut self.token, msg) };
+        mem::forget(self);
+        res.map_err(SendError)
+    }
+
+    /// Completes the send operation on a [`SyncSender`].
+    ///
+    /// Returns an error containing the message if the channel is disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `s` is not the sender of the selected operation.
+    #[unstable(feature = "mpsc_select", issue = "none")]
+    pub fn sync_send<T>(mut self, s: &SyncSender<T>, msg: T) -> Result<(), SendError<T>> {
+        assert!(
+            &s.inner as *const mpmc::Sender<T> as *const u8 == self.ptr,
+            "passed a sender that wasn't selected",
+        );
+        let res = unsafe { s.inner.write(&mut self.token, msg) };
+        mem::forget(self);
+        res.map_err(SendError)
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl fmt::Debug for SelectedOperation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectedOperation").field("index", &self.index).finish_non_exhaustive()
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl Drop for SelectedOperation<'_> {
+    fn drop(&mut self) {
+        // Don't panic again while unwinding from a failed completion.
+        if !thread::panicking() {
+            panic!("dropped `SelectedOperation` without completing the operation");
+        }
+    }
+}
+
+/// An error returned from [`Select::try_select`] when no operation is ready.
+#[unstable(feature = "mpsc_select", issue = "none")]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct TrySelectError;
+
+/// An error returned from [`Select::select_timeout`] and [`Select::select_deadline`] when no
+/// operation became ready in time.
+#[unstable(feature = "mpsc_select", issue = "none")]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct SelectTimeoutError;
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl fmt::Display for TrySelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "all operations in select would block".fmt(f)
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl error::Error for TrySelectError {}
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl fmt::Display for SelectTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "timed out waiting on select".fmt(f)
+    }
+}
+
+#[unstable(feature = "mpsc_select", issue = "none")]
+impl error::Error for SelectTimeoutError {}
@@ -0,0 +1,232 @@
+use super::*;
+use crate::sync::mpsc::{channel, sync_channel};
+use crate::thread;
+use crate::time::{Duration, Instant};
+
+const MS: Duration = Duration::from_millis(1);
+
+#[test]
+fn smoke() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<i32>();
+
+    tx1.send(1).unwrap();
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx1), Ok(1));
+
+    tx2.send(2).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&rx2), Ok(2));
+}
+
+#[test]
+fn disconnected() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = sync_channel::<i32>(0);
+
+    drop(tx1);
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    let oper = sel.select_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx1), Err(RecvError));
+
+    sel.remove(oper1);
+    thread::spawn(move || {
+        thread::sleep(50 * MS);
+        drop(tx2);
+    });
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&rx2), Err(RecvError));
+}
+
+#[test]
+fn try_select() {
+    let (tx, rx) = sync_channel::<i32>(1);
+
+    let mut sel = Select::new();
+    let recv = sel.recv(&rx);
+    assert_eq!(sel.try_select().unwrap_err(), TrySelectError);
+    tx.send(1).unwrap();
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), recv);
+    assert_eq!(oper.recv(&rx), Ok(1));
+
+    // The channel is empty now, so a send is ready but a receive is not.
+    let mut sel = Select::new();
+    sel.recv(&rx);
+    let send = sel.sync_send(&tx);
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), send);
+    oper.sync_send(&tx, 2).unwrap();
+    assert_eq!(rx.recv(), Ok(2));
+}
+
+#[test]
+fn timeout() {
+    let (_tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<i32>();
+
+    let start = Instant::now();
+    let mut sel = Select::new();
+    sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    assert_eq!(sel.select_timeout(100 * MS).unwrap_err(), SelectTimeoutError);
+    assert!(start.elapsed() >= 100 * MS);
+
+    thread::spawn(move || {
+        thread::sleep(50 * MS);
+        tx2.send(2).unwrap();
+    });
+    let oper = sel.select_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&rx2), Ok(2));
+}
+
+#[test]
+fn empty_timeout() {
+    let start = Instant::now();
+    let mut sel = Select::new();
+    assert!(sel.try_select().is_err());
+    assert!(sel.select_timeout(50 * MS).is_err());
+    assert!(start.elapsed() >= 50 * MS);
+}
+
+#[test]
+fn zero_capacity_recv() {
+    let (tx, rx) = sync_channel::<i32>(0);
+
+    let t = thread::spawn(move || {
+        thread::sleep(50 * MS);
+        tx.send(1).unwrap();
+    });
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx), Ok(1));
+    t.join().unwrap();
+}
+
+#[test]
+fn zero_capacity_send() {
+    let (tx, rx) = sync_channel::<i32>(0);
+
+    let t = thread::spawn(move || {
+        thread::sleep(50 * MS);
+        assert_eq!(rx.recv(), Ok(1));
+    });
+
+    let mut sel = Select::new();
+    let oper1 = sel.sync_send(&tx);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    oper.sync_send(&tx, 1).unwrap();
+    t.join().unwrap();
+}
+
+#[test]
+fn zero_capacity_both_sides_select() {
+    let (tx, rx) = sync_channel::<i32>(0);
+
+    let t = thread::spawn(move || {
+        let mut sel = Select::new();
+        sel.sync_send(&tx);
+        let oper = sel.select();
+        oper.sync_send(&tx, 7).unwrap();
+    });
+
+    let mut sel = Select::new();
+    sel.recv(&rx);
+    let oper = sel.select();
+    assert_eq!(oper.recv(&rx), Ok(7));
+    t.join().unwrap();
+}
+
+#[test]
+fn mixed_flavors() {
+    const COUNT: usize = 1000;
+
+    let (tx1, rx1) = channel::<usize>();
+    let (tx2, rx2) = sync_channel::<usize>(4);
+    let (tx3, rx3) = sync_channel::<usize>(0);
+
+    let t1 = thread::spawn(move || (0..COUNT).for_each(|i| tx1.send(i).unwrap()));
+    let t2 = thread::spawn(move || (0..COUNT).for_each(|i| tx2.send(i).unwrap()));
+    let t3 = thread::spawn(move || (0..COUNT).for_each(|i| tx3.send(i).unwrap()));
+
+    let mut received = [0; 3];
+    let mut sel = Select::new();
+    let opers = [sel.recv(&rx1), sel.recv(&rx2), sel.recv(&rx3)];
+    let receivers = [&rx1, &rx2, &rx3];
+    let mut remaining = 3;
+    while remaining > 0 {
+        let oper = sel.select();
+        let i = opers.iter().position(|&index| index == oper.index()).unwrap();
+        match oper.recv(receivers[i]) {
+            Ok(msg) => {
+                assert_eq!(msg, received[i]);
+                received[i] += 1;
+            }
+            Err(RecvError) => {
+                sel.remove(opers[i]);
+                remaining -= 1;
+            }
+        }
+    }
+    assert_eq!(received, [COUNT; 3]);
+
+    t1.join().unwrap();
+    t2.join().unwrap();
+    t3.join().unwrap();
+}
+
+#[test]
+fn fairness() {
+    const COUNT: usize = 10_000;
+
+    let (tx1, rx1) = channel::<()>();
+    let (tx2, rx2) = channel::<()>();
+    for _ in 0..COUNT {
+        tx1.send(()).unwrap();
+        tx2.send(()).unwrap();
+    }
+
+    let mut hits = [0usize; 2];
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx1);
+    sel.recv(&rx2);
+    for _ in 0..COUNT {
+        let oper = sel.select();
+        if oper.index() == oper1 {
+            oper.recv(&rx1).unwrap();
+            hits[0] += 1;
+        } else {
+            oper.recv(&rx2).unwrap();
+            hits[1] += 1;
+        }
+    }
+    assert!(hits.iter().all(|&hit| hit >= COUNT / 4), "{hits:?}");
+}
+
+#[test]
+#[should_panic(expected = "passed a receiver that wasn't selected")]
+fn wrong_receiver() {
+    let (tx1, rx1) = channel::<i32>();
+    let (_tx2, rx2) = channel::<i32>();
+
+    tx1.send(1).unwrap();
+    let mut sel = Select::new();
+    sel.recv(&rx1);
+    sel.recv(&rx2);
+    let oper = sel.select();
+    let _ = oper.recv(&rx2);
+}
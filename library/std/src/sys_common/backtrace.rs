@@ -185,7 +185,22 @@ pub fn output_filename(
     print_fmt: PrintFmt,
     cwd: Option<&PathBuf>,
 ) -> fmt::Result {
-    let file: Cow<'_, Path> = match bows {
+    let file = filename_to_path(bows);
+    if print_fmt == PrintFmt::Short && file.is_absolute() {
+        if let Some(cwd) = cwd {
+            if let Ok(stripped) = file.strip_prefix(&cwd) {
+                if let Some(s) = stripped.to_str() {
+                    return write!(fmt, ".{}{s}", path::MAIN_SEPARATOR);
+                }
+            }
+        }
+    }
+    fmt::Display::fmt(&file.display(), fmt)
+}
+
+/// Converts a filename in the platform's native representation into a path.
+pub fn filename_to_path(bows: BytesOrWideString<'_>) -> Cow<'_, Path> {
+    match bows {
         #[cfg(unix)]
         BytesOrWideString::Bytes(bytes) => {
             use crate::os::unix::prelude::*;
@@ -202,17 +217,7 @@ pub fn output_filename(
         }
         #[cfg(not(windows))]
         BytesOrWideString::Wide(_wide) => Path::new("<unknown>").into(),
-    };
-    if print_fmt == PrintFmt::Short && file.is_absolute() {
-        if let Some(cwd) = cwd {
-            if let Ok(stripped) = file.strip_prefix(&cwd) {
-                if let Some(s) = stripped.to_str() {
-                    return write!(fmt, ".{}{s}", path::MAIN_SEPARATOR);
-                }
-            }
-        }
     }
-    fmt::Display::fmt(&file.display(), fmt)
 }
 
 #[cfg(all(target_vendor = "fortanix", target_env = "sgx"))]
@@ -2,6 +2,7 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
 use super::time::TestTimeOptions;
@@ -26,6 +27,14 @@ pub struct TestOpts {
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    /// Only run the tests of the shard with the given index, out of the given
+    /// number of shards.
+    pub shard: Option<(usize, usize)>,
+    /// Number of times to run a failing test again before reporting it as
+    /// failed.
+    pub retries: usize,
+    /// Kill tests running in subprocesses after this time.
+    pub timeout: Option<Duration>,
     /// Stop at first failing test.
     /// May run a few more tests due to threading, but will
     /// abort as soon as possible.
@@ -148,6 +157,34 @@ fn optgroups() -> getopts::Options {
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests of the shard with index INDEX (starting at 0) out of \
+             --shard-count shards",
+            "INDEX",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Split the tests into COUNT shards, assigning them round-robin in the \
+             order of their names",
+            "COUNT",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failing tests up to N more times, reporting tests that pass on \
+             a later attempt as flaky",
+            "N",
+        )
+        .optopt(
+            "",
+            "timeout",
+            "Kill tests still running after SECONDS and report them as failed. \
+             Only supported when tests run in subprocesses, i.e. with panic=abort",
+            "SECONDS",
         );
     opts
 }
@@ -261,6 +298,9 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let time_options = get_time_options(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let timeout = get_timeout(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -299,6 +339,9 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         test_threads,
         skip,
         time_options,
+        shard,
+        retries,
+        timeout,
         options,
         fail_fast: false,
     };
@@ -375,6 +418,82 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<(usize, usize)>> {
+    let shard_index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let shard_count = unstable_optopt!(matches, allow_unstable, "shard-count");
+    let (shard_index, shard_count) = match (shard_index, shard_count) {
+        (Some(index), Some(count)) => (index, count),
+        (None, None) => return Ok(None),
+        _ => return Err("the options --shard-index and --shard-count must be used together".into()),
+    };
+
+    let shard_count = match shard_count.parse::<usize>() {
+        Ok(0) => return Err("argument for --shard-count must not be 0".to_string()),
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-count must be a number > 0 \
+                 (error: {e})"
+            ));
+        }
+    };
+    let shard_index = match shard_index.parse::<usize>() {
+        Ok(n) if n < shard_count => n,
+        Ok(n) => {
+            return Err(format!(
+                "argument for --shard-index must be less than the shard count {shard_count} \
+                 (was {n})"
+            ));
+        }
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-index must be a number \
+                 (error: {e})"
+            ));
+        }
+    };
+
+    Ok(Some((shard_index, shard_count)))
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_timeout(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<Duration>> {
+    let timeout = match unstable_optopt!(matches, allow_unstable, "timeout") {
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --timeout must not be 0".to_string()),
+            Ok(n) => Some(Duration::from_secs(n)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --timeout must be a number > 0 \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(timeout)
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -86,6 +86,7 @@ pub struct ConsoleTestState {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub flaky: usize,
     pub ignored: usize,
     pub filtered_out: usize,
     pub measured: usize,
@@ -95,6 +96,7 @@ pub struct ConsoleTestState {
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub ignores: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
     pub options: Options,
 }
 
@@ -110,6 +112,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             total: 0,
             passed: 0,
             failed: 0,
+            flaky: 0,
             ignored: 0,
             filtered_out: 0,
             measured: 0,
@@ -119,6 +122,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             not_failures: Vec::new(),
             ignores: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
             options: opts.options,
         })
     }
@@ -161,6 +165,9 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrFlaky(failed_attempts) => {
+                        format!("flaky: passed on attempt {}", failed_attempts + 1)
+                    }
                 },
                 name,
             )
@@ -172,7 +179,7 @@ pub fn write_log_result(
     }
 
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured
+        self.passed + self.failed + self.flaky + self.ignored + self.measured
     }
 }
 
@@ -253,6 +260,10 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flaky_tests.push((test, stdout));
+        }
     }
 }
 
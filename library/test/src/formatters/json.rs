@@ -155,6 +155,15 @@ fn write_result(
                 Some(&*format!(r#""message": "{}""#, EscapedString(m))),
             ),
 
+            TestResult::TrFlaky(failed_attempts) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""failed_attempts": {failed_attempts}"#)),
+            ),
+
             TestResult::TrIgnored => self.write_event(
                 "test",
                 desc.name.as_slice(),
@@ -198,6 +207,8 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let event = if state.failed == 0 { "ok" } else { "failed" };
         let passed = state.passed;
+        let flaky_json =
+            if state.flaky > 0 { format!(r#", "flaky": {}"#, state.flaky) } else { String::new() };
         let failed = state.failed;
         let ignored = state.ignored;
         let measured = state.measured;
@@ -210,7 +221,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}{flaky_json}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
         ))?;
 
         Ok(state.failed == 0)
@@ -88,10 +88,14 @@ fn write_result(
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         self.write_message("<testsuites>")?;
 
+        // Flaky tests passed, so they're only counted separately when there are any.
+        let flakes =
+            if state.flaky > 0 { format!("flakes=\"{}\" ", state.flaky) } else { String::new() };
         self.write_message(&format!(
             "<testsuite name=\"test\" package=\"test\" id=\"0\" \
              errors=\"0\" \
              failures=\"{}\" \
+             {flakes}\
              tests=\"{}\" \
              skipped=\"{}\" \
              >",
@@ -147,6 +151,27 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                     self.write_message("</testcase>")?;
                 }
 
+                TestResult::TrFlaky(failed_attempts) => {
+                    // Report the failed attempts like Maven Surefire reports reruns,
+                    // which most consumers of JUnit reports understand.
+                    self.write_message(&format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    for _ in 0..failed_attempts {
+                        self.write_message("<flakyFailure type=\"assert\"/>")?;
+                    }
+                    if !stdout.is_empty() {
+                        self.write_message("<system-out>")?;
+                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                        self.write_message("</system-out>")?;
+                    }
+                    self.write_message("</testcase>")?;
+                }
+
                 TestResult::TrBench(ref b) => {
                     self.write_message(&format!(
                         "<testcase classname=\"benchmark::{}\" \
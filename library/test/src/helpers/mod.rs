@@ -4,4 +4,6 @@
 pub mod concurrency;
 pub mod exit_code;
 pub mod metrics;
+pub mod shard;
 pub mod shuffle;
+pub mod subprocess;
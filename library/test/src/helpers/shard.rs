@@ -0,0 +1,20 @@
+//! Helper module to split the tests into shards run by separate test runs.
+
+use crate::types::TestDescAndFn;
+
+/// Keeps the tests of the shard with index `shard_index` out of `shard_count`
+/// shards.
+///
+/// The tests are sorted by name and assigned to the shards round-robin, so
+/// that every test ends up in exactly one shard, the shards have about the
+/// same number of tests, and the partitioning only depends on the names of
+/// the tests, not on the order they were passed in.
+pub fn shard_tests(shard_index: usize, shard_count: usize, tests: &mut Vec<TestDescAndFn>) {
+    tests.sort_by(|a, b| a.desc.name.as_slice().cmp(b.desc.name.as_slice()));
+    let mut position = 0;
+    tests.retain(|_| {
+        let keep = position % shard_count == shard_index;
+        position += 1;
+        keep
+    });
+}
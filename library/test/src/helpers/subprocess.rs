@@ -0,0 +1,62 @@
+//! Helper module to run test subprocesses with a timeout.
+
+use std::io::{self, Read};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs `command` like `Command::output`, but kills the child process if it
+/// hasn't exited after `timeout`. Unlike with `Command::output`, stdout and
+/// stderr are only captured if they were configured to be piped.
+///
+/// Returns the output of the child process and whether it was killed.
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<(Output, bool)> {
+    let deadline = Instant::now() + timeout;
+    let mut child = command.stdin(Stdio::null()).spawn()?;
+
+    // Read the output on separate threads, so that a child filling up a pipe
+    // can't block before it exits.
+    let stdout = child.stdout.take().map(read_to_end_on_thread);
+    let stderr = child.stderr.take().map(read_to_end_on_thread);
+
+    // There's no portable way to wait for a child process with a timeout, so
+    // poll its status, backing off to not waste time on long-running tests.
+    let mut poll_interval = Duration::from_millis(1);
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, false);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // Killing fails if the child exited in the meantime, which `wait`
+            // then reports.
+            let _ = child.kill();
+            break (child.wait()?, true);
+        }
+        thread::sleep(poll_interval.min(deadline - now));
+        poll_interval = (poll_interval * 2).min(MAX_POLL_INTERVAL);
+    };
+
+    let stdout = match stdout {
+        Some(reader) => reader.join().unwrap()?,
+        None => Vec::new(),
+    };
+    let stderr = match stderr {
+        Some(reader) => reader.join().unwrap()?,
+        None => Vec::new(),
+    };
+
+    Ok((Output { status, stdout, stderr }, timed_out))
+}
+
+fn read_to_end_on_thread<R: Read + Send + 'static>(
+    mut pipe: R,
+) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        pipe.read_to_end(&mut output)?;
+        Ok(output)
+    })
+}
@@ -57,7 +57,7 @@ pub mod test {
     collections::VecDeque,
     env, io,
     io::prelude::Write,
-    mem::ManuallyDrop,
+    mem::{self, ManuallyDrop},
     panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo},
     process::{self, Command, Termination},
     sync::mpsc::{channel, Sender},
@@ -86,7 +86,9 @@ pub mod test {
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
 use helpers::exit_code::get_exit_code;
+use helpers::shard::shard_tests;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
+use helpers::subprocess::output_with_timeout;
 use options::RunStrategy;
 use test_result::*;
 use time::TestExecTime;
@@ -111,6 +113,12 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
     if let Some(options) = options {
         opts.options = options;
     }
+    if opts.timeout.is_some() && !(opts.options.panic_abort && !opts.force_run_in_process) {
+        eprintln!(
+            "error: --timeout is only supported when tests run in subprocesses (panic=abort)"
+        );
+        process::exit(ERROR_EXIT_CODE);
+    }
     if opts.list {
         if let Err(e) = console::list_tests_console(&opts, tests) {
             eprintln!("error: io error when listing tests: {e:?}");
@@ -288,6 +296,15 @@ fn join(self, completed_test: &mut CompletedTest) {
     // Use a deterministic hasher
     type TestMap = HashMap<TestId, RunningTest, BuildHasherDefault<DefaultHasher>>;
 
+    /// A copy of a test to run it again if it fails, with `--retries`.
+    struct RetryableTest {
+        testfn: TestFn,
+        failed_attempts: usize,
+        failure_output: Vec<u8>,
+    }
+
+    type RetryableTestMap = HashMap<TestId, RetryableTest, BuildHasherDefault<DefaultHasher>>;
+
     struct TimeoutEntry {
         id: TestId,
         desc: TestDesc,
@@ -352,6 +369,7 @@ struct TimeoutEntry {
 
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
+    let mut retryable_tests: RetryableTestMap = HashMap::default();
 
     fn get_timed_out_tests(
         running_tests: &TestMap,
@@ -371,6 +389,51 @@ fn get_timed_out_tests(
         timed_out
     }
 
+    fn add_retryable_test(
+        opts: &TestOpts,
+        retryable_tests: &mut RetryableTestMap,
+        id: TestId,
+        test: &TestDescAndFn,
+    ) {
+        // Dynamic tests can only be run once.
+        if opts.retries > 0 {
+            if let Some(testfn) = test.testfn.try_clone() {
+                let test = RetryableTest { testfn, failed_attempts: 0, failure_output: Vec::new() };
+                retryable_tests.insert(id, test);
+            }
+        }
+    }
+
+    // Returns the test to run again if it failed and has retries left.
+    // Otherwise, a test that passed after failing is reported as flaky, with
+    // the output of its last failed attempt.
+    fn retry_failed_test(
+        opts: &TestOpts,
+        retryable_tests: &mut RetryableTestMap,
+        completed_test: &mut CompletedTest,
+    ) -> Option<TestDescAndFn> {
+        let failed = matches!(completed_test.result, TrFailed | TrFailedMsg(_) | TrTimedFail);
+        let retryable_test = retryable_tests.get_mut(&completed_test.id)?;
+        if failed && retryable_test.failed_attempts < opts.retries {
+            retryable_test.failed_attempts += 1;
+            retryable_test.failure_output = mem::take(&mut completed_test.stdout);
+            if let TrFailedMsg(msg) = &completed_test.result {
+                retryable_test
+                    .failure_output
+                    .extend_from_slice(format!("\nnote: {msg}\n").as_bytes());
+            }
+            let testfn = retryable_test.testfn.try_clone().unwrap();
+            return Some(TestDescAndFn { desc: completed_test.desc.clone(), testfn });
+        }
+
+        let retryable_test = retryable_tests.remove(&completed_test.id).unwrap();
+        if completed_test.result == TrOk && retryable_test.failed_attempts > 0 {
+            completed_test.result = TrFlaky(retryable_test.failed_attempts);
+            completed_test.stdout = retryable_test.failure_output;
+        }
+        None
+    }
+
     fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         timeout_queue.front().map(|&TimeoutEntry { timeout: next_timeout, .. }| {
             let now = Instant::now();
@@ -380,16 +443,25 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
 
     if concurrency == 1 {
         while !remaining.is_empty() {
-            let (id, test) = remaining.pop_front().unwrap();
+            let (id, mut test) = remaining.pop_front().unwrap();
             let event = TestEvent::TeWait(test.desc.clone());
             notify_about_test_event(event)?;
-            let join_handle = run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
-            // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
-            RunningTest { join_handle }.join(&mut completed_test);
+            add_retryable_test(opts, &mut retryable_tests, id, &test);
+            let completed_test = loop {
+                let join_handle =
+                    run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
+                // Wait for the test to complete.
+                let mut completed_test = rx.recv().unwrap();
+                RunningTest { join_handle }.join(&mut completed_test);
+
+                match retry_failed_test(opts, &mut retryable_tests, &mut completed_test) {
+                    Some(retry) => test = retry,
+                    None => break completed_test,
+                }
+            };
 
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
                 TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
             };
 
@@ -409,6 +481,7 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
 
                 let event = TestEvent::TeWait(desc.clone());
                 notify_about_test_event(event)?; //here no pad
+                add_retryable_test(opts, &mut retryable_tests, id, &test);
                 let join_handle =
                     run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
                 running_tests.insert(id, RunningTest { join_handle });
@@ -444,8 +517,22 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let running_test = running_tests.remove(&completed_test.id).unwrap();
             running_test.join(&mut completed_test);
 
+            if let Some(test) = retry_failed_test(opts, &mut retryable_tests, &mut completed_test) {
+                let id = completed_test.id;
+                let timeout = time::get_default_test_timeout();
+                let desc = test.desc.clone();
+
+                let join_handle =
+                    run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
+                running_tests.insert(id, RunningTest { join_handle });
+                // Don't report the retry as timed out based on the failed attempt.
+                timeout_queue.retain(|timeout_entry| timeout_entry.id != id);
+                timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
+                continue;
+            }
+
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
                 TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
             };
 
@@ -504,6 +591,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         filtered.retain(|test| test.desc.should_panic == ShouldPanic::No);
     }
 
+    // Only keep the tests of the requested shard
+    if let Some((shard_index, shard_count)) = opts.shard {
+        shard_tests(shard_index, shard_count, &mut filtered);
+    }
+
     // maybe unignore tests
     match opts.run_ignored {
         RunIgnored::Yes => {
@@ -568,6 +660,7 @@ pub fn run_test(
             let nocapture = opts.nocapture;
             let time_options = opts.time_options;
             let bench_benchmarks = opts.bench_benchmarks;
+            let timeout = opts.timeout;
 
             let runtest = move || match strategy {
                 RunStrategy::InProcess => run_test_in_process(
@@ -587,6 +680,7 @@ pub fn run_test(
                     monitor_ch,
                     time_options,
                     bench_benchmarks,
+                    timeout,
                 ),
             };
 
@@ -685,6 +779,7 @@ fn spawn_test_subprocess(
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
     bench_benchmarks: bool,
+    timeout: Option<Duration>,
 ) {
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
@@ -698,10 +793,17 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match timeout {
+            Some(timeout) => output_with_timeout(&mut command, timeout),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -718,6 +820,13 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        if timed_out {
+            let timeout = timeout.unwrap();
+            write!(&mut test_output, "note: test was killed after running for over {timeout:?}")
+                .unwrap();
+            return (TrTimedFail, test_output, exec_time);
+        }
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
@@ -21,6 +21,8 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// Passed after failing the given number of attempts with `--retries`.
+    TrFlaky(usize),
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
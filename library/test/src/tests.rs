@@ -33,6 +33,9 @@ fn new() -> TestOpts {
             test_threads: None,
             skip: vec![],
             time_options: None,
+            shard: None,
+            retries: 0,
+            timeout: None,
             options: Options::new(),
             fail_fast: false,
         }
@@ -666,6 +669,138 @@ fn testfn() -> Result<(), String> {
     tests
 }
 
+#[test]
+fn parse_shard_options_require_unstable_options() {
+    let args = vec!["progname".to_string(), "--shard-index".to_string(), "0".to_string()];
+    let error = parse_opts(&args).unwrap().unwrap_err();
+    assert!(error.contains("only accepted on the nightly compiler"), "{error}");
+}
+
+#[test]
+pub fn shard_tests() {
+    let shards: Vec<Vec<String>> = (0..3)
+        .map(|shard_index| {
+            let opts = TestOpts { shard: Some((shard_index, 3)), ..TestOpts::new() };
+            filter_tests(&opts, sample_tests())
+                .into_iter()
+                .map(|test| test.desc.name.to_string())
+                .collect()
+        })
+        .collect();
+
+    assert_eq!(shards.iter().map(|shard| shard.len()).collect::<Vec<_>>(), [4, 4, 3]);
+
+    let mut sharded = shards.concat();
+    sharded.sort();
+    let mut all =
+        sample_tests().into_iter().map(|test| test.desc.name.to_string()).collect::<Vec<_>>();
+    all.sort();
+    assert_eq!(sharded, all);
+}
+
+fn run_retried_test(
+    test_threads: usize,
+    should_panic: ShouldPanic,
+    testfn: fn() -> Result<(), String>,
+) -> CompletedTest {
+    let desc = TestDescAndFn {
+        desc: TestDesc { should_panic, ..typed_test_desc(TestType::Unknown) },
+        testfn: StaticTestFn(testfn),
+    };
+    let opts = TestOpts {
+        run_tests: true,
+        retries: 2,
+        test_threads: Some(test_threads),
+        ..TestOpts::new()
+    };
+    let (tx, rx) = channel();
+    let notify = move |event: TestEvent| {
+        if let TestEvent::TeResult(result) = event {
+            tx.send(result).unwrap();
+        }
+        Ok(())
+    };
+    run_tests(&opts, vec![desc], notify).unwrap();
+    let completed_test = rx.recv().unwrap();
+    assert!(rx.try_recv().is_err(), "failed attempts must not be reported");
+    completed_test
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_retries_flaky() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn f() -> Result<(), String> {
+        match ATTEMPTS.fetch_add(1, Ordering::Relaxed) {
+            0 => Err("first attempt".into()),
+            _ => Ok(()),
+        }
+    }
+
+    let completed_test = run_retried_test(1, ShouldPanic::No, f);
+    assert_eq!(completed_test.result, TrFlaky(1));
+    assert_eq!(ATTEMPTS.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_retries_exhausted() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn f() -> Result<(), String> {
+        ATTEMPTS.fetch_add(1, Ordering::Relaxed);
+        Err("always fails".into())
+    }
+
+    let completed_test = run_retried_test(2, ShouldPanic::No, f);
+    assert_eq!(completed_test.result, TrFailed);
+    assert_eq!(ATTEMPTS.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_retries_flaky_failure_message() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn f() -> Result<(), String> {
+        match ATTEMPTS.fetch_add(1, Ordering::Relaxed) {
+            0 => Ok(()),
+            _ => panic!("second attempt"),
+        }
+    }
+
+    // The first attempt fails with a message, which is kept on its own line
+    // after the output of that attempt.
+    let completed_test = run_retried_test(1, ShouldPanic::Yes, f);
+    assert_eq!(completed_test.result, TrFlaky(1));
+    assert_eq!(completed_test.stdout, b"\nnote: test did not panic as expected\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_subprocess_timeout() {
+    let (output, timed_out) = helpers::subprocess::output_with_timeout(
+        Command::new("sleep").arg("10"),
+        Duration::from_millis(100),
+    )
+    .unwrap();
+    assert!(timed_out);
+    assert!(!output.status.success());
+
+    let (output, timed_out) = helpers::subprocess::output_with_timeout(
+        Command::new("echo").arg("done").stdout(process::Stdio::piped()),
+        Duration::from_secs(60),
+    )
+    .unwrap();
+    assert!(!timed_out);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"done\n");
+}
+
 #[test]
 pub fn shuffle_tests() {
     let mut opts = TestOpts::new();
@@ -865,6 +1000,7 @@ fn should_sort_failures_before_printing_them() {
         total: 0,
         passed: 0,
         failed: 0,
+        flaky: 0,
         ignored: 0,
         filtered_out: 0,
         measured: 0,
@@ -875,6 +1011,7 @@ fn should_sort_failures_before_printing_them() {
         not_failures: Vec::new(),
         ignores: Vec::new(),
         time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
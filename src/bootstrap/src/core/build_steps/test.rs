@@ -1540,6 +1540,22 @@ fn run(self, builder: &Builder<'_>) {
     }
 }
 
+/// Returns whether the test at `test_path` in the suite at `suite_path` matches the filters of the
+/// command, i.e. its paths in the suite and its test arguments, as compiletest filters tests.
+fn test_may_be_selected(builder: &Builder<'_>, suite_path: &str, test_path: &str) -> bool {
+    let paths = match &builder.config.cmd {
+        Subcommand::Test { .. } => &builder.config.paths[..],
+        _ => &[],
+    };
+    let test_args = builder.config.test_args();
+    let mut filters = paths
+        .iter()
+        .filter_map(|p| helpers::is_valid_test_suite_arg(p, suite_path, builder))
+        .chain(test_args.into_iter().filter(|arg| !arg.starts_with('-')))
+        .peekable();
+    filters.peek().is_none() || filters.any(|filter| test_path.contains(filter))
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct Compiletest {
     compiler: Compiler,
@@ -1665,6 +1681,15 @@ fn run(self, builder: &Builder<'_>) {
             cmd.arg("--coverage-dump-path").arg(coverage_dump);
         }
 
+        // Only the `api-usage` test uses `unused-pub-api`, so don't build it for the other tests.
+        if suite == "run-make" && test_may_be_selected(builder, suite_path, "api-usage") {
+            let unused_pub_api = builder.ensure(tool::UnusedPubApi {
+                compiler: compiler.with_stage(0),
+                target: compiler.host,
+            });
+            cmd.arg("--unused-pub-api-path").arg(unused_pub_api);
+        }
+
         if mode == "coverage-run" {
             // The demangler doesn't need the current compiler, so we can avoid
             // unnecessary rebuilds by using the bootstrap compiler instead.
@@ -318,6 +318,7 @@ fn run(self, builder: &Builder<'_>) -> PathBuf {
     RustdocGUITest, "src/tools/rustdoc-gui-test", "rustdoc-gui-test", is_unstable_tool = true, allow_features = "test";
     OptimizedDist, "src/tools/opt-dist", "opt-dist";
     CoverageDump, "src/tools/coverage-dump", "coverage-dump";
+    UnusedPubApi, "src/tools/unused-pub-api", "unused-pub-api";
 );
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
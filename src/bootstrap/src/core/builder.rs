@@ -720,6 +720,7 @@ macro_rules! describe {
                 tool::RustdocGUITest,
                 tool::OptimizedDist,
                 tool::CoverageDump,
+                tool::UnusedPubApi,
             ),
             Kind::Check | Kind::Clippy | Kind::Fix => describe!(
                 check::Std,
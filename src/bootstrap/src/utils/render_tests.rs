@@ -72,6 +72,7 @@ fn run_tests(builder: &Builder<'_>, cmd: &mut Command, stream: bool) -> bool {
 struct Renderer<'a> {
     stdout: BufReader<ChildStdout>,
     failures: Vec<TestOutcome>,
+    flaky_tests: Vec<String>,
     benches: Vec<BenchOutcome>,
     builder: &'a Builder<'a>,
     tests_count: Option<usize>,
@@ -85,6 +86,7 @@ fn new(stdout: ChildStdout, builder: &'a Builder<'a>) -> Self {
             stdout: BufReader::new(stdout),
             benches: Vec::new(),
             failures: Vec::new(),
+            flaky_tests: Vec::new(),
             builder,
             tests_count: None,
             executed_tests: 0,
@@ -141,7 +143,9 @@ fn render_test_outcome(&mut self, outcome: Outcome<'_>, test: &TestOutcome) {
         self.builder.metrics.record_test(
             &test.name,
             match outcome {
-                Outcome::Ok | Outcome::BenchOk => build_helper::metrics::TestOutcome::Passed,
+                Outcome::Ok | Outcome::BenchOk | Outcome::Flaky { .. } => {
+                    build_helper::metrics::TestOutcome::Passed
+                }
                 Outcome::Failed => build_helper::metrics::TestOutcome::Failed,
                 Outcome::Ignored { reason } => build_helper::metrics::TestOutcome::Ignored {
                     ignore_reason: reason.map(|s| s.to_string()),
@@ -208,6 +212,13 @@ fn render_suite_outcome(&self, outcome: Outcome<'_>, suite: &SuiteOutcome) {
             }
         }
 
+        if !self.flaky_tests.is_empty() {
+            println!("\nflaky tests:");
+            for name in &self.flaky_tests {
+                println!("    {name}");
+            }
+        }
+
         if !self.benches.is_empty() {
             println!("\nbenchmarks:");
 
@@ -230,8 +241,10 @@ fn render_suite_outcome(&self, outcome: Outcome<'_>, suite: &SuiteOutcome) {
 
         print!("\ntest result: ");
         self.builder.colored_stdout(|stdout| outcome.write_long(stdout)).unwrap();
+        let flaky =
+            if suite.flaky > 0 { format!("; {} flaky", suite.flaky) } else { String::new() };
         println!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out; \
+            ". {} passed{flaky}; {} failed; {} ignored; {} measured; {} filtered out; \
              finished in {:.2?}\n",
             suite.passed,
             suite.failed,
@@ -280,6 +293,10 @@ fn render_message(&mut self, message: Message) {
                     &outcome,
                 );
             }
+            Message::Test(TestMessage::Flaky(FlakyOutcome { test, failed_attempts })) => {
+                self.render_test_outcome(Outcome::Flaky { failed_attempts }, &test);
+                self.flaky_tests.push(test.name);
+            }
             Message::Test(TestMessage::Failed(outcome)) => {
                 self.render_test_outcome(Outcome::Failed, &outcome);
                 self.failures.push(outcome);
@@ -296,6 +313,7 @@ enum Outcome<'a> {
     Ok,
     BenchOk,
     Failed,
+    Flaky { failed_attempts: usize },
     Ignored { reason: Option<&'a str> },
 }
 
@@ -314,6 +332,10 @@ fn write_short(&self, writer: &mut dyn WriteColor) -> Result<(), std::io::Error>
                 writer.set_color(&ColorSpec::new().set_fg(Some(Color::Red)))?;
                 write!(writer, "F")?;
             }
+            Outcome::Flaky { .. } => {
+                writer.set_color(&ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+                write!(writer, "f")?;
+            }
             Outcome::Ignored { .. } => {
                 writer.set_color(&ColorSpec::new().set_fg(Some(Color::Yellow)))?;
                 write!(writer, "i")?;
@@ -336,6 +358,10 @@ fn write_long(&self, writer: &mut dyn WriteColor) -> Result<(), std::io::Error>
                 writer.set_color(&ColorSpec::new().set_fg(Some(Color::Red)))?;
                 write!(writer, "FAILED")?;
             }
+            Outcome::Flaky { failed_attempts } => {
+                writer.set_color(&ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+                write!(writer, "flaky, passed on attempt {}", failed_attempts + 1)?;
+            }
             Outcome::Ignored { reason } => {
                 writer.set_color(&ColorSpec::new().set_fg(Some(Color::Yellow)))?;
                 write!(writer, "ignored")?;
@@ -367,6 +393,9 @@ enum SuiteMessage {
 #[derive(serde_derive::Deserialize)]
 struct SuiteOutcome {
     passed: usize,
+    // Only present when some tests were flaky.
+    #[serde(default)]
+    flaky: usize,
     failed: usize,
     ignored: usize,
     measured: usize,
@@ -379,6 +408,7 @@ struct SuiteOutcome {
 enum TestMessage {
     Ok(TestOutcome),
     Failed(TestOutcome),
+    Flaky(FlakyOutcome),
     Ignored(TestOutcome),
     Timeout { name: String },
     Started,
@@ -398,3 +428,10 @@ struct TestOutcome {
     stdout: Option<String>,
     message: Option<String>,
 }
+
+#[derive(serde_derive::Deserialize)]
+struct FlakyOutcome {
+    #[serde(flatten)]
+    test: TestOutcome,
+    failed_attempts: usize,
+}
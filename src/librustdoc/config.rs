@@ -319,11 +319,15 @@ pub(crate) fn should_emit_crate(&self) -> bool {
 impl Options {
     /// Parses the given command-line for options. If an error message or other early-return has
     /// been printed, returns `Err` with the exit code.
+    ///
+    /// The `DiagCtxt` the errors in the options are reported to is returned too, to report the
+    /// errors of the rest of the run: with `--error-format=sarif`, the log is only written once the
+    /// last `DiagCtxt` writing to it is dropped.
     pub(crate) fn from_matches(
         early_dcx: &mut EarlyDiagCtxt,
         matches: &getopts::Matches,
         args: Vec<String>,
-    ) -> Result<(Options, RenderOptions), i32> {
+    ) -> Result<(Options, RenderOptions, rustc_errors::DiagCtxt), i32> {
         // Check for unstable options.
         nightly_options::check_nightly_options(early_dcx, matches, &opts());
 
@@ -793,7 +797,7 @@ fn println_condition(condition: Condition) {
             no_emit_shared: false,
             html_no_source,
         };
-        Ok((options, render_options))
+        Ok((options, render_options, dcx))
     }
 
     /// Returns `true` if the file given as `self.input` is a Markdown file.
@@ -733,20 +733,18 @@ fn main_args(
 
     // Note that we discard any distinction between different non-zero exit
     // codes from `from_matches` here.
-    let (options, render_options) = match config::Options::from_matches(early_dcx, &matches, args) {
-        Ok(opts) => opts,
-        Err(code) => {
-            return if code == 0 {
-                Ok(())
-            } else {
-                #[allow(deprecated)]
-                Err(ErrorGuaranteed::unchecked_claim_error_was_emitted())
-            };
-        }
-    };
-
-    let diag =
-        core::new_dcx(options.error_format, None, options.diagnostic_width, &options.unstable_opts);
+    let (options, render_options, diag) =
+        match config::Options::from_matches(early_dcx, &matches, args) {
+            Ok(opts) => opts,
+            Err(code) => {
+                return if code == 0 {
+                    Ok(())
+                } else {
+                    #[allow(deprecated)]
+                    Err(ErrorGuaranteed::unchecked_claim_error_was_emitted())
+                };
+            }
+        };
 
     match (options.should_test, options.markdown_input()) {
         (true, true) => return wrap_return(&diag, markdown::test(options)),
@@ -191,6 +191,9 @@ pub struct Config {
     /// The coverage-dump executable.
     pub coverage_dump_path: Option<PathBuf>,
 
+    /// The unused-pub-api executable, for run-make tests.
+    pub unused_pub_api_path: Option<PathBuf>,
+
     /// The Python executable to use for LLDB and htmldocck.
     pub python: String,
 
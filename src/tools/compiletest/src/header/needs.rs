@@ -104,6 +104,11 @@ pub(super) fn handle_needs(
             condition: cache.rust_lld,
             ignore_reason: "ignored on targets without Rust's LLD",
         },
+        Need {
+            name: "needs-cranelift-backend",
+            condition: cache.cranelift_backend,
+            ignore_reason: "ignored when the Cranelift backend is not in the sysroot",
+        },
         Need {
             name: "needs-rust-lldb",
             condition: config.debugger != Some(Debugger::Lldb) || config.lldb_native_rust,
@@ -202,6 +207,7 @@ pub(super) struct CachedNeedsConditions {
     profiler_support: bool,
     xray: bool,
     rust_lld: bool,
+    cranelift_backend: bool,
     i686_dlltool: bool,
     x86_64_dlltool: bool,
     dlltool: bool,
@@ -261,6 +267,26 @@ pub(super) fn load(config: &Config) -> Self {
                 .join(if config.host.contains("windows") { "rust-lld.exe" } else { "rust-lld" })
                 .exists(),
 
+            // The codegen backends are loaded from the sysroot of the compiler under test, which
+            // only contains `rustc_codegen_cranelift` when it is in `rust.codegen-backends`.
+            cranelift_backend: config
+                .compile_lib_path
+                .parent()
+                .expect("couldn't traverse to the parent of the specified --compile-lib-path")
+                .join("lib")
+                .join("rustlib")
+                .join(&config.host)
+                .join("codegen-backends")
+                .read_dir()
+                .map(|mut entries| {
+                    entries.any(|entry| {
+                        entry.is_ok_and(|entry| {
+                            entry.file_name().to_string_lossy().contains("rustc_codegen_cranelift")
+                        })
+                    })
+                })
+                .unwrap_or(false),
+
             #[cfg(windows)]
             i686_dlltool: dlltool,
             #[cfg(windows)]
@@ -19,7 +19,6 @@
 use regex::{Captures, Regex};
 use rustfix::{apply_suggestions, get_suggestions_from_json, Filter};
 
-use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::{OsStr, OsString};
@@ -564,6 +563,7 @@ fn run_coverage_run_test(&self) {
         // Run `llvm-cov show` to produce a coverage report in text format.
         let proc_res = self.run_llvm_tool("llvm-cov", |cmd| {
             cmd.args(["show", "--format=text", "--show-line-counts-or-regions"]);
+            cmd.args(&self.props.llvm_cov_flags);
 
             cmd.arg("--Xdemangler");
             cmd.arg(self.config.rust_demangler_path.as_ref().unwrap());
@@ -723,7 +723,7 @@ fn normalize_coverage_output(&self, coverage: &str) -> Result<String, String> {
 
     /// Replace line numbers in coverage reports with the placeholder `LL`,
     /// so that the tests are less sensitive to lines being added/removed.
-    fn anonymize_coverage_line_numbers(coverage: &str) -> Cow<'_, str> {
+    fn anonymize_coverage_line_numbers(coverage: &str) -> String {
         // The coverage reporter prints line numbers at the start of a line.
         // They are truncated or left-padded to occupy exactly 5 columns.
         // (`LineNumberColumnWidth` in `SourceCoverageViewText.cpp`.)
@@ -731,9 +731,31 @@ fn anonymize_coverage_line_numbers(coverage: &str) -> Cow<'_, str> {
         //
         // Line numbers that appear inside expansion/instantiation subviews
         // have an additional prefix of `  |` for each nesting level.
+        //
+        // Branch views and MC/DC views also mention line numbers, as
+        // `Branch (LL:C)`, `Decision Region (LL:C) to (LL:C)` and
+        // `Condition C1 --> (LL:C)`. Only the line numbers are replaced,
+        // because the columns tell the branches of a line apart.
         static LINE_NUMBER_RE: Lazy<Regex> =
             Lazy::new(|| Regex::new(r"(?m:^)(?<prefix>(?:  \|)*) *[0-9]+\|").unwrap());
-        LINE_NUMBER_RE.replace_all(coverage, "$prefix   LL|")
+        static BRANCH_LINE_NUMBER_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?m:^)(?<prefix>(?:  \|)+  Branch \()[0-9]+:").unwrap());
+        static MCDC_DECISION_LINE_NUMBER_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(concat!(
+                r"(?m:^)(?<prefix>(?:  \|)+---> MC/DC Decision Region \()[0-9]+:",
+                r"(?<middle>[0-9]+\) to \()[0-9]+:",
+            ))
+            .unwrap()
+        });
+        static MCDC_CONDITION_LINE_NUMBER_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?m:^)(?<prefix>(?:  \|)+     Condition C[0-9]+ --> \()[0-9]+:").unwrap()
+        });
+
+        let coverage = LINE_NUMBER_RE.replace_all(coverage, "$prefix   LL|");
+        let coverage = BRANCH_LINE_NUMBER_RE.replace_all(&coverage, "${prefix}LL:");
+        let coverage =
+            MCDC_DECISION_LINE_NUMBER_RE.replace_all(&coverage, "${prefix}LL:${middle}LL:");
+        MCDC_CONDITION_LINE_NUMBER_RE.replace_all(&coverage, "${prefix}LL:").into_owned()
     }
 
     /// Coverage reports can describe multiple source files, separated by
@@ -3610,6 +3632,10 @@ fn run_rmake_test(&self) {
             cmd.env("REMOTE_TEST_CLIENT", remote_test_client);
         }
 
+        if let Some(ref unused_pub_api) = self.config.unused_pub_api_path {
+            cmd.env("UNUSED_PUB_API", cwd.join(unused_pub_api));
+        }
+
         // We don't want RUSTFLAGS set from the outside to interfere with
         // compiler flags set in the test cases:
         cmd.env_remove("RUSTFLAGS");
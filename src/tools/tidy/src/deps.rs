@@ -197,6 +197,7 @@
     "ar_archive_writer",
     "arrayvec",
     "autocfg",
+    "bincode",
     "bitflags",
     "block-buffer",
     "byteorder", // via ruzstd in object in thorin-dwp
@@ -36,6 +36,10 @@
     "tests/ui/unused-crate-deps/test.mk", // why would you use make
     "tests/ui/proc-macro/auxiliary/included-file.txt", // more include
     "tests/ui/invalid/foo.natvis.xml", // sample debugger visualizer
+    "tests/ui/lint/lint-config/basic.toml", // lint configuration files
+    "tests/ui/lint/lint-config/invalid.toml", // lint configuration files
+    "tests/ui/lint/lint-config/overrides.toml", // lint configuration files
+    "tests/ui/lint/lint-config/unknown-lint.toml", // lint configuration files
 ];
 
 fn check_entries(tests_path: &Path, bad: &mut bool) {
@@ -0,0 +1,89 @@
+//! Reports the public items of the crates of a workspace that none of its
+//! crates use, from the `.api_usage.json` files written by `-Z api-usage`.
+//!
+//! The arguments are the files to combine, or directories to search for them.
+
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct ApiUsage {
+    crate_name: String,
+    exports: Vec<Item>,
+    uses: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    def_path_hash: String,
+    path: String,
+    kind: String,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut files = vec![];
+    for arg in std::env::args_os().skip(1) {
+        let path = Path::new(&arg);
+        if path.is_dir() {
+            collect_files(path, &mut files)?;
+        } else {
+            files.push(path.to_owned());
+        }
+    }
+    if files.is_empty() {
+        return Err("expected `.api_usage.json` files or directories containing them".into());
+    }
+    files.sort();
+
+    let mut crates = vec![];
+    for file in &files {
+        let usage: ApiUsage = serde_json::from_str(&fs::read_to_string(file)?)
+            .map_err(|err| format!("{}: {err}", file.display()))?;
+        crates.push(usage);
+    }
+
+    let used: HashSet<&str> =
+        crates.iter().flat_map(|usage| &usage.uses).map(|item| &*item.def_path_hash).collect();
+
+    // A crate built for several targets or crate types is reported once.
+    let mut unused: BTreeMap<&str, BTreeMap<&str, &str>> = BTreeMap::new();
+    for usage in &crates {
+        let items = unused.entry(&usage.crate_name).or_default();
+        for item in &usage.exports {
+            if !used.contains(&*item.def_path_hash) {
+                items.insert(&item.path, &item.kind);
+            }
+        }
+    }
+
+    let mut total = 0;
+    for (crate_name, items) in &unused {
+        if items.is_empty() {
+            continue;
+        }
+        println!("{crate_name}:");
+        for (path, kind) in items {
+            println!("    {kind} `{path}`");
+        }
+        total += items.len();
+    }
+    println!("{total} unused public items across {} crates", unused.len());
+    Ok(())
+}
+
+/// Collects the `.api_usage.json` files in `dir` and its subdirectories.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.to_str().is_some_and(|path| path.ends_with(".api_usage.json")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+// compile-flags: -O -Zmir-specialize-const-args
+
+#![crate_type = "lib"]
+
+// This function is too large to be inlined, but it switches on its argument, so its MIR is made
+// available to other crates to clone it for constant arguments.
+pub fn mix(x: u32, fast: bool) -> u32 {
+    if fast {
+        x ^ 0x5555_5555
+    } else {
+        let a = x ^ (x >> 7);
+        let b = a ^ (a << 9);
+        let c = b ^ (b >> 13);
+        let d = c ^ (c << 17);
+        d & 0xff00_ff00 | (d >> 8) & 0x00ff_00ff
+    }
+}
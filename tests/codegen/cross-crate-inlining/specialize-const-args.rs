@@ -0,0 +1,24 @@
+// compile-flags: -O -C no-prepopulate-passes -Csymbol-mangling-version=v0
+// compile-flags: -Zmir-specialize-const-args
+// aux-build:specialize.rs
+
+#![crate_type = "lib"]
+
+extern crate specialize;
+
+// Check that a cross-crate call with a constant argument calls a local clone of the callee.
+// CHECK-LABEL: @constant(
+#[no_mangle]
+pub fn constant(x: u32) -> u32 {
+    // CHECK: call noundef i32 @_RNSNv{{.*}}10specialize3mix{{.*}}10const_args(
+    specialize::mix(x, true)
+}
+
+// CHECK-LABEL: @variable(
+#[no_mangle]
+pub fn variable(x: u32, fast: bool) -> u32 {
+    // CHECK: call noundef i32 @_RNv{{.*}}10specialize3mix(
+    specialize::mix(x, fast)
+}
+
+// CHECK: define {{.*}}i32 @_RNSNv{{.*}}10specialize3mix{{.*}}10const_args(
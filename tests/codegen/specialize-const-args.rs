@@ -0,0 +1,37 @@
+// compile-flags: -O -C no-prepopulate-passes -Csymbol-mangling-version=v0
+// compile-flags: -Zmir-specialize-const-args
+
+#![crate_type = "lib"]
+
+// Too large to be inlined, but smaller once `fast` is known.
+fn mix(x: u32, fast: bool) -> u32 {
+    if fast {
+        x ^ 0x5555_5555
+    } else {
+        let a = x ^ (x >> 7);
+        let b = a ^ (a << 9);
+        let c = b ^ (b >> 13);
+        let d = c ^ (c << 17);
+        d & 0xff00_ff00 | (d >> 8) & 0x00ff_00ff
+    }
+}
+
+// CHECK-LABEL: @constant(
+#[no_mangle]
+pub fn constant(x: u32) -> u32 {
+    // CHECK: call noundef i32 @_RNSNv{{.*}}3mix{{.*}}10const_args(i32 noundef %x, i1 noundef zeroext true)
+    mix(x, true)
+}
+
+// CHECK-LABEL: @variable(
+#[no_mangle]
+pub fn variable(x: u32, fast: bool) -> u32 {
+    // CHECK: call noundef i32 @_RNv{{.*}}3mix(i32 noundef %x, i1 noundef zeroext %fast)
+    mix(x, fast)
+}
+
+// The clone ignores `fast` and only keeps the first branch.
+// CHECK: define {{.*}}i32 @_RNSNv{{.*}}3mix{{.*}}10const_args(
+// CHECK-NOT: br
+// CHECK: xor i32 %x, 1431655765
+// CHECK-NEXT: ret i32
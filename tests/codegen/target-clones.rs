@@ -0,0 +1,34 @@
+// Checks that a `#[target_clones]` function is dispatched through a thunk calling the clone
+// selected by a resolver, which checks the clones in order and whose result is cached.
+
+// only-x86_64
+// compile-flags: -C no-prepopulate-passes -C panic=abort
+
+#![crate_type = "lib"]
+#![feature(target_clones)]
+
+// CHECK: @sum.cache = internal global ptr null
+
+// CHECK-DAG: define internal {{.*}} @sum.default(
+// CHECK-DAG: define internal {{.*}} @sum.avx2(
+// CHECK-DAG: define internal {{.*}} @sum.sse4.2_popcnt(
+#[no_mangle]
+#[target_clones("avx2", "sse4.2,popcnt", "default")]
+pub fn sum(xs: &[u32]) -> u32 {
+    xs.iter().sum()
+}
+
+// CHECK-LABEL: define internal ptr @sum.resolver()
+// CHECK: call {{.*}} @{{.*}}target_clone_supported
+// CHECK: ret ptr @sum.avx2
+// CHECK: call {{.*}} @{{.*}}target_clone_supported
+// CHECK: ret ptr @sum.sse4.2_popcnt
+// CHECK: ret ptr @sum.default
+
+// CHECK-LABEL: define {{.*}} @sum(
+// CHECK: [[CACHED:%.*]] = load atomic ptr, ptr @sum.cache acquire
+// CHECK: icmp eq ptr [[CACHED]], null
+// CHECK: [[RESOLVED:%.*]] = call ptr @sum.resolver()
+// CHECK: store atomic ptr [[RESOLVED]], ptr @sum.cache release
+// CHECK: [[SELECTED:%.*]] = phi ptr [ [[CACHED]], %start ], [ [[RESOLVED]], %resolve ]
+// CHECK: musttail call {{.*}} [[SELECTED]](
@@ -0,0 +1,42 @@
+#![feature(coverage_attribute)]
+// edition: 2021
+// compile-flags: -Zunstable-options -Cinstrument-coverage=branch
+// llvm-cov-flags: --show-branches=count
+
+fn branch_not(a: bool) {
+    if !a {
+        say("not a");
+    }
+}
+
+fn branch_and(a: bool, b: bool) {
+    if a && b {
+        say("both");
+    } else {
+        say("not both");
+    }
+}
+
+fn branch_or(a: bool, b: bool) {
+    if a || b {
+        say("either");
+    } else {
+        say("neither");
+    }
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    for a in [false, true, true] {
+        branch_not(a);
+        for b in [false, true] {
+            branch_and(a, b);
+            branch_or(a, b);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+#![feature(coverage_attribute)]
+// edition: 2021
+// compile-flags: -Zunstable-options -Cinstrument-coverage=branch
+// llvm-cov-flags: --show-branches=count
+
+// Each arm of a `match` except the last is reported as a branch, whose false
+// outcome is the total count of the later arms.
+
+enum Enum {
+    A,
+    B,
+    C,
+}
+
+fn match_arms(value: Enum) {
+    match value {
+        Enum::A => say("a"),
+        Enum::B => say("b"),
+        Enum::C => say("c"),
+    }
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    for value in [Enum::A, Enum::B, Enum::B, Enum::C, Enum::C, Enum::C] {
+        match_arms(value);
+    }
+}
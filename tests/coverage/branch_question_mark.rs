@@ -0,0 +1,19 @@
+#![feature(coverage_attribute)]
+// edition: 2021
+// compile-flags: -Zunstable-options -Cinstrument-coverage=branch
+// llvm-cov-flags: --show-branches=count
+
+// The `?` operator is desugared to a `match` whose first arm returns early, so
+// it is reported as a branch that is taken when the early return happens.
+
+fn question_mark(value: Option<u32>) -> Option<u32> {
+    value?;
+    Some(1)
+}
+
+#[coverage(off)]
+fn main() {
+    for value in [Some(1), None, Some(2)] {
+        core::hint::black_box(question_mark(value));
+    }
+}
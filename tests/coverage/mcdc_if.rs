@@ -0,0 +1,40 @@
+#![feature(coverage_attribute)]
+// edition: 2021
+// min-llvm-version: 18
+// compile-flags: -Zunstable-options -Cinstrument-coverage=mcdc
+// llvm-cov-flags: --show-branches=count --show-mcdc
+
+fn mcdc_and(a: bool, b: bool) {
+    if a && b {
+        say("a and b");
+    } else {
+        say("not both");
+    }
+}
+
+fn mcdc_or(a: bool, b: bool) {
+    if a || b {
+        say("a or b");
+    } else {
+        say("neither");
+    }
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    // Every condition of the decision is shown to independently affect its
+    // outcome.
+    mcdc_and(true, true);
+    mcdc_and(true, false);
+    mcdc_and(false, true);
+
+    // `b` is only evaluated once, so it is never shown to independently
+    // affect the outcome.
+    mcdc_or(false, false);
+    mcdc_or(true, false);
+}
@@ -0,0 +1,77 @@
+// EMIT_MIR_FOR_EACH_PANIC_STRATEGY
+// unit-test: HeapToStack
+
+// EMIT_MIR heap_to_stack.read.HeapToStack.diff
+pub fn read(x: u32) -> u32 {
+    // CHECK-LABEL: fn read(
+    // CHECK-NOT: Box::<u32>::new
+    // CHECK: [[stack:_.*]] = move _3;
+    // CHECK: _4 = [[stack]];
+    // CHECK-NOT: drop(
+    let b = Box::new(x);
+    *b ^ 1
+}
+
+// EMIT_MIR heap_to_stack.escapes.HeapToStack.diff
+pub fn escapes(x: u32) -> Box<u32> {
+    // CHECK-LABEL: fn escapes(
+    // CHECK: _2 = Box::<u32>::new(move _3)
+    // CHECK: _0 = move _2;
+    let b = Box::new(x);
+    b
+}
+
+// EMIT_MIR heap_to_stack.string.HeapToStack.diff
+pub fn string(s: String) {
+    // CHECK-LABEL: fn string(
+    // CHECK-NOT: Box::<String>::new
+    // CHECK: [[stack:_.*]] = move _3;
+    // CHECK: drop([[stack]])
+    let _b = Box::new(s);
+}
+
+#[inline(never)]
+fn consume(x: &u32) -> u32 {
+    *x
+}
+
+// EMIT_MIR heap_to_stack.borrowed.HeapToStack.diff
+pub fn borrowed(x: u32) -> u32 {
+    // CHECK-LABEL: fn borrowed(
+    // CHECK: _2 = Box::<u32>::new(move _3)
+    // CHECK: _4 = &(*_5);
+    // CHECK: drop(_2)
+    let b = Box::new(x);
+    consume(&*b)
+}
+
+// EMIT_MIR heap_to_stack.moved_out.HeapToStack.diff
+pub fn moved_out(s: String) -> String {
+    // CHECK-LABEL: fn moved_out(
+    // CHECK-NOT: Box::<String>::new
+    // CHECK: [[stack:_.*]] = move _3;
+    // CHECK: _0 = move [[stack]];
+    // CHECK-NOT: as Drop>::drop
+    // CHECK-NOT: drop(
+    let b = Box::new(s);
+    *b
+}
+
+// EMIT_MIR heap_to_stack.too_large.HeapToStack.diff
+pub fn too_large(x: [u8; 5000]) -> [u8; 5000] {
+    // CHECK-LABEL: fn too_large(
+    // CHECK: _2 = Box::<[u8; 5000]>::new(move _3)
+    // CHECK: _0 = (*_4);
+    // CHECK: drop(_2)
+    let b = Box::new(x);
+    *b
+}
+
+fn main() {
+    read(5);
+    escapes(5);
+    string(String::new());
+    borrowed(5);
+    moved_out(String::new());
+    too_large([0; 5000]);
+}
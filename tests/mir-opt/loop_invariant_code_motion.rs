@@ -0,0 +1,126 @@
+// unit-test: LoopInvariantCodeMotion
+#![feature(custom_mir, core_intrinsics)]
+
+use std::intrinsics::mir::*;
+
+// EMIT_MIR loop_invariant_code_motion.hoist_len.LoopInvariantCodeMotion.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn hoist_len(s: &[u32], n: usize) -> usize {
+    // CHECK-LABEL: fn hoist_len(
+    mir!(
+        let i: usize;
+        let cond: bool;
+        let len: usize;
+        {
+            // The only block entering the loop is used as preheader.
+            // CHECK: bb0: {
+            // CHECK:     [[len:_.*]] = Len((*_1));
+            // CHECK-NEXT: goto -> bb1;
+            i = 0;
+            RET = 0;
+            Goto(bb1)
+        }
+        bb1 = {
+            cond = i < n;
+            match cond { true => bb2, _ => bb3 }
+        }
+        bb2 = {
+            // CHECK: bb2: {
+            // CHECK-NEXT: nop;
+            // CHECK-NEXT: _0 = Add(_0, [[len]]);
+            len = Len(*s);
+            RET = RET + len;
+            i = i + 1;
+            Goto(bb1)
+        }
+        bb3 = {
+            Return()
+        }
+    )
+}
+
+// EMIT_MIR loop_invariant_code_motion.hoist_discriminant.LoopInvariantCodeMotion.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn hoist_discriminant(x: &Option<u32>, n: usize) -> usize {
+    // CHECK-LABEL: fn hoist_discriminant(
+    mir!(
+        let i: usize;
+        let cond: bool;
+        let discr: isize;
+        let is_some: bool;
+        {
+            // A preheader is created, as the entry block doesn't only jump to the loop.
+            // CHECK: bb0: {
+            // CHECK:     switchInt(_2) -> [0: bb4, otherwise: bb6];
+            i = 0;
+            RET = 0;
+            match n { 0 => bb4, _ => bb1 }
+        }
+        bb1 = {
+            cond = i < n;
+            match cond { true => bb2, _ => bb4 }
+        }
+        bb2 = {
+            // CHECK: bb2: {
+            // CHECK-NEXT: nop;
+            // CHECK-NEXT: nop;
+            // CHECK-NEXT: switchInt([[is_some:_.*]]) -> [1: bb3, otherwise: bb5];
+            discr = Discriminant(*x);
+            is_some = discr == 1;
+            match is_some { true => bb3, _ => bb5 }
+        }
+        bb3 = {
+            RET = RET + 1;
+            Goto(bb5)
+        }
+        bb4 = {
+            Return()
+        }
+        bb5 = {
+            i = i + 1;
+            Goto(bb1)
+        }
+        // CHECK: bb6: {
+        // CHECK-NEXT: [[discr:_.*]] = discriminant((*_1));
+        // CHECK-NEXT: [[is_some]] = Eq([[discr]], const 1_isize);
+        // CHECK-NEXT: goto -> bb1;
+    )
+}
+
+// EMIT_MIR loop_invariant_code_motion.keep_division.LoopInvariantCodeMotion.diff
+#[custom_mir(dialect = "runtime", phase = "post-cleanup")]
+fn keep_division(a: u32, b: u32, n: usize) -> u32 {
+    // CHECK-LABEL: fn keep_division(
+    mir!(
+        let i: usize;
+        let cond: bool;
+        let quotient: u32;
+        {
+            i = 0;
+            RET = 0;
+            Goto(bb1)
+        }
+        bb1 = {
+            cond = i < n;
+            match cond { true => bb2, _ => bb3 }
+        }
+        bb2 = {
+            // A division by zero is UB, so it cannot be executed when the loop body isn't.
+            // CHECK: bb2: {
+            // CHECK-NEXT: [[quotient:_.*]] = Div(_1, _2);
+            quotient = a / b;
+            RET = RET ^ quotient;
+            i = i + 1;
+            Goto(bb1)
+        }
+        bb3 = {
+            Return()
+        }
+    )
+}
+
+fn main() {
+    hoist_len(&[1, 2, 3], 3);
+    hoist_discriminant(&Some(1), 3);
+    keep_division(6, 2, 3);
+}
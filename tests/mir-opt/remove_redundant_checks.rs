@@ -0,0 +1,61 @@
+// EMIT_MIR_FOR_EACH_PANIC_STRATEGY
+// unit-test: RemoveRedundantChecks
+// compile-flags: -C overflow-checks=on
+
+// EMIT_MIR remove_redundant_checks.masked_index.RemoveRedundantChecks.diff
+pub fn masked_index(a: [u8; 16], i: usize) -> u8 {
+    // CHECK-LABEL: fn masked_index(
+    // CHECK-NOT: assert(
+    // CHECK: _0 = _1[_3];
+    a[i & 15]
+}
+
+// EMIT_MIR remove_redundant_checks.guarded_index.RemoveRedundantChecks.diff
+pub fn guarded_index(a: [u8; 16], i: usize) -> u8 {
+    // CHECK-LABEL: fn guarded_index(
+    // CHECK: switchInt(
+    // CHECK-NOT: assert(
+    // CHECK: _0 = _1[_5];
+    if i < 8 { a[i] } else { 0 }
+}
+
+// EMIT_MIR remove_redundant_checks.too_large_mask.RemoveRedundantChecks.diff
+pub fn too_large_mask(a: [u8; 16], i: usize) -> u8 {
+    // CHECK-LABEL: fn too_large_mask(
+    // CHECK: assert(move _6, "index out of bounds
+    a[i & 31]
+}
+
+// EMIT_MIR remove_redundant_checks.slice_indices.RemoveRedundantChecks.diff
+pub fn slice_indices(s: &[u8]) -> u8 {
+    // CHECK-LABEL: fn slice_indices(
+    // CHECK: assert(move _5, "index out of bounds
+    // CHECK-NOT: assert(
+    // CHECK: _6 = (*_1)[_7];
+    s[3] ^ s[2]
+}
+
+// EMIT_MIR remove_redundant_checks.guarded_add.RemoveRedundantChecks.diff
+pub fn guarded_add(x: u8) -> u8 {
+    // CHECK-LABEL: fn guarded_add(
+    // CHECK: switchInt(
+    // CHECK-NOT: assert(
+    // CHECK: _0 = move (_5.0: u8);
+    if x < 100 { x + 100 } else { 0 }
+}
+
+// EMIT_MIR remove_redundant_checks.unguarded_add.RemoveRedundantChecks.diff
+pub fn unguarded_add(x: u8) -> u8 {
+    // CHECK-LABEL: fn unguarded_add(
+    // CHECK: assert(!move (_3.1: bool), "attempt to compute `{} + {}`, which would overflow
+    x + 100
+}
+
+fn main() {
+    masked_index([0; 16], 3);
+    guarded_index([0; 16], 3);
+    too_large_mask([0; 16], 3);
+    slice_indices(&[0; 4]);
+    guarded_add(3);
+    unguarded_add(3);
+}
@@ -0,0 +1,34 @@
+// EMIT_MIR_FOR_EACH_PANIC_STRATEGY
+// compile-flags: -O -Zmir-specialize-const-args
+
+// Too large to be inlined, but smaller once `fast` is known.
+fn mix(x: u32, fast: bool) -> u32 {
+    if fast {
+        x ^ 0x5555_5555
+    } else {
+        let a = x ^ (x >> 7);
+        let b = a ^ (a << 9);
+        let c = b ^ (b >> 13);
+        let d = c ^ (c << 17);
+        d & 0xff00_ff00 | (d >> 8) & 0x00ff_00ff
+    }
+}
+
+// EMIT_MIR specialize_const_args.constant.SpecializeConstArgs.diff
+pub fn constant(x: u32) -> u32 {
+    // CHECK-LABEL: fn constant(
+    // CHECK: _0 = const {mix - shim(const args 0b10: [true]) as fn(u32, bool) -> u32}(_1,
+    mix(x, true)
+}
+
+// EMIT_MIR specialize_const_args.variable.SpecializeConstArgs.diff
+pub fn variable(x: u32, fast: bool) -> u32 {
+    // CHECK-LABEL: fn variable(
+    // CHECK: _0 = mix(_1, _2)
+    mix(x, fast)
+}
+
+fn main() {
+    constant(1);
+    variable(2, false);
+}
@@ -0,0 +1,21 @@
+#![crate_type = "lib"]
+
+pub fn used() {}
+
+pub fn unused() {}
+
+pub struct Tuple(pub u32);
+
+pub struct S;
+
+impl S {
+    pub fn method(&self) {}
+
+    fn private(&self) {}
+}
+
+pub mod inner {
+    pub fn reexported() {}
+}
+
+pub use inner::reexported;
@@ -0,0 +1,10 @@
+#![crate_type = "lib"]
+
+extern crate a;
+
+pub fn b() {
+    a::used();
+    let _ = a::Tuple(1);
+    a::S.method();
+    a::reexported();
+}
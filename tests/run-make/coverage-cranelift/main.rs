@@ -0,0 +1,21 @@
+#[no_mangle]
+#[inline(never)]
+pub fn used(x: u32) -> u32 {
+    if x > 10 {
+        x - 10
+    } else {
+        x + 1
+    }
+}
+
+#[no_mangle]
+#[inline(never)]
+pub fn unused() {
+    println!("unused");
+}
+
+fn main() {
+    for i in 0..3 {
+        std::hint::black_box(used(i));
+    }
+}
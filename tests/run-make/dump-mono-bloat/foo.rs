@@ -0,0 +1,10 @@
+pub fn root() {
+    middle::<u32>();
+    middle::<u64>();
+}
+
+fn middle<T: Default>() {
+    generic::<T>(T::default());
+}
+
+fn generic<T>(_: T) {}
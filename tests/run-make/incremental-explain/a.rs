@@ -0,0 +1,7 @@
+pub fn unchanged() -> u32 {
+    1
+}
+
+pub fn changed() -> u32 {
+    2
+}
@@ -0,0 +1,10 @@
+#[inline(never)]
+fn big<T: std::fmt::Debug>(value: T) {
+    println!("{value:?} {value:?} {value:?}");
+}
+
+fn main() {
+    big(1u8);
+    big("two");
+    big(vec![3.0f32]);
+}
@@ -0,0 +1,3 @@
+//! Links to [`Missing`], which doesn't exist.
+
+pub fn foo() {}
@@ -0,0 +1,15 @@
+#!/usr/bin/env python
+
+import sys
+import json
+
+# `json.load` fails if stderr contains anything but a single JSON document.
+log = json.load(sys.stdin)
+
+assert len(log["runs"]) == 1, "expected a single run"
+run = log["runs"][0]
+assert run["tool"]["driver"]["name"] == "rustdoc", run["tool"]
+messages = [result["message"]["text"] for result in run["results"]]
+assert any("`no-defaults` flag" in message for message in messages), messages
+rule_ids = [result.get("ruleId") for result in run["results"]]
+assert "rustdoc::broken_intra_doc_links" in rule_ids, rule_ids
@@ -0,0 +1,15 @@
+#!/usr/bin/env python
+
+import sys
+import json
+
+# `json.load` fails if stderr contains anything but a single JSON document.
+log = json.load(sys.stdin)
+
+assert len(log["runs"]) == 1, "expected a single run"
+results = log["runs"][0]["results"]
+messages = [result["message"]["text"] for result in results]
+assert any("-Z remark-dir" in message for message in messages), messages
+rule_ids = [result.get("ruleId") for result in results]
+assert "E0308" in rule_ids, rule_ids
+assert "unused_variables" in rule_ids, rule_ids
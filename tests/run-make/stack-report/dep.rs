@@ -0,0 +1,17 @@
+#![no_std]
+
+#[inline(never)]
+pub fn checksum(data: &[u8; 64]) -> u32 {
+    let mut copy = *data;
+    mix(&mut copy)
+}
+
+#[inline(never)]
+fn mix(data: &mut [u8; 64]) -> u32 {
+    let mut sum = 0u32;
+    for byte in data.iter_mut() {
+        unsafe { core::ptr::write_volatile(byte, *byte ^ 0x5a) };
+        sum = sum.wrapping_add(u32::from(*byte));
+    }
+    sum
+}
@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+extern crate dep;
+
+#[no_mangle]
+pub extern "C" fn reset() {
+    let mut buffer = [0u8; 256];
+    fill(&mut buffer);
+}
+
+#[no_mangle]
+pub extern "C" fn interrupt(depth: u32) -> u32 {
+    recurse(depth)
+}
+
+// LLVM copies the array with a call to `memcpy`, which isn't a mono item.
+#[no_mangle]
+pub extern "C" fn copy(dst: &mut [u8; 4096], src: &[u8; 4096]) {
+    *dst = *src;
+}
+
+// `dep::checksum` is codegened by `dep`, whose call graph is read from its rlib.
+#[no_mangle]
+pub extern "C" fn verify(data: &[u8; 64]) -> u32 {
+    dep::checksum(data)
+}
+
+#[inline(never)]
+fn fill(buffer: &mut [u8; 256]) {
+    for (i, byte) in buffer.iter_mut().enumerate() {
+        unsafe { core::ptr::write_volatile(byte, i as u8) };
+    }
+}
+
+#[inline(never)]
+fn recurse(depth: u32) -> u32 {
+    if depth == 0 { 0 } else { recurse(depth - 1) + 1 }
+}
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo<'_>) -> ! {
+    loop {}
+}
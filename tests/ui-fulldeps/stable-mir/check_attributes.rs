@@ -0,0 +1,122 @@
+// run-pass
+//! Test that users are able to retrieve the attributes, visibility and documentation of items.
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(control_flow_enum)]
+
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_smir::rustc_internal;
+use stable_mir::crate_def::{AttrArgs, Attribute, Visibility};
+use stable_mir::*;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn test_attributes(_tcx: TyCtxt<'_>) -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+
+    let documented = get_item(&items, "documented");
+    let attrs = documented.attrs();
+    let paths: Vec<_> = attrs.iter().map(|attr| attr.path.join("::")).collect();
+    assert_eq!(paths, ["doc", "doc", "doc", "inline", "must_use", "kani::proof"]);
+    check_attr(&attrs[0], AttrArgs::Eq(r#"" Documented function.""#.to_string()));
+    check_attr(&attrs[3], AttrArgs::Empty);
+    check_attr(&attrs[4], AttrArgs::Eq(r#""the result""#.to_string()));
+    let proof = documented.attrs_by_path(&["kani", "proof"]);
+    assert_eq!(proof.len(), 1);
+    check_attr(&proof[0], AttrArgs::Delimited("unwind = 3".to_string()));
+    assert_eq!(documented.docs().as_deref(), Some("Documented function.\n\nWith two paragraphs."));
+    assert_eq!(documented.visibility(), Some(Visibility::Public));
+
+    let restricted = get_item(&items, "restricted");
+    let Some(Visibility::Restricted(root)) = restricted.visibility() else {
+        panic!("Expected a restricted visibility: {:?}", restricted.visibility())
+    };
+    assert_eq!(restricted.docs(), None);
+    assert!(restricted.attrs_by_path(&["kani", "proof"]).is_empty());
+
+    let private = get_item(&items, "private");
+    assert_eq!(private.visibility(), Some(Visibility::Restricted(root)));
+    let nested = get_item(&items, "inner::nested");
+    assert_eq!(nested.visibility(), Some(Visibility::Restricted(root)));
+    let inner_private = get_item(&items, "inner::inner_private");
+    let Some(Visibility::Restricted(inner)) = inner_private.visibility() else {
+        panic!("Expected a restricted visibility: {:?}", inner_private.visibility())
+    };
+    assert_ne!(inner, root);
+    assert_eq!(inner_private.docs().as_deref(), Some("Raw documentation."));
+    ControlFlow::Continue(())
+}
+
+fn check_attr(attr: &Attribute, args: AttrArgs) {
+    assert_eq!(attr.args, args, "{attr:?}");
+    assert!(!attr.is_inner);
+}
+
+fn get_item<'a>(items: &'a CrateItems, name: &str) -> &'a CrateItem {
+    items.iter().find(|item| item.name() == name).unwrap()
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "attributes_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, tcx, test_attributes(tcx)).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    #![feature(register_tool)]
+    #![register_tool(kani)]
+
+    /// Documented function.
+    ///
+    /// With two paragraphs.
+    #[inline]
+    #[must_use = "the result"]
+    #[kani::proof(unwind = 3)]
+    pub fn documented() -> u32 {{
+        1
+    }}
+
+    pub(crate) fn restricted() {{}}
+
+    fn private() {{}}
+
+    mod inner {{
+        pub(super) fn nested() {{}}
+
+        #[doc = "Raw documentation."]
+        fn inner_private() {{}}
+    }}
+    "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,145 @@
+// run-pass
+//! Test that users are able to retrieve the mono items of a crate and the items they use.
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(control_flow_enum)]
+
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_smir::rustc_internal;
+use stable_mir::mir::mono::{MonoItem, MonoItemGraph, MonoItemUse, UsageKind};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn test_mono_graph(_tcx: TyCtxt<'_>) -> ControlFlow<()> {
+    let graph = stable_mir::mono_item_graph();
+    let main = find_item(&graph, |name| name == "main");
+    assert_eq!(graph.index_of(&graph.items()[main]), Some(main));
+    let uses = graph.uses(main);
+    let used = |item, kind| uses.contains(&MonoItemUse { item, kind });
+
+    // A call to a generic function, which is not also reported as mentioned.
+    let generic = find_item(&graph, |name| name == "generic::<u32>");
+    assert!(used(generic, UsageKind::Call), "{uses:?}");
+    assert!(!used(generic, UsageKind::Mentioned), "{uses:?}");
+    // A function turned into a function pointer.
+    let pointed = find_item(&graph, |name| name == "pointed");
+    assert!(used(pointed, UsageKind::FnPointer), "{uses:?}");
+    // A method of the vtable of a trait object.
+    let speak = find_item(&graph, |name| name == "<Dog as Speak>::speak");
+    assert!(used(speak, UsageKind::Vtable), "{uses:?}");
+    // The drop glue of a local.
+    let drop = find_item(&graph, |name| name.contains("drop_in_place::<Noisy>"));
+    assert!(used(drop, UsageKind::DropGlue), "{uses:?}");
+    assert!(graph.users(drop).contains(&main));
+    let drop_impl = find_item(&graph, |name| name == "<Noisy as Drop>::drop");
+    assert_eq!(graph.uses(drop), [MonoItemUse { item: drop_impl, kind: UsageKind::Call }]);
+    // A function passed by value to a generic function, which calls it.
+    let mentioned = find_item(&graph, |name| name == "mentioned");
+    assert!(used(mentioned, UsageKind::Mentioned), "{uses:?}");
+    assert!(graph.users(mentioned).iter().any(|&user| user != main));
+
+    // The graph doesn't depend on the order the compiler collected the items in.
+    assert_eq!(graph, stable_mir::mono_item_graph());
+    assert!(uses.windows(2).all(|pair| pair[0] < pair[1]));
+    ControlFlow::Continue(())
+}
+
+fn name(item: &MonoItem) -> String {
+    match item {
+        MonoItem::Fn(instance) => instance.trimmed_name(),
+        MonoItem::Static(def) => def.0.trimmed_name(),
+        MonoItem::GlobalAsm(_) => String::new(),
+    }
+}
+
+/// Find the index of the unique item whose name matches `predicate`.
+fn find_item(graph: &MonoItemGraph, predicate: impl Fn(&str) -> bool) -> usize {
+    let indices: Vec<_> =
+        (0..graph.items().len()).filter(|&index| predicate(&name(&graph.items()[index]))).collect();
+    assert_eq!(indices.len(), 1, "{:?}", graph.items());
+    indices[0]
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "mono_graph_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=bin".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    let options = rustc_internal::RunOptions { mono_item_mentions: true, ..Default::default() };
+    run!(args, options, tcx, test_mono_graph(tcx)).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    struct Noisy;
+
+    impl Drop for Noisy {{
+        fn drop(&mut self) {{}}
+    }}
+
+    trait Speak {{
+        fn speak(&self) -> u32;
+    }}
+
+    struct Dog;
+
+    impl Speak for Dog {{
+        fn speak(&self) -> u32 {{
+            1
+        }}
+    }}
+
+    fn generic<T: Default>() -> T {{
+        T::default()
+    }}
+
+    fn pointed() -> u32 {{
+        2
+    }}
+
+    fn mentioned() -> u32 {{
+        3
+    }}
+
+    fn apply<F: Fn() -> u32>(function: F) -> u32 {{
+        function()
+    }}
+
+    fn main() {{
+        let _noisy = Noisy;
+        let value: u32 = generic();
+        let speaker: Box<dyn Speak> = Box::new(Dog);
+        let function: fn() -> u32 = pointed;
+        let _total = value + speaker.speak() + function() + apply(mentioned);
+    }}
+    "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// run-pass
+//! Test that users are able to retrieve the results of the dataflow analyses and of the borrow
+//! checker for the bodies of the local crate when they ask for them, and that the results are not
+//! available anymore once the bodies are optimized.
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(control_flow_enum)]
+
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_smir::rustc_internal;
+use stable_mir::mir::analysis::{
+    analysis_body, borrowck_results, local_dataflow, place_dataflow, BodyLocation, LocalAnalysis,
+    PlaceAnalysis,
+};
+use stable_mir::mir::{Body, BorrowKind, Operand, Place, Rvalue, StatementKind, TerminatorKind};
+use stable_mir::ty::RegionKind;
+use stable_mir::*;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// The argument `v` of `input`.
+const ARG: usize = 1;
+
+fn input_item() -> CrateItem {
+    stable_mir::all_local_items().into_iter().find(|item| item.trimmed_name() == "input").unwrap()
+}
+
+fn test_analysis(_tcx: TyCtxt<'_>) -> ControlFlow<()> {
+    let item = input_item();
+    let body = analysis_body(item).unwrap();
+    let arg = Place::from(ARG);
+    let entry = BodyLocation { block: 0, statement_index: 0 };
+
+    // `let r = &v;`
+    let borrow = find_location(&body, |kind| match kind {
+        Ok(StatementKind::Assign(_, Rvalue::Ref(region, BorrowKind::Shared, place))) => {
+            assert!(matches!(region.kind, RegionKind::ReVar(_)));
+            *place == arg
+        }
+        _ => false,
+    });
+    // `drop(v);`
+    let moved = [Operand::Move(arg.clone())];
+    let drop = find_location(
+        &body,
+        |kind| matches!(kind, Err(TerminatorKind::Call { args, .. }) if *args == moved),
+    );
+
+    let inits = place_dataflow(item, PlaceAnalysis::MaybeInitialized).unwrap();
+    assert!(inits.before(entry).contains(&arg));
+    assert!(inits.before(drop).contains(&arg));
+    assert!(!inits.after(drop).contains(&arg));
+
+    let uninits = place_dataflow(item, PlaceAnalysis::MaybeUninitialized).unwrap();
+    assert!(!uninits.before(drop).contains(&arg));
+    assert!(uninits.after(drop).contains(&arg));
+
+    let live = local_dataflow(item, LocalAnalysis::MaybeLive).unwrap();
+    assert!(live.before(drop).contains(&ARG));
+    assert!(!live.after(drop).contains(&ARG));
+
+    let storage_live = local_dataflow(item, LocalAnalysis::MaybeStorageLive).unwrap();
+    assert!(storage_live.before(entry).contains(&ARG));
+
+    let borrowed = local_dataflow(item, LocalAnalysis::MaybeBorrowed).unwrap();
+    assert!(!borrowed.before(borrow).contains(&ARG));
+    assert!(borrowed.after(borrow).contains(&ARG));
+
+    let borrowck = borrowck_results(item).unwrap();
+    let (loan_idx, loan) =
+        borrowck.loans.iter().enumerate().find(|(_, loan)| loan.borrowed_place == arg).unwrap();
+    assert_eq!(loan.kind, BorrowKind::Shared);
+    assert_eq!(loan.reserve_location, borrow);
+    assert_eq!(loan.activation_location, None);
+    assert!(borrowck.loans_in_scope.after(borrow).contains(&loan_idx));
+    // The reference is not used anymore when `v` is moved.
+    assert!(!borrowck.loans_in_scope.before(drop).contains(&loan_idx));
+    let constraints = &borrowck.outlives_constraints;
+    assert!(constraints.iter().any(|constraint| constraint.longer == loan.region));
+    ControlFlow::Continue(())
+}
+
+/// Without asking for the facts of the borrow checker, the callback runs after the bodies are
+/// optimized.
+fn test_optimized(_tcx: TyCtxt<'_>) -> ControlFlow<()> {
+    let item = input_item();
+    assert!(analysis_body(item).is_err());
+    assert!(borrowck_results(item).is_err());
+    ControlFlow::Continue(())
+}
+
+/// Find the location of the unique statement or terminator that matches `predicate`.
+fn find_location(
+    body: &Body,
+    predicate: impl Fn(Result<&StatementKind, &TerminatorKind>) -> bool,
+) -> BodyLocation {
+    let mut locations = vec![];
+    for (block, data) in body.blocks.iter().enumerate() {
+        for (statement_index, statement) in data.statements.iter().enumerate() {
+            if predicate(Ok(&statement.kind)) {
+                locations.push(BodyLocation { block, statement_index });
+            }
+        }
+        if predicate(Err(&data.terminator.kind)) {
+            locations.push(BodyLocation { block, statement_index: data.statements.len() });
+        }
+    }
+    assert_eq!(locations.len(), 1, "{locations:?}");
+    locations[0]
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "analysis_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    let options = rustc_internal::RunOptions { borrowck_facts: true, ..Default::default() };
+    run!(args.clone(), options, tcx, test_analysis(tcx)).unwrap();
+    run!(args, tcx, test_optimized(tcx)).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    pub fn input(v: Vec<u8>) -> usize {{
+        let r = &v;
+        let len = r.len();
+        drop(v);
+        len
+    }}
+    "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+// run-pass
+//! Test that users are able to serialize the Stable MIR of a crate, and to load it once the
+//! compiler is done.
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(control_flow_enum)]
+
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_smir::rustc_internal;
+use stable_mir::mir::mono::{Instance, StaticDef};
+use stable_mir::mir::Mutability;
+use stable_mir::serialize::{MirDump, FORMAT_VERSION};
+use stable_mir::ty::{RigidTy, TyKind};
+use stable_mir::*;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn dump_crate(_tcx: TyCtxt<'_>) -> ControlFlow<(), (String, Vec<u8>)> {
+    let mut dump = MirDump::new();
+    for item in stable_mir::all_local_items() {
+        match item.kind() {
+            ItemKind::Fn => {
+                dump.add_body(item.name(), item.body());
+                dump.add_instance(Instance::try_from(item).unwrap());
+            }
+            ItemKind::Static => {
+                let allocation = StaticDef::try_from(item).unwrap().eval_initializer().unwrap();
+                dump.add_allocation(item.name(), allocation);
+            }
+            ItemKind::Const | ItemKind::Ctor(_) => {}
+        }
+    }
+    ControlFlow::Continue((dump.to_json().unwrap(), dump.to_binary().unwrap()))
+}
+
+/// Check the content of the dump without the compiler.
+fn check_dump(dump: &MirDump) {
+    let [(name, body)] = dump.bodies() else { panic!("Expected one body") };
+    assert_eq!(name, "norm");
+    let arg = body.arg_locals()[0].ty;
+    let Some(TyKind::RigidTy(RigidTy::Ref(_, pointee, Mutability::Not))) = dump.ty_kind(arg) else {
+        panic!("Expected a reference: {:?}", dump.ty_kind(arg))
+    };
+    let Some(TyKind::RigidTy(RigidTy::Adt(def, _))) = dump.ty_kind(*pointee) else {
+        panic!("Expected an ADT: {:?}", dump.ty_kind(*pointee))
+    };
+    assert_eq!(dump.def_name(def.def_id()), Some("Point"));
+
+    let span = dump.span(body.span).unwrap();
+    assert!(span.filename.ends_with("serde_input.rs"), "{span:?}");
+    assert_eq!((span.lines.start_line, span.lines.end_line), (6, 8));
+    for block in &body.blocks {
+        assert!(dump.span(block.terminator.span).is_some());
+    }
+
+    let [instance] = dump.instances() else { panic!("Expected one instance") };
+    let info = dump.instance(instance).unwrap();
+    assert_eq!(info.name, "norm");
+    assert!(matches!(dump.ty_kind(info.ty), Some(TyKind::RigidTy(RigidTy::FnDef(..)))));
+
+    let [(name, allocation)] = dump.allocations() else { panic!("Expected one allocation") };
+    assert_eq!(name, "ORIGIN");
+    assert_eq!(allocation.bytes, [Some(0); 8]);
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "serde_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    let (json, binary) = run!(args, tcx, dump_crate(tcx)).unwrap();
+
+    let dump = MirDump::from_json(&json).unwrap();
+    check_dump(&dump);
+    let from_binary = MirDump::from_binary(&binary).unwrap();
+    check_dump(&from_binary);
+    assert_eq!(from_binary.to_json().unwrap(), json);
+    assert!(binary.len() < json.len());
+
+    let version = format!(r#""format_version": {FORMAT_VERSION}"#);
+    let other_version = json.replacen(&version, r#""format_version": 0"#, 1);
+    assert_ne!(other_version, json);
+    assert!(MirDump::from_json(&other_version).is_err());
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    pub struct Point {{ pub x: u32, pub y: u32 }}
+
+    pub static ORIGIN: Point = Point {{ x: 0, y: 0 }};
+
+    pub fn norm(point: &Point) -> u32 {{
+        point.x + point.y
+    }}
+    "#
+    )?;
+    Ok(())
+}
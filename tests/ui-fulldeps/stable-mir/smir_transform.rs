@@ -0,0 +1,145 @@
+// run-pass
+//! Test that users are able to instrument the MIR of the local crate with a transformation given
+//! to `run!`, that the transformed bodies are compiled, and that an invalid transformation is
+//! reported as an error.
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+#![feature(control_flow_enum)]
+
+extern crate rustc_middle;
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_smir::rustc_internal;
+use stable_mir::mir::transform::BodyBuilder;
+use stable_mir::mir::{Body, Constant, Mutability, Operand, TerminatorKind, UnwindAction};
+use stable_mir::ty::{Const, RigidTy, Ty, TyKind};
+use stable_mir::*;
+use std::assert_matches::assert_matches;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::process::Command;
+
+const CRATE_NAME: &str = "input";
+
+/// Insert a call to `probe` at the start of every function but `probe` itself.
+fn instrument(item: CrateItem, body: &mut Body) {
+    if item.trimmed_name() == "probe" {
+        return;
+    }
+    let probe = stable_mir::all_local_items()
+        .into_iter()
+        .find(|item| item.trimmed_name() == "probe")
+        .unwrap();
+    let func = Operand::Constant(Constant {
+        span: body.span,
+        user_ty: None,
+        literal: Const::try_new_zero_sized(probe.ty()).unwrap(),
+    });
+    let mut builder = BodyBuilder::new(body);
+    let span = builder.body().span;
+    let destination = builder.new_local(Ty::new_tuple(&[]), span, Mutability::Mut);
+    let next = builder.insert_call(0, 0, func, vec![], destination.into(), UnwindAction::Continue);
+    assert_eq!(next, builder.body().blocks.len() - 1);
+}
+
+/// Remove every local but the return place, which the compiler refuses.
+fn remove_locals(_item: CrateItem, body: &mut Body) {
+    *body = Body::new(
+        body.blocks.clone(),
+        body.locals()[..1].to_vec(),
+        0,
+        body.var_debug_info.clone(),
+        body.spread_arg(),
+        body.span,
+    );
+}
+
+/// The optimized MIR of `main` starts with the call to `probe`.
+fn test_transform(_tcx: TyCtxt<'_>) -> ControlFlow<()> {
+    let main_fn = stable_mir::entry_fn().unwrap();
+    let body = main_fn.body();
+    let TerminatorKind::Call { func, args, .. } = &body.blocks[0].terminator.kind else {
+        panic!("Expected a call to `probe`, found: {:?}", body.blocks[0].terminator.kind);
+    };
+    assert!(args.is_empty());
+    let TyKind::RigidTy(RigidTy::FnDef(def, _)) = func.ty(body.locals()).unwrap().kind() else {
+        unreachable!()
+    };
+    assert_eq!(def.trimmed_name(), "probe");
+    ControlFlow::Continue(())
+}
+
+/// Only compile the crate.
+fn compile(_tcx: TyCtxt<'_>) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
+
+/// This test will generate and compile a dummy crate with an instrumentation, then run it to check
+/// that the probes were executed. The transformation only applies to the compilation it is given
+/// to, and an invalid transformation fails the compilation.
+fn main() {
+    let path = "transform_input.rs";
+    let output = "transform_output";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        "-o".to_string(),
+        output.to_string(),
+        path.to_string(),
+    ];
+    let options =
+        rustc_internal::RunOptions { mir_transform: Some(instrument), ..Default::default() };
+    run!(args.clone(), options, tcx, test_transform(tcx)).unwrap();
+    let status = Command::new(format!("./{output}")).status().unwrap();
+    assert!(status.success());
+
+    // Without the instrumentation, the probes are not executed.
+    run!(args.clone(), tcx, compile(tcx)).unwrap();
+    let run_output = Command::new(format!("./{output}")).output().unwrap();
+    assert!(!run_output.status.success());
+
+    let options =
+        rustc_internal::RunOptions { mir_transform: Some(remove_locals), ..Default::default() };
+    let result = run!(args, options, tcx, compile(tcx));
+    assert_matches!(result, Err(CompilerError::CompilationFailed));
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    static mut PROBES: u32 = 0;
+
+    fn probe() {{
+        unsafe {{ PROBES += 1 }};
+    }}
+
+    fn add(a: u32, b: u32) -> u32 {{
+        a + b
+    }}
+
+    pub fn main() {{
+        let sum = add(1, 2);
+        assert_eq!(sum, 3);
+        // Both `main` and `add` called `probe`.
+        assert_eq!(unsafe {{ PROBES }}, 2);
+    }}
+    "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,8 @@
+// only-x86_64
+
+#[target_clones("avx2", "default")] //~ ERROR the `#[target_clones]` attribute is
+fn foo() {}
+
+fn main() {
+    foo();
+}
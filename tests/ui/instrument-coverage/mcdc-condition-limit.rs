@@ -0,0 +1,20 @@
+// check-pass
+// needs-profiler-support
+// min-llvm-version: 18
+// compile-flags: -Zunstable-options -Cinstrument-coverage=mcdc
+
+// Decisions with more conditions than LLVM supports for MC/DC are reported as
+// plain branches, with a warning.
+
+fn main() {
+    let [a, b, c, d, e, f, g] = [std::hint::black_box(true); 7];
+
+    if a && b && c && d && e && f {
+        println!("six conditions");
+    }
+
+    if a && b && c && d && e && f && g {
+        //~^ WARNING number of conditions in decision (7) exceeds limit (6)
+        println!("seven conditions");
+    }
+}
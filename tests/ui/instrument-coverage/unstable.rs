@@ -1,5 +1,6 @@
-// revisions: branch except-unused-functions except-unused-generics
+// revisions: branch mcdc except-unused-functions except-unused-generics
 // [branch] compile-flags: -Cinstrument-coverage=branch
+// [mcdc] compile-flags: -Cinstrument-coverage=mcdc
 // [except-unused-functions] compile-flags: -Cinstrument-coverage=except-unused-functions
 // [except-unused-generics] compile-flags: -Cinstrument-coverage=except-unused-generics
 
@@ -0,0 +1,5 @@
+pub struct Generated;
+
+pub fn generated() -> Generated {
+    Generated
+}
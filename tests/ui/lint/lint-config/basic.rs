@@ -0,0 +1,9 @@
+// compile-flags: -Z lint-config={{src-base}}/lint/lint-config/basic.toml
+
+use std::mem; //~ WARN unused import: `std::mem`
+
+fn never_called() {}
+
+fn main() {
+    let x = 1; //~ ERROR unused variable: `x`
+}
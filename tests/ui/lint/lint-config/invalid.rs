@@ -0,0 +1,4 @@
+// compile-flags: -Z lint-config={{src-base}}/lint/lint-config/invalid.toml
+// error-pattern: unknown lint level `warning`
+
+fn main() {}
@@ -0,0 +1,11 @@
+// compile-flags: -Z lint-config={{src-base}}/lint/lint-config/overrides.toml
+
+//! Per-path overrides of the lint configuration file apply to the modules
+//! loaded from these paths.
+
+#![crate_type = "lib"]
+
+#[path = "auxiliary/generated.rs"]
+pub mod generated;
+
+pub fn undocumented() {} //~ ERROR missing documentation for a function
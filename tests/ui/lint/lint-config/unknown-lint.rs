@@ -0,0 +1,4 @@
+// check-pass
+// compile-flags: -Z lint-config={{src-base}}/lint/lint-config/unknown-lint.toml
+
+fn main() {}
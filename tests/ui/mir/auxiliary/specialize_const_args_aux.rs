@@ -0,0 +1,18 @@
+// compile-flags: -O -Zmir-specialize-const-args
+
+#![crate_type = "lib"]
+
+pub fn scale(x: i64, mode: u8) -> i64 {
+    match mode {
+        0 => x,
+        1 => x.wrapping_mul(3).wrapping_add(1),
+        2 => x.rotate_left(7) ^ 0x1234,
+        _ => {
+            let mut acc = x;
+            for i in 0..mode {
+                acc = acc.wrapping_mul(31).wrapping_add(i as i64);
+            }
+            acc
+        }
+    }
+}
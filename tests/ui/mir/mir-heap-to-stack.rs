@@ -0,0 +1,89 @@
+// run-pass
+// needs-unwind
+// compile-flags: -O
+
+// Boxes whose contents don't escape are promoted to the stack. The promoted contents must keep
+// their values and be dropped once, in the order the boxes would have dropped them.
+
+use std::cell::RefCell;
+
+thread_local! {
+    static DROPS: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+}
+
+struct Noisy(u32);
+
+impl Drop for Noisy {
+    fn drop(&mut self) {
+        DROPS.with(|drops| drops.borrow_mut().push(self.0));
+    }
+}
+
+fn take_drops() -> Vec<u32> {
+    DROPS.with(|drops| std::mem::take(&mut *drops.borrow_mut()))
+}
+
+fn read(x: u32) -> u32 {
+    let b = Box::new(x);
+    *b ^ 1
+}
+
+fn drop_order() {
+    let _first = Box::new(Noisy(1));
+    let _second = Box::new(Noisy(2));
+    let _third = Noisy(3);
+}
+
+fn field(x: u32) -> u32 {
+    let b = Box::new((Noisy(x), x + 1));
+    b.1
+}
+
+fn moved_out(x: u32) -> Noisy {
+    let b = Box::new(Noisy(x));
+    *b
+}
+
+#[inline(never)]
+fn consume(x: &Noisy) -> u32 {
+    x.0
+}
+
+fn borrowed(x: u32) -> u32 {
+    let b = Box::new(Noisy(x));
+    consume(&*b)
+}
+
+fn too_large(x: u8) -> u8 {
+    let b = Box::new(([x; 5000], x));
+    b.1
+}
+
+fn unwinding(x: u32) {
+    let _b = Box::new(Noisy(x));
+    panic!("unwinding with a promoted box");
+}
+
+fn main() {
+    assert_eq!(read(4), 5);
+
+    drop_order();
+    assert_eq!(take_drops(), [3, 2, 1]);
+
+    assert_eq!(field(5), 6);
+    assert_eq!(take_drops(), [5]);
+
+    let moved = moved_out(6);
+    assert!(take_drops().is_empty());
+    assert_eq!(moved.0, 6);
+    drop(moved);
+    assert_eq!(take_drops(), [6]);
+
+    assert_eq!(borrowed(7), 7);
+    assert_eq!(take_drops(), [7]);
+
+    assert_eq!(too_large(8), 8);
+
+    assert!(std::panic::catch_unwind(|| unwinding(9)).is_err());
+    assert_eq!(take_drops(), [9]);
+}
@@ -0,0 +1,54 @@
+// run-pass
+// compile-flags: -O -Zmir-specialize-const-args
+// aux-build:specialize_const_args_aux.rs
+
+// Calls with constant arguments go to clones of the callee specialized for them, which must
+// compute the same results as the callee.
+
+extern crate specialize_const_args_aux as aux;
+
+use std::hint::black_box;
+
+fn mix(x: u32, fast: bool, shift: u32) -> u32 {
+    if fast {
+        x ^ 0x5555_5555
+    } else {
+        let a = x ^ (x >> shift);
+        let b = a ^ (a << 9);
+        let c = b ^ (b >> 13);
+        let d = c ^ (c << 17);
+        d & 0xff00_ff00 | (d >> 8) & 0x00ff_00ff
+    }
+}
+
+fn checked(values: &[u8], index: usize, wrap: bool) -> Option<u8> {
+    let value = *values.get(index)?;
+    if wrap { Some(value.wrapping_add(200)) } else { value.checked_add(200) }
+}
+
+fn main() {
+    for x in [0, 1, 0x1234_5678, u32::MAX] {
+        assert_eq!(mix(x, true, 7), mix(black_box(x), black_box(true), black_box(7)));
+        assert_eq!(mix(x, false, 7), mix(black_box(x), black_box(false), black_box(7)));
+        assert_eq!(mix(x, false, 3), mix(black_box(x), black_box(false), black_box(3)));
+    }
+
+    let values = [10, 100];
+    assert_eq!(checked(&values, 0, false), Some(210));
+    assert_eq!(checked(&values, 1, false), None);
+    assert_eq!(checked(&values, 1, true), Some(44));
+    assert_eq!(checked(&values, 2, true), None);
+
+    // The clones of a function from another crate are built from its MIR in this crate.
+    for x in [-5, 0, 7, i64::MAX] {
+        assert_eq!(aux::scale(x, 0), aux::scale(black_box(x), black_box(0)));
+        assert_eq!(aux::scale(x, 1), aux::scale(black_box(x), black_box(1)));
+        assert_eq!(aux::scale(x, 2), aux::scale(black_box(x), black_box(2)));
+        assert_eq!(aux::scale(x, 5), aux::scale(black_box(x), black_box(5)));
+    }
+
+    // Each clone is a distinct function, and function pointers to the callee still work.
+    let f: fn(i64, u8) -> i64 = aux::scale;
+    assert_eq!(f(black_box(4), 1), 13);
+    assert_eq!(aux::scale(4, 1), 13);
+}
@@ -0,0 +1,61 @@
+// Calls `#[target_clones]` functions through the dispatcher, which selects a clone on the first
+// call on whatever CPU runs the test, and checks that the selected clone computes the expected
+// results.
+
+// only-x86_64
+// run-pass
+// compile-flags: -O
+
+#![feature(target_clones)]
+
+use std::hint::black_box;
+use std::thread;
+
+#[target_clones("avx2", "sse4.2,popcnt", "default")]
+fn sum(xs: &[u32]) -> u32 {
+    xs.iter().fold(0, |acc, x| acc.wrapping_add(*x))
+}
+
+#[target_clones("avx2", "default")]
+fn count_ones(xs: &[u64]) -> u32 {
+    xs.iter().map(|x| x.count_ones()).sum()
+}
+
+// The recursive calls go through the dispatcher too.
+#[target_clones("avx2", "default")]
+fn fib(n: u64) -> u64 {
+    if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+}
+
+#[target_clones("sse4.2", "default")]
+fn nothing() {}
+
+#[target_clones("avx2", "sse4.2", "default")]
+fn square(x: u64) -> u64 {
+    x * x
+}
+
+fn main() {
+    let xs: Vec<u32> = (0..1000).collect();
+    let expected: u32 = xs.iter().sum();
+    assert_eq!(sum(&xs), expected);
+    assert_eq!(sum(black_box(&xs)), expected);
+    assert_eq!(sum(&[]), 0);
+
+    let ys: Vec<u64> = (0..100).map(|i| u64::MAX >> i).collect();
+    let ones: u32 = (0..100).map(|i| 64 - i).sum();
+    assert_eq!(count_ones(&ys), ones);
+
+    assert_eq!(fib(20), 6765);
+    nothing();
+
+    // Calls through a function pointer use the dispatcher as well.
+    let f = black_box(sum as fn(&[u32]) -> u32);
+    assert_eq!(f(&xs), expected);
+
+    // The first calls race to select the clone.
+    let threads: Vec<_> = (0..8).map(|i| thread::spawn(move || square(i))).collect();
+    for (i, thread) in threads.into_iter().enumerate() {
+        assert_eq!(thread.join().unwrap(), (i * i) as u64);
+    }
+}
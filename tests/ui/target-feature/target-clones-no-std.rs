@@ -0,0 +1,12 @@
+// only-x86_64
+// build-pass
+
+#![feature(target_clones)]
+#![no_std]
+#![crate_type = "lib"]
+
+#[target_clones("avx2", "default")]
+//~^ WARN `#[target_clones]` cannot select a clone without the `target_clone_supported` lang item
+pub fn sum(xs: &[u32]) -> u32 {
+    xs.iter().sum()
+}
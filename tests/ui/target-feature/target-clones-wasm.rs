@@ -0,0 +1,12 @@
+// needs-llvm-components: webassembly
+// compile-flags: --target=wasm32-unknown-unknown --crate-type=rlib
+
+#![feature(no_core, lang_items, target_clones, wasm_target_feature)]
+#![no_core]
+
+#[lang = "sized"]
+trait Sized {}
+
+#[target_clones("bulk-memory", "default")]
+//~^ ERROR `#[target_clones]` is not supported on the `wasm32` architecture
+pub fn fill() {}
@@ -0,0 +1,38 @@
+// only-x86_64
+
+#![feature(target_clones)]
+
+#[target_clones("avx2", "sse4.2,popcnt", "default")]
+fn ok() {}
+
+#[target_clones("avx2")]
+//~^ ERROR `#[target_clones]` requires a `default` clone
+fn missing_default() {}
+
+#[target_clones("default", "avx2")]
+//~^ ERROR clones after the `default` clone are never selected
+fn after_default() {}
+
+#[target_clones("foo", "default")]
+//~^ ERROR the feature named `foo` is not valid for this target
+//~| NOTE `foo` is not valid for this target
+fn unknown_feature() {}
+
+#[target_clones(avx2, "default")]
+//~^ ERROR malformed `target_clones` attribute input
+//~| NOTE expected a string listing target features
+fn malformed() {}
+
+#[inline(always)]
+//~^ ERROR cannot use `#[inline(always)]` with `#[target_clones]`
+#[target_clones("avx2", "default")]
+fn inline_always() {}
+
+fn main() {
+    ok();
+    missing_default();
+    after_default();
+    unknown_feature();
+    malformed();
+    inline_always();
+}